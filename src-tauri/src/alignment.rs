@@ -0,0 +1,314 @@
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How the typed text deviates from the target at one spot
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EditKind {
+    /// A wrong character in place of the expected one
+    Substitution,
+    /// An extra character that isn't in the target
+    Insertion,
+    /// A target character that was skipped
+    Omission,
+    /// Two neighbouring characters typed in swapped order
+    Transposition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TextDifference {
+    pub kind: EditKind,
+    pub target_index: usize, // grapheme index in the target where the difference starts
+    pub typed_index: usize,  // grapheme index in the typed text
+    pub expected: String,    // empty for insertions
+    pub typed: String,       // empty for omissions
+}
+
+/// Result of aligning typed text against the target, in graphemes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alignment {
+    pub target_len: usize,
+    pub typed_len: usize,
+    pub matches: usize,
+    pub differences: Vec<TextDifference>,
+    target_matched: Vec<bool>,
+}
+
+impl Alignment {
+    pub fn count(&self, kind: EditKind) -> usize {
+        self.differences.iter().filter(|d| d.kind == kind).count()
+    }
+
+    /// Whether the target grapheme at `index` was typed correctly
+    pub fn is_matched(&self, target_index: usize) -> bool {
+        self.target_matched
+            .get(target_index)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Omissions after the last typed character: text not reached yet rather than skipped
+    pub fn trailing_omissions(&self) -> usize {
+        self.differences
+            .iter()
+            .rev()
+            .take_while(|d| d.kind == EditKind::Omission && d.typed_index == self.typed_len)
+            .count()
+    }
+
+    /// Number of positions in the alignment; a transposition spans two
+    pub fn len(&self) -> usize {
+        self.matches
+            + self
+                .differences
+                .iter()
+                .map(|d| {
+                    if d.kind == EditKind::Transposition {
+                        2
+                    } else {
+                        1
+                    }
+                })
+                .sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Largest edit table `align` builds (16 MB); typed text comes from the frontend unchecked
+const MAX_TABLE_CELLS: usize = 4_000_000;
+
+/// Align `typed` against `target` with the fewest edits (restricted Damerau-Levenshtein).
+/// Ties are broken toward matches, then transpositions, substitutions, omissions and insertions.
+/// Texts too long for the edit table are compared position by position instead.
+pub fn align(target: &str, typed: &str) -> Alignment {
+    let a: Vec<&str> = target.graphemes(true).collect();
+    let b: Vec<&str> = typed.graphemes(true).collect();
+    let (n, m) = (a.len(), b.len());
+    if (n + 1).saturating_mul(m + 1) > MAX_TABLE_CELLS {
+        return align_positions(&a, &b);
+    }
+    let width = m + 1;
+
+    // dist[i * width + j] = edits needed to turn a[..i] into b[..j]
+    let mut dist = vec![0u32; (n + 1) * width];
+    for i in 0..=n {
+        dist[i * width] = i as u32;
+    }
+    for (j, cell) in dist.iter_mut().take(width).enumerate() {
+        *cell = j as u32;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let cost = u32::from(a[i - 1] != b[j - 1]);
+            let mut best = (dist[(i - 1) * width + j - 1] + cost)
+                .min(dist[(i - 1) * width + j] + 1)
+                .min(dist[i * width + j - 1] + 1);
+            if i > 1
+                && j > 1
+                && a[i - 1] == b[j - 2]
+                && a[i - 2] == b[j - 1]
+                && a[i - 1] != a[i - 2]
+            {
+                best = best.min(dist[(i - 2) * width + j - 2] + 1);
+            }
+            dist[i * width + j] = best;
+        }
+    }
+
+    let at = |i: usize, j: usize| dist[i * width + j];
+    let mut differences = Vec::new();
+    let mut target_matched = vec![false; n];
+    let mut matches = 0;
+    let (mut i, mut j) = (n, m);
+
+    while i > 0 || j > 0 {
+        let here = at(i, j);
+        // Once the typed text is used up, leftover target text is omitted at the end,
+        // so unfinished attempts read as "not reached" rather than skips mid-text
+        let trailing = j == m && i > 0 && here == at(i - 1, j) + 1;
+        if !trailing && i > 0 && j > 0 && a[i - 1] == b[j - 1] && here == at(i - 1, j - 1) {
+            matches += 1;
+            target_matched[i - 1] = true;
+            i -= 1;
+            j -= 1;
+        } else if !trailing
+            && i > 1
+            && j > 1
+            && a[i - 1] == b[j - 2]
+            && a[i - 2] == b[j - 1]
+            && a[i - 1] != a[i - 2]
+            && here == at(i - 2, j - 2) + 1
+        {
+            differences.push(TextDifference {
+                kind: EditKind::Transposition,
+                target_index: i - 2,
+                typed_index: j - 2,
+                expected: format!("{}{}", a[i - 2], a[i - 1]),
+                typed: format!("{}{}", b[j - 2], b[j - 1]),
+            });
+            i -= 2;
+            j -= 2;
+        } else if !trailing && i > 0 && j > 0 && here == at(i - 1, j - 1) + 1 {
+            differences.push(TextDifference {
+                kind: EditKind::Substitution,
+                target_index: i - 1,
+                typed_index: j - 1,
+                expected: a[i - 1].to_string(),
+                typed: b[j - 1].to_string(),
+            });
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && here == at(i - 1, j) + 1 {
+            differences.push(TextDifference {
+                kind: EditKind::Omission,
+                target_index: i - 1,
+                typed_index: j,
+                expected: a[i - 1].to_string(),
+                typed: String::new(),
+            });
+            i -= 1;
+        } else {
+            differences.push(TextDifference {
+                kind: EditKind::Insertion,
+                target_index: i,
+                typed_index: j - 1,
+                expected: String::new(),
+                typed: b[j - 1].to_string(),
+            });
+            j -= 1;
+        }
+    }
+
+    differences.reverse();
+    Alignment {
+        target_len: n,
+        typed_len: m,
+        matches,
+        differences,
+        target_matched,
+    }
+}
+
+/// Linear fallback for huge texts: the i-th typed grapheme against the i-th target grapheme,
+/// then the leftover target omitted or the leftover typed text inserted at the end
+fn align_positions(a: &[&str], b: &[&str]) -> Alignment {
+    let (n, m) = (a.len(), b.len());
+    let mut differences = Vec::new();
+    let mut target_matched = vec![false; n];
+    let mut matches = 0;
+    for (i, (expected, typed)) in a.iter().zip(b).enumerate() {
+        if expected == typed {
+            matches += 1;
+            target_matched[i] = true;
+        } else {
+            differences.push(TextDifference {
+                kind: EditKind::Substitution,
+                target_index: i,
+                typed_index: i,
+                expected: expected.to_string(),
+                typed: typed.to_string(),
+            });
+        }
+    }
+    differences.extend(
+        a.iter()
+            .enumerate()
+            .skip(m)
+            .map(|(i, expected)| TextDifference {
+                kind: EditKind::Omission,
+                target_index: i,
+                typed_index: m,
+                expected: expected.to_string(),
+                typed: String::new(),
+            }),
+    );
+    differences.extend(
+        b.iter()
+            .enumerate()
+            .skip(n)
+            .map(|(j, typed)| TextDifference {
+                kind: EditKind::Insertion,
+                target_index: n,
+                typed_index: j,
+                expected: String::new(),
+                typed: typed.to_string(),
+            }),
+    );
+    Alignment {
+        target_len: n,
+        typed_len: m,
+        matches,
+        differences,
+        target_matched,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(alignment: &Alignment) -> Vec<EditKind> {
+        alignment.differences.iter().map(|d| d.kind).collect()
+    }
+
+    #[test]
+    fn test_identical_text() {
+        let al = align("hello", "hello");
+        assert_eq!(al.matches, 5);
+        assert!(al.differences.is_empty());
+        assert_eq!(al.len(), 5);
+    }
+
+    #[test]
+    fn test_skipped_character_does_not_cascade() {
+        let al = align("the quick fox", "te quick fox");
+        assert_eq!(kinds(&al), vec![EditKind::Omission]);
+        assert_eq!(al.differences[0].expected, "h");
+        assert_eq!(al.differences[0].target_index, 1);
+        assert_eq!(al.matches, 12);
+        assert!(!al.is_matched(1));
+        assert!(al.is_matched(2));
+    }
+
+    #[test]
+    fn test_classifies_each_kind() {
+        assert_eq!(kinds(&align("cat", "cot")), vec![EditKind::Substitution]);
+        assert_eq!(kinds(&align("cat", "caat")), vec![EditKind::Insertion]);
+        let swapped = align("form", "from");
+        assert_eq!(kinds(&swapped), vec![EditKind::Transposition]);
+        assert_eq!(swapped.differences[0].expected, "or");
+        assert_eq!(swapped.differences[0].typed, "ro");
+        assert_eq!(swapped.len(), 4);
+    }
+
+    #[test]
+    fn test_huge_paste_is_aligned_by_position() {
+        let target = "abcd".repeat(500);
+        let typed = format!("{}{}", "abcd".replace('b', "x"), "z".repeat(5000));
+        let al = align(&target, &typed);
+        assert_eq!(al.typed_len, 5004);
+        assert_eq!(al.matches, 3);
+        assert_eq!(al.count(EditKind::Substitution), 1997);
+        assert_eq!(al.count(EditKind::Insertion), 3004);
+        assert_eq!(al.trailing_omissions(), 0);
+        assert_eq!(al.len(), 5004);
+    }
+
+    #[test]
+    fn test_trailing_omissions_and_graphemes() {
+        let al = align("hello", "hel");
+        assert_eq!(al.count(EditKind::Omission), 2);
+        assert_eq!(al.trailing_omissions(), 2);
+
+        let al = align("hello", "hllo");
+        assert_eq!(al.trailing_omissions(), 0);
+
+        // "e\u{301}" is a single grapheme, so only one substitution
+        let al = align("cafe\u{301}", "cafe");
+        assert_eq!(kinds(&al), vec![EditKind::Substitution]);
+        assert_eq!(al.target_len, 4);
+    }
+}
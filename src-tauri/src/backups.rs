@@ -0,0 +1,371 @@
+use crate::review::DAY_MS;
+use crate::storage::{app_data_dir, Database, StorageError, SCHEMA_VERSION};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FILE_PREFIX: &str = "data-";
+const TIME_FORMAT: &str = "%Y%m%d-%H%M%S%3f";
+const RETENTION_FILE: &str = "retention.json";
+
+/// Directory next to the database holding the backups and their retention settings
+pub fn backups_dir() -> PathBuf {
+    app_data_dir().join("backups")
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+    Startup,
+    Daily,
+    PreMigration,
+    /// Taken right before a restore overwrites the database
+    PreRestore,
+    Manual,
+}
+
+impl BackupKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackupKind::Startup => "startup",
+            BackupKind::Daily => "daily",
+            BackupKind::PreMigration => "pre-migration",
+            BackupKind::PreRestore => "pre-restore",
+            BackupKind::Manual => "manual",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "startup" => Some(BackupKind::Startup),
+            "daily" => Some(BackupKind::Daily),
+            "pre-migration" => Some(BackupKind::PreMigration),
+            "pre-restore" => Some(BackupKind::PreRestore),
+            "manual" => Some(BackupKind::Manual),
+            _ => None,
+        }
+    }
+}
+
+/// How many backups of each kind to keep. Manual backups are never pruned.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackupRetention {
+    pub startup: usize,
+    pub daily: usize,
+    pub pre_migration: usize,
+    pub pre_restore: usize,
+}
+
+impl Default for BackupRetention {
+    fn default() -> Self {
+        BackupRetention {
+            startup: 5,
+            daily: 14,
+            pre_migration: 5,
+            pre_restore: 5,
+        }
+    }
+}
+
+impl BackupRetention {
+    fn keep(&self, kind: BackupKind) -> Option<usize> {
+        match kind {
+            BackupKind::Startup => Some(self.startup),
+            BackupKind::Daily => Some(self.daily),
+            BackupKind::PreMigration => Some(self.pre_migration),
+            BackupKind::PreRestore => Some(self.pre_restore),
+            BackupKind::Manual => None,
+        }
+    }
+
+    /// The settings saved in `dir`, or the defaults
+    pub fn load(dir: &Path) -> BackupRetention {
+        fs::read_to_string(dir.join(RETENTION_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), StorageError> {
+        fs::create_dir_all(dir)?;
+        fs::write(
+            dir.join(RETENTION_FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub kind: BackupKind,
+    pub created_at: i64, // ms since epoch
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupCheck {
+    pub file_name: String,
+    /// `PRAGMA integrity_check` found nothing wrong
+    pub ok: bool,
+    pub problems: Vec<String>,
+    pub schema_version: i64,
+}
+
+/// `data-20240601-101500123-daily.db`
+fn file_name(kind: BackupKind, at: DateTime<Utc>) -> String {
+    format!(
+        "{}{}-{}.db",
+        FILE_PREFIX,
+        at.format(TIME_FORMAT),
+        kind.as_str()
+    )
+}
+
+fn parse_file_name(name: &str) -> Option<(BackupKind, i64)> {
+    let rest = name.strip_prefix(FILE_PREFIX)?.strip_suffix(".db")?;
+    // The time is two dash-separated fields, the kind may contain dashes itself
+    let (date, rest) = rest.split_once('-')?;
+    let (time, kind) = rest.split_once('-')?;
+    let at = NaiveDateTime::parse_from_str(&format!("{}-{}", date, time), TIME_FORMAT).ok()?;
+    Some((BackupKind::parse(kind)?, at.and_utc().timestamp_millis()))
+}
+
+/// The path of a listed backup. Only names we write are accepted, so a name can't reach
+/// outside `dir`.
+pub fn path_of(dir: &Path, file_name: &str) -> Result<PathBuf, StorageError> {
+    let path = dir.join(file_name);
+    if parse_file_name(file_name).is_none() || !path.is_file() {
+        return Err(StorageError::NotFound(format!("backup '{}'", file_name)));
+    }
+    Ok(path)
+}
+
+/// Backups in `dir`, newest first. A missing directory has none.
+pub fn list(dir: &Path) -> Result<Vec<BackupInfo>, StorageError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut backups = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if let Some((kind, created_at)) = parse_file_name(&name) {
+            backups.push(BackupInfo {
+                file_name: name,
+                kind,
+                created_at,
+                size: entry.metadata()?.len(),
+            });
+        }
+    }
+    backups.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then(b.file_name.cmp(&a.file_name))
+    });
+    Ok(backups)
+}
+
+pub fn create(
+    db: &Database,
+    dir: &Path,
+    kind: BackupKind,
+    now: DateTime<Utc>,
+) -> Result<BackupInfo, StorageError> {
+    fs::create_dir_all(dir)?;
+    let name = file_name(kind, now);
+    let path = dir.join(&name);
+    db.backup_to(&path)?;
+    Ok(BackupInfo {
+        file_name: name,
+        kind,
+        created_at: now.timestamp_millis(),
+        size: fs::metadata(&path)?.len(),
+    })
+}
+
+/// Delete the oldest backups of each kind beyond what `retention` keeps.
+/// Returns the deleted file names.
+pub fn prune(dir: &Path, retention: &BackupRetention) -> Result<Vec<String>, StorageError> {
+    let mut kept: Vec<(BackupKind, usize)> = Vec::new();
+    let mut removed = Vec::new();
+    for backup in list(dir)? {
+        let Some(keep) = retention.keep(backup.kind) else {
+            continue;
+        };
+        let count = match kept.iter_mut().find(|(kind, _)| *kind == backup.kind) {
+            Some((_, count)) => count,
+            None => {
+                kept.push((backup.kind, 0));
+                &mut kept.last_mut().expect("just pushed").1
+            }
+        };
+        if *count < keep {
+            *count += 1;
+        } else {
+            fs::remove_file(dir.join(&backup.file_name))?;
+            removed.push(backup.file_name);
+        }
+    }
+    Ok(removed)
+}
+
+/// Take the startup backup, and the daily one when the last is a day old, then prune
+pub fn run_scheduled(
+    db: &Database,
+    dir: &Path,
+    startup: bool,
+    now: DateTime<Utc>,
+) -> Result<Vec<BackupInfo>, StorageError> {
+    let mut created = Vec::new();
+    if startup {
+        created.push(create(db, dir, BackupKind::Startup, now)?);
+    }
+    let last_daily = list(dir)?
+        .into_iter()
+        .find(|b| b.kind == BackupKind::Daily)
+        .map(|b| b.created_at);
+    if last_daily.is_none_or(|at| now.timestamp_millis() - at >= DAY_MS) {
+        created.push(create(db, dir, BackupKind::Daily, now)?);
+    }
+    prune(dir, &BackupRetention::load(dir))?;
+    Ok(created)
+}
+
+pub fn verify(dir: &Path, file_name: &str) -> Result<BackupCheck, StorageError> {
+    let (problems, schema_version) = Database::check_file(&path_of(dir, file_name)?)?;
+    Ok(BackupCheck {
+        file_name: file_name.to_string(),
+        ok: problems.is_empty(),
+        problems,
+        schema_version,
+    })
+}
+
+/// Replace the database with a backup that passes its integrity check. The current data is
+/// backed up first; that backup is returned.
+pub fn restore(
+    db: &mut Database,
+    dir: &Path,
+    file_name: &str,
+    now: DateTime<Utc>,
+) -> Result<BackupInfo, StorageError> {
+    let check = verify(dir, file_name)?;
+    if !check.ok {
+        return Err(StorageError::Unsupported(format!(
+            "backup '{}' failed its integrity check: {}",
+            file_name,
+            check.problems.join("; ")
+        )));
+    }
+    if check.schema_version == 0 || check.schema_version > SCHEMA_VERSION {
+        return Err(StorageError::Unsupported(format!(
+            "backup '{}' has schema version {}, this app supports up to {}",
+            file_name, check.schema_version, SCHEMA_VERSION
+        )));
+    }
+
+    let safety = create(db, dir, BackupKind::PreRestore, now)?;
+    db.restore_from(&path_of(dir, file_name)?)?;
+    Ok(safety)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("backups-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_file_names_round_trip() {
+        let name = file_name(BackupKind::PreMigration, at(1, 10));
+        assert_eq!(name, "data-20240601-100000000-pre-migration.db");
+        assert_eq!(
+            parse_file_name(&name),
+            Some((BackupKind::PreMigration, at(1, 10).timestamp_millis()))
+        );
+        assert_eq!(parse_file_name("data-20240601-100000000-weekly.db"), None);
+        assert!(path_of(Path::new("."), "../data.db").is_err());
+    }
+
+    #[test]
+    fn test_schedule_and_prune() {
+        let dir = temp_dir("schedule");
+        let db = Database::in_memory().unwrap();
+
+        let first = run_scheduled(&db, &dir, true, at(1, 10)).unwrap();
+        assert_eq!(first.len(), 2);
+        // The daily backup waits a full day
+        assert_eq!(run_scheduled(&db, &dir, true, at(1, 20)).unwrap().len(), 1);
+        assert_eq!(run_scheduled(&db, &dir, false, at(2, 10)).unwrap().len(), 1);
+        create(&db, &dir, BackupKind::Manual, at(2, 11)).unwrap();
+
+        BackupRetention {
+            startup: 1,
+            daily: 1,
+            ..Default::default()
+        }
+        .save(&dir)
+        .unwrap();
+        run_scheduled(&db, &dir, false, at(2, 12)).unwrap();
+        let kinds: Vec<(BackupKind, i64)> = list(&dir)
+            .unwrap()
+            .iter()
+            .map(|b| (b.kind, b.created_at))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (BackupKind::Manual, at(2, 11).timestamp_millis()),
+                (BackupKind::Daily, at(2, 10).timestamp_millis()),
+                (BackupKind::Startup, at(1, 20).timestamp_millis()),
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_verify_and_restore() {
+        let dir = temp_dir("restore");
+        let mut db = Database::in_memory().unwrap();
+        db.create_user(1, "Ada", "cat", "2024-01-01").unwrap();
+        let backup = create(&db, &dir, BackupKind::Manual, at(1, 10)).unwrap();
+        let check = verify(&dir, &backup.file_name).unwrap();
+        assert!(check.ok, "{:?}", check.problems);
+        assert_eq!(check.schema_version, SCHEMA_VERSION);
+
+        db.create_user(2, "Bob", "dog", "2024-01-02").unwrap();
+        let safety = restore(&mut db, &dir, &backup.file_name, at(1, 11)).unwrap();
+        assert_eq!(safety.kind, BackupKind::PreRestore);
+        let names: Vec<String> = db
+            .get_all_users()
+            .unwrap()
+            .into_iter()
+            .map(|u| u.name)
+            .collect();
+        assert_eq!(names, ["Ada"]);
+
+        let corrupt = file_name(BackupKind::Manual, at(1, 12));
+        fs::write(dir.join(&corrupt), b"SQLite format 3\0 but not really").unwrap();
+        assert!(restore(&mut db, &dir, &corrupt, at(1, 13)).is_err());
+        assert_eq!(db.get_all_users().unwrap().len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
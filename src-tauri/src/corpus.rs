@@ -0,0 +1,449 @@
+use crate::lessons::{Difficulty, Lesson, LessonCategory, Task};
+use flate2::read::GzDecoder;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+#[derive(Error, Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "code", content = "message")]
+pub enum CorpusError {
+    #[error("Band not available: {0}")]
+    BandNotAvailable(String),
+}
+
+/// Languages with a bundled word list, by ISO 639-1 code
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    En,
+    De,
+    Fr,
+    Es,
+    It,
+    Pt,
+}
+
+impl Language {
+    pub const ALL: [Language; 6] = [
+        Language::En,
+        Language::De,
+        Language::Fr,
+        Language::Es,
+        Language::It,
+        Language::Pt,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::De => "de",
+            Language::Fr => "fr",
+            Language::Es => "es",
+            Language::It => "it",
+            Language::Pt => "pt",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::En => "English",
+            Language::De => "German",
+            Language::Fr => "French",
+            Language::Es => "Spanish",
+            Language::It => "Italian",
+            Language::Pt => "Portuguese",
+        }
+    }
+
+    /// The language of a locale such as "de-CH" or "pt_BR"
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale.split(['-', '_']).next()?.to_ascii_lowercase();
+        Language::ALL.into_iter().find(|l| l.as_str() == code)
+    }
+
+    // One word per line, most frequent first
+    fn compressed(&self) -> &'static [u8] {
+        match self {
+            Language::En => include_bytes!("../corpora/en.txt.gz"),
+            Language::De => include_bytes!("../corpora/de.txt.gz"),
+            Language::Fr => include_bytes!("../corpora/fr.txt.gz"),
+            Language::Es => include_bytes!("../corpora/es.txt.gz"),
+            Language::It => include_bytes!("../corpora/it.txt.gz"),
+            Language::Pt => include_bytes!("../corpora/pt.txt.gz"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RankBand {
+    Top200,
+    Top1k,
+    Top10k,
+}
+
+impl RankBand {
+    pub const ALL: [RankBand; 3] = [RankBand::Top200, RankBand::Top1k, RankBand::Top10k];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RankBand::Top200 => "top200",
+            RankBand::Top1k => "top1k",
+            RankBand::Top10k => "top10k",
+        }
+    }
+
+    fn limit(&self) -> usize {
+        match self {
+            RankBand::Top200 => 200,
+            RankBand::Top1k => 1_000,
+            RankBand::Top10k => 10_000,
+        }
+    }
+}
+
+/// A frequency-ranked word list
+#[derive(Debug)]
+pub struct Corpus {
+    pub language: Language,
+    words: Vec<String>,
+}
+
+static CORPORA: OnceLock<Vec<Corpus>> = OnceLock::new();
+
+impl Corpus {
+    /// The bundled list for `language`, decompressed on first use
+    pub fn get(language: Language) -> &'static Corpus {
+        let corpora = CORPORA.get_or_init(|| {
+            Language::ALL
+                .into_iter()
+                .map(|language| {
+                    let mut text = String::new();
+                    GzDecoder::new(language.compressed())
+                        .read_to_string(&mut text)
+                        .expect("bundled corpora are gzipped UTF-8");
+                    Corpus {
+                        language,
+                        words: text
+                            .lines()
+                            .map(str::trim)
+                            .filter(|w| !w.is_empty())
+                            .map(String::from)
+                            .collect(),
+                    }
+                })
+                .collect()
+        });
+        corpora
+            .iter()
+            .find(|c| c.language == language)
+            .expect("every language has a corpus")
+    }
+
+    /// All words, most frequent first
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// The most frequent words up to the band's rank. A list shorter than the band can't
+    /// back it, so the band isn't offered for that language.
+    pub fn band(&self, band: RankBand) -> Result<&[String], CorpusError> {
+        self.words.get(..band.limit()).ok_or_else(|| {
+            CorpusError::BandNotAvailable(format!(
+                "the {} list has {} words, fewer than {}",
+                self.language.name(),
+                self.words.len(),
+                band.as_str()
+            ))
+        })
+    }
+
+    /// Bands the list is long enough for
+    pub fn bands(&self) -> Vec<RankBand> {
+        RankBand::ALL
+            .into_iter()
+            .filter(|band| band.limit() <= self.words.len())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorpusInfo {
+    pub language: Language,
+    pub name: String,
+    pub word_count: usize,
+    pub bands: Vec<RankBand>,
+}
+
+pub fn available() -> Vec<CorpusInfo> {
+    Language::ALL
+        .into_iter()
+        .map(|language| CorpusInfo {
+            language,
+            name: language.name().to_string(),
+            word_count: Corpus::get(language).words().len(),
+            bands: Corpus::get(language).bands(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WordTaskOptions {
+    pub seed: u64,
+    pub band: RankBand,
+    pub words_per_task: usize,
+    /// Break the words into sentences with commas and end marks
+    pub punctuation: bool,
+    /// Capitalize sentence starts, or some words when there is no punctuation
+    pub capitalization: bool,
+    pub min_accuracy: f32,
+}
+
+impl Default for WordTaskOptions {
+    fn default() -> Self {
+        WordTaskOptions {
+            seed: 0,
+            band: RankBand::Top200,
+            words_per_task: 20,
+            punctuation: false,
+            capitalization: false,
+            min_accuracy: 0.9,
+        }
+    }
+}
+
+/// Longest task the generators are asked for; requests beyond it are clamped
+pub const MAX_WORDS_PER_TASK: usize = 200;
+
+impl WordTaskOptions {
+    /// These options with `words_per_task` clamped to 1..=MAX_WORDS_PER_TASK
+    pub fn clamped(&self) -> Self {
+        WordTaskOptions {
+            words_per_task: self.words_per_task.clamp(1, MAX_WORDS_PER_TASK),
+            ..self.clone()
+        }
+    }
+}
+
+const SENTENCE_ENDS: [char; 5] = ['.', '.', '.', '?', '!'];
+const COMMA_CHANCE: f64 = 0.12;
+const CAPITAL_CHANCE: f64 = 0.2;
+
+/// Task `index` of an endless stream of word tasks. Each task has its own generator, so
+/// any task can be produced again from the seed and its index alone.
+pub fn word_task(
+    language: Language,
+    options: &WordTaskOptions,
+    index: u64,
+) -> Result<Task, CorpusError> {
+    let words = Corpus::get(language).band(options.band)?;
+    let mut rng = StdRng::seed_from_u64(options.seed ^ index.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+
+    let mut items = Vec::with_capacity(options.words_per_task);
+    let mut sentence_start = true;
+    let mut sentence_left = rng.gen_range(4..=10);
+    for n in 0..options.words_per_task {
+        let mut word = words[rng.gen_range(0..words.len())].clone();
+        let capitalize = if options.punctuation {
+            sentence_start
+        } else {
+            rng.gen_bool(CAPITAL_CHANCE)
+        };
+        if options.capitalization && capitalize {
+            word = capitalized(&word);
+        }
+        sentence_start = false;
+
+        if options.punctuation {
+            sentence_left -= 1;
+            if sentence_left == 0 || n + 1 == options.words_per_task {
+                word.push(SENTENCE_ENDS[rng.gen_range(0..SENTENCE_ENDS.len())]);
+                sentence_start = true;
+                sentence_left = rng.gen_range(4..=10);
+            } else if rng.gen_bool(COMMA_CHANCE) {
+                word.push(',');
+            }
+        }
+        items.push(word);
+    }
+
+    Ok(Task {
+        id: format!("{}-{}", lesson_id(language, options), index + 1),
+        instruction: format!("Type these common {} words.", language.name()),
+        target_text: items.join(" "),
+        time_limit: None,
+        min_accuracy: options.min_accuracy,
+        min_wpm: None,
+        max_uncorrected_errors: None,
+    })
+}
+
+/// The first `task_count` tasks of the stream as a `Words` lesson
+pub fn generate_word_lesson(
+    language: Language,
+    options: &WordTaskOptions,
+    task_count: usize,
+) -> Result<Lesson, CorpusError> {
+    let top = options.band.limit();
+    Ok(Lesson {
+        id: lesson_id(language, options),
+        name: format!("{} Words: Top {}", language.name(), top),
+        description: format!(
+            "Practice the {} most frequent {} words.",
+            top,
+            language.name()
+        ),
+        category: LessonCategory::Words,
+        difficulty: match options.band {
+            RankBand::Top200 => Difficulty::Beginner,
+            RankBand::Top1k => Difficulty::Intermediate,
+            RankBand::Top10k => Difficulty::Advanced,
+        },
+        tasks: (0..task_count as u64)
+            .map(|index| word_task(language, options, index))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Names every option that shapes the text or its pass criteria, so tasks generated with
+/// different options never share an id
+pub fn lesson_id(language: Language, options: &WordTaskOptions) -> String {
+    format!(
+        "words-{}-{}-w{}{}{}-a{}-s{}",
+        language.as_str(),
+        options.band.as_str(),
+        options.words_per_task,
+        if options.punctuation { "-punct" } else { "" },
+        if options.capitalization { "-caps" } else { "" },
+        options.min_accuracy,
+        options.seed
+    )
+}
+
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_corpora_and_bands() {
+        for info in available() {
+            assert!(info.word_count >= 200, "{}", info.name);
+            let corpus = Corpus::get(info.language);
+            for band in RankBand::ALL {
+                let backed = band.limit() <= info.word_count;
+                assert_eq!(info.bands.contains(&band), backed, "{}", info.name);
+                match corpus.band(band) {
+                    Ok(words) => assert_eq!(words.len(), band.limit()),
+                    Err(CorpusError::BandNotAvailable(_)) => assert!(!backed),
+                }
+            }
+            assert_eq!(info.bands[0], RankBand::Top200);
+        }
+        assert_eq!(Corpus::get(Language::En).words()[0], "the");
+        assert_eq!(Language::from_locale("de-CH"), Some(Language::De));
+        assert_eq!(Language::from_locale("pt_BR"), Some(Language::Pt));
+        assert_eq!(Language::from_locale("tr-TR"), None);
+    }
+
+    #[test]
+    fn test_tasks_depend_only_on_seed_and_index() {
+        let options = WordTaskOptions {
+            seed: 9,
+            ..Default::default()
+        };
+        let lesson = generate_word_lesson(Language::De, &options, 3).unwrap();
+        assert_eq!(lesson.id, "words-de-top200-w20-a0.9-s9");
+        assert_eq!(lesson.tasks[2].id, "words-de-top200-w20-a0.9-s9-3");
+        assert_eq!(
+            lesson.tasks[2].target_text,
+            word_task(Language::De, &options, 2).unwrap().target_text
+        );
+        assert_ne!(lesson.tasks[0].target_text, lesson.tasks[1].target_text);
+
+        let top200 = Corpus::get(Language::De).band(RankBand::Top200).unwrap();
+        assert!(lesson
+            .tasks
+            .iter()
+            .flat_map(|t| t.target_text.split(' '))
+            .all(|w| top200.iter().any(|t| t == w)));
+    }
+
+    #[test]
+    fn test_punctuation_and_capitalization() {
+        let options = WordTaskOptions {
+            seed: 4,
+            words_per_task: 60,
+            punctuation: true,
+            capitalization: true,
+            ..Default::default()
+        };
+        let text = word_task(Language::En, &options, 0).unwrap().target_text;
+        assert!(text.ends_with(['.', '?', '!']));
+        let words: Vec<&str> = text.split(' ').collect();
+        assert!(words[0].starts_with(char::is_uppercase));
+        for pair in words.windows(2) {
+            if pair[0].ends_with(['.', '?', '!']) {
+                assert!(pair[1].starts_with(char::is_uppercase), "{}", text);
+            }
+        }
+
+        let plain = word_task(Language::En, &WordTaskOptions::default(), 0)
+            .unwrap()
+            .target_text;
+        assert!(!plain.contains([',', '.']));
+    }
+
+    #[test]
+    fn test_options_that_change_the_text_change_the_id() {
+        let base = WordTaskOptions::default();
+        let variants = [
+            WordTaskOptions {
+                words_per_task: 30,
+                ..base.clone()
+            },
+            WordTaskOptions {
+                punctuation: true,
+                ..base.clone()
+            },
+            WordTaskOptions {
+                capitalization: true,
+                ..base.clone()
+            },
+            WordTaskOptions {
+                min_accuracy: 0.95,
+                ..base.clone()
+            },
+        ];
+        let mut ids: Vec<String> = variants
+            .iter()
+            .map(|options| lesson_id(Language::En, options))
+            .collect();
+        ids.push(lesson_id(Language::En, &base));
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), variants.len() + 1);
+
+        let huge = WordTaskOptions {
+            words_per_task: usize::MAX,
+            ..base.clone()
+        }
+        .clamped();
+        assert_eq!(huge.words_per_task, MAX_WORDS_PER_TASK);
+        let task = word_task(Language::En, &huge, 0).unwrap();
+        assert_eq!(task.target_text.split(' ').count(), MAX_WORDS_PER_TASK);
+        assert_eq!(base.clamped().words_per_task, base.words_per_task);
+    }
+}
@@ -0,0 +1,1123 @@
+use crate::lessons::{Difficulty, Lesson, LessonCategory, Task};
+
+// The lessons taught by the stages of the built-in courses, ported from the frontend's
+// course data. Their tasks resolve by id like any other built-in lesson.
+
+/// Lessons of every built-in course, course by course in stage order
+pub fn course_lessons() -> Vec<Lesson> {
+    let mut lessons = ten_finger_lessons();
+    lessons.extend(cli_lessons());
+    lessons.extend(claude_code_lessons());
+    lessons.extend(sql_lessons());
+    lessons.extend(pyspark_lessons());
+    lessons
+}
+
+fn task(id: &str, instruction: &str, target_text: &str, min_accuracy: f32) -> Task {
+    Task {
+        id: id.to_string(),
+        instruction: instruction.to_string(),
+        target_text: target_text.to_string(),
+        time_limit: None,
+        min_accuracy,
+        min_wpm: None,
+        max_uncorrected_errors: None,
+    }
+}
+
+fn lesson(
+    id: &str,
+    name: &str,
+    description: &str,
+    category: LessonCategory,
+    difficulty: Difficulty,
+    tasks: Vec<Task>,
+) -> Lesson {
+    Lesson {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        category,
+        difficulty,
+        tasks,
+    }
+}
+
+fn ten_finger_lessons() -> Vec<Lesson> {
+    vec![
+        // Stage 1: Foundation (Home Row ASDF JKL;)
+        lesson("home-1", "Meet the Home Row", "Position your fingers on A S D F and J K L ;", LessonCategory::HomeRow, Difficulty::Beginner, vec![
+            task("h1-1", "Rest your fingers on the home row. Type: asdf", "asdf asdf asdf asdf", 0.9),
+            task("h1-2", "Now the right hand: jkl;", "jkl; jkl; jkl; jkl;", 0.9),
+            task("h1-3", "Alternate hands", "asdf jkl; asdf jkl; asdf jkl;", 0.9),
+            task("h1-4", "Mix it up", "asjk dfkl asjk dfkl", 0.9),
+        ]),
+        lesson("home-2", "Home Row Patterns", "Practice common letter combinations", LessonCategory::HomeRow, Difficulty::Beginner, vec![
+            task("h2-1", "Practice: as", "as as as as as as as as", 0.9),
+            task("h2-2", "Practice: add", "add add add add add", 0.9),
+            task("h2-3", "Practice: fall", "fall fall fall fall", 0.9),
+            task("h2-4", "Practice: ask", "ask ask ask ask ask", 0.9),
+        ]),
+        lesson("home-3", "Home Row Words", "Type simple words using only home row keys", LessonCategory::HomeRow, Difficulty::Beginner, vec![
+            task("h3-1", "Type: sad", "sad sad sad sad sad sad", 0.9),
+            task("h3-2", "Type: dad", "dad dad dad dad dad dad", 0.9),
+            task("h3-3", "Type: add", "add add add add add add", 0.9),
+            task("h3-4", "Type: salad", "salad salad salad salad", 0.9),
+        ]),
+
+        // Stage 2: Home Row Mastery
+        lesson("home-4", "Home Row G and H", "Extend to G and H with your index fingers", LessonCategory::HomeRow, Difficulty::Beginner, vec![
+            task("h4-1", "Practice: g with left index", "fgf fgf fgf gag gag gag", 0.9),
+            task("h4-2", "Practice: h with right index", "jhj jhj jhj hah hah hah", 0.9),
+            task("h4-3", "Combine g and h", "gh gh gh hg hg hg", 0.9),
+            task("h4-4", "Words with g and h", "gash gash flash flash", 0.9),
+        ]),
+        lesson("home-5", "Full Home Row", "Use all home row keys including G and H", LessonCategory::HomeRow, Difficulty::Beginner, vec![
+            task("h5-1", "All letters", "asdfghjkl; asdfghjkl;", 0.9),
+            task("h5-2", "Words: glad", "glad glad glad glad", 0.9),
+            task("h5-3", "Words: hall", "hall hall hall hall", 0.9),
+            task("h5-4", "Sentence", "a lad had a glass flask", 0.9),
+        ]),
+
+        // Stage 3: Top Row Left (QWERT)
+        lesson("top-1", "Q W E", "Reach up to Q, W, and E", LessonCategory::TopRow, Difficulty::Beginner, vec![
+            task("t1-1", "Practice: q with pinky", "aqa aqa aqa qaq qaq", 0.9),
+            task("t1-2", "Practice: w with ring", "sws sws sws wsw wsw", 0.9),
+            task("t1-3", "Practice: e with middle", "ded ded ded ede ede", 0.9),
+            task("t1-4", "Combine", "qwe qwe qwe ewq ewq", 0.9),
+        ]),
+        lesson("top-2", "R and T", "Complete the left top row with R and T", LessonCategory::TopRow, Difficulty::Beginner, vec![
+            task("t2-1", "Practice: r with index", "frf frf frf rfr rfr", 0.9),
+            task("t2-2", "Practice: t with index", "ftf ftf ftf tft tft", 0.9),
+            task("t2-3", "Combine r and t", "rt rt rt tr tr tr", 0.9),
+            task("t2-4", "Words", "wet wet tree tree", 0.9),
+        ]),
+        lesson("top-3", "Left Top Row Words", "Practice words using left side top row", LessonCategory::TopRow, Difficulty::Beginner, vec![
+            task("t3-1", "Type: tree", "tree tree tree tree", 0.9),
+            task("t3-2", "Type: west", "west west west west", 0.9),
+            task("t3-3", "Type: read", "read read read read", 0.9),
+            task("t3-4", "Type: great", "great great great", 0.9),
+        ]),
+
+        // Stage 4: Top Row Right (YUIOP)
+        lesson("top-4", "Y U I", "Learn the right top row: Y, U, I", LessonCategory::TopRow, Difficulty::Beginner, vec![
+            task("t4-1", "Practice: y with index", "jyj jyj jyj yjy yjy", 0.9),
+            task("t4-2", "Practice: u with index", "juj juj juj uju uju", 0.9),
+            task("t4-3", "Practice: i with middle", "kik kik kik iki iki", 0.9),
+            task("t4-4", "Combine", "yui yui yui iuy iuy", 0.9),
+        ]),
+        lesson("top-5", "O and P", "Complete the top row with O and P", LessonCategory::TopRow, Difficulty::Beginner, vec![
+            task("t5-1", "Practice: o with ring", "lol lol lol olo olo", 0.9),
+            task("t5-2", "Practice: p with pinky", ";p; ;p; ;p; p;p p;p", 0.9),
+            task("t5-3", "Combine o and p", "op op op po po po", 0.9),
+            task("t5-4", "Words", "type type your your", 0.9),
+        ]),
+
+        // Stage 5: Top Row Integration
+        lesson("top-6", "Full Top Row", "Practice the entire top row", LessonCategory::TopRow, Difficulty::Intermediate, vec![
+            task("t6-1", "All top row", "qwertyuiop qwertyuiop", 0.9),
+            task("t6-2", "Mixed with home", "the the this this", 0.9),
+            task("t6-3", "Words", "write write quite quite", 0.9),
+            task("t6-4", "Sentence", "the quick red tiger", 0.9),
+        ]),
+        lesson("top-7", "Top and Home Practice", "Fluent switching between rows", LessonCategory::TopRow, Difficulty::Intermediate, vec![
+            task("t7-1", "Type: their", "their their their", 0.9),
+            task("t7-2", "Type: question", "question question", 0.9),
+            task("t7-3", "Type: poetry", "poetry poetry poetry", 0.9),
+            task("t7-4", "Sentence", "type with all your fingers", 0.9),
+        ]),
+
+        // Stage 6: Bottom Row Left (ZXCVB)
+        lesson("bot-1", "Z X C", "Reach down to Z, X, and C", LessonCategory::BottomRow, Difficulty::Intermediate, vec![
+            task("b1-1", "Practice: z with pinky", "aza aza aza zaz zaz", 0.9),
+            task("b1-2", "Practice: x with ring", "sxs sxs sxs xsx xsx", 0.9),
+            task("b1-3", "Practice: c with middle", "dcd dcd dcd cdc cdc", 0.9),
+            task("b1-4", "Combine", "zxc zxc zxc cxz cxz", 0.9),
+        ]),
+        lesson("bot-2", "V and B", "Complete the left bottom row", LessonCategory::BottomRow, Difficulty::Intermediate, vec![
+            task("b2-1", "Practice: v with index", "fvf fvf fvf vfv vfv", 0.9),
+            task("b2-2", "Practice: b with index", "fbf fbf fbf bfb bfb", 0.9),
+            task("b2-3", "Combine", "vb vb vb bv bv bv", 0.9),
+            task("b2-4", "Words", "cave cave verb verb", 0.9),
+        ]),
+
+        // Stage 7: Bottom Row Right (NM,.)
+        lesson("bot-3", "N and M", "Learn N and M with your index finger", LessonCategory::BottomRow, Difficulty::Intermediate, vec![
+            task("b3-1", "Practice: n with index", "jnj jnj jnj njn njn", 0.9),
+            task("b3-2", "Practice: m with index", "jmj jmj jmj mjm mjm", 0.9),
+            task("b3-3", "Combine", "nm nm nm mn mn mn", 0.9),
+            task("b3-4", "Words", "name name main main", 0.9),
+        ]),
+        lesson("bot-4", "Comma and Period", "Essential punctuation marks", LessonCategory::BottomRow, Difficulty::Intermediate, vec![
+            task("b4-1", "Practice comma", "k,k k,k a, b, c, d,", 0.9),
+            task("b4-2", "Practice period", "l.l l.l end. stop. go.", 0.9),
+            task("b4-3", "Both", "yes, no. maybe, ok.", 0.9),
+            task("b4-4", "In sentences", "hello, world. hi, there.", 0.9),
+        ]),
+
+        // Stage 8: Full Alphabet
+        lesson("full-1", "All Letters", "Practice using the complete alphabet", LessonCategory::Words, Difficulty::Intermediate, vec![
+            task("f1-1", "Pangram", "the quick brown fox jumps over the lazy dog", 0.9),
+            task("f1-2", "Common words", "the and you that with have", 0.9),
+            task("f1-3", "More words", "from this will your they what", 0.9),
+            task("f1-4", "Longer words", "example through between another", 0.9),
+        ]),
+        lesson("full-2", "Word Practice", "Build speed with common words", LessonCategory::Words, Difficulty::Intermediate, vec![
+            task("f2-1", "Short words", "is it an to be or as at by do go", 0.9),
+            task("f2-2", "Medium words", "time work life make just know take", 0.9),
+            task("f2-3", "Longer words", "different important something government", 0.9),
+            task("f2-4", "Mixed", "the important thing is to keep trying", 0.9),
+        ]),
+
+        // Stage 9: Shift and Capitals
+        lesson("shift-1", "Capital Letters", "Use Shift for uppercase letters", LessonCategory::Words, Difficulty::Intermediate, vec![
+            task("s1-1", "Left hand capitals", "A S D F G a s d f g", 0.9),
+            task("s1-2", "Right hand capitals", "J K L H J k l h j", 0.9),
+            task("s1-3", "Mixed", "The Quick Brown Fox", 0.9),
+            task("s1-4", "Names", "Alice Bob Carol Dave", 0.9),
+        ]),
+        lesson("shift-2", "Sentences", "Proper capitalization in sentences", LessonCategory::Sentences, Difficulty::Intermediate, vec![
+            task("s2-1", "Simple", "Hello. Goodbye. Yes. No.", 0.9),
+            task("s2-2", "Questions", "How are you? What is this?", 0.9),
+            task("s2-3", "Statements", "This is a test. I can type.", 0.9),
+            task("s2-4", "Mixed", "Hello! How are you today?", 0.9),
+        ]),
+
+        // Stage 10: Numbers
+        lesson("num-1", "Numbers 1-5", "Learn the left side number row", LessonCategory::Numbers, Difficulty::Intermediate, vec![
+            task("n1-1", "Practice 1 2 3", "123 123 321 321 132 132", 0.9),
+            task("n1-2", "Practice 4 5", "45 45 54 54 1234 1234", 0.9),
+            task("n1-3", "Mixed", "12345 54321 13524 24531", 0.9),
+            task("n1-4", "In context", "I have 5 apples and 3 oranges", 0.9),
+        ]),
+        lesson("num-2", "Numbers 6-0", "Complete the number row", LessonCategory::Numbers, Difficulty::Intermediate, vec![
+            task("n2-1", "Practice 6 7 8", "678 678 876 876 768 768", 0.9),
+            task("n2-2", "Practice 9 0", "90 90 09 09 0987 0987", 0.9),
+            task("n2-3", "All numbers", "1234567890 0987654321", 0.9),
+            task("n2-4", "In context", "The code is 4829 or 7156", 0.9),
+        ]),
+
+        // Stage 11: Common Symbols
+        lesson("sym-1", "Basic Punctuation", "Common punctuation marks", LessonCategory::Symbols, Difficulty::Advanced, vec![
+            task("sym1-1", "Question and exclamation", "What? Why! How? Yes!", 0.9),
+            task("sym1-2", "Apostrophe", "it's don't won't can't", 0.9),
+            task("sym1-3", "Quotation marks", "\"Hello\" \"World\" \"Test\"", 0.9),
+            task("sym1-4", "Mixed", "\"What?\" she asked. \"I don't know!\"", 0.9),
+        ]),
+        lesson("sym-2", "Programming Symbols", "Symbols used in coding", LessonCategory::Symbols, Difficulty::Advanced, vec![
+            task("sym2-1", "Brackets", "[] {} () <> []{}()<>", 0.9),
+            task("sym2-2", "Operators", "+ - * / = += -= *= /=", 0.9),
+            task("sym2-3", "Special", "@ # $ % ^ & _ | \\ ~", 0.9),
+            task("sym2-4", "Combined", "arr[0] = (a + b) * c;", 0.9),
+        ]),
+
+        // Stage 12: Advanced Symbols
+        lesson("sym-3", "Code Syntax", "Practice programming syntax patterns", LessonCategory::Code, Difficulty::Advanced, vec![
+            task("sym3-1", "Variables", "const x = 10; let y = 20;", 0.9),
+            task("sym3-2", "Functions", "function add(a, b) { return a + b; }", 0.9),
+            task("sym3-3", "Objects", "{ name: \"test\", value: 42 }", 0.9),
+            task("sym3-4", "Arrays", "[1, 2, 3].map(x => x * 2)", 0.9),
+        ]),
+        lesson("sym-4", "More Code Patterns", "Common code structures", LessonCategory::Code, Difficulty::Advanced, vec![
+            task("sym4-1", "Conditionals", "if (x > 0) { return true; }", 0.9),
+            task("sym4-2", "Loops", "for (let i = 0; i < n; i++) {}", 0.9),
+            task("sym4-3", "Classes", "class User { constructor() {} }", 0.9),
+            task("sym4-4", "Imports", "import { useState } from \"react\";", 0.9),
+        ]),
+
+        // Stage 13: Speed Building
+        lesson("speed-1", "Common Phrases", "Build speed with frequent phrases", LessonCategory::Sentences, Difficulty::Advanced, vec![
+            task("sp1-1", "Greetings", "Hello, how are you? I am fine, thank you.", 0.9),
+            task("sp1-2", "Business", "Please let me know if you have any questions.", 0.9),
+            task("sp1-3", "Casual", "That sounds great! I would love to help.", 0.9),
+            task("sp1-4", "Technical", "The function returns a boolean value.", 0.9),
+        ]),
+        lesson("speed-2", "Paragraphs", "Type longer passages fluently", LessonCategory::Sentences, Difficulty::Advanced, vec![
+            task("sp2-1", "Short paragraph", "Practice makes perfect. The more you type, the better you become.", 0.9),
+            task("sp2-2", "Medium paragraph", "Touch typing is a valuable skill that allows you to type without looking at the keyboard. This frees your mind to focus on what you are writing.", 0.9),
+        ]),
+
+        // Stage 14: Final Mastery
+        lesson("master-1", "Advanced Text", "Challenge yourself with complex text", LessonCategory::Sentences, Difficulty::Expert, vec![
+            task("m1-1", "Technical", "The algorithm processes data with O(n log n) complexity.", 0.9),
+            task("m1-2", "Mixed case", "JavaScript, TypeScript, Python, and Rust are popular languages.", 0.9),
+            task("m1-3", "Numbers and symbols", "The price is $99.99 (20% off from $124.99).", 0.9),
+        ]),
+        lesson("master-2", "Code Mastery", "Type real code snippets", LessonCategory::Code, Difficulty::Expert, vec![
+            task("m2-1", "JavaScript", "const add = (a, b) => a + b;\nconsole.log(add(2, 3));", 0.85),
+            task("m2-2", "Python", "def greet(name):\n    return f\"Hello, {name}!\"\n\nprint(greet(\"World\"))", 0.85),
+            task("m2-3", "HTML", "<div class=\"container\">\n  <h1>Title</h1>\n  <p>Content</p>\n</div>", 0.85),
+        ]),
+    ]
+}
+
+fn cli_lessons() -> Vec<Lesson> {
+    vec![
+        // Stage 1: Basic Navigation & Help
+        lesson("cli-basics-1", "First Commands", "Learn your very first terminal commands", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cli-b1-1", "Learn pwd (Print Working Directory)", "pwd - print working directory", 0.9),
+            task("cli-b1-2", "Learn ls (List files)", "ls - list directory contents", 0.9),
+            task("cli-b1-3", "Learn clear (Clear screen)", "clear - clear the terminal screen", 0.9),
+            task("cli-b1-4", "Practice basic commands", "pwd - print working directory\nls - list directory contents\nclear - clear the terminal screen", 0.9),
+        ]),
+        lesson("cli-basics-2", "Getting Help", "Learn how to find help for any command", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cli-b2-1", "Use --help flag", "ls --help - display help information for ls", 0.9),
+            task("cli-b2-2", "Read a manual page", "man ls - display the manual page for ls", 0.9),
+            task("cli-b2-3", "Find command location", "which python - show the full path of a command", 0.9),
+            task("cli-b2-4", "Check command type", "type ls - display how a command name is interpreted", 0.9),
+        ]),
+
+        // Stage 2: File Navigation
+        lesson("cli-nav-1", "Directory Navigation", "Navigate the filesystem with cd", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cli-n1-1", "Go to home directory", "cd ~ - change directory to home", 0.9),
+            task("cli-n1-2", "Go up one level", "cd .. - move up one directory level", 0.9),
+            task("cli-n1-3", "Go to specific path", "cd /home/user - change to a specific directory", 0.9),
+            task("cli-n1-4", "Return to previous directory", "cd - - switch to the previous directory", 0.9),
+        ]),
+        lesson("cli-nav-2", "Listing Files", "Learn different ways to list files", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cli-n2-1", "List all files", "ls -a - list all entries including hidden files", 0.9),
+            task("cli-n2-2", "Long format with details", "ls -l - list in long format with permissions and size", 0.9),
+            task("cli-n2-3", "Human readable sizes", "ls -lh - long format with human-readable file sizes", 0.9),
+            task("cli-n2-4", "Combined options", "ls -la - list all files in long format", 0.9),
+        ]),
+
+        // Stage 3: File Operations
+        lesson("cli-file-1", "Creating Files & Directories", "Learn to create files and folders", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cli-f1-1", "Create empty file", "touch newfile.txt - create an empty file or update timestamp", 0.9),
+            task("cli-f1-2", "Create directory", "mkdir projects - make a new directory", 0.9),
+            task("cli-f1-3", "Create nested directories", "mkdir -p parent/child/grandchild - create parent directories as needed", 0.9),
+            task("cli-f1-4", "Create multiple files", "touch file1.txt file2.txt file3.txt - create multiple files at once", 0.9),
+        ]),
+        lesson("cli-file-2", "Copy, Move, Delete", "Essential file manipulation commands", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cli-f2-1", "Copy a file", "cp source.txt dest.txt - copy files and directories", 0.9),
+            task("cli-f2-2", "Copy directory recursively", "cp -r folder/ backup/ - copy directories recursively", 0.9),
+            task("cli-f2-3", "Move or rename", "mv old.txt new.txt - move or rename files", 0.9),
+            task("cli-f2-4", "Remove file", "rm unwanted.txt - remove files permanently", 0.9),
+        ]),
+
+        // Stage 4: Reading Files
+        lesson("cli-read-1", "Viewing File Contents", "Different ways to read files", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cli-r1-1", "Display entire file", "cat readme.txt - concatenate and print file contents", 0.9),
+            task("cli-r1-2", "View first lines", "head -n 10 file.txt - output the first 10 lines of a file", 0.9),
+            task("cli-r1-3", "View last lines", "tail -n 20 log.txt - output the last 20 lines of a file", 0.9),
+            task("cli-r1-4", "Paginated view", "less largefile.txt - view file contents with scrollable pagination", 0.9),
+        ]),
+        lesson("cli-read-2", "Following Logs", "Monitor files in real-time", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-r2-1", "Follow log updates", "tail -f app.log - follow file output in real-time", 0.9),
+            task("cli-r2-2", "Count lines in file", "wc -l data.txt - print the number of lines in a file", 0.9),
+            task("cli-r2-3", "Count words", "wc -w document.txt - print the number of words in a file", 0.9),
+            task("cli-r2-4", "Count with cat", "cat file.txt | wc -l - pipe file contents to count lines", 0.9),
+        ]),
+
+        // Stage 5: Search Commands
+        lesson("cli-search-1", "Finding Files", "Locate files on your system", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-s1-1", "Find by name", "find . -name \"*.txt\" - search for files by name pattern", 0.9),
+            task("cli-s1-2", "Find by type", "find . -type f - find only regular files", 0.9),
+            task("cli-s1-3", "Find directories", "find . -type d -name \"src\" - find directories by name", 0.9),
+            task("cli-s1-4", "Find by modification time", "find . -mtime -7 - find files modified in the last 7 days", 0.9),
+        ]),
+        lesson("cli-search-2", "Searching Content with grep", "Search text patterns in files", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-s2-1", "Basic grep", "grep \"error\" log.txt - print lines matching a pattern", 0.9),
+            task("cli-s2-2", "Case insensitive", "grep -i \"warning\" file.txt - ignore case when matching", 0.9),
+            task("cli-s2-3", "Recursive search", "grep -r \"TODO\" ./src - search recursively in directories", 0.9),
+            task("cli-s2-4", "Show line numbers", "grep -n \"function\" *.js - prefix output with line numbers", 0.9),
+        ]),
+
+        // Stage 6: Text Processing
+        lesson("cli-text-1", "Sorting and Filtering", "Process and organize text data", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-t1-1", "Sort alphabetically", "sort names.txt - sort lines of text alphabetically", 0.9),
+            task("cli-t1-2", "Sort numerically", "sort -n numbers.txt - sort lines by numeric value", 0.9),
+            task("cli-t1-3", "Reverse sort", "sort -r data.txt - sort in reverse order", 0.9),
+            task("cli-t1-4", "Find unique lines", "sort file.txt | uniq - filter out repeated adjacent lines", 0.9),
+        ]),
+        lesson("cli-text-2", "Text Extraction", "Extract specific parts of text", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-t2-1", "Extract columns with cut", "cut -d\",\" -f1 data.csv - remove sections from each line", 0.9),
+            task("cli-t2-2", "Print first field with awk", "awk \"{print $1}\" file.txt - pattern scanning and processing", 0.9),
+            task("cli-t2-3", "Simple substitution", "sed \"s/old/new/g\" file.txt - stream editor for text transformation", 0.9),
+            task("cli-t2-4", "Count occurrences", "uniq -c sorted.txt - report or omit repeated lines with counts", 0.9),
+        ]),
+
+        // Stage 7: Pipes and Redirection
+        lesson("cli-pipe-1", "Basic Pipes", "Chain commands together", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-p1-1", "Pipe to grep", "ls -la | grep \".txt\" - pipe output to filter results", 0.9),
+            task("cli-p1-2", "Pipe to wc", "cat file.txt | wc -l - pipe file contents to count lines", 0.9),
+            task("cli-p1-3", "Multiple pipes", "cat log.txt | grep ERROR | wc -l - chain multiple filters", 0.9),
+            task("cli-p1-4", "Sort and unique", "cat data.txt | sort | uniq - sort and remove duplicates", 0.9),
+        ]),
+        lesson("cli-pipe-2", "Redirection", "Control input and output", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-p2-1", "Redirect to file", "echo \"Hello\" > file.txt - redirect output, overwrite file", 0.9),
+            task("cli-p2-2", "Append to file", "echo \"World\" >> file.txt - redirect output, append to file", 0.9),
+            task("cli-p2-3", "Input redirection", "sort < unsorted.txt - read input from a file", 0.9),
+            task("cli-p2-4", "Combined redirection", "sort < input.txt > output.txt - redirect both input and output", 0.9),
+        ]),
+
+        // Stage 8: System Information
+        lesson("cli-sys-1", "Process Information", "View running processes", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-sys1-1", "List all processes", "ps aux - report a snapshot of all running processes", 0.9),
+            task("cli-sys1-2", "Interactive viewer", "top - display real-time system process information", 0.9),
+            task("cli-sys1-3", "Find specific process", "ps aux | grep node - filter processes by name", 0.9),
+            task("cli-sys1-4", "Better process viewer", "htop - interactive process viewer with color display", 0.9),
+        ]),
+        lesson("cli-sys-2", "Disk and Memory", "Check system resources", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-sys2-1", "Check disk space", "df -h - report file system disk space usage", 0.9),
+            task("cli-sys2-2", "Directory size", "du -sh ./folder - estimate file and directory space usage", 0.9),
+            task("cli-sys2-3", "Memory usage", "free -h - display amount of free and used memory", 0.9),
+            task("cli-sys2-4", "System info", "uname -a - print system information", 0.9),
+        ]),
+
+        // Stage 9: Git Commands
+        lesson("cli-git-1", "Git Basics", "Essential version control commands", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-git1-1", "Initialize repository", "git init - create an empty Git repository", 0.9),
+            task("cli-git1-2", "Check status", "git status - show the working tree status", 0.9),
+            task("cli-git1-3", "Stage changes", "git add . - add all changes to the staging area", 0.9),
+            task("cli-git1-4", "Commit changes", "git commit -m \"Initial commit\" - record changes to the repository", 0.9),
+        ]),
+        lesson("cli-git-2", "Git Workflow", "Common Git operations", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cli-git2-1", "Clone repository", "git clone https://github.com/user/repo.git - clone a remote repository", 0.9),
+            task("cli-git2-2", "Push changes", "git push origin main - update remote refs with local commits", 0.9),
+            task("cli-git2-3", "Pull updates", "git pull origin main - fetch and integrate remote changes", 0.9),
+            task("cli-git2-4", "View history", "git log --oneline - show commit logs in compact format", 0.9),
+        ]),
+
+        // Stage 10: Permissions
+        lesson("cli-perm-1", "File Permissions", "Manage file access rights", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cli-perm1-1", "Make executable", "chmod +x script.sh - change file mode bits to add execute", 0.9),
+            task("cli-perm1-2", "Set octal permissions", "chmod 755 file.sh - set permissions using octal notation", 0.9),
+            task("cli-perm1-3", "View permissions", "ls -l file.txt - list file with permission details", 0.9),
+            task("cli-perm1-4", "Change owner", "chown user:group file.txt - change file owner and group", 0.9),
+        ]),
+        lesson("cli-perm-2", "User Commands", "User and group management", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cli-perm2-1", "Current user", "whoami - print the current username", 0.9),
+            task("cli-perm2-2", "User ID info", "id - print user and group IDs", 0.9),
+            task("cli-perm2-3", "User groups", "groups - print group memberships for a user", 0.9),
+            task("cli-perm2-4", "Switch user", "sudo su - username - switch to another user account", 0.9),
+        ]),
+
+        // Stage 11: Networking
+        lesson("cli-net-1", "Network Basics", "Test connectivity and download", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cli-net1-1", "Test connection", "ping -c 4 google.com - send ICMP echo requests to a host", 0.9),
+            task("cli-net1-2", "HTTP request", "curl https://api.example.com - transfer data from a URL", 0.9),
+            task("cli-net1-3", "Download file", "wget https://example.com/file.zip - non-interactive network downloader", 0.9),
+            task("cli-net1-4", "View IP address", "ip addr show - display IP addresses and network interfaces", 0.9),
+        ]),
+        lesson("cli-net-2", "Remote Access", "Connect to remote servers", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cli-net2-1", "SSH connection", "ssh user@hostname - open a secure shell to a remote host", 0.9),
+            task("cli-net2-2", "Copy to remote", "scp file.txt user@host:/path/ - secure copy files over SSH", 0.9),
+            task("cli-net2-3", "Check listening ports", "ss -tuln - investigate sockets and listening ports", 0.9),
+            task("cli-net2-4", "DNS lookup", "dig example.com - DNS lookup utility", 0.9),
+        ]),
+
+        // Stage 12: Archives
+        lesson("cli-arch-1", "Creating Archives", "Compress files and folders", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cli-arch1-1", "Create tar archive", "tar -cvf archive.tar folder/ - create a tar archive", 0.9),
+            task("cli-arch1-2", "Create gzipped tar", "tar -czvf archive.tar.gz folder/ - create a gzip compressed archive", 0.9),
+            task("cli-arch1-3", "Create zip", "zip -r archive.zip folder/ - package and compress files into a zip", 0.9),
+            task("cli-arch1-4", "List archive contents", "tar -tvf archive.tar - list the contents of a tar archive", 0.9),
+        ]),
+        lesson("cli-arch-2", "Extracting Archives", "Unpack compressed files", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cli-arch2-1", "Extract tar", "tar -xvf archive.tar - extract files from a tar archive", 0.9),
+            task("cli-arch2-2", "Extract gzipped tar", "tar -xzvf archive.tar.gz - extract a gzip compressed archive", 0.9),
+            task("cli-arch2-3", "Extract to directory", "unzip archive.zip -d output/ - extract zip to a directory", 0.9),
+            task("cli-arch2-4", "Extract specific file", "tar -xvf archive.tar file.txt - extract a single file from archive", 0.9),
+        ]),
+
+        // Stage 13: Process Management
+        lesson("cli-proc-1", "Process Control", "Manage running processes", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cli-proc1-1", "Kill by PID", "kill 1234 - send a signal to a process by ID", 0.9),
+            task("cli-proc1-2", "Force kill", "kill -9 1234 - forcefully terminate a process", 0.9),
+            task("cli-proc1-3", "Kill by name", "pkill -f \"python script.py\" - signal processes by name pattern", 0.9),
+            task("cli-proc1-4", "List jobs", "jobs - list active jobs in the current shell", 0.9),
+        ]),
+        lesson("cli-proc-2", "Background Tasks", "Run processes in background", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cli-proc2-1", "Run in background", "nohup ./script.sh & - run immune to hangups in background", 0.9),
+            task("cli-proc2-2", "Bring to foreground", "fg %1 - move a background job to the foreground", 0.9),
+            task("cli-proc2-3", "Send to background", "bg %1 - resume a suspended job in the background", 0.9),
+            task("cli-proc2-4", "Disown process", "disown %1 - remove job from the shell job table", 0.9),
+        ]),
+
+        // Stage 14: Docker
+        lesson("cli-docker-1", "Docker Basics", "Container fundamentals", LessonCategory::Commands, Difficulty::Expert, vec![
+            task("cli-docker1-1", "Pull image", "docker pull nginx - download an image from a registry", 0.9),
+            task("cli-docker1-2", "List images", "docker images - list all downloaded container images", 0.9),
+            task("cli-docker1-3", "Run container", "docker run -d -p 80:80 nginx - create and start a container", 0.9),
+            task("cli-docker1-4", "List containers", "docker ps -a - list all containers including stopped", 0.9),
+        ]),
+        lesson("cli-docker-2", "Docker Management", "Advanced container operations", LessonCategory::Commands, Difficulty::Expert, vec![
+            task("cli-docker2-1", "Enter container", "docker exec -it container /bin/bash - run a command in a running container", 0.9),
+            task("cli-docker2-2", "View logs", "docker logs -f container - fetch and follow container logs", 0.9),
+            task("cli-docker2-3", "Build image", "docker build -t myapp . - build an image from a Dockerfile", 0.9),
+            task("cli-docker2-4", "Docker Compose", "docker-compose up -d - start multi-container applications", 0.9),
+        ]),
+
+        // Stage 15: CLI Mastery Test
+        lesson("cli-test-basic", "Basic Commands Test", "Test your knowledge of fundamental commands", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("test-b1", "Navigate to home and list", "cd ~ && ls -la - go home and list all files", 0.9),
+            task("test-b2", "Create dir and file", "mkdir test && touch test/file.txt - create directory and file", 0.9),
+            task("test-b3", "Copy and view", "cp file.txt backup.txt && cat backup.txt - copy then display", 0.9),
+            task("test-b4", "Find text files", "find . -name \"*.txt\" -type f - search for text files recursively", 0.9),
+            task("test-b5", "Search in file", "grep -n \"error\" app.log - search with line numbers", 0.9),
+        ]),
+        lesson("cli-test-intermediate", "Intermediate Commands Test", "Test pipes, redirection, and text processing", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("test-i1", "Count error lines", "grep \"ERROR\" log.txt | wc -l - count lines matching ERROR", 0.9),
+            task("test-i2", "Sort and unique", "cat data.txt | sort | uniq -c | sort -rn - count unique occurrences", 0.9),
+            task("test-i3", "Extract column", "cut -d\",\" -f2 users.csv | sort - extract and sort a CSV column", 0.9),
+            task("test-i4", "Replace text in file", "sed \"s/http/https/g\" urls.txt > secure.txt - find and replace text", 0.9),
+            task("test-i5", "Git workflow", "git add . && git commit -m \"Update\" && git push - stage, commit, push", 0.9),
+        ]),
+        lesson("cli-test-advanced", "Advanced Commands Test", "Test system admin and DevOps commands", LessonCategory::Commands, Difficulty::Expert, vec![
+            task("test-a1", "Find large files", "find . -type f -size +100M -exec ls -lh {} + - find files over 100MB", 0.9),
+            task("test-a2", "Monitor process memory", "ps aux | grep node | awk \"{sum += $6} END {print sum/1024}\" - sum memory usage", 0.9),
+            task("test-a3", "Create and extract archive", "tar -czvf backup.tar.gz ./data && tar -xzvf backup.tar.gz - archive round-trip", 0.9),
+            task("test-a4", "Docker cleanup", "docker system prune -a --volumes - remove all unused Docker data", 0.9),
+            task("test-a5", "Network check", "ss -tuln | grep LISTEN | wc -l - count listening ports", 0.9),
+        ]),
+        lesson("cli-test-master", "CLI Mastery Final Test", "Comprehensive command line challenge", LessonCategory::Commands, Difficulty::Expert, vec![
+            task("test-m1", "Complex pipeline", "find . -name \"*.log\" -mtime -7 | xargs grep -l \"ERROR\" | wc -l - count error log files", 0.9),
+            task("test-m2", "Process management", "ps aux | sort -nrk 4 | head -5 - top 5 memory-consuming processes", 0.9),
+            task("test-m3", "Archive with exclusion", "tar --exclude=\"node_modules\" -czvf project.tar.gz ./ - archive excluding node_modules", 0.9),
+            task("test-m4", "Real-time log filter", "tail -f /var/log/syslog | grep --line-buffered \"error\" - follow logs for errors", 0.9),
+            task("test-m5", "Full Git workflow", "git checkout -b feature && git add . && git commit -m \"Add feature\" && git push -u origin feature - branch, commit, push", 0.9),
+            task("test-m6", "Docker deploy", "docker build -t app:v1 . && docker run -d -p 3000:3000 --name myapp app:v1 - build and run container", 0.9),
+        ]),
+    ]
+}
+
+fn claude_code_lessons() -> Vec<Lesson> {
+    vec![
+        // Stage 1: Getting Started
+        lesson("cc-start-1", "First Steps", "Launch Claude Code and learn the basics", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cc-s1-1", "Type the command to start Claude Code", "claude - Start an interactive Claude Code session in your terminal", 0.9),
+            task("cc-s1-2", "Type the command to start with a prompt", "claude \"explain this project\" - Start a session with an initial prompt", 0.9),
+            task("cc-s1-3", "Type the command to check the version", "claude -v - Output the current version number of Claude Code", 0.9),
+            task("cc-s1-4", "Type the command to update", "claude update - Update Claude Code to the latest version", 0.9),
+        ]),
+        lesson("cc-start-2", "Getting Help", "Learn how to find help and check status", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cc-s2-1", "Type the help command", "/help - Get usage help and list all available slash commands", 0.9),
+            task("cc-s2-2", "Type the status command", "/status - Open Settings interface showing version, model, and account info", 0.9),
+            task("cc-s2-3", "Type the doctor command", "/doctor - Check the health of your Claude Code installation", 0.9),
+            task("cc-s2-4", "Type the cost command", "/cost - Show token usage statistics for the current session", 0.9),
+        ]),
+
+        // Stage 2: Print Mode & Piping
+        lesson("cc-print-1", "Print Mode Basics", "Use Claude Code for one-shot queries and scripting", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cc-p1-1", "Type the print mode flag", "claude -p \"explain this function\" - Print response without interactive mode", 0.9),
+            task("cc-p1-2", "Type a piped command", "cat logs.txt | claude -p \"explain these errors\" - Process piped content", 0.9),
+            task("cc-p1-3", "Type the JSON output flag", "claude -p --output-format json \"analyze this\" - Get structured JSON output", 0.9),
+            task("cc-p1-4", "Type the stream JSON flag", "claude -p --output-format stream-json \"query\" - Stream JSON events", 0.9),
+        ]),
+        lesson("cc-print-2", "Budget & Limits", "Control spending and turn limits in print mode", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("cc-p2-1", "Type the budget flag", "claude -p --max-budget-usd 5.00 \"query\" - Set maximum dollar spend", 0.9),
+            task("cc-p2-2", "Type the max turns flag", "claude -p --max-turns 3 \"query\" - Limit the number of agentic turns", 0.9),
+            task("cc-p2-3", "Type a scripting workflow", "cat build-error.txt | claude -p \"explain the root cause\" > output.txt", 0.9),
+            task("cc-p2-4", "Type a linting workflow", "claude -p \"look at changes vs main and report issues\"", 0.9),
+        ]),
+
+        // Stage 3: Session Management
+        lesson("cc-session-1", "Continue & Resume", "Pick up where you left off", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cc-se1-1", "Type the continue flag", "claude -c - Continue the most recent conversation in the current directory", 0.9),
+            task("cc-se1-2", "Type the continue with print flag", "claude -c -p \"check for type errors\" - Continue in print mode", 0.9),
+            task("cc-se1-3", "Type the resume flag", "claude -r \"auth-refactor\" \"finish this PR\" - Resume a session by name", 0.9),
+            task("cc-se1-4", "Type the fork session flag", "claude --resume abc123 --fork-session - Fork a session into a new one", 0.9),
+        ]),
+        lesson("cc-session-2", "Session Slash Commands", "Manage sessions from inside the REPL", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cc-se2-1", "Type the rename command", "/rename auth-refactor - Rename the current session for easy identification", 0.9),
+            task("cc-se2-2", "Type the resume command", "/resume - Open the session picker to resume a previous conversation", 0.9),
+            task("cc-se2-3", "Type the export command", "/export conversation.md - Export the current conversation to a file", 0.9),
+            task("cc-se2-4", "Type the clear command", "/clear - Clear conversation history and start fresh", 0.9),
+        ]),
+
+        // Stage 4: Context & Memory
+        lesson("cc-ctx-1", "Context Management", "Control what Claude knows about your project", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cc-c1-1", "Type the compact command", "/compact - Compress conversation to free up context window space", 0.9),
+            task("cc-c1-2", "Type compact with focus", "/compact \"focus on authentication\" - Compact with focus instructions", 0.9),
+            task("cc-c1-3", "Type the context command", "/context - Visualize current context usage as a colored grid", 0.9),
+            task("cc-c1-4", "Type the rewind command", "/rewind - Rewind the conversation and code to a previous state", 0.9),
+        ]),
+        lesson("cc-ctx-2", "Memory & CLAUDE.md", "Set up persistent project memory", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cc-c2-1", "Type the init command", "/init - Initialize project with a CLAUDE.md guide file", 0.9),
+            task("cc-c2-2", "Type the memory command", "/memory - Edit CLAUDE.md memory files for persistent instructions", 0.9),
+            task("cc-c2-3", "Type a file reference", "@src/utils/auth.js - Reference a file to add it to context", 0.9),
+            task("cc-c2-4", "Type a directory reference", "@src/components/ - Reference a directory listing for context", 0.9),
+        ]),
+
+        // Stage 5: Model & Thinking
+        lesson("cc-model-1", "Model Selection", "Choose and configure AI models", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cc-m1-1", "Type the model flag", "claude --model sonnet - Set the model for the session", 0.9),
+            task("cc-m1-2", "Type the model slash command", "/model - Select or change the AI model interactively", 0.9),
+            task("cc-m1-3", "Type the fallback model flag", "claude -p --fallback-model sonnet \"query\" - Auto-fallback when overloaded", 0.9),
+            task("cc-m1-4", "Type the effort level env var", "CLAUDE_CODE_EFFORT_LEVEL=high claude - Set reasoning effort level", 0.9),
+        ]),
+        lesson("cc-model-2", "Extended Thinking", "Enable deeper reasoning for complex tasks", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cc-m2-1", "Type the verbose flag", "claude --verbose - Enable verbose logging with full turn-by-turn output", 0.9),
+            task("cc-m2-2", "Type the plan mode command", "/plan - Enter plan mode for read-only analysis before coding", 0.9),
+            task("cc-m2-3", "Type the thinking env var", "MAX_THINKING_TOKENS=16000 - Override the extended thinking token budget", 0.9),
+            task("cc-m2-4", "Type the output tokens env var", "CLAUDE_CODE_MAX_OUTPUT_TOKENS=64000 - Set maximum output token limit", 0.9),
+        ]),
+
+        // Stage 6: Permissions & Security
+        lesson("cc-perm-1", "Permission Modes", "Control what Claude can do automatically", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cc-pe1-1", "Type the permissions command", "/permissions - View or update current permission settings", 0.9),
+            task("cc-pe1-2", "Type the permission mode flag", "claude --permission-mode plan - Start in plan mode (read-only)", 0.9),
+            task("cc-pe1-3", "Type the allowed tools flag", "claude --allowedTools \"Bash(git log *)\" \"Read\" - Allow specific tools", 0.9),
+            task("cc-pe1-4", "Type the disallowed tools flag", "claude --disallowedTools \"Bash(curl *)\" - Block specific tools", 0.9),
+        ]),
+        lesson("cc-perm-2", "Settings Configuration", "Configure Claude Code behavior", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cc-pe2-1", "Type the config command", "/config - Open the Settings interface to configure Claude Code", 0.9),
+            task("cc-pe2-2", "Type the theme command", "/theme - Change the color theme of the interface", 0.9),
+            task("cc-pe2-3", "Type the settings flag", "claude --settings ./settings.json - Load settings from a JSON file", 0.9),
+            task("cc-pe2-4", "Type the add dir flag", "claude --add-dir ../apps ../lib - Add extra working directories", 0.9),
+        ]),
+
+        // Stage 7: MCP Servers
+        lesson("cc-mcp-1", "MCP Basics", "Add and manage Model Context Protocol servers", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-mc1-1", "Type the MCP list command", "claude mcp list - List all configured MCP servers", 0.9),
+            task("cc-mc1-2", "Type the MCP add command", "claude mcp add --transport http notion https://mcp.notion.com/mcp", 0.9),
+            task("cc-mc1-3", "Type the MCP remove command", "claude mcp remove github - Remove a configured MCP server", 0.9),
+            task("cc-mc1-4", "Type the MCP get command", "claude mcp get github - Get details for a specific MCP server", 0.9),
+        ]),
+        lesson("cc-mcp-2", "MCP Advanced", "Advanced MCP configuration and scoping", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-mc2-1", "Type the MCP scope flag", "claude mcp add --scope user my-server - Add server with user scope", 0.9),
+            task("cc-mc2-2", "Type the MCP serve command", "claude mcp serve - Start Claude Code itself as an MCP server", 0.9),
+            task("cc-mc2-3", "Type the MCP config flag", "claude --mcp-config ./mcp.json - Load MCP servers from a config file", 0.9),
+            task("cc-mc2-4", "Type the MCP import command", "claude mcp add-from-claude-desktop - Import servers from Claude Desktop", 0.9),
+        ]),
+
+        // Stage 8: Keyboard Shortcuts
+        lesson("cc-keys-1", "Essential Shortcuts", "Core keyboard shortcuts for the REPL", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cc-k1-1", "Type the cancel shortcut", "Ctrl+C - Cancel current input or generation", 0.9),
+            task("cc-k1-2", "Type the exit shortcut", "Ctrl+D - Exit the Claude Code session", 0.9),
+            task("cc-k1-3", "Type the clear screen shortcut", "Ctrl+L - Clear terminal screen while keeping conversation", 0.9),
+            task("cc-k1-4", "Type the verbose toggle", "Ctrl+O - Toggle verbose output to show thinking", 0.9),
+        ]),
+        lesson("cc-keys-2", "Advanced Shortcuts", "Power user keyboard shortcuts", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("cc-k2-1", "Type the background shortcut", "Ctrl+B - Background running tasks", 0.9),
+            task("cc-k2-2", "Type the task list toggle", "Ctrl+T - Toggle task list display", 0.9),
+            task("cc-k2-3", "Type the editor shortcut", "Ctrl+G - Open current prompt in your default text editor", 0.9),
+            task("cc-k2-4", "Type the model switch shortcut", "Option+P - Switch model without clearing the current prompt", 0.9),
+        ]),
+
+        // Stage 9: System Prompts & Agents
+        lesson("cc-sys-1", "Custom System Prompts", "Override and extend the system prompt", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-sy1-1", "Type the system prompt flag", "claude --system-prompt \"You are a code reviewer\" - Replace system prompt", 0.9),
+            task("cc-sy1-2", "Type the append system prompt flag", "claude --append-system-prompt \"Always use TypeScript\" - Extend prompt", 0.9),
+            task("cc-sy1-3", "Type the system prompt file flag", "claude -p --system-prompt-file prompt.txt \"query\" - Load prompt from file", 0.9),
+            task("cc-sy1-4", "Type the append file flag", "claude -p --append-system-prompt-file rules.txt \"query\" - Append from file", 0.9),
+        ]),
+        lesson("cc-sys-2", "Agents & Tasks", "Create and manage custom agents", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-sy2-1", "Type the agents command", "/agents - Manage subagents: view, create, and edit them", 0.9),
+            task("cc-sy2-2", "Type the agent flag", "claude --agent my-custom-agent - Use a specific agent for the session", 0.9),
+            task("cc-sy2-3", "Type the tasks command", "/tasks - List and manage background tasks", 0.9),
+            task("cc-sy2-4", "Type the todos command", "/todos - List current TODO items being tracked", 0.9),
+        ]),
+
+        // Stage 10: Git & GitHub Integration
+        lesson("cc-git-1", "GitHub Workflows", "Use Claude Code with GitHub", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-g1-1", "Type the from-pr flag", "claude --from-pr 123 - Resume sessions linked to a specific GitHub PR", 0.9),
+            task("cc-g1-2", "Type the install github app command", "/install-github-app - Set up GitHub Actions for auto PR reviews", 0.9),
+            task("cc-g1-3", "Type a bash mode command", "!git status - Run shell commands directly with output added to context", 0.9),
+            task("cc-g1-4", "Type the copy command", "/copy - Copy the last assistant response to clipboard", 0.9),
+        ]),
+        lesson("cc-git-2", "Remote Sessions", "Work with Claude Code across environments", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-g2-1", "Type the remote flag", "claude --remote \"Fix the login bug\" - Create a web session on claude.ai", 0.9),
+            task("cc-g2-2", "Type the teleport flag", "claude --teleport - Resume a web session in your local terminal", 0.9),
+            task("cc-g2-3", "Type the teleport slash command", "/teleport - Resume a remote session from inside the REPL", 0.9),
+            task("cc-g2-4", "Type the IDE flag", "claude --ide - Auto-connect to IDE on startup if available", 0.9),
+        ]),
+
+        // Stage 11: Environment Variables
+        lesson("cc-env-1", "Core Environment Variables", "Configure Claude Code with environment variables", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-e1-1", "Type the API key variable", "ANTHROPIC_API_KEY=sk-ant-... claude - Authenticate with an API key", 0.9),
+            task("cc-e1-2", "Type the model variable", "ANTHROPIC_MODEL=claude-sonnet-4-5-20250929 claude - Override default model", 0.9),
+            task("cc-e1-3", "Type the shell variable", "CLAUDE_CODE_SHELL=/bin/zsh claude - Override shell detection", 0.9),
+            task("cc-e1-4", "Type the tmpdir variable", "CLAUDE_CODE_TMPDIR=/custom/tmp claude - Override temp directory", 0.9),
+        ]),
+        lesson("cc-env-2", "Advanced Variables", "Fine-tune behavior with environment variables", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-e2-1", "Type the MCP timeout variable", "MCP_TIMEOUT=10000 claude - Set MCP server startup timeout in ms", 0.9),
+            task("cc-e2-2", "Type the MCP output variable", "MAX_MCP_OUTPUT_TOKENS=25000 - Set max tokens for MCP tool output", 0.9),
+            task("cc-e2-3", "Type the telemetry variable", "DISABLE_TELEMETRY=1 claude - Opt out of telemetry collection", 0.9),
+            task("cc-e2-4", "Type the auto-update variable", "DISABLE_AUTOUPDATER=1 claude - Disable automatic updates", 0.9),
+        ]),
+
+        // Stage 12: Configuration Files
+        lesson("cc-cfg-1", "Settings Files", "Understand the Claude Code configuration hierarchy", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-cf1-1", "Type the user settings path", "~/.claude/settings.json - User-level global settings file", 0.9),
+            task("cc-cf1-2", "Type the project settings path", ".claude/settings.json - Project-level team-shared settings file", 0.9),
+            task("cc-cf1-3", "Type the local settings path", ".claude/settings.local.json - Project-level personal settings (gitignored)", 0.9),
+            task("cc-cf1-4", "Type the MCP config path", ".mcp.json - Project-scoped MCP server configuration file", 0.9),
+        ]),
+        lesson("cc-cfg-2", "Skills & Commands", "Create custom slash commands and skills", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-cf2-1", "Type the project skills path", ".claude/skills/ - Project-specific skills directory", 0.9),
+            task("cc-cf2-2", "Type the personal skills path", "~/.claude/skills/ - Personal skills directory for all projects", 0.9),
+            task("cc-cf2-3", "Type the project commands path", ".claude/commands/ - Project-specific custom slash commands", 0.9),
+            task("cc-cf2-4", "Type the agents path", ".claude/agents/ - Project-specific subagent definitions", 0.9),
+        ]),
+
+        // Stage 13: Advanced Workflows
+        lesson("cc-adv-1", "Multiline & Editing", "Master multiline input and editing modes", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("cc-a1-1", "Type the multiline escape", "Press \\ then Enter to start a new line in the prompt", 0.9),
+            task("cc-a1-2", "Type the vim command", "/vim - Enable vim-style editing mode in the REPL", 0.9),
+            task("cc-a1-3", "Type the terminal setup command", "/terminal-setup - Install Shift+Enter binding for multiline input", 0.9),
+            task("cc-a1-4", "Type the stats command", "/stats - Visualize daily usage, session history, and streaks", 0.9),
+        ]),
+        lesson("cc-adv-2", "Hooks & Lifecycle", "Run custom commands at lifecycle events", LessonCategory::Commands, Difficulty::Expert, vec![
+            task("cc-a2-1", "Type the PreToolUse hook", "PreToolUse - Hook that fires before a tool is executed", 0.9),
+            task("cc-a2-2", "Type the PostToolUse hook", "PostToolUse - Hook that fires after a tool completes", 0.9),
+            task("cc-a2-3", "Type the UserPromptSubmit hook", "UserPromptSubmit - Hook that fires when the user submits a prompt", 0.9),
+            task("cc-a2-4", "Type the SessionStart hook", "SessionStart - Hook that fires when a new session begins", 0.9),
+        ]),
+
+        // Stage 14: Mastery Test
+        lesson("cc-test-1", "Claude Code Mastery Test", "Prove your Claude Code knowledge", LessonCategory::Commands, Difficulty::Expert, vec![
+            task("cc-t1-1", "Type a complete print mode workflow", "cat error.log | claude -p --output-format json \"diagnose this\"", 0.9),
+            task("cc-t1-2", "Type a session management workflow", "claude -r \"feature-auth\" --fork-session \"continue from here\"", 0.9),
+            task("cc-t1-3", "Type an MCP configuration command", "claude mcp add --transport http --scope user github https://mcp.github.com", 0.9),
+            task("cc-t1-4", "Type a full flags combo", "claude --model opus --permission-mode plan --add-dir ../shared --verbose", 0.9),
+            task("cc-t1-5", "Type environment setup", "ANTHROPIC_API_KEY=sk-ant-key CLAUDE_CODE_EFFORT_LEVEL=high claude", 0.9),
+            task("cc-t1-6", "Type all essential slash commands", "/compact /context /model /permissions /memory /config", 0.9),
+        ]),
+    ]
+}
+
+fn sql_lessons() -> Vec<Lesson> {
+    vec![
+        // Stage 1: Your First Queries
+        lesson("sql-basics-1", "SELECT Fundamentals", "Learn to retrieve data from tables", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("sql-b1-1", "Select all columns from a table", "SELECT * FROM users; -- select all columns from a table", 0.9),
+            task("sql-b1-2", "Select specific columns", "SELECT name, email FROM users; -- choose only the columns you need", 0.9),
+            task("sql-b1-3", "Select with alias", "SELECT name AS full_name FROM users; -- rename a column in the output", 0.9),
+            task("sql-b1-4", "Select distinct values", "SELECT DISTINCT city FROM users; -- remove duplicate rows", 0.9),
+        ]),
+        lesson("sql-basics-2", "Filtering with WHERE", "Filter rows using conditions", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("sql-b2-1", "Filter by equality", "SELECT * FROM users WHERE active = true; -- filter rows by condition", 0.9),
+            task("sql-b2-2", "Filter by comparison", "SELECT * FROM products WHERE price > 100; -- compare column values", 0.9),
+            task("sql-b2-3", "Filter with LIKE", "SELECT * FROM users WHERE name LIKE 'J%'; -- pattern matching with wildcards", 0.9),
+            task("sql-b2-4", "Filter with IN", "SELECT * FROM users WHERE city IN ('NYC', 'LA'); -- match against a list", 0.9),
+        ]),
+
+        // Stage 2: Sorting & Limiting
+        lesson("sql-sort-1", "ORDER BY & LIMIT", "Sort and limit your query results", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("sql-so1-1", "Sort ascending", "SELECT * FROM users ORDER BY name ASC; -- sort results A to Z", 0.9),
+            task("sql-so1-2", "Sort descending", "SELECT * FROM users ORDER BY created_at DESC; -- newest first", 0.9),
+            task("sql-so1-3", "Limit results", "SELECT * FROM products ORDER BY price DESC LIMIT 10; -- return only 10 rows", 0.9),
+            task("sql-so1-4", "Offset results", "SELECT * FROM products ORDER BY id LIMIT 10 OFFSET 20; -- skip rows for pagination", 0.9),
+        ]),
+        lesson("sql-sort-2", "Combining Conditions", "Use AND, OR, NOT, and BETWEEN", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("sql-so2-1", "AND operator", "SELECT * FROM users WHERE active = true AND age > 18; -- both conditions must be true", 0.9),
+            task("sql-so2-2", "OR operator", "SELECT * FROM products WHERE category = 'books' OR category = 'music'; -- either condition", 0.9),
+            task("sql-so2-3", "NOT operator", "SELECT * FROM users WHERE NOT deleted; -- negate a condition", 0.9),
+            task("sql-so2-4", "BETWEEN operator", "SELECT * FROM orders WHERE total BETWEEN 50 AND 200; -- inclusive range check", 0.9),
+        ]),
+
+        // Stage 3: INSERT, UPDATE, DELETE
+        lesson("sql-write-1", "Inserting Data", "Add new rows to tables", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("sql-w1-1", "Insert a single row", "INSERT INTO users (name, email) VALUES ('Alice', 'alice@example.com'); -- add a new row", 0.9),
+            task("sql-w1-2", "Insert multiple rows", "INSERT INTO tags (name) VALUES ('sql'), ('python'), ('rust'); -- batch insert", 0.9),
+            task("sql-w1-3", "Insert with default values", "INSERT INTO orders (user_id, total) VALUES (1, 99.99); -- omitted columns use defaults", 0.9),
+            task("sql-w1-4", "Insert from select", "INSERT INTO archive SELECT * FROM logs WHERE created_at < NOW() - INTERVAL 1 YEAR; -- copy rows from another table", 0.9),
+        ]),
+        lesson("sql-write-2", "Updating & Deleting", "Modify and remove existing data", LessonCategory::Commands, Difficulty::Beginner, vec![
+            task("sql-w2-1", "Update a row", "UPDATE users SET email = 'new@example.com' WHERE id = 1; -- modify existing data", 0.9),
+            task("sql-w2-2", "Update multiple columns", "UPDATE products SET price = 19.99, stock = 50 WHERE id = 42; -- change several fields at once", 0.9),
+            task("sql-w2-3", "Delete a row", "DELETE FROM sessions WHERE expired_at < NOW(); -- remove rows matching condition", 0.9),
+            task("sql-w2-4", "Truncate a table", "TRUNCATE TABLE temp_logs; -- remove all rows instantly", 0.9),
+        ]),
+
+        // Stage 4: Aggregate Functions
+        lesson("sql-agg-1", "COUNT, SUM, AVG", "Summarize data with aggregate functions", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-a1-1", "Count rows", "SELECT COUNT(*) FROM users; -- count the number of rows", 0.9),
+            task("sql-a1-2", "Count with condition", "SELECT COUNT(*) FROM orders WHERE status = 'completed'; -- count filtered rows", 0.9),
+            task("sql-a1-3", "Sum values", "SELECT SUM(total) FROM orders; -- add up all values in a column", 0.9),
+            task("sql-a1-4", "Average value", "SELECT AVG(price) FROM products WHERE category = 'electronics'; -- compute the mean", 0.9),
+        ]),
+        lesson("sql-agg-2", "MIN, MAX & GROUP BY", "Find extremes and group results", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-a2-1", "Find minimum", "SELECT MIN(price) FROM products; -- find the smallest value", 0.9),
+            task("sql-a2-2", "Find maximum", "SELECT MAX(created_at) FROM users; -- find the largest value", 0.9),
+            task("sql-a2-3", "Group by column", "SELECT city, COUNT(*) FROM users GROUP BY city; -- aggregate rows by group", 0.9),
+            task("sql-a2-4", "Group with HAVING", "SELECT city, COUNT(*) FROM users GROUP BY city HAVING COUNT(*) > 5; -- filter groups", 0.9),
+        ]),
+
+        // Stage 5: INNER & LEFT JOINs
+        lesson("sql-join-1", "INNER JOIN", "Combine rows from related tables", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-j1-1", "Basic inner join", "SELECT * FROM orders INNER JOIN users ON orders.user_id = users.id; -- combine matching rows", 0.9),
+            task("sql-j1-2", "Join with aliases", "SELECT o.id, u.name FROM orders o INNER JOIN users u ON o.user_id = u.id; -- use short table aliases", 0.9),
+            task("sql-j1-3", "Join with filter", "SELECT u.name, o.total FROM users u JOIN orders o ON u.id = o.user_id WHERE o.total > 100; -- join then filter", 0.9),
+            task("sql-j1-4", "Multi-table join", "SELECT u.name, p.title FROM users u JOIN orders o ON u.id = o.user_id JOIN products p ON o.product_id = p.id; -- chain multiple joins", 0.9),
+        ]),
+        lesson("sql-join-2", "LEFT & RIGHT JOIN", "Include unmatched rows from one side", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-j2-1", "Left join", "SELECT u.name, o.id FROM users u LEFT JOIN orders o ON u.id = o.user_id; -- keep all left rows", 0.9),
+            task("sql-j2-2", "Find unmatched rows", "SELECT u.name FROM users u LEFT JOIN orders o ON u.id = o.user_id WHERE o.id IS NULL; -- find rows with no match", 0.9),
+            task("sql-j2-3", "Right join", "SELECT o.id, u.name FROM orders o RIGHT JOIN users u ON o.user_id = u.id; -- keep all right rows", 0.9),
+            task("sql-j2-4", "Left join with aggregation", "SELECT u.name, COUNT(o.id) FROM users u LEFT JOIN orders o ON u.id = o.user_id GROUP BY u.name; -- count per user", 0.9),
+        ]),
+
+        // Stage 6: Advanced JOINs
+        lesson("sql-join-3", "FULL & CROSS JOIN", "All join types and self-joins", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-j3-1", "Full outer join", "SELECT * FROM employees e FULL OUTER JOIN departments d ON e.dept_id = d.id; -- keep all rows from both sides", 0.9),
+            task("sql-j3-2", "Cross join", "SELECT colors.name, sizes.label FROM colors CROSS JOIN sizes; -- every combination of rows", 0.9),
+            task("sql-j3-3", "Self join", "SELECT e.name AS employee, m.name AS manager FROM employees e JOIN employees m ON e.manager_id = m.id; -- join a table to itself", 0.9),
+            task("sql-j3-4", "Natural join", "SELECT * FROM orders NATURAL JOIN users; -- join on columns with matching names", 0.9),
+        ]),
+        lesson("sql-join-4", "JOIN Patterns", "Common real-world join patterns", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-j4-1", "Many-to-many join", "SELECT s.name, c.title FROM students s JOIN enrollments e ON s.id = e.student_id JOIN courses c ON e.course_id = c.id; -- bridge table pattern", 0.9),
+            task("sql-j4-2", "Join with subquery", "SELECT u.name, t.total FROM users u JOIN (SELECT user_id, SUM(amount) AS total FROM payments GROUP BY user_id) t ON u.id = t.user_id; -- join to a derived table", 0.9),
+            task("sql-j4-3", "Conditional join", "SELECT * FROM products p JOIN discounts d ON p.category = d.category AND p.price > d.min_price; -- multi-condition join", 0.9),
+            task("sql-j4-4", "Anti-join pattern", "SELECT p.title FROM products p LEFT JOIN order_items oi ON p.id = oi.product_id WHERE oi.id IS NULL; -- find never-ordered products", 0.9),
+        ]),
+
+        // Stage 7: Subqueries
+        lesson("sql-sub-1", "Basic Subqueries", "Use queries inside queries", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-sq1-1", "Subquery in WHERE", "SELECT * FROM users WHERE id IN (SELECT user_id FROM orders WHERE total > 500); -- filter using a subquery", 0.9),
+            task("sql-sq1-2", "Scalar subquery", "SELECT name, price, (SELECT AVG(price) FROM products) AS avg_price FROM products; -- embed a single-value query", 0.9),
+            task("sql-sq1-3", "EXISTS subquery", "SELECT u.name FROM users u WHERE EXISTS (SELECT 1 FROM orders o WHERE o.user_id = u.id); -- check if related rows exist", 0.9),
+            task("sql-sq1-4", "NOT EXISTS subquery", "SELECT u.name FROM users u WHERE NOT EXISTS (SELECT 1 FROM orders o WHERE o.user_id = u.id); -- find rows with no match", 0.9),
+        ]),
+        lesson("sql-sub-2", "Correlated Subqueries", "Subqueries that reference the outer query", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-sq2-1", "Correlated subquery", "SELECT p.title, p.price FROM products p WHERE p.price > (SELECT AVG(price) FROM products WHERE category = p.category); -- reference outer query", 0.9),
+            task("sql-sq2-2", "Subquery in FROM", "SELECT dept, avg_salary FROM (SELECT department AS dept, AVG(salary) AS avg_salary FROM employees GROUP BY department) sub; -- derived table", 0.9),
+            task("sql-sq2-3", "ALL operator", "SELECT * FROM products WHERE price >= ALL (SELECT price FROM products WHERE category = 'premium'); -- compare against every row", 0.9),
+            task("sql-sq2-4", "ANY operator", "SELECT * FROM users WHERE age > ANY (SELECT age FROM users WHERE city = 'NYC'); -- compare against any row", 0.9),
+        ]),
+
+        // Stage 8: String & Date Functions
+        lesson("sql-func-1", "String Functions", "Manipulate text data in queries", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-fn1-1", "Concatenate strings", "SELECT CONCAT(first_name, ' ', last_name) AS full_name FROM users; -- join strings together", 0.9),
+            task("sql-fn1-2", "Uppercase and lowercase", "SELECT UPPER(name), LOWER(email) FROM users; -- change text case", 0.9),
+            task("sql-fn1-3", "Substring extraction", "SELECT SUBSTRING(phone, 1, 3) AS area_code FROM contacts; -- extract part of a string", 0.9),
+            task("sql-fn1-4", "Replace and trim", "SELECT TRIM(name), REPLACE(email, 'old.com', 'new.com') FROM users; -- clean and transform text", 0.9),
+        ]),
+        lesson("sql-func-2", "Date & Time Functions", "Work with dates and timestamps", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-fn2-1", "Current date and time", "SELECT NOW(), CURRENT_DATE, CURRENT_TIMESTAMP; -- get the current date and time", 0.9),
+            task("sql-fn2-2", "Extract date parts", "SELECT EXTRACT(YEAR FROM created_at), EXTRACT(MONTH FROM created_at) FROM orders; -- pull out year or month", 0.9),
+            task("sql-fn2-3", "Date arithmetic", "SELECT * FROM subscriptions WHERE end_date > NOW() + INTERVAL '30 days'; -- add or subtract time", 0.9),
+            task("sql-fn2-4", "Format dates", "SELECT TO_CHAR(created_at, 'YYYY-MM-DD') AS date_str FROM events; -- format a timestamp as text", 0.9),
+        ]),
+
+        // Stage 9: CASE, COALESCE & Type Casting
+        lesson("sql-case-1", "CASE Expressions", "Add conditional logic to queries", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-cs1-1", "Simple CASE", "SELECT name, CASE status WHEN 'active' THEN 'Active' WHEN 'inactive' THEN 'Inactive' END FROM users; -- value-based branching", 0.9),
+            task("sql-cs1-2", "Searched CASE", "SELECT name, CASE WHEN age < 18 THEN 'minor' WHEN age < 65 THEN 'adult' ELSE 'senior' END AS group_label FROM users; -- condition-based branching", 0.9),
+            task("sql-cs1-3", "CASE in aggregate", "SELECT COUNT(CASE WHEN status = 'completed' THEN 1 END) AS completed FROM orders; -- conditional counting", 0.9),
+            task("sql-cs1-4", "CASE in ORDER BY", "SELECT * FROM tasks ORDER BY CASE priority WHEN 'high' THEN 1 WHEN 'medium' THEN 2 ELSE 3 END; -- custom sort order", 0.9),
+        ]),
+        lesson("sql-case-2", "COALESCE & NULL Handling", "Handle NULL values gracefully", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-cs2-1", "COALESCE function", "SELECT COALESCE(nickname, first_name, 'Anonymous') AS display_name FROM users; -- return first non-null value", 0.9),
+            task("sql-cs2-2", "NULLIF function", "SELECT NULLIF(status, 'unknown') FROM records; -- return null if values are equal", 0.9),
+            task("sql-cs2-3", "IS NULL check", "SELECT * FROM users WHERE deleted_at IS NULL; -- test for null values", 0.9),
+            task("sql-cs2-4", "Cast types", "SELECT CAST(price AS INTEGER), CAST(created_at AS DATE) FROM products; -- convert between data types", 0.9),
+        ]),
+
+        // Stage 10: Table DDL
+        lesson("sql-ddl-1", "CREATE TABLE", "Define new tables with constraints", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("sql-d1-1", "Create a table", "CREATE TABLE users (id SERIAL PRIMARY KEY, name VARCHAR(100) NOT NULL, email VARCHAR(255) UNIQUE); -- define a new table", 0.9),
+            task("sql-d1-2", "Add foreign key", "CREATE TABLE orders (id SERIAL PRIMARY KEY, user_id INTEGER REFERENCES users(id), total DECIMAL(10,2)); -- reference another table", 0.9),
+            task("sql-d1-3", "Add check constraint", "CREATE TABLE products (id SERIAL PRIMARY KEY, price DECIMAL(10,2) CHECK (price > 0), stock INTEGER DEFAULT 0); -- enforce data rules", 0.9),
+            task("sql-d1-4", "Create with timestamp", "CREATE TABLE posts (id SERIAL PRIMARY KEY, title TEXT NOT NULL, created_at TIMESTAMP DEFAULT NOW()); -- auto-set creation time", 0.9),
+        ]),
+        lesson("sql-ddl-2", "ALTER & DROP TABLE", "Modify and remove table structures", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("sql-d2-1", "Add a column", "ALTER TABLE users ADD COLUMN phone VARCHAR(20); -- add a new column to a table", 0.9),
+            task("sql-d2-2", "Drop a column", "ALTER TABLE users DROP COLUMN phone; -- remove a column from a table", 0.9),
+            task("sql-d2-3", "Rename a column", "ALTER TABLE users RENAME COLUMN name TO full_name; -- change a column name", 0.9),
+            task("sql-d2-4", "Drop table safely", "DROP TABLE IF EXISTS temp_data CASCADE; -- delete a table if it exists", 0.9),
+        ]),
+
+        // Stage 11: Indexes & Performance
+        lesson("sql-idx-1", "Creating Indexes", "Speed up queries with indexes", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("sql-i1-1", "Create an index", "CREATE INDEX idx_users_email ON users (email); -- speed up lookups on a column", 0.9),
+            task("sql-i1-2", "Create unique index", "CREATE UNIQUE INDEX idx_users_username ON users (username); -- enforce uniqueness with an index", 0.9),
+            task("sql-i1-3", "Composite index", "CREATE INDEX idx_orders_user_date ON orders (user_id, created_at); -- index on multiple columns", 0.9),
+            task("sql-i1-4", "Drop an index", "DROP INDEX IF EXISTS idx_users_email; -- remove an index", 0.9),
+        ]),
+        lesson("sql-idx-2", "EXPLAIN & Query Plans", "Analyze and optimize query performance", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("sql-i2-1", "Explain a query", "EXPLAIN SELECT * FROM users WHERE email = 'test@example.com'; -- show the query execution plan", 0.9),
+            task("sql-i2-2", "Explain analyze", "EXPLAIN ANALYZE SELECT * FROM orders WHERE user_id = 42; -- run and measure actual performance", 0.9),
+            task("sql-i2-3", "Partial index", "CREATE INDEX idx_active_users ON users (email) WHERE active = true; -- index only matching rows", 0.9),
+            task("sql-i2-4", "Expression index", "CREATE INDEX idx_lower_email ON users (LOWER(email)); -- index on a computed expression", 0.9),
+        ]),
+
+        // Stage 12: Window Functions
+        lesson("sql-win-1", "ROW_NUMBER & RANK", "Assign row numbers and rankings", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("sql-wn1-1", "ROW_NUMBER", "SELECT name, salary, ROW_NUMBER() OVER (ORDER BY salary DESC) AS rank FROM employees; -- assign sequential numbers", 0.9),
+            task("sql-wn1-2", "RANK with ties", "SELECT name, score, RANK() OVER (ORDER BY score DESC) AS rank FROM students; -- rank with gaps for ties", 0.9),
+            task("sql-wn1-3", "DENSE_RANK", "SELECT name, score, DENSE_RANK() OVER (ORDER BY score DESC) AS dense_rank FROM students; -- rank without gaps", 0.9),
+            task("sql-wn1-4", "Partition by", "SELECT dept, name, salary, ROW_NUMBER() OVER (PARTITION BY dept ORDER BY salary DESC) AS dept_rank FROM employees; -- rank within groups", 0.9),
+        ]),
+        lesson("sql-win-2", "LAG, LEAD & Aggregates", "Access adjacent rows and running totals", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("sql-wn2-1", "LAG function", "SELECT date, revenue, LAG(revenue) OVER (ORDER BY date) AS prev_revenue FROM daily_sales; -- access the previous row", 0.9),
+            task("sql-wn2-2", "LEAD function", "SELECT date, price, LEAD(price) OVER (ORDER BY date) AS next_price FROM stocks; -- access the next row", 0.9),
+            task("sql-wn2-3", "Running total", "SELECT date, amount, SUM(amount) OVER (ORDER BY date) AS running_total FROM transactions; -- cumulative sum", 0.9),
+            task("sql-wn2-4", "Moving average", "SELECT date, value, AVG(value) OVER (ORDER BY date ROWS BETWEEN 6 PRECEDING AND CURRENT ROW) AS moving_avg FROM metrics; -- 7-day rolling average", 0.9),
+        ]),
+
+        // Stage 13: CTEs & Transactions
+        lesson("sql-cte-1", "Common Table Expressions", "Write readable queries with WITH clauses", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("sql-ct1-1", "Basic CTE", "WITH active_users AS (SELECT * FROM users WHERE active = true) SELECT * FROM active_users WHERE created_at > NOW() - INTERVAL '30 days'; -- named temporary result set", 0.9),
+            task("sql-ct1-2", "Multiple CTEs", "WITH buyers AS (SELECT DISTINCT user_id FROM orders), active AS (SELECT id FROM users WHERE active = true) SELECT * FROM buyers JOIN active ON buyers.user_id = active.id; -- chain CTEs", 0.9),
+            task("sql-ct1-3", "Recursive CTE", "WITH RECURSIVE tree AS (SELECT id, name, parent_id, 0 AS depth FROM categories WHERE parent_id IS NULL UNION ALL SELECT c.id, c.name, c.parent_id, t.depth + 1 FROM categories c JOIN tree t ON c.parent_id = t.id) SELECT * FROM tree; -- traverse hierarchies", 0.9),
+            task("sql-ct1-4", "CTE with aggregation", "WITH monthly AS (SELECT DATE_TRUNC('month', created_at) AS month, SUM(total) AS revenue FROM orders GROUP BY 1) SELECT month, revenue FROM monthly ORDER BY month; -- summarize by month", 0.9),
+        ]),
+        lesson("sql-cte-2", "Transactions & Locks", "Ensure data integrity with transactions", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("sql-ct2-1", "Begin a transaction", "BEGIN; UPDATE accounts SET balance = balance - 100 WHERE id = 1; UPDATE accounts SET balance = balance + 100 WHERE id = 2; COMMIT; -- atomic transfer", 0.9),
+            task("sql-ct2-2", "Rollback on error", "BEGIN; DELETE FROM orders WHERE id = 99; ROLLBACK; -- undo all changes", 0.9),
+            task("sql-ct2-3", "Savepoints", "BEGIN; SAVEPOINT sp1; UPDATE users SET name = 'test'; ROLLBACK TO sp1; COMMIT; -- partial rollback", 0.9),
+            task("sql-ct2-4", "Select for update", "SELECT * FROM inventory WHERE product_id = 1 FOR UPDATE; -- lock rows for update", 0.9),
+        ]),
+
+        // Stage 14: Views & Stored Procedures
+        lesson("sql-view-1", "Views", "Create reusable virtual tables", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("sql-v1-1", "Create a view", "CREATE VIEW active_users AS SELECT id, name, email FROM users WHERE active = true; -- define a reusable virtual table", 0.9),
+            task("sql-v1-2", "Query a view", "SELECT * FROM active_users WHERE name LIKE 'A%'; -- query a view like a table", 0.9),
+            task("sql-v1-3", "Materialized view", "CREATE MATERIALIZED VIEW monthly_revenue AS SELECT DATE_TRUNC('month', created_at) AS month, SUM(total) AS revenue FROM orders GROUP BY 1; -- cached query result", 0.9),
+            task("sql-v1-4", "Refresh materialized view", "REFRESH MATERIALIZED VIEW CONCURRENTLY monthly_revenue; -- update cached data", 0.9),
+        ]),
+        lesson("sql-view-2", "Functions & Procedures", "Encapsulate logic in the database", LessonCategory::Commands, Difficulty::Expert, vec![
+            task("sql-v2-1", "Create a function", "CREATE FUNCTION get_user_count() RETURNS INTEGER AS $$ SELECT COUNT(*) FROM users; $$ LANGUAGE SQL; -- reusable logic", 0.9),
+            task("sql-v2-2", "Function with parameter", "CREATE FUNCTION get_order_total(uid INTEGER) RETURNS DECIMAL AS $$ SELECT SUM(total) FROM orders WHERE user_id = uid; $$ LANGUAGE SQL; -- parameterized function", 0.9),
+            task("sql-v2-3", "Create a trigger", "CREATE TRIGGER update_timestamp BEFORE UPDATE ON users FOR EACH ROW EXECUTE FUNCTION set_updated_at(); -- auto-run on changes", 0.9),
+            task("sql-v2-4", "Drop function safely", "DROP FUNCTION IF EXISTS get_user_count(); -- remove a function if it exists", 0.9),
+        ]),
+
+        // Stage 15: SQL Mastery Test
+        lesson("sql-test-basic", "Fundamentals Test", "Test your core SQL knowledge", LessonCategory::Commands, Difficulty::Intermediate, vec![
+            task("sql-tb1", "Select with filter and sort", "SELECT name, email FROM users WHERE active = true ORDER BY created_at DESC LIMIT 20; -- filter, sort, limit", 0.9),
+            task("sql-tb2", "Insert and update", "INSERT INTO users (name, email) VALUES ('Bob', 'bob@test.com'); UPDATE users SET active = true WHERE email = 'bob@test.com'; -- insert then update", 0.9),
+            task("sql-tb3", "Group and aggregate", "SELECT category, COUNT(*) AS cnt, AVG(price) AS avg_price FROM products GROUP BY category HAVING COUNT(*) > 3; -- group with filter", 0.9),
+            task("sql-tb4", "Inner join with filter", "SELECT u.name, o.total FROM users u JOIN orders o ON u.id = o.user_id WHERE o.total > 100 ORDER BY o.total DESC; -- join and filter", 0.9),
+            task("sql-tb5", "Subquery in WHERE", "SELECT * FROM products WHERE price > (SELECT AVG(price) FROM products); -- above-average prices", 0.9),
+        ]),
+        lesson("sql-test-intermediate", "Intermediate Test", "Test joins, subqueries, and functions", LessonCategory::Commands, Difficulty::Advanced, vec![
+            task("sql-ti1", "Left join with NULL check", "SELECT u.name FROM users u LEFT JOIN orders o ON u.id = o.user_id WHERE o.id IS NULL; -- users without orders", 0.9),
+            task("sql-ti2", "CTE with aggregation", "WITH monthly AS (SELECT DATE_TRUNC('month', created_at) AS m, SUM(total) AS rev FROM orders GROUP BY 1) SELECT m, rev FROM monthly ORDER BY m; -- monthly revenue", 0.9),
+            task("sql-ti3", "Window function ranking", "SELECT name, salary, RANK() OVER (PARTITION BY department ORDER BY salary DESC) AS dept_rank FROM employees; -- rank per department", 0.9),
+            task("sql-ti4", "CASE with aggregation", "SELECT COUNT(CASE WHEN status = 'active' THEN 1 END) AS active, COUNT(CASE WHEN status = 'inactive' THEN 1 END) AS inactive FROM users; -- pivot counts", 0.9),
+            task("sql-ti5", "Correlated subquery", "SELECT p.title FROM products p WHERE p.price > (SELECT AVG(price) FROM products WHERE category = p.category); -- above category average", 0.9),
+        ]),
+        lesson("sql-test-advanced", "Advanced Test", "Test DDL, transactions, and optimization", LessonCategory::Commands, Difficulty::Expert, vec![
+            task("sql-ta1", "Create table with constraints", "CREATE TABLE reviews (id SERIAL PRIMARY KEY, user_id INTEGER REFERENCES users(id), rating INTEGER CHECK (rating BETWEEN 1 AND 5), body TEXT, created_at TIMESTAMP DEFAULT NOW()); -- full DDL", 0.9),
+            task("sql-ta2", "Transaction with savepoint", "BEGIN; UPDATE inventory SET stock = stock - 1 WHERE product_id = 1; SAVEPOINT check_stock; SELECT stock FROM inventory WHERE product_id = 1; COMMIT; -- safe stock update", 0.9),
+            task("sql-ta3", "Recursive CTE", "WITH RECURSIVE chain AS (SELECT id, name, manager_id FROM employees WHERE manager_id IS NULL UNION ALL SELECT e.id, e.name, e.manager_id FROM employees e JOIN chain c ON e.manager_id = c.id) SELECT * FROM chain; -- org chart", 0.9),
+            task("sql-ta4", "Create index and explain", "CREATE INDEX idx_orders_date ON orders (created_at); EXPLAIN ANALYZE SELECT * FROM orders WHERE created_at > '2024-01-01'; -- index then measure", 0.9),
+            task("sql-ta5", "Complex window function", "SELECT date, revenue, SUM(revenue) OVER (ORDER BY date ROWS BETWEEN 6 PRECEDING AND CURRENT ROW) AS weekly_total, LAG(revenue, 7) OVER (ORDER BY date) AS last_week FROM daily_sales; -- weekly comparison", 0.9),
+        ]),
+        lesson("sql-test-master", "SQL Mastery Final Test", "Comprehensive SQL challenge", LessonCategory::Commands, Difficulty::Expert, vec![
+            task("sql-tm1", "Multi-join aggregation", "SELECT c.name, COUNT(o.id) AS order_count, SUM(o.total) AS total_spent FROM customers c JOIN orders o ON c.id = o.customer_id JOIN order_items oi ON o.id = oi.order_id GROUP BY c.name HAVING SUM(o.total) > 1000 ORDER BY total_spent DESC; -- top customer report", 0.9),
+            task("sql-tm2", "Window + CTE combo", "WITH ranked AS (SELECT name, department, salary, DENSE_RANK() OVER (PARTITION BY department ORDER BY salary DESC) AS rank FROM employees) SELECT * FROM ranked WHERE rank <= 3; -- top 3 per department", 0.9),
+            task("sql-tm3", "Upsert pattern", "INSERT INTO user_stats (user_id, login_count) VALUES (1, 1) ON CONFLICT (user_id) DO UPDATE SET login_count = user_stats.login_count + 1; -- insert or increment", 0.9),
+            task("sql-tm4", "Materialized view with index", "CREATE MATERIALIZED VIEW top_products AS SELECT p.id, p.title, SUM(oi.quantity) AS total_sold FROM products p JOIN order_items oi ON p.id = oi.product_id GROUP BY p.id, p.title; CREATE INDEX idx_top_products_sold ON top_products (total_sold DESC); -- cached ranking", 0.9),
+            task("sql-tm5", "Full reporting query", "SELECT DATE_TRUNC('month', o.created_at) AS month, COUNT(DISTINCT o.user_id) AS unique_buyers, COUNT(o.id) AS total_orders, SUM(o.total) AS revenue, AVG(o.total) AS avg_order_value FROM orders o WHERE o.created_at >= NOW() - INTERVAL '12 months' GROUP BY 1 ORDER BY 1; -- monthly dashboard", 0.9),
+        ]),
+    ]
+}
+
+fn pyspark_lessons() -> Vec<Lesson> {
+    vec![
+        // Stage 1: Getting Started with PySpark
+        lesson("pyspark-start-1", "SparkSession Basics", "Create and configure a SparkSession", LessonCategory::Code, Difficulty::Beginner, vec![
+            task("ps-s1-1", "Import SparkSession", "from pyspark.sql import SparkSession", 0.9),
+            task("ps-s1-2", "Create a SparkSession", "spark = SparkSession.builder.appName(\"MyApp\").getOrCreate()", 0.9),
+            task("ps-s1-3", "Create with config", "spark = SparkSession.builder.appName(\"MyApp\").config(\"spark.sql.shuffle.partitions\", \"200\").getOrCreate()", 0.9),
+            task("ps-s1-4", "Stop a session", "spark.stop() # always stop when done", 0.9),
+        ]),
+        lesson("pyspark-start-2", "Creating DataFrames", "Create DataFrames from various sources", LessonCategory::Code, Difficulty::Beginner, vec![
+            task("ps-s2-1", "Create from list", "df = spark.createDataFrame([(1, \"Alice\"), (2, \"Bob\")], [\"id\", \"name\"])", 0.9),
+            task("ps-s2-2", "Read CSV file", "df = spark.read.csv(\"data.csv\", header=True, inferSchema=True)", 0.9),
+            task("ps-s2-3", "Read JSON file", "df = spark.read.json(\"data.json\")", 0.9),
+            task("ps-s2-4", "Read Parquet file", "df = spark.read.parquet(\"data.parquet\")", 0.9),
+        ]),
+
+        // Stage 2: DataFrame Basics
+        lesson("pyspark-df-1", "Exploring DataFrames", "Inspect and understand your data", LessonCategory::Code, Difficulty::Beginner, vec![
+            task("ps-d1-1", "Show data", "df.show() # display first 20 rows", 0.9),
+            task("ps-d1-2", "Print schema", "df.printSchema() # show column names and types", 0.9),
+            task("ps-d1-3", "Count rows", "df.count() # return the number of rows", 0.9),
+            task("ps-d1-4", "Get columns", "df.columns # list of column names", 0.9),
+        ]),
+        lesson("pyspark-df-2", "Selecting Columns", "Choose and rename columns", LessonCategory::Code, Difficulty::Beginner, vec![
+            task("ps-d2-1", "Select columns", "df.select(\"name\", \"age\").show()", 0.9),
+            task("ps-d2-2", "Select with col", "from pyspark.sql.functions import col\ndf.select(col(\"name\"), col(\"age\")).show()", 0.9),
+            task("ps-d2-3", "Rename a column", "df.withColumnRenamed(\"name\", \"full_name\").show()", 0.9),
+            task("ps-d2-4", "Select with alias", "df.select(col(\"name\").alias(\"user_name\")).show()", 0.9),
+        ]),
+
+        // Stage 3: Filtering & Sorting
+        lesson("pyspark-filter-1", "Filtering Rows", "Filter data using conditions", LessonCategory::Code, Difficulty::Beginner, vec![
+            task("ps-f1-1", "Filter by condition", "df.filter(col(\"age\") > 25).show()", 0.9),
+            task("ps-f1-2", "Filter with where", "df.where(col(\"status\") == \"active\").show()", 0.9),
+            task("ps-f1-3", "Multiple conditions", "df.filter((col(\"age\") > 25) & (col(\"city\") == \"NYC\")).show()", 0.9),
+            task("ps-f1-4", "Filter with isin", "df.filter(col(\"city\").isin(\"NYC\", \"LA\", \"SF\")).show()", 0.9),
+        ]),
+        lesson("pyspark-filter-2", "Sorting Data", "Order your DataFrame rows", LessonCategory::Code, Difficulty::Beginner, vec![
+            task("ps-f2-1", "Sort ascending", "df.orderBy(\"age\").show()", 0.9),
+            task("ps-f2-2", "Sort descending", "df.orderBy(col(\"age\").desc()).show()", 0.9),
+            task("ps-f2-3", "Sort by multiple columns", "df.orderBy(col(\"city\").asc(), col(\"age\").desc()).show()", 0.9),
+            task("ps-f2-4", "Limit results", "df.orderBy(col(\"salary\").desc()).limit(10).show()", 0.9),
+        ]),
+
+        // Stage 4: Adding & Transforming Columns
+        lesson("pyspark-col-1", "Adding Columns", "Create new columns from existing data", LessonCategory::Code, Difficulty::Beginner, vec![
+            task("ps-c1-1", "Add a constant column", "from pyspark.sql.functions import lit\ndf.withColumn(\"country\", lit(\"US\")).show()", 0.9),
+            task("ps-c1-2", "Add computed column", "df.withColumn(\"salary_monthly\", col(\"salary\") / 12).show()", 0.9),
+            task("ps-c1-3", "Add conditional column", "from pyspark.sql.functions import when\ndf.withColumn(\"level\", when(col(\"age\") > 30, \"senior\").otherwise(\"junior\")).show()", 0.9),
+            task("ps-c1-4", "Drop a column", "df.drop(\"temp_column\").show()", 0.9),
+        ]),
+        lesson("pyspark-col-2", "String Functions", "Manipulate text columns", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-c2-1", "Upper case", "from pyspark.sql.functions import upper\ndf.withColumn(\"name_upper\", upper(col(\"name\"))).show()", 0.9),
+            task("ps-c2-2", "Concatenate strings", "from pyspark.sql.functions import concat, lit\ndf.withColumn(\"full\", concat(col(\"first\"), lit(\" \"), col(\"last\"))).show()", 0.9),
+            task("ps-c2-3", "Substring", "from pyspark.sql.functions import substring\ndf.withColumn(\"code\", substring(col(\"id\"), 1, 3)).show()", 0.9),
+            task("ps-c2-4", "Trim whitespace", "from pyspark.sql.functions import trim\ndf.withColumn(\"name_clean\", trim(col(\"name\"))).show()", 0.9),
+        ]),
+
+        // Stage 5: Aggregations
+        lesson("pyspark-agg-1", "Basic Aggregations", "Summarize data with aggregate functions", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-a1-1", "Count rows", "df.groupBy(\"department\").count().show()", 0.9),
+            task("ps-a1-2", "Sum values", "from pyspark.sql.functions import sum\ndf.groupBy(\"department\").agg(sum(\"salary\")).show()", 0.9),
+            task("ps-a1-3", "Average values", "from pyspark.sql.functions import avg\ndf.groupBy(\"department\").agg(avg(\"salary\").alias(\"avg_salary\")).show()", 0.9),
+            task("ps-a1-4", "Min and max", "from pyspark.sql.functions import min, max\ndf.groupBy(\"department\").agg(min(\"salary\"), max(\"salary\")).show()", 0.9),
+        ]),
+        lesson("pyspark-agg-2", "Multiple Aggregations", "Combine multiple aggregate functions", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-a2-1", "Multiple agg functions", "df.groupBy(\"dept\").agg(count(\"*\").alias(\"total\"), avg(\"salary\").alias(\"avg_pay\")).show()", 0.9),
+            task("ps-a2-2", "Aggregate without group", "from pyspark.sql.functions import count, sum\ndf.agg(count(\"*\").alias(\"total\"), sum(\"revenue\").alias(\"total_rev\")).show()", 0.9),
+            task("ps-a2-3", "Count distinct", "from pyspark.sql.functions import countDistinct\ndf.agg(countDistinct(\"city\").alias(\"unique_cities\")).show()", 0.9),
+            task("ps-a2-4", "Collect list", "from pyspark.sql.functions import collect_list\ndf.groupBy(\"dept\").agg(collect_list(\"name\").alias(\"members\")).show()", 0.9),
+        ]),
+
+        // Stage 6: Joins
+        lesson("pyspark-join-1", "Inner & Left Joins", "Combine DataFrames on matching keys", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-j1-1", "Inner join", "result = df1.join(df2, df1.id == df2.user_id, \"inner\")", 0.9),
+            task("ps-j1-2", "Inner join on same column", "result = df1.join(df2, \"id\", \"inner\")", 0.9),
+            task("ps-j1-3", "Left outer join", "result = df1.join(df2, \"id\", \"left\")", 0.9),
+            task("ps-j1-4", "Left join with select", "result = df1.join(df2, \"id\", \"left\").select(df1.name, df2.score)", 0.9),
+        ]),
+        lesson("pyspark-join-2", "Advanced Joins", "Full outer, cross, and anti joins", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-j2-1", "Full outer join", "result = df1.join(df2, \"id\", \"full\")", 0.9),
+            task("ps-j2-2", "Cross join", "result = df1.crossJoin(df2) # cartesian product", 0.9),
+            task("ps-j2-3", "Left anti join", "result = df1.join(df2, \"id\", \"left_anti\") # rows in df1 not in df2", 0.9),
+            task("ps-j2-4", "Left semi join", "result = df1.join(df2, \"id\", \"left_semi\") # rows in df1 that exist in df2", 0.9),
+        ]),
+
+        // Stage 7: Null Handling & Type Casting
+        lesson("pyspark-null-1", "Handling Nulls", "Deal with missing values", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-n1-1", "Drop rows with nulls", "df.na.drop().show() # remove rows with any null", 0.9),
+            task("ps-n1-2", "Drop if column null", "df.na.drop(subset=[\"email\"]).show() # drop if email is null", 0.9),
+            task("ps-n1-3", "Fill nulls with value", "df.na.fill(0, subset=[\"salary\"]).show() # replace nulls with 0", 0.9),
+            task("ps-n1-4", "Fill with dict", "df.na.fill({\"salary\": 0, \"name\": \"Unknown\"}).show()", 0.9),
+        ]),
+        lesson("pyspark-null-2", "Type Casting & Nulls", "Cast types and use coalesce", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-n2-1", "Cast column type", "df.withColumn(\"age\", col(\"age\").cast(\"integer\")).show()", 0.9),
+            task("ps-n2-2", "Cast to date", "from pyspark.sql.functions import to_date\ndf.withColumn(\"date\", to_date(col(\"date_str\"), \"yyyy-MM-dd\")).show()", 0.9),
+            task("ps-n2-3", "Coalesce nulls", "from pyspark.sql.functions import coalesce\ndf.withColumn(\"val\", coalesce(col(\"primary\"), col(\"fallback\"))).show()", 0.9),
+            task("ps-n2-4", "Check for null", "df.filter(col(\"email\").isNull()).show() # find null rows", 0.9),
+        ]),
+
+        // Stage 8: Window Functions
+        lesson("pyspark-win-1", "Window Basics", "Rank and number rows within partitions", LessonCategory::Code, Difficulty::Advanced, vec![
+            task("ps-w1-1", "Define a window", "from pyspark.sql.window import Window\nw = Window.partitionBy(\"dept\").orderBy(\"salary\")", 0.9),
+            task("ps-w1-2", "Row number", "from pyspark.sql.functions import row_number\ndf.withColumn(\"rn\", row_number().over(w)).show()", 0.9),
+            task("ps-w1-3", "Rank function", "from pyspark.sql.functions import rank\ndf.withColumn(\"rank\", rank().over(w)).show()", 0.9),
+            task("ps-w1-4", "Dense rank", "from pyspark.sql.functions import dense_rank\ndf.withColumn(\"drank\", dense_rank().over(w)).show()", 0.9),
+        ]),
+        lesson("pyspark-win-2", "Window Aggregations", "Running totals and lag/lead", LessonCategory::Code, Difficulty::Advanced, vec![
+            task("ps-w2-1", "Running sum", "from pyspark.sql.functions import sum\nw = Window.partitionBy(\"dept\").orderBy(\"date\").rowsBetween(Window.unboundedPreceding, 0)\ndf.withColumn(\"running_total\", sum(\"amount\").over(w)).show()", 0.9),
+            task("ps-w2-2", "Lag function", "from pyspark.sql.functions import lag\ndf.withColumn(\"prev_salary\", lag(\"salary\", 1).over(w)).show()", 0.9),
+            task("ps-w2-3", "Lead function", "from pyspark.sql.functions import lead\ndf.withColumn(\"next_salary\", lead(\"salary\", 1).over(w)).show()", 0.9),
+            task("ps-w2-4", "Moving average", "w = Window.partitionBy(\"dept\").orderBy(\"date\").rowsBetween(-2, 0)\ndf.withColumn(\"moving_avg\", avg(\"value\").over(w)).show()", 0.9),
+        ]),
+
+        // Stage 9: Date & Timestamp Functions
+        lesson("pyspark-date-1", "Date Functions", "Work with dates in PySpark", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-dt1-1", "Current date", "from pyspark.sql.functions import current_date\ndf.withColumn(\"today\", current_date()).show()", 0.9),
+            task("ps-dt1-2", "Date difference", "from pyspark.sql.functions import datediff\ndf.withColumn(\"days\", datediff(col(\"end\"), col(\"start\"))).show()", 0.9),
+            task("ps-dt1-3", "Add months", "from pyspark.sql.functions import add_months\ndf.withColumn(\"next_q\", add_months(col(\"date\"), 3)).show()", 0.9),
+            task("ps-dt1-4", "Extract year and month", "from pyspark.sql.functions import year, month\ndf.withColumn(\"yr\", year(col(\"date\"))).withColumn(\"mo\", month(col(\"date\"))).show()", 0.9),
+        ]),
+        lesson("pyspark-date-2", "Timestamp Operations", "Format and manipulate timestamps", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-dt2-1", "Current timestamp", "from pyspark.sql.functions import current_timestamp\ndf.withColumn(\"now\", current_timestamp()).show()", 0.9),
+            task("ps-dt2-2", "Format timestamp", "from pyspark.sql.functions import date_format\ndf.withColumn(\"formatted\", date_format(col(\"ts\"), \"yyyy-MM-dd HH:mm\")).show()", 0.9),
+            task("ps-dt2-3", "Unix timestamp", "from pyspark.sql.functions import unix_timestamp\ndf.withColumn(\"epoch\", unix_timestamp(col(\"ts\"))).show()", 0.9),
+            task("ps-dt2-4", "Truncate to month", "from pyspark.sql.functions import date_trunc\ndf.withColumn(\"month_start\", date_trunc(\"month\", col(\"date\"))).show()", 0.9),
+        ]),
+
+        // Stage 10: UDFs & Complex Types
+        lesson("pyspark-udf-1", "User Defined Functions", "Create and apply custom functions", LessonCategory::Code, Difficulty::Advanced, vec![
+            task("ps-u1-1", "Define a UDF", "from pyspark.sql.functions import udf\nfrom pyspark.sql.types import StringType\n@udf(returnType=StringType())\ndef greet(name):\n    return f\"Hello, {name}\"", 0.9),
+            task("ps-u1-2", "Apply a UDF", "df.withColumn(\"greeting\", greet(col(\"name\"))).show()", 0.9),
+            task("ps-u1-3", "Lambda UDF", "square = udf(lambda x: x * x, IntegerType())\ndf.withColumn(\"squared\", square(col(\"value\"))).show()", 0.9),
+            task("ps-u1-4", "Pandas UDF", "from pyspark.sql.functions import pandas_udf\n@pandas_udf(\"double\")\ndef multiply(s: pd.Series) -> pd.Series:\n    return s * 2", 0.9),
+        ]),
+        lesson("pyspark-udf-2", "Arrays & Maps", "Work with complex column types", LessonCategory::Code, Difficulty::Advanced, vec![
+            task("ps-u2-1", "Create array column", "from pyspark.sql.functions import array\ndf.withColumn(\"tags\", array(col(\"tag1\"), col(\"tag2\"))).show()", 0.9),
+            task("ps-u2-2", "Explode array", "from pyspark.sql.functions import explode\ndf.select(\"id\", explode(\"tags\").alias(\"tag\")).show()", 0.9),
+            task("ps-u2-3", "Array contains", "from pyspark.sql.functions import array_contains\ndf.filter(array_contains(col(\"tags\"), \"python\")).show()", 0.9),
+            task("ps-u2-4", "Create map column", "from pyspark.sql.functions import create_map\ndf.withColumn(\"info\", create_map(lit(\"name\"), col(\"name\"))).show()", 0.9),
+        ]),
+
+        // Stage 11: Reading & Writing Data
+        lesson("pyspark-io-1", "Reading Data", "Load data from various formats", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-io1-1", "Read CSV with options", "df = spark.read.option(\"header\", True).option(\"delimiter\", \";\").csv(\"data.csv\")", 0.9),
+            task("ps-io1-2", "Read with schema", "from pyspark.sql.types import StructType, StructField, StringType, IntegerType\nschema = StructType([StructField(\"name\", StringType()), StructField(\"age\", IntegerType())])", 0.9),
+            task("ps-io1-3", "Read from JDBC", "df = spark.read.format(\"jdbc\").option(\"url\", \"jdbc:postgresql://localhost/db\").option(\"dbtable\", \"users\").load()", 0.9),
+            task("ps-io1-4", "Read Delta table", "df = spark.read.format(\"delta\").load(\"/data/events\")", 0.9),
+        ]),
+        lesson("pyspark-io-2", "Writing Data", "Save DataFrames to storage", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-io2-1", "Write Parquet", "df.write.parquet(\"output/data.parquet\")", 0.9),
+            task("ps-io2-2", "Write with partition", "df.write.partitionBy(\"year\", \"month\").parquet(\"output/partitioned\")", 0.9),
+            task("ps-io2-3", "Write mode overwrite", "df.write.mode(\"overwrite\").csv(\"output/data.csv\", header=True)", 0.9),
+            task("ps-io2-4", "Write to table", "df.write.mode(\"append\").saveAsTable(\"analytics.events\")", 0.9),
+        ]),
+
+        // Stage 12: Spark SQL
+        lesson("pyspark-sql-1", "SQL Queries in PySpark", "Use SQL syntax with DataFrames", LessonCategory::Code, Difficulty::Intermediate, vec![
+            task("ps-sq1-1", "Register temp view", "df.createOrReplaceTempView(\"users\")", 0.9),
+            task("ps-sq1-2", "Run SQL query", "result = spark.sql(\"SELECT name, age FROM users WHERE age > 25\")", 0.9),
+            task("ps-sq1-3", "SQL with aggregation", "result = spark.sql(\"SELECT dept, AVG(salary) as avg_sal FROM employees GROUP BY dept\")", 0.9),
+            task("ps-sq1-4", "SQL join", "result = spark.sql(\"SELECT u.name, o.total FROM users u JOIN orders o ON u.id = o.user_id\")", 0.9),
+        ]),
+        lesson("pyspark-sql-2", "Advanced SQL Features", "CTEs, subqueries, and SQL functions", LessonCategory::Code, Difficulty::Advanced, vec![
+            task("ps-sq2-1", "Common Table Expression", "result = spark.sql(\"WITH top_users AS (SELECT * FROM users WHERE score > 90) SELECT * FROM top_users\")", 0.9),
+            task("ps-sq2-2", "Subquery", "result = spark.sql(\"SELECT * FROM orders WHERE user_id IN (SELECT id FROM users WHERE active = true)\")", 0.9),
+            task("ps-sq2-3", "Window in SQL", "result = spark.sql(\"SELECT name, salary, RANK() OVER (PARTITION BY dept ORDER BY salary DESC) as rnk FROM employees\")", 0.9),
+            task("ps-sq2-4", "Create SQL table", "spark.sql(\"CREATE TABLE IF NOT EXISTS events (id INT, name STRING, ts TIMESTAMP) USING parquet\")", 0.9),
+        ]),
+
+        // Stage 13: Performance & Caching
+        lesson("pyspark-perf-1", "Caching & Persistence", "Cache DataFrames for repeated use", LessonCategory::Code, Difficulty::Advanced, vec![
+            task("ps-p1-1", "Cache a DataFrame", "df.cache() # store in memory for reuse", 0.9),
+            task("ps-p1-2", "Persist with level", "from pyspark import StorageLevel\ndf.persist(StorageLevel.MEMORY_AND_DISK)", 0.9),
+            task("ps-p1-3", "Unpersist", "df.unpersist() # free cached data from memory", 0.9),
+            task("ps-p1-4", "Check if cached", "df.is_cached # returns True if DataFrame is cached", 0.9),
+        ]),
+        lesson("pyspark-perf-2", "Partitioning & Optimization", "Optimize shuffle and partitions", LessonCategory::Code, Difficulty::Advanced, vec![
+            task("ps-p2-1", "Repartition", "df.repartition(10) # redistribute data across 10 partitions", 0.9),
+            task("ps-p2-2", "Coalesce", "df.coalesce(4) # reduce partitions without full shuffle", 0.9),
+            task("ps-p2-3", "Broadcast join", "from pyspark.sql.functions import broadcast\nresult = large_df.join(broadcast(small_df), \"id\")", 0.9),
+            task("ps-p2-4", "Explain plan", "df.explain(True) # show physical and logical execution plan", 0.9),
+        ]),
+
+        // Stage 14: Streaming & Advanced Patterns
+        lesson("pyspark-adv-1", "Structured Streaming", "Process real-time data streams", LessonCategory::Code, Difficulty::Expert, vec![
+            task("ps-ad1-1", "Read stream", "stream_df = spark.readStream.format(\"kafka\").option(\"subscribe\", \"topic\").load()", 0.9),
+            task("ps-ad1-2", "Write stream", "query = stream_df.writeStream.format(\"console\").outputMode(\"append\").start()", 0.9),
+            task("ps-ad1-3", "Windowed aggregation", "from pyspark.sql.functions import window\nresult = stream_df.groupBy(window(col(\"timestamp\"), \"5 minutes\")).count()", 0.9),
+            task("ps-ad1-4", "Watermark", "result = stream_df.withWatermark(\"timestamp\", \"10 minutes\").groupBy(window(col(\"timestamp\"), \"5 minutes\")).count()", 0.9),
+        ]),
+        lesson("pyspark-adv-2", "Advanced Patterns", "Pivoting, unpivoting, and schema evolution", LessonCategory::Code, Difficulty::Expert, vec![
+            task("ps-ad2-1", "Pivot table", "df.groupBy(\"dept\").pivot(\"year\").sum(\"revenue\").show()", 0.9),
+            task("ps-ad2-2", "Stack (unpivot)", "from pyspark.sql.functions import expr\ndf.select(\"id\", expr(\"stack(2, 'q1', q1, 'q2', q2) as (quarter, value)\")).show()", 0.9),
+            task("ps-ad2-3", "Schema merging", "df = spark.read.option(\"mergeSchema\", True).parquet(\"data/\")", 0.9),
+            task("ps-ad2-4", "Repartition by range", "df.repartitionByRange(10, col(\"date\")) # range-based partitioning", 0.9),
+        ]),
+
+        // Stage 15: PySpark Mastery Test
+        lesson("pyspark-test-1", "PySpark Fundamentals Test", "Test your core PySpark knowledge", LessonCategory::Code, Difficulty::Expert, vec![
+            task("ps-t1-1", "Create session and read data", "spark = SparkSession.builder.appName(\"Test\").getOrCreate()\ndf = spark.read.csv(\"sales.csv\", header=True, inferSchema=True)", 0.9),
+            task("ps-t1-2", "Filter and transform", "result = df.filter(col(\"amount\") > 100).withColumn(\"tax\", col(\"amount\") * 0.1)", 0.9),
+            task("ps-t1-3", "Aggregate by group", "summary = df.groupBy(\"region\").agg(sum(\"amount\").alias(\"total\"), avg(\"amount\").alias(\"avg_sale\"))", 0.9),
+            task("ps-t1-4", "Join and select", "output = sales.join(customers, \"customer_id\", \"left\").select(\"name\", \"amount\", \"region\")", 0.9),
+        ]),
+        lesson("pyspark-test-2", "PySpark Mastery Final Test", "Comprehensive PySpark challenge", LessonCategory::Code, Difficulty::Expert, vec![
+            task("ps-t2-1", "Window ranking", "w = Window.partitionBy(\"dept\").orderBy(col(\"salary\").desc())\ndf.withColumn(\"rank\", dense_rank().over(w)).filter(col(\"rank\") <= 3).show()", 0.9),
+            task("ps-t2-2", "Complex aggregation", "result = df.groupBy(\"category\").agg(\n    count(\"*\").alias(\"total\"),\n    sum(\"revenue\").alias(\"total_rev\"),\n    avg(\"rating\").alias(\"avg_rating\")\n).orderBy(col(\"total_rev\").desc())", 0.9),
+            task("ps-t2-3", "Pivot and join", "pivot_df = sales.groupBy(\"region\").pivot(\"quarter\").sum(\"amount\")\nfinal = pivot_df.join(broadcast(targets), \"region\", \"left\")", 0.9),
+            task("ps-t2-4", "Write partitioned output", "result.write.mode(\"overwrite\").partitionBy(\"year\", \"month\").parquet(\"output/analytics\")", 0.9),
+        ]),
+    ]
+}
@@ -0,0 +1,713 @@
+use crate::models::{CourseProgressRow, LessonProgressRow};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+#[derive(Error, Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "code", content = "message")]
+pub enum CourseError {
+    #[error("Unknown course: {0}")]
+    UnknownCourse(String),
+    #[error("Unknown stage: {0}")]
+    UnknownStage(String),
+    #[error("Not enrolled: {0}")]
+    NotEnrolled(String),
+    #[error("Already enrolled: {0}")]
+    AlreadyEnrolled(String),
+    #[error("Stage locked: {0}")]
+    StageLocked(String),
+    #[error("Unlock criteria not met: {0}")]
+    CriteriaNotMet(String),
+    #[error("Lessons incomplete: {0}")]
+    LessonsIncomplete(String),
+    #[error("Stage already completed: {0}")]
+    AlreadyCompleted(String),
+    #[error("Cannot skip: {0}")]
+    CannotSkip(String),
+    #[error("No placement test: {0}")]
+    NoPlacementTest(String),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockCriteria {
+    pub previous_stage_complete: bool,
+    #[serde(default)]
+    pub min_wpm: Option<f64>,
+    #[serde(default)]
+    pub min_accuracy: Option<f64>, // 0.0 - 1.0
+}
+
+impl UnlockCriteria {
+    /// Open as soon as the user enrolls
+    pub const OPEN: Self = UnlockCriteria {
+        previous_stage_complete: false,
+        min_wpm: None,
+        min_accuracy: None,
+    };
+    /// Open once the previous stage is completed or skipped
+    pub const AFTER_PREVIOUS: Self = UnlockCriteria {
+        previous_stage_complete: true,
+        min_wpm: None,
+        min_accuracy: None,
+    };
+
+    /// After the previous stage, and only for users who have reached the given speed and accuracy
+    pub const fn gated(min_wpm: Option<f64>, min_accuracy: Option<f64>) -> Self {
+        UnlockCriteria {
+            previous_stage_complete: true,
+            min_wpm,
+            min_accuracy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseStage {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub lessons: Vec<String>, // lesson ids
+    pub unlock_criteria: UnlockCriteria,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Course {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub stages: Vec<CourseStage>,
+}
+
+impl Course {
+    pub fn stage_index(&self, stage_id: &str) -> Result<usize, CourseError> {
+        self.stages
+            .iter()
+            .position(|s| s.id == stage_id)
+            .ok_or_else(|| CourseError::UnknownStage(format!("{} in {}", stage_id, self.id)))
+    }
+}
+
+static COURSES: OnceLock<Vec<Course>> = OnceLock::new();
+
+pub fn all_courses() -> &'static [Course] {
+    COURSES.get_or_init(builtin_courses)
+}
+
+pub fn get_course(id: &str) -> Result<&'static Course, CourseError> {
+    all_courses()
+        .iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| CourseError::UnknownCourse(id.to_string()))
+}
+
+/// A user's place in one course. Stage lists keep the order the stages were reached in.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseProgress {
+    pub course_id: String,
+    pub current_stage_id: Option<String>,
+    pub completed_stages: Vec<String>,
+    pub skipped_stages: Vec<String>,
+    pub enrolled_at: String,
+    pub completed_at: Option<String>,
+    #[serde(default)]
+    pub version: i64,
+}
+
+impl CourseProgress {
+    /// Read the stage lists of the row format the frontend stores
+    pub fn from_row(row: &CourseProgressRow) -> serde_json::Result<Self> {
+        Ok(CourseProgress {
+            course_id: row.course_id.clone(),
+            current_stage_id: row.current_stage_id.clone(),
+            completed_stages: serde_json::from_str(&row.completed_stages_json)?,
+            skipped_stages: serde_json::from_str(&row.skipped_stages_json)?,
+            enrolled_at: row.enrolled_at.clone(),
+            completed_at: row.completed_at.clone(),
+            version: row.version,
+        })
+    }
+
+    pub fn to_row(&self) -> CourseProgressRow {
+        CourseProgressRow {
+            course_id: self.course_id.clone(),
+            current_stage_id: self.current_stage_id.clone(),
+            completed_stages_json: serde_json::to_string(&self.completed_stages)
+                .unwrap_or_else(|_| "[]".to_string()),
+            skipped_stages_json: serde_json::to_string(&self.skipped_stages)
+                .unwrap_or_else(|_| "[]".to_string()),
+            enrolled_at: self.enrolled_at.clone(),
+            completed_at: self.completed_at.clone(),
+            version: self.version,
+        }
+    }
+
+    fn is_completed(&self, stage_id: &str) -> bool {
+        self.completed_stages.iter().any(|s| s == stage_id)
+    }
+
+    fn is_skipped(&self, stage_id: &str) -> bool {
+        self.skipped_stages.iter().any(|s| s == stage_id)
+    }
+
+    fn mark_skipped(&mut self, stage_id: &str) {
+        if !self.is_completed(stage_id) && !self.is_skipped(stage_id) {
+            self.skipped_stages.push(stage_id.to_string());
+        }
+    }
+}
+
+/// The speed and accuracy a user has shown, checked against `UnlockCriteria`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PracticeStats {
+    pub best_wpm: f64,
+    pub accuracy: f64,
+}
+
+impl PracticeStats {
+    /// Best WPM of any lesson and the mean accuracy of the lessons practiced
+    pub fn from_lessons(progress: &[LessonProgressRow]) -> Self {
+        let practiced: Vec<_> = progress.iter().filter(|p| p.completed_tasks > 0).collect();
+        if practiced.is_empty() {
+            return PracticeStats::default();
+        }
+        PracticeStats {
+            best_wpm: practiced.iter().map(|p| p.best_wpm).fold(0.0, f64::max),
+            accuracy: practiced.iter().map(|p| p.average_accuracy).sum::<f64>()
+                / practiced.len() as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    Locked,
+    Unlocked,
+    Completed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageState {
+    pub stage_id: String,
+    pub status: StageStatus,
+    /// Why a locked stage is locked
+    pub reason: Option<String>,
+}
+
+pub fn enroll(course: &Course, now: &str) -> CourseProgress {
+    CourseProgress {
+        course_id: course.id.clone(),
+        current_stage_id: course.stages.first().map(|s| s.id.clone()),
+        completed_stages: Vec::new(),
+        skipped_stages: Vec::new(),
+        enrolled_at: now.to_string(),
+        completed_at: None,
+        version: 0,
+    }
+}
+
+pub fn stage_states(
+    course: &Course,
+    progress: &CourseProgress,
+    stats: &PracticeStats,
+) -> Vec<StageState> {
+    (0..course.stages.len())
+        .map(|index| {
+            let stage = &course.stages[index];
+            let (status, reason) = if progress.is_completed(&stage.id) {
+                (StageStatus::Completed, None)
+            } else if progress.is_skipped(&stage.id) {
+                (StageStatus::Skipped, None)
+            } else {
+                match check_unlocked(course, progress, index, stats) {
+                    Ok(()) => (StageStatus::Unlocked, None),
+                    Err(err) => (StageStatus::Locked, Some(err.to_string())),
+                }
+            };
+            StageState {
+                stage_id: stage.id.clone(),
+                status,
+                reason,
+            }
+        })
+        .collect()
+}
+
+/// A stage opens once the stage before it is completed or skipped and the user meets its
+/// speed and accuracy criteria. Stages the user skipped to are always open.
+fn check_unlocked(
+    course: &Course,
+    progress: &CourseProgress,
+    index: usize,
+    stats: &PracticeStats,
+) -> Result<(), CourseError> {
+    let stage = &course.stages[index];
+    if progress.is_skipped(&stage.id) {
+        return Ok(());
+    }
+    if stage.unlock_criteria.previous_stage_complete && index > 0 {
+        let previous = &course.stages[index - 1].id;
+        if !progress.is_completed(previous) && !progress.is_skipped(previous) {
+            return Err(CourseError::StageLocked(format!(
+                "{} opens after {} is completed or skipped",
+                stage.id, previous
+            )));
+        }
+    }
+    check_criteria(stage, stats)
+}
+
+pub(crate) fn check_criteria(
+    stage: &CourseStage,
+    stats: &PracticeStats,
+) -> Result<(), CourseError> {
+    let criteria = &stage.unlock_criteria;
+    if let Some(min_wpm) = criteria.min_wpm.filter(|&wpm| stats.best_wpm < wpm) {
+        return Err(CourseError::CriteriaNotMet(format!(
+            "{} needs {} WPM, best so far is {:.0}",
+            stage.id, min_wpm, stats.best_wpm
+        )));
+    }
+    if let Some(min_accuracy) = criteria.min_accuracy.filter(|&acc| stats.accuracy < acc) {
+        return Err(CourseError::CriteriaNotMet(format!(
+            "{} needs {:.0}% accuracy, average so far is {:.0}%",
+            stage.id,
+            min_accuracy * 100.0,
+            stats.accuracy * 100.0
+        )));
+    }
+    Ok(())
+}
+
+/// Complete an open stage whose lessons are all finished, then move on to the next stage.
+/// Completing the last stage completes the course.
+pub fn complete_stage(
+    course: &Course,
+    progress: &mut CourseProgress,
+    stage_id: &str,
+    lessons: &[LessonProgressRow],
+    stats: &PracticeStats,
+    now: &str,
+) -> Result<(), CourseError> {
+    let index = course.stage_index(stage_id)?;
+    if progress.is_completed(stage_id) {
+        return Err(CourseError::AlreadyCompleted(stage_id.to_string()));
+    }
+    check_unlocked(course, progress, index, stats)?;
+
+    let unfinished: Vec<&str> = course.stages[index]
+        .lessons
+        .iter()
+        .filter(|id| {
+            !lessons.iter().any(|p| {
+                &p.lesson_id == *id && p.total_tasks > 0 && p.completed_tasks >= p.total_tasks
+            })
+        })
+        .map(String::as_str)
+        .collect();
+    if !unfinished.is_empty() {
+        return Err(CourseError::LessonsIncomplete(format!(
+            "{} still needs {}",
+            stage_id,
+            unfinished.join(", ")
+        )));
+    }
+
+    progress.skipped_stages.retain(|s| s != stage_id);
+    progress.completed_stages.push(stage_id.to_string());
+    match course.stages.get(index + 1) {
+        Some(next) => progress.current_stage_id = Some(next.id.clone()),
+        None => progress.completed_at = Some(now.to_string()),
+    }
+    Ok(())
+}
+
+/// With a `stage_id`, jump to that stage: it and every unfinished stage before it are
+/// marked skipped. Without one, skip the stage the user is working on and move to the
+/// next. Skipping never gets around a stage's speed and accuracy criteria.
+pub fn skip_stage(
+    course: &Course,
+    progress: &mut CourseProgress,
+    stage_id: Option<&str>,
+    stats: &PracticeStats,
+) -> Result<(), CourseError> {
+    match stage_id {
+        Some(stage_id) => {
+            let index = course.stage_index(stage_id)?;
+            if progress.is_completed(stage_id) {
+                return Err(CourseError::AlreadyCompleted(stage_id.to_string()));
+            }
+            check_criteria(&course.stages[index], stats)?;
+            for stage in &course.stages[..=index] {
+                progress.mark_skipped(&stage.id);
+            }
+            progress.current_stage_id = Some(stage_id.to_string());
+        }
+        None => {
+            let index = (0..course.stages.len())
+                .find(|&i| {
+                    let id = &course.stages[i].id;
+                    !progress.is_completed(id)
+                        && !progress.is_skipped(id)
+                        && check_unlocked(course, progress, i, stats).is_ok()
+                })
+                .ok_or_else(|| {
+                    CourseError::CannotSkip(format!("{} has no open stage", course.id))
+                })?;
+            let next = course.stages.get(index + 1).ok_or_else(|| {
+                CourseError::CannotSkip(format!(
+                    "{} is the last stage of {}",
+                    course.stages[index].id, course.id
+                ))
+            })?;
+            check_criteria(next, stats)?;
+            progress.mark_skipped(&course.stages[index].id);
+            progress.current_stage_id = Some(next.id.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Skip every unfinished stage before `stage_id` and start the user there, unless they are
+/// already further along
+pub fn place(
+    course: &Course,
+    progress: &mut CourseProgress,
+    stage_id: &str,
+) -> Result<(), CourseError> {
+    let index = course.stage_index(stage_id)?;
+    for stage in &course.stages[..index] {
+        progress.mark_skipped(&stage.id);
+    }
+    let current = progress
+        .current_stage_id
+        .as_deref()
+        .and_then(|id| course.stage_index(id).ok());
+    if current.is_none_or(|current| current < index) {
+        progress.current_stage_id = Some(stage_id.to_string());
+    }
+    Ok(())
+}
+
+fn course(id: &str, name: &str, description: &str, stages: Vec<CourseStage>) -> Course {
+    Course {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        stages,
+    }
+}
+
+fn stage(
+    id: &str,
+    name: &str,
+    description: &str,
+    lessons: &[&str],
+    unlock_criteria: UnlockCriteria,
+) -> CourseStage {
+    CourseStage {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        lessons: lessons.iter().map(|l| l.to_string()).collect(),
+        unlock_criteria,
+    }
+}
+
+// Stage graphs of the built-in courses; their lessons are in `course_lessons`
+pub fn builtin_courses() -> Vec<Course> {
+    vec![
+        course(
+            "ten-finger",
+            "10 Fingers (touch typing)",
+            "A comprehensive course to master touch typing from beginner to expert",
+            vec![
+                stage("stage-1", "Foundation", "Learn the home row keys: A S D F and J K L ;", &["home-1", "home-2", "home-3"], UnlockCriteria::OPEN),
+                stage("stage-2", "Home Row Mastery", "Master the complete home row including G and H", &["home-4", "home-5"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-3", "Top Row Left", "Learn Q W E R T", &["top-1", "top-2", "top-3"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-4", "Top Row Right", "Learn Y U I O P", &["top-4", "top-5"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-5", "Top Row Integration", "Practice the full top row", &["top-6", "top-7"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-6", "Bottom Row Left", "Learn Z X C V B", &["bot-1", "bot-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-7", "Bottom Row Right", "Learn N M , .", &["bot-3", "bot-4"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-8", "Full Alphabet", "Practice using all letters", &["full-1", "full-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-9", "Shift & Capitals", "Master uppercase letters", &["shift-1", "shift-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-10", "Numbers", "Learn the number row", &["num-1", "num-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-11", "Common Symbols", "Basic punctuation and symbols", &["sym-1", "sym-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-12", "Advanced Symbols", "Programming symbols and syntax", &["sym-3", "sym-4"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("stage-13", "Speed Building", "Build typing speed with longer texts", &["speed-1", "speed-2"], UnlockCriteria::gated(Some(30.0), None)),
+                stage("stage-14", "Final Mastery", "Advanced challenges for experts", &["master-1", "master-2"], UnlockCriteria::gated(Some(40.0), Some(0.95))),
+            ],
+        ),
+        course(
+            "cli-mastery",
+            "Command Line Mastery",
+            "Master the terminal from basic navigation to advanced DevOps commands",
+            vec![
+                stage("cli-stage-1", "Getting Started", "Your first terminal commands and how to get help", &["cli-basics-1", "cli-basics-2"], UnlockCriteria::OPEN),
+                stage("cli-stage-2", "File Navigation", "Navigate directories and list files", &["cli-nav-1", "cli-nav-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-3", "File Operations", "Create, copy, move, and delete files", &["cli-file-1", "cli-file-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-4", "Reading Files", "View and monitor file contents", &["cli-read-1", "cli-read-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-5", "Search Commands", "Find files and search content with find & grep", &["cli-search-1", "cli-search-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-6", "Text Processing", "Sort, filter, and transform text data", &["cli-text-1", "cli-text-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-7", "Pipes & Redirection", "Chain commands and control I/O", &["cli-pipe-1", "cli-pipe-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-8", "System Information", "Monitor processes and system resources", &["cli-sys-1", "cli-sys-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-9", "Git Version Control", "Essential Git commands for developers", &["cli-git-1", "cli-git-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-10", "Permissions & Users", "Manage file permissions and users", &["cli-perm-1", "cli-perm-2"], UnlockCriteria::gated(Some(20.0), None)),
+                stage("cli-stage-11", "Networking", "Network commands and remote access", &["cli-net-1", "cli-net-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-12", "Archives & Compression", "Create and extract archives", &["cli-arch-1", "cli-arch-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-13", "Process Management", "Control and manage running processes", &["cli-proc-1", "cli-proc-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cli-stage-14", "Docker", "Container management essentials", &["cli-docker-1", "cli-docker-2"], UnlockCriteria::gated(Some(25.0), None)),
+                stage("cli-stage-test", "CLI Mastery Test", "Prove your command line skills", &["cli-test-basic", "cli-test-intermediate", "cli-test-advanced", "cli-test-master"], UnlockCriteria::gated(Some(30.0), Some(0.9))),
+            ],
+        ),
+        course(
+            "claude-code",
+            "Claude Code Mastery",
+            "Master Claude Code from basic commands to advanced workflows and MCP configuration",
+            vec![
+                stage("cc-stage-1", "Getting Started", "Launch Claude Code and learn the essential first commands", &["cc-start-1", "cc-start-2"], UnlockCriteria::OPEN),
+                stage("cc-stage-2", "Print Mode & Piping", "Use Claude Code for scripting, one-shot queries, and CI/CD", &["cc-print-1", "cc-print-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-3", "Session Management", "Continue, resume, and manage conversations", &["cc-session-1", "cc-session-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-4", "Context & Memory", "Manage context, compact conversations, and set up CLAUDE.md", &["cc-ctx-1", "cc-ctx-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-5", "Model & Thinking", "Choose models, configure reasoning depth, and use plan mode", &["cc-model-1", "cc-model-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-6", "Permissions & Security", "Control permissions, tool access, and settings", &["cc-perm-1", "cc-perm-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-7", "MCP Servers", "Add, configure, and manage Model Context Protocol servers", &["cc-mcp-1", "cc-mcp-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-8", "Keyboard Shortcuts", "Essential and advanced keyboard shortcuts for the REPL", &["cc-keys-1", "cc-keys-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-9", "System Prompts & Agents", "Customize system prompts and work with subagents", &["cc-sys-1", "cc-sys-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-10", "Git & GitHub", "GitHub integration, remote sessions, and IDE connection", &["cc-git-1", "cc-git-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-11", "Environment Variables", "Configure Claude Code with environment variables", &["cc-env-1", "cc-env-2"], UnlockCriteria::gated(Some(20.0), None)),
+                stage("cc-stage-12", "Configuration Files", "Understand settings hierarchy, skills, and custom commands", &["cc-cfg-1", "cc-cfg-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-13", "Advanced Workflows", "Multiline editing, vim mode, and lifecycle hooks", &["cc-adv-1", "cc-adv-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("cc-stage-test", "Claude Code Mastery Test", "Prove your Claude Code expertise", &["cc-test-1"], UnlockCriteria::gated(Some(25.0), Some(0.9))),
+            ],
+        ),
+        course(
+            "sql-mastery",
+            "SQL Mastery",
+            "Master SQL from basic queries to advanced window functions, CTEs, and database design",
+            vec![
+                stage("sql-stage-1", "Your First Queries", "SELECT, WHERE, and filtering fundamentals", &["sql-basics-1", "sql-basics-2"], UnlockCriteria::OPEN),
+                stage("sql-stage-2", "Sorting & Limiting", "ORDER BY, LIMIT, and combining conditions", &["sql-sort-1", "sql-sort-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-3", "Data Manipulation", "INSERT, UPDATE, DELETE, and TRUNCATE", &["sql-write-1", "sql-write-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-4", "Aggregate Functions", "COUNT, SUM, AVG, MIN, MAX, and GROUP BY", &["sql-agg-1", "sql-agg-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-5", "INNER & LEFT JOINs", "Combine data from multiple tables", &["sql-join-1", "sql-join-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-6", "Advanced JOINs", "FULL, CROSS, self-joins, and join patterns", &["sql-join-3", "sql-join-4"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-7", "Subqueries", "Nested queries, EXISTS, and correlated subqueries", &["sql-sub-1", "sql-sub-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-8", "String & Date Functions", "Text manipulation and date/time operations", &["sql-func-1", "sql-func-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-9", "CASE & NULL Handling", "Conditional logic, COALESCE, and type casting", &["sql-case-1", "sql-case-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-10", "Table Design (DDL)", "CREATE, ALTER, DROP tables with constraints", &["sql-ddl-1", "sql-ddl-2"], UnlockCriteria::gated(Some(20.0), None)),
+                stage("sql-stage-11", "Indexes & Performance", "Create indexes and analyze query plans with EXPLAIN", &["sql-idx-1", "sql-idx-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-12", "Window Functions", "ROW_NUMBER, RANK, LAG, LEAD, and running totals", &["sql-win-1", "sql-win-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-13", "CTEs & Transactions", "Common Table Expressions, recursive CTEs, and transaction control", &["sql-cte-1", "sql-cte-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("sql-stage-14", "Views & Procedures", "Views, materialized views, functions, and triggers", &["sql-view-1", "sql-view-2"], UnlockCriteria::gated(Some(25.0), None)),
+                stage("sql-stage-test", "SQL Mastery Test", "Prove your SQL expertise", &["sql-test-basic", "sql-test-intermediate", "sql-test-advanced", "sql-test-master"], UnlockCriteria::gated(Some(30.0), Some(0.9))),
+            ],
+        ),
+        course(
+            "pyspark-mastery",
+            "PySpark Mastery",
+            "Master PySpark from DataFrames and transformations to window functions, streaming, and performance tuning",
+            vec![
+                stage("pyspark-stage-1", "Getting Started", "SparkSession setup and creating DataFrames", &["pyspark-start-1", "pyspark-start-2"], UnlockCriteria::OPEN),
+                stage("pyspark-stage-2", "DataFrame Basics", "Explore, select, and rename columns", &["pyspark-df-1", "pyspark-df-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-3", "Filtering & Sorting", "Filter rows and sort results", &["pyspark-filter-1", "pyspark-filter-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-4", "Column Transformations", "Add, compute, and manipulate columns", &["pyspark-col-1", "pyspark-col-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-5", "Aggregations", "Group by, count, sum, avg, and collect", &["pyspark-agg-1", "pyspark-agg-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-6", "Joins", "Inner, left, full, cross, and anti joins", &["pyspark-join-1", "pyspark-join-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-7", "Null Handling & Types", "Handle missing values and cast types", &["pyspark-null-1", "pyspark-null-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-8", "Window Functions", "Rank, row_number, lag, lead, and running totals", &["pyspark-win-1", "pyspark-win-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-9", "Date & Timestamp", "Date arithmetic, formatting, and extraction", &["pyspark-date-1", "pyspark-date-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-10", "UDFs & Complex Types", "Custom functions, arrays, and maps", &["pyspark-udf-1", "pyspark-udf-2"], UnlockCriteria::gated(Some(20.0), None)),
+                stage("pyspark-stage-11", "Reading & Writing Data", "CSV, Parquet, JDBC, Delta, and partitioned output", &["pyspark-io-1", "pyspark-io-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-12", "Spark SQL", "SQL queries, CTEs, and temp views", &["pyspark-sql-1", "pyspark-sql-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-13", "Performance & Caching", "Cache, persist, broadcast, and explain plans", &["pyspark-perf-1", "pyspark-perf-2"], UnlockCriteria::AFTER_PREVIOUS),
+                stage("pyspark-stage-14", "Streaming & Advanced", "Structured streaming, pivoting, and schema evolution", &["pyspark-adv-1", "pyspark-adv-2"], UnlockCriteria::gated(Some(25.0), None)),
+                stage("pyspark-stage-test", "PySpark Mastery Test", "Prove your PySpark expertise", &["pyspark-test-1", "pyspark-test-2"], UnlockCriteria::gated(Some(30.0), Some(0.9))),
+            ],
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished(lesson_id: &str) -> LessonProgressRow {
+        LessonProgressRow {
+            lesson_id: lesson_id.to_string(),
+            completed_tasks: 4,
+            total_tasks: 4,
+            best_wpm: 25.0,
+            average_accuracy: 0.96,
+            last_task_index: Some(3),
+            task_results_json: "[]".to_string(),
+            version: 1,
+        }
+    }
+
+    fn status(
+        course: &Course,
+        progress: &CourseProgress,
+        stats: &PracticeStats,
+        stage: &str,
+    ) -> StageStatus {
+        let states = stage_states(course, progress, stats);
+        states
+            .into_iter()
+            .find(|s| s.stage_id == stage)
+            .unwrap()
+            .status
+    }
+
+    #[test]
+    fn test_builtin_courses() {
+        let ids: Vec<_> = all_courses().iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "ten-finger",
+                "cli-mastery",
+                "claude-code",
+                "sql-mastery",
+                "pyspark-mastery"
+            ]
+        );
+        for course in all_courses() {
+            assert!(!course.stages[0].unlock_criteria.previous_stage_complete);
+            assert!(course.stages[1..]
+                .iter()
+                .all(|s| s.unlock_criteria.previous_stage_complete));
+            assert!(course.stages.iter().all(|s| !s.lessons.is_empty()));
+        }
+        let ten_finger = get_course("ten-finger").unwrap();
+        assert_eq!(ten_finger.stages[0].lessons, ["home-1", "home-2", "home-3"]);
+        assert_eq!(
+            ten_finger.stages[13].unlock_criteria,
+            UnlockCriteria::gated(Some(40.0), Some(0.95))
+        );
+        assert!(matches!(
+            get_course("latin"),
+            Err(CourseError::UnknownCourse(_))
+        ));
+    }
+
+    #[test]
+    fn test_every_stage_lesson_resolves() {
+        let lessons = crate::lessons::bundled_lessons();
+        for course in all_courses() {
+            for stage in &course.stages {
+                for lesson_id in &stage.lessons {
+                    let lesson = lessons.iter().find(|l| &l.id == lesson_id);
+                    assert!(
+                        lesson.is_some_and(|l| !l.tasks.is_empty()),
+                        "{} {}",
+                        stage.id,
+                        lesson_id
+                    );
+                }
+            }
+        }
+
+        // Course tasks never share an id with another bundled task
+        let mut task_ids = std::collections::HashSet::new();
+        for task in lessons.iter().flat_map(|l| &l.tasks) {
+            assert!(task_ids.insert(task.id.as_str()), "{}", task.id);
+        }
+    }
+
+    #[test]
+    fn test_completing_stages_in_order() {
+        let course = get_course("ten-finger").unwrap();
+        let stats = PracticeStats::default();
+        let mut progress = enroll(course, "2026-01-01T00:00:00Z");
+        assert_eq!(progress.current_stage_id.as_deref(), Some("stage-1"));
+        assert_eq!(
+            status(course, &progress, &stats, "stage-1"),
+            StageStatus::Unlocked
+        );
+        assert_eq!(
+            status(course, &progress, &stats, "stage-2"),
+            StageStatus::Locked
+        );
+
+        let err = complete_stage(course, &mut progress, "stage-2", &[], &stats, "now").unwrap_err();
+        assert!(matches!(err, CourseError::StageLocked(_)));
+        let lessons = [finished("home-1"), finished("home-2")];
+        let err =
+            complete_stage(course, &mut progress, "stage-1", &lessons, &stats, "now").unwrap_err();
+        assert_eq!(
+            err,
+            CourseError::LessonsIncomplete("stage-1 still needs home-3".to_string())
+        );
+
+        let lessons = [finished("home-1"), finished("home-2"), finished("home-3")];
+        complete_stage(course, &mut progress, "stage-1", &lessons, &stats, "now").unwrap();
+        assert_eq!(progress.completed_stages, ["stage-1"]);
+        assert_eq!(progress.current_stage_id.as_deref(), Some("stage-2"));
+        assert_eq!(
+            status(course, &progress, &stats, "stage-2"),
+            StageStatus::Unlocked
+        );
+        assert!(matches!(
+            complete_stage(course, &mut progress, "stage-1", &lessons, &stats, "now"),
+            Err(CourseError::AlreadyCompleted(_))
+        ));
+    }
+
+    #[test]
+    fn test_skipping_respects_speed_gates() {
+        let course = get_course("ten-finger").unwrap();
+        let slow = PracticeStats {
+            best_wpm: 20.0,
+            accuracy: 0.9,
+        };
+        let mut progress = enroll(course, "now");
+
+        skip_stage(course, &mut progress, None, &slow).unwrap();
+        assert_eq!(progress.skipped_stages, ["stage-1"]);
+        assert_eq!(progress.current_stage_id.as_deref(), Some("stage-2"));
+        assert_eq!(
+            status(course, &progress, &slow, "stage-2"),
+            StageStatus::Unlocked
+        );
+
+        let err = skip_stage(course, &mut progress, Some("stage-13"), &slow).unwrap_err();
+        assert!(matches!(err, CourseError::CriteriaNotMet(_)));
+        let fast = PracticeStats {
+            best_wpm: 35.0,
+            accuracy: 0.9,
+        };
+        skip_stage(course, &mut progress, Some("stage-13"), &fast).unwrap();
+        assert_eq!(progress.skipped_stages.len(), 13);
+        assert_eq!(
+            status(course, &progress, &fast, "stage-14"),
+            StageStatus::Locked
+        );
+
+        // Completing a skipped stage moves it over
+        let lessons = [finished("speed-1"), finished("speed-2")];
+        complete_stage(course, &mut progress, "stage-13", &lessons, &fast, "now").unwrap();
+        assert!(!progress.skipped_stages.contains(&"stage-13".to_string()));
+        // Stage 14 is still out of reach, so nothing is open to skip
+        assert!(matches!(
+            skip_stage(course, &mut progress, None, &fast),
+            Err(CourseError::CannotSkip(_))
+        ));
+        let expert = PracticeStats {
+            best_wpm: 45.0,
+            accuracy: 0.97,
+        };
+        let err = skip_stage(course, &mut progress, None, &expert).unwrap_err();
+        assert_eq!(
+            err,
+            CourseError::CannotSkip("stage-14 is the last stage of ten-finger".to_string())
+        );
+    }
+}
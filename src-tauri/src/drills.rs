@@ -0,0 +1,807 @@
+use crate::layouts::{KeyLevel, KeyboardLayout};
+use crate::lessons::{Difficulty, Lesson, LessonCategory, Task};
+use crate::review::{ReviewItem, ReviewKind};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use thiserror::Error;
+
+// Common English words used as raw material for drills
+pub(crate) const WORDS: &[&str] = &[
+    "the", "be", "to", "of", "and", "a", "in", "that", "have", "it", "for", "not", "on", "with",
+    "he", "as", "you", "do", "at", "this", "but", "his", "by", "from", "they", "we", "say", "her",
+    "she", "or", "an", "will", "my", "one", "all", "would", "there", "their", "what", "so", "up",
+    "out", "if", "about", "who", "get", "which", "go", "me", "when", "make", "can", "like", "time",
+    "no", "just", "him", "know", "take", "people", "into", "year", "your", "good", "some", "could",
+    "them", "see", "other", "than", "then", "now", "look", "only", "come", "its", "over", "think",
+    "also", "back", "after", "use", "two", "how", "our", "work", "first", "well", "way", "even",
+    "new", "want", "because", "any", "these", "give", "day", "most", "us", "quick", "quite",
+    "question", "quiet", "queen", "equal", "zero", "size", "lazy", "zone", "amaze", "puzzle",
+    "jump", "join", "major", "object", "project", "enjoy", "box", "next", "fix", "mix", "text",
+    "six", "exact", "expect", "key", "keep", "kind", "kept", "black", "check", "quickly", "value",
+    "very", "voice", "move", "above", "every", "world", "week", "while", "where", "why", "few",
+    "view", "yes", "yet", "young", "play", "party", "happy", "type", "typing", "finger", "hand",
+    "home", "row", "fast", "slow", "learn", "practice", "letter", "word", "line", "space", "shift",
+    "enter", "bring", "field", "grow", "high", "light", "might", "night", "right", "thought",
+    "through", "both", "each", "much", "such", "child", "change", "chance", "school", "should",
+    "show", "still", "small",
+];
+
+// Weight of a word that doesn't contain any problem key, relative to a fully weighted key
+const BASE_WORD_WEIGHT: f64 = 1.0;
+const PROBLEM_KEY_BOOST: f64 = 20.0;
+const MAX_FOCUS_KEYS: usize = 5;
+
+// How much more often newly unlocked keys are picked than ones the learner already knows
+const NEW_KEY_BOOST: f64 = 3.0;
+// Below this many reachable dictionary words, word tasks use pseudo-words instead
+const MIN_DICTIONARY_WORDS: usize = 5;
+const VOWELS: &str = "aeiouyàáâãäåæèéêëìíîïòóôõöøœùúûü";
+// Two-key groups for warm-ups: 0 is one key, 1 the other
+const KEY_PATTERNS: &[&[usize]] = &[&[0, 0, 1], &[0, 1, 0], &[0, 1, 1, 0], &[0, 1, 0, 1]];
+
+/// Most tasks a generated lesson may have
+pub const MAX_TASK_COUNT: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct DrillOptions {
+    /// Whose lesson it is; part of the lesson and task ids
+    pub user_id: i64,
+    pub seed: u64,
+    pub task_count: usize,
+    pub items_per_task: usize,
+    pub min_accuracy: f32,
+}
+
+impl Default for DrillOptions {
+    fn default() -> Self {
+        DrillOptions {
+            user_id: 0,
+            seed: 0,
+            task_count: 4,
+            items_per_task: 12,
+            min_accuracy: 0.9,
+        }
+    }
+}
+
+impl DrillOptions {
+    /// The default options for a user's lesson from `seed`, with a requested task count
+    /// clamped to 1..=MAX_TASK_COUNT
+    pub fn for_user(user_id: i64, seed: u64, task_count: Option<usize>) -> Self {
+        let defaults = DrillOptions::default();
+        DrillOptions {
+            user_id,
+            seed,
+            task_count: task_count
+                .unwrap_or(defaults.task_count)
+                .clamp(1, MAX_TASK_COUNT),
+            ..defaults
+        }
+    }
+}
+
+/// Name a generated lesson `{kind}-{user}-{hash}` and number its tasks after it. The hash
+/// covers every task's text and criteria, so different lessons never share a task id and
+/// the same lesson generated again gets the same ids.
+fn identify(kind: &str, options: &DrillOptions, tasks: &mut [Task]) -> String {
+    // FNV-1a, which unlike the std hasher gives the same value in every build
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for task in tasks.iter() {
+        let bytes = task
+            .instruction
+            .bytes()
+            .chain([0])
+            .chain(task.target_text.bytes())
+            .chain([0])
+            .chain(task.min_accuracy.to_bits().to_le_bytes());
+        for byte in bytes {
+            hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    let lesson_id = format!("{}-{}-{:016x}", kind, options.user_id, hash);
+    for (n, task) in tasks.iter_mut().enumerate() {
+        task.id = format!("{}-{}", lesson_id, n + 1);
+    }
+    lesson_id
+}
+
+/// Build a synthetic lesson from a user's problem keys.
+/// The first task drills n-grams around the weakest keys; the rest are words weighted toward them.
+/// The same keys and seed always produce the same lesson.
+pub fn generate_adaptive_lesson(problem_keys: &[(String, i64)], options: &DrillOptions) -> Lesson {
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let weights = key_weights(problem_keys);
+
+    let mut focus: Vec<(char, f64)> = weights.iter().map(|(&k, &w)| (k, w)).collect();
+    focus.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    focus.truncate(MAX_FOCUS_KEYS);
+    let focus_keys: Vec<char> = focus.iter().map(|(k, _)| *k).collect();
+
+    let word_weights: Vec<f64> = WORDS
+        .iter()
+        .map(|word| {
+            BASE_WORD_WEIGHT
+                + word
+                    .chars()
+                    .filter_map(|c| weights.get(&c))
+                    .map(|w| w * PROBLEM_KEY_BOOST)
+                    .sum::<f64>()
+        })
+        .collect();
+    let word_dist = WeightedIndex::new(&word_weights).expect("word weights are positive");
+
+    let ngrams = ngrams_for(&focus);
+    let ngram_dist = if ngrams.is_empty() {
+        None
+    } else {
+        let w: Vec<f64> = ngrams.iter().map(|(_, w)| *w).collect();
+        WeightedIndex::new(&w).ok()
+    };
+
+    let keys_label = focus_keys
+        .iter()
+        .map(|k| k.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut tasks = Vec::with_capacity(options.task_count);
+    for n in 0..options.task_count {
+        let (instruction, items): (String, Vec<&str>) = match (&ngram_dist, n) {
+            (Some(dist), 0) => (
+                format!("Warm up on the combinations you miss most: {}.", keys_label),
+                (0..options.items_per_task)
+                    .map(|_| ngrams[dist.sample(&mut rng)].0.as_str())
+                    .collect(),
+            ),
+            _ => (
+                if focus_keys.is_empty() {
+                    "Type these common words.".to_string()
+                } else {
+                    format!("Type words that lean on your problem keys: {}.", keys_label)
+                },
+                (0..options.items_per_task)
+                    .map(|_| WORDS[word_dist.sample(&mut rng)])
+                    .collect(),
+            ),
+        };
+
+        tasks.push(Task {
+            id: String::new(),
+            instruction,
+            target_text: items.join(" "),
+            time_limit: None,
+            min_accuracy: options.min_accuracy,
+            min_wpm: None,
+            max_uncorrected_errors: None,
+        });
+    }
+
+    let lesson_id = identify("adaptive", options, &mut tasks);
+    Lesson {
+        id: lesson_id,
+        name: "Adaptive Drill".to_string(),
+        description: if focus_keys.is_empty() {
+            "A general warm-up; practice more to personalize your drills.".to_string()
+        } else {
+            format!("Practice generated from your problem keys: {}.", keys_label)
+        },
+        category: LessonCategory::Custom,
+        difficulty: Difficulty::Intermediate,
+        tasks,
+    }
+}
+
+/// Build a lesson from the review items that are due. The first task drills the due
+/// characters and bigrams; the rest mix due words with dictionary words that contain them.
+/// Items that keep lapsing come up more often.
+pub fn generate_review_lesson(items: &[ReviewItem], options: &DrillOptions) -> Lesson {
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let weight = |item: &ReviewItem| 1.0 + item.lapses as f64;
+    let of_kind = |kind| items.iter().filter(move |i| i.kind == kind);
+
+    let chars: Vec<(char, f64)> = of_kind(ReviewKind::Char)
+        .filter_map(|i| i.item.chars().next().map(|c| (c, weight(i))))
+        .collect();
+    let mut ngrams = ngrams_for(&chars);
+    ngrams.extend(
+        of_kind(ReviewKind::Bigram).map(|i| (i.item.clone(), weight(i) * PROBLEM_KEY_BOOST)),
+    );
+
+    // Due words as they are, then dictionary words by how much due material they contain
+    let mut words: Vec<(String, f64)> = of_kind(ReviewKind::Word)
+        .map(|i| (i.item.clone(), weight(i) * PROBLEM_KEY_BOOST))
+        .collect();
+    words.extend(WORDS.iter().map(|word| {
+        let boost: f64 = items
+            .iter()
+            .filter(|i| i.kind != ReviewKind::Word && word.contains(i.item.as_str()))
+            .map(|i| weight(i) * PROBLEM_KEY_BOOST)
+            .sum();
+        (word.to_string(), BASE_WORD_WEIGHT + boost)
+    }));
+    let word_dist =
+        WeightedIndex::new(words.iter().map(|(_, w)| *w)).expect("word weights are positive");
+    let ngram_dist = WeightedIndex::new(ngrams.iter().map(|(_, w)| *w)).ok();
+
+    let label = items
+        .iter()
+        .take(MAX_FOCUS_KEYS)
+        .map(|i| i.item.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut tasks: Vec<Task> = (0..options.task_count)
+        .map(|n| {
+            let (instruction, items): (String, Vec<&str>) = match (&ngram_dist, n) {
+                (Some(dist), 0) => (
+                    format!("Review the keys and pairs that are due: {}.", label),
+                    (0..options.items_per_task)
+                        .map(|_| ngrams[dist.sample(&mut rng)].0.as_str())
+                        .collect(),
+                ),
+                _ => (
+                    if items.is_empty() {
+                        "Type these common words.".to_string()
+                    } else {
+                        format!("Type words with what is due for review: {}.", label)
+                    },
+                    (0..options.items_per_task)
+                        .map(|_| words[word_dist.sample(&mut rng)].0.as_str())
+                        .collect(),
+                ),
+            };
+            Task {
+                id: String::new(),
+                instruction,
+                target_text: items.join(" "),
+                time_limit: None,
+                min_accuracy: options.min_accuracy,
+                min_wpm: None,
+                max_uncorrected_errors: None,
+            }
+        })
+        .collect();
+
+    let lesson_id = identify("review", options, &mut tasks);
+    Lesson {
+        id: lesson_id,
+        name: "Review Drill".to_string(),
+        description: if items.is_empty() {
+            "Nothing is due for review; a general warm-up instead.".to_string()
+        } else {
+            format!("Spaced review of {} due items.", items.len())
+        },
+        category: LessonCategory::Custom,
+        difficulty: Difficulty::Intermediate,
+        tasks,
+    }
+}
+
+/// Normalize error counts to weights in 0..=1, keyed by lowercase single characters
+fn key_weights(problem_keys: &[(String, i64)]) -> HashMap<char, f64> {
+    let mut counts: HashMap<char, i64> = HashMap::new();
+    for (key, count) in problem_keys {
+        let mut chars = key.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if !c.is_whitespace() && *count > 0 {
+                *counts
+                    .entry(c.to_lowercase().next().unwrap_or(c))
+                    .or_insert(0) += count;
+            }
+        }
+    }
+
+    let max = counts.values().copied().max().unwrap_or(0);
+    counts
+        .into_iter()
+        .map(|(c, count)| (c, count as f64 / max as f64))
+        .collect()
+}
+
+/// Bigrams and trigrams from the word list that contain a focus key, weighted by frequency.
+/// Keys that never appear in a word (punctuation, digits) are attached to short words instead.
+fn ngrams_for(focus: &[(char, f64)]) -> Vec<(String, f64)> {
+    let mut found: HashMap<String, f64> = HashMap::new();
+
+    for &(key, weight) in focus {
+        let mut covered = false;
+        for word in WORDS {
+            let chars: Vec<char> = word.chars().collect();
+            for size in 2..=3 {
+                for gram in chars.windows(size) {
+                    if gram.contains(&key) {
+                        *found.entry(gram.iter().collect()).or_insert(0.0) += weight;
+                        covered = true;
+                    }
+                }
+            }
+        }
+        if !covered {
+            for word in WORDS.iter().filter(|w| w.len() <= 3) {
+                *found.entry(format!("{}{}", word, key)).or_insert(0.0) += weight;
+            }
+        }
+    }
+
+    let mut ngrams: Vec<(String, f64)> = found.into_iter().collect();
+    // HashMap order is random; sort so the seed alone decides the output
+    ngrams.sort_by(|a, b| a.0.cmp(&b.0));
+    ngrams
+}
+
+#[derive(Error, Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "code", content = "message")]
+pub enum DrillError {
+    #[error("Key not on layout: {0}")]
+    KeyNotOnLayout(String),
+    #[error("No letters unlocked: {0}")]
+    NoLetters(String),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyRow {
+    Number,
+    Top,
+    Home,
+    Bottom,
+}
+
+impl KeyRow {
+    /// Index into `KeyboardLayout::rows`
+    fn index(self) -> usize {
+        match self {
+            KeyRow::Number => 0,
+            KeyRow::Top => 1,
+            KeyRow::Home => 2,
+            KeyRow::Bottom => 3,
+        }
+    }
+}
+
+/// The keys a learner may use: whole rows, plus single keys unlocked on top of them.
+/// The single keys count as new and show up more often.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockedKeys {
+    #[serde(default)]
+    pub rows: Vec<KeyRow>,
+    #[serde(default)]
+    pub keys: Vec<char>,
+}
+
+/// Build a lesson that only uses the unlocked keys of `layout`: a warm-up on key pairs,
+/// then alternating pseudo-words and real words from `dictionary` that the keys can spell.
+/// The same keys and seed always produce the same lesson.
+pub fn generate_key_lesson(
+    layout: &KeyboardLayout,
+    unlocked: &UnlockedKeys,
+    dictionary: &[&str],
+    options: &DrillOptions,
+) -> Result<Lesson, DrillError> {
+    let mut allowed: BTreeSet<char> = BTreeSet::new();
+    for row in &unlocked.rows {
+        let keys = layout.rows.get(row.index()).into_iter().flatten();
+        allowed.extend(keys.filter_map(|key| {
+            let mut chars = key.key.chars();
+            chars
+                .next()
+                .filter(|c| !c.is_whitespace() && chars.next().is_none())
+        }));
+    }
+    let mut new_keys = BTreeSet::new();
+    for &key in &unlocked.keys {
+        let key = key.to_lowercase().next().unwrap_or(key);
+        match layout.locate(key) {
+            Some(position) if position.level == KeyLevel::Base => {
+                new_keys.insert(key);
+            }
+            _ => {
+                return Err(DrillError::KeyNotOnLayout(format!(
+                    "'{}' is not a base key on {}",
+                    key, layout.name
+                )))
+            }
+        }
+    }
+    allowed.extend(&new_keys);
+
+    let weight = |c: &char| {
+        if new_keys.contains(c) {
+            NEW_KEY_BOOST
+        } else {
+            1.0
+        }
+    };
+    let keys: Vec<char> = allowed.iter().copied().collect();
+    let letters: Vec<char> = keys.iter().copied().filter(|c| c.is_alphabetic()).collect();
+    if letters.is_empty() {
+        return Err(DrillError::NoLetters(format!(
+            "the unlocked keys on {} spell no words",
+            layout.name
+        )));
+    }
+    let (vowels, consonants): (Vec<char>, Vec<char>) =
+        letters.iter().partition(|c| VOWELS.contains(**c));
+    let picker = |chars: &[char]| {
+        let weights: Vec<f64> = chars.iter().map(weight).collect();
+        WeightedIndex::new(weights).ok()
+    };
+    let (key_dist, vowel_dist, consonant_dist, letter_dist) = (
+        picker(&keys).expect("at least one key is unlocked"),
+        picker(&vowels),
+        picker(&consonants),
+        picker(&letters).expect("at least one letter is unlocked"),
+    );
+
+    let mut reachable: Vec<&str> = dictionary
+        .iter()
+        .copied()
+        .filter(|word| word.chars().count() >= 2 && word.chars().all(|c| allowed.contains(&c)))
+        .collect();
+    reachable.sort_unstable();
+    reachable.dedup();
+    let word_dist = if reachable.len() >= MIN_DICTIONARY_WORDS {
+        let weights: Vec<f64> = reachable
+            .iter()
+            .map(|word| word.chars().map(|c| weight(&c)).fold(1.0, f64::max))
+            .collect();
+        WeightedIndex::new(weights).ok()
+    } else {
+        None
+    };
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let pseudo_word = |rng: &mut StdRng| {
+        let length = rng.gen_range(2..=5);
+        let mut vowel = rng.gen_bool(0.3);
+        let mut word = String::new();
+        for _ in 0..length {
+            // Alternate consonants and vowels so the words can be pronounced
+            let ch = match (vowel, &vowel_dist, &consonant_dist) {
+                (true, Some(dist), _) => vowels[dist.sample(rng)],
+                (false, _, Some(dist)) => consonants[dist.sample(rng)],
+                _ => letters[letter_dist.sample(rng)],
+            };
+            word.push(ch);
+            vowel = !vowel;
+        }
+        word
+    };
+
+    let new_label = new_keys
+        .iter()
+        .map(|k| k.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let keys_label = keys
+        .iter()
+        .map(|k| k.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut tasks = Vec::with_capacity(options.task_count);
+    for n in 0..options.task_count {
+        let (instruction, items): (String, Vec<String>) = match (n, &word_dist) {
+            (0, _) => (
+                if new_keys.is_empty() {
+                    format!("Warm up on your keys: {}.", keys_label)
+                } else {
+                    format!("Get used to the new keys: {}.", new_label)
+                },
+                (0..options.items_per_task)
+                    .map(|_| {
+                        let pair = [
+                            keys[key_dist.sample(&mut rng)],
+                            keys[key_dist.sample(&mut rng)],
+                        ];
+                        let pattern = KEY_PATTERNS[rng.gen_range(0..KEY_PATTERNS.len())];
+                        pattern.iter().map(|&i| pair[i]).collect()
+                    })
+                    .collect(),
+            ),
+            (n, Some(dist)) if n % 2 == 0 => (
+                "Type real words you can already reach.".to_string(),
+                (0..options.items_per_task)
+                    .map(|_| reachable[dist.sample(&mut rng)].to_string())
+                    .collect(),
+            ),
+            _ => (
+                "Type these made-up words built from your keys.".to_string(),
+                (0..options.items_per_task)
+                    .map(|_| pseudo_word(&mut rng))
+                    .collect(),
+            ),
+        };
+
+        tasks.push(Task {
+            id: String::new(),
+            instruction,
+            target_text: items.join(" "),
+            time_limit: None,
+            min_accuracy: options.min_accuracy,
+            min_wpm: None,
+            max_uncorrected_errors: None,
+        });
+    }
+
+    // File the lesson under the furthest row it reaches
+    let furthest = allowed
+        .iter()
+        .filter_map(|&c| layout.locate(c))
+        .map(|position| position.row)
+        .filter(|&row| row == KeyRow::Top.index() || row == KeyRow::Bottom.index())
+        .max();
+    let category = match furthest {
+        Some(row) if row == KeyRow::Bottom.index() => LessonCategory::BottomRow,
+        Some(_) => LessonCategory::TopRow,
+        None => LessonCategory::HomeRow,
+    };
+
+    let lesson_id = identify(&format!("keys-{}", layout.id), options, &mut tasks);
+    Ok(Lesson {
+        id: lesson_id,
+        name: if new_keys.is_empty() {
+            "Key Practice".to_string()
+        } else {
+            format!("New Keys: {}", new_label)
+        },
+        description: format!(
+            "Practice on {} using only these keys: {}",
+            layout.name, keys_label
+        ),
+        category,
+        difficulty: Difficulty::Beginner,
+        tasks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layouts::LayoutId;
+
+    fn keys(entries: &[(&str, i64)]) -> Vec<(String, i64)> {
+        entries.iter().map(|(k, c)| (k.to_string(), *c)).collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_lesson() {
+        let problem = keys(&[("k", 12), ("q", 4)]);
+        let options = DrillOptions {
+            seed: 42,
+            ..Default::default()
+        };
+
+        let a = generate_adaptive_lesson(&problem, &options);
+        let b = generate_adaptive_lesson(&problem, &options);
+        let texts = |l: &Lesson| {
+            l.tasks
+                .iter()
+                .map(|t| t.target_text.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&a), texts(&b));
+
+        let c = generate_adaptive_lesson(
+            &problem,
+            &DrillOptions {
+                seed: 43,
+                ..options
+            },
+        );
+        assert_ne!(texts(&a), texts(&c));
+
+        let huge = DrillOptions::for_user(1, 42, Some(1_000_000));
+        assert_eq!(huge.task_count, MAX_TASK_COUNT);
+        assert_eq!(
+            DrillOptions::for_user(1, 42, None).task_count,
+            options.task_count
+        );
+    }
+
+    #[test]
+    fn test_ids_follow_the_user_and_the_content() {
+        let ids = |lesson: &Lesson| {
+            let mut ids = vec![lesson.id.clone()];
+            ids.extend(lesson.tasks.iter().map(|t| t.id.clone()));
+            ids
+        };
+        let problem = keys(&[("K", 20)]);
+        let options = DrillOptions::for_user(1, 42, None);
+        let a = generate_adaptive_lesson(&problem, &options);
+        assert!(a.id.starts_with("adaptive-1-"));
+        assert_eq!(a.tasks[0].id, format!("{}-1", a.id));
+        assert_eq!(ids(&a), ids(&generate_adaptive_lesson(&problem, &options)));
+
+        let other_user = generate_adaptive_lesson(&problem, &DrillOptions::for_user(2, 42, None));
+        assert_eq!(a.tasks[0].target_text, other_user.tasks[0].target_text);
+        assert!(ids(&a).iter().all(|id| !ids(&other_user).contains(id)));
+
+        let other_keys = generate_adaptive_lesson(&keys(&[("Q", 20)]), &options);
+        assert!(ids(&a).iter().all(|id| !ids(&other_keys).contains(id)));
+    }
+
+    #[test]
+    fn test_words_lean_on_problem_keys() {
+        let problem = keys(&[("K", 20)]);
+        let lesson = generate_adaptive_lesson(
+            &problem,
+            &DrillOptions {
+                seed: 7,
+                task_count: 3,
+                items_per_task: 40,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(lesson.tasks.len(), 3);
+        assert!(lesson.tasks[0]
+            .target_text
+            .split(' ')
+            .all(|g| g.contains('k')));
+
+        let words: Vec<&str> = lesson.tasks[1..]
+            .iter()
+            .flat_map(|t| t.target_text.split(' '))
+            .collect();
+        let with_key = words.iter().filter(|w| w.contains('k')).count();
+        assert!(
+            with_key * 2 > words.len(),
+            "{} of {} words contain k",
+            with_key,
+            words.len()
+        );
+    }
+
+    #[test]
+    fn test_punctuation_keys_and_empty_stats() {
+        let lesson = generate_adaptive_lesson(&keys(&[(";", 3)]), &DrillOptions::default());
+        assert!(lesson.tasks[0]
+            .target_text
+            .split(' ')
+            .all(|g| g.ends_with(';')));
+
+        let general = generate_adaptive_lesson(&[], &DrillOptions::default());
+        assert_eq!(general.tasks.len(), 4);
+        assert!(general.tasks.iter().all(|t| !t.target_text.is_empty()));
+    }
+
+    fn unlocked(rows: &[KeyRow], keys: &[char]) -> UnlockedKeys {
+        UnlockedKeys {
+            rows: rows.to_vec(),
+            keys: keys.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_review_lesson_drills_due_items() {
+        let item = |kind, item: &str, lapses| ReviewItem {
+            kind,
+            item: item.to_string(),
+            ease: 2.0,
+            interval_days: 0.0,
+            repetitions: 0,
+            lapses,
+            due_at: 0,
+            last_reviewed_at: 0,
+        };
+        let due = [
+            item(ReviewKind::Char, "z", 0),
+            item(ReviewKind::Bigram, "qu", 2),
+            item(ReviewKind::Word, "puzzle", 1),
+        ];
+        let lesson = generate_review_lesson(&due, &DrillOptions::default());
+        assert!(lesson.id.starts_with("review-0-"), "{}", lesson.id);
+        let warm_up = &lesson.tasks[0].target_text;
+        assert!(
+            warm_up.split(' ').all(|g| g.contains('z') || g == "qu"),
+            "{}",
+            warm_up
+        );
+        let words = lesson.tasks[1..]
+            .iter()
+            .flat_map(|t| t.target_text.split(' '))
+            .collect::<Vec<_>>();
+        let on_topic = words
+            .iter()
+            .filter(|w| w.contains('z') || w.contains("qu"))
+            .count();
+        assert!(on_topic * 2 > words.len(), "{:?}", words);
+
+        let empty = generate_review_lesson(&[], &DrillOptions::default());
+        assert_eq!(empty.tasks.len(), 4);
+        assert!(empty.tasks[0].instruction.contains("common words"));
+    }
+
+    #[test]
+    fn test_key_lesson_only_uses_unlocked_keys() {
+        for id in [LayoutId::Dvorak, LayoutId::Colemak, LayoutId::QwertyUs] {
+            let layout = KeyboardLayout::builtin(id);
+            let lesson = generate_key_lesson(
+                &layout,
+                &unlocked(&[KeyRow::Home], &[]),
+                WORDS,
+                &DrillOptions {
+                    seed: 3,
+                    items_per_task: 30,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let home: Vec<char> = layout.rows[2].iter().flat_map(|k| k.key.chars()).collect();
+
+            assert_eq!(lesson.category, LessonCategory::HomeRow);
+            assert_eq!(lesson.tasks.len(), 4);
+            for task in &lesson.tasks {
+                assert!(
+                    task.target_text
+                        .chars()
+                        .all(|c| c == ' ' || home.contains(&c)),
+                    "{}: {}",
+                    id.as_str(),
+                    task.target_text
+                );
+            }
+        }
+
+        // Dvorak's home row spells plenty of real words
+        let dvorak = KeyboardLayout::builtin(LayoutId::Dvorak);
+        let lesson = generate_key_lesson(
+            &dvorak,
+            &unlocked(&[KeyRow::Home], &[]),
+            WORDS,
+            &DrillOptions::default(),
+        )
+        .unwrap();
+        assert!(lesson.tasks[2]
+            .target_text
+            .split(' ')
+            .all(|w| WORDS.contains(&w)));
+    }
+
+    #[test]
+    fn test_new_keys_on_top_of_the_home_row() {
+        let layout = KeyboardLayout::builtin(LayoutId::QwertyUs);
+        let keys = unlocked(&[KeyRow::Home], &['E', 'i']);
+        let options = DrillOptions {
+            seed: 11,
+            items_per_task: 40,
+            ..Default::default()
+        };
+        let lesson = generate_key_lesson(&layout, &keys, WORDS, &options).unwrap();
+        let again = generate_key_lesson(&layout, &keys, WORDS, &options).unwrap();
+
+        assert_eq!(lesson.name, "New Keys: e, i");
+        assert_eq!(lesson.category, LessonCategory::TopRow);
+        assert_eq!(lesson.tasks[0].target_text, again.tasks[0].target_text);
+        let words = &lesson.tasks[2].target_text;
+        assert!(words.split(' ').all(|w| WORDS.contains(&w)), "{}", words);
+        assert!(words.contains('e') && words.contains('i'), "{}", words);
+    }
+
+    #[test]
+    fn test_key_lesson_errors_and_bottom_row() {
+        let layout = KeyboardLayout::builtin(LayoutId::QwertyUs);
+        let options = DrillOptions::default();
+        assert!(matches!(
+            generate_key_lesson(&layout, &unlocked(&[KeyRow::Home], &['€']), WORDS, &options),
+            Err(DrillError::KeyNotOnLayout(_))
+        ));
+        assert!(matches!(
+            generate_key_lesson(&layout, &unlocked(&[KeyRow::Number], &[]), WORDS, &options),
+            Err(DrillError::NoLetters(_))
+        ));
+
+        let lesson = generate_key_lesson(
+            &layout,
+            &unlocked(&[KeyRow::Home, KeyRow::Bottom], &[]),
+            WORDS,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(lesson.category, LessonCategory::BottomRow);
+    }
+}
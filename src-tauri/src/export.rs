@@ -0,0 +1,498 @@
+use crate::models::*;
+use crate::storage::{Database, StorageError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Identifies a file as one of our exports
+pub const BUNDLE_FORMAT: &str = "exceptional-typing-export";
+/// Bumped when the bundle layout itself changes; table contents follow `schema_version`.
+/// Version 2 added sessions.
+pub const BUNDLE_VERSION: i64 = 2;
+
+/// Everything we keep about one or more users, as a single JSON document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportBundle {
+    pub format: String,
+    pub format_version: i64,
+    /// Database schema the rows were read from
+    pub schema_version: i64,
+    pub exported_at: String,
+    pub users: Vec<UserExport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserExport {
+    pub profile: UserProfile,
+    pub settings: Option<String>,
+    pub stats: Option<UserStatsRow>,
+    pub lesson_progress: Vec<LessonProgressRow>,
+    pub course_progress: Vec<CourseProgressRow>,
+    pub snippets: Vec<CustomSnippetRow>,
+    pub daily_results: Vec<DailyTestResultRow>,
+    pub activity: Vec<DailyActivityRow>,
+    #[serde(default)]
+    pub sessions: Vec<SessionRow>,
+}
+
+/// Read one user, or every user when `user_id` is None
+pub fn bundle(
+    db: &Database,
+    user_id: Option<i64>,
+    exported_at: &str,
+) -> Result<ExportBundle, StorageError> {
+    let mut profiles = db.get_all_users()?;
+    if let Some(id) = user_id {
+        profiles.retain(|p| p.id == id);
+        if profiles.is_empty() {
+            return Err(StorageError::NotFound(format!("user {}", id)));
+        }
+    }
+    let daily_results = db.get_daily_results()?;
+
+    let mut users = Vec::with_capacity(profiles.len());
+    for profile in profiles {
+        let id = profile.id;
+        users.push(UserExport {
+            settings: db.get_settings(id)?,
+            stats: db.get_user_stats(id)?,
+            lesson_progress: db.get_all_lesson_progress(id)?,
+            course_progress: db.get_all_course_progress(id)?,
+            snippets: db.get_snippets(id)?,
+            daily_results: daily_results
+                .iter()
+                .filter(|r| r.user_id == id)
+                .cloned()
+                .collect(),
+            activity: db.get_activity(id)?,
+            sessions: db.get_sessions(id, None)?,
+            profile,
+        });
+    }
+
+    Ok(ExportBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        format_version: BUNDLE_VERSION,
+        schema_version: db.get_schema_version(),
+        exported_at: exported_at.to_string(),
+        users,
+    })
+}
+
+pub fn write_json(bundle: &ExportBundle, path: &Path) -> Result<(), StorageError> {
+    fs::write(path, serde_json::to_string_pretty(bundle)?)?;
+    Ok(())
+}
+
+/// Write one CSV file per table into `dir`, each row keyed by `user_id`.
+/// Returns the files written.
+pub fn write_csv(bundle: &ExportBundle, dir: &Path) -> Result<Vec<PathBuf>, StorageError> {
+    fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for table in tables(bundle)? {
+        let path = dir.join(format!("{}.csv", table.name));
+        let mut out = csv_line(table.header.iter().map(|h| h.to_string()));
+        for row in table.rows {
+            out.push_str(&csv_line(row));
+        }
+        fs::write(&path, out)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+struct Table {
+    name: &'static str,
+    header: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+fn tables(bundle: &ExportBundle) -> Result<Vec<Table>, StorageError> {
+    let users = &bundle.users;
+    let mut stats = Vec::new();
+    for u in users {
+        if let Some(s) = &u.stats {
+            stats.push(vec![
+                u.profile.id.to_string(),
+                s.total_practice_time.to_string(),
+                s.total_words_typed.to_string(),
+                s.average_wpm.to_string(),
+                s.average_accuracy.to_string(),
+                s.average_true_accuracy.to_string(),
+                s.total_keystrokes.to_string(),
+                s.total_backspaces.to_string(),
+                s.total_correct_keystrokes.to_string(),
+                s.lessons_completed.to_string(),
+                s.current_streak.to_string(),
+                s.longest_streak.to_string(),
+                opt(&s.last_practice_date),
+                serde_json::to_string(&s.problem_keys)?,
+            ]);
+        }
+    }
+
+    Ok(vec![
+        Table {
+            name: "users",
+            header: &[
+                "id",
+                "name",
+                "avatar",
+                "created_at",
+                "last_active_at",
+                "settings_json",
+            ],
+            rows: per_user(users, |u| {
+                let p = &u.profile;
+                vec![vec![
+                    p.id.to_string(),
+                    p.name.clone(),
+                    p.avatar.clone(),
+                    p.created_at.clone(),
+                    opt(&p.last_active_at),
+                    opt(&u.settings),
+                ]]
+            }),
+        },
+        Table {
+            name: "user_stats",
+            header: &[
+                "user_id",
+                "total_practice_time",
+                "total_words_typed",
+                "average_wpm",
+                "average_accuracy",
+                "average_true_accuracy",
+                "total_keystrokes",
+                "total_backspaces",
+                "total_correct_keystrokes",
+                "lessons_completed",
+                "current_streak",
+                "longest_streak",
+                "last_practice_date",
+                "problem_keys_json",
+            ],
+            rows: stats,
+        },
+        Table {
+            name: "lesson_progress",
+            header: &[
+                "user_id",
+                "lesson_id",
+                "completed_tasks",
+                "total_tasks",
+                "best_wpm",
+                "average_accuracy",
+                "last_task_index",
+                "task_results_json",
+            ],
+            rows: per_user(users, |u| {
+                u.lesson_progress
+                    .iter()
+                    .map(|p| {
+                        vec![
+                            u.profile.id.to_string(),
+                            p.lesson_id.clone(),
+                            p.completed_tasks.to_string(),
+                            p.total_tasks.to_string(),
+                            p.best_wpm.to_string(),
+                            p.average_accuracy.to_string(),
+                            opt(&p.last_task_index),
+                            p.task_results_json.clone(),
+                        ]
+                    })
+                    .collect()
+            }),
+        },
+        Table {
+            name: "course_progress",
+            header: &[
+                "user_id",
+                "course_id",
+                "current_stage_id",
+                "completed_stages_json",
+                "skipped_stages_json",
+                "enrolled_at",
+                "completed_at",
+            ],
+            rows: per_user(users, |u| {
+                u.course_progress
+                    .iter()
+                    .map(|p| {
+                        vec![
+                            u.profile.id.to_string(),
+                            p.course_id.clone(),
+                            opt(&p.current_stage_id),
+                            p.completed_stages_json.clone(),
+                            p.skipped_stages_json.clone(),
+                            p.enrolled_at.clone(),
+                            opt(&p.completed_at),
+                        ]
+                    })
+                    .collect()
+            }),
+        },
+        Table {
+            name: "custom_snippets",
+            header: &[
+                "user_id",
+                "id",
+                "name",
+                "content",
+                "language",
+                "mode",
+                "created_at",
+                "practice_count",
+                "best_wpm",
+                "best_accuracy",
+            ],
+            rows: per_user(users, |u| {
+                u.snippets
+                    .iter()
+                    .map(|s| {
+                        vec![
+                            u.profile.id.to_string(),
+                            s.id.clone(),
+                            s.name.clone(),
+                            s.content.clone(),
+                            opt(&s.language),
+                            s.mode.clone(),
+                            s.created_at.clone(),
+                            s.practice_count.to_string(),
+                            opt(&s.best_wpm),
+                            opt(&s.best_accuracy),
+                        ]
+                    })
+                    .collect()
+            }),
+        },
+        Table {
+            name: "daily_test_results",
+            header: &[
+                "user_id",
+                "date",
+                "wpm",
+                "accuracy",
+                "true_accuracy",
+                "duration",
+                "completed_at",
+            ],
+            rows: per_user(users, |u| {
+                u.daily_results
+                    .iter()
+                    .map(|r| {
+                        vec![
+                            r.user_id.to_string(),
+                            r.date.clone(),
+                            r.wpm.to_string(),
+                            r.accuracy.to_string(),
+                            r.true_accuracy.to_string(),
+                            r.duration.to_string(),
+                            r.completed_at.to_string(),
+                        ]
+                    })
+                    .collect()
+            }),
+        },
+        Table {
+            name: "daily_activity",
+            header: &["user_id", "date", "practice_time", "characters", "sessions"],
+            rows: per_user(users, |u| {
+                u.activity
+                    .iter()
+                    .map(|a| {
+                        vec![
+                            u.profile.id.to_string(),
+                            a.date.clone(),
+                            a.practice_time.to_string(),
+                            a.characters.to_string(),
+                            a.sessions.to_string(),
+                        ]
+                    })
+                    .collect()
+            }),
+        },
+        Table {
+            name: "sessions",
+            header: &[
+                "user_id",
+                "id",
+                "lesson_id",
+                "task_id",
+                "layout",
+                "started_at",
+                "completed_at",
+                "wpm",
+                "accuracy",
+                "duration",
+                "total_keystrokes",
+                "backspaces",
+                "error_count",
+                "passed",
+                "result_json",
+            ],
+            rows: per_user(users, |u| {
+                u.sessions
+                    .iter()
+                    .map(|s| {
+                        vec![
+                            u.profile.id.to_string(),
+                            s.id.clone(),
+                            opt(&s.lesson_id),
+                            s.task_id.clone(),
+                            opt(&s.layout),
+                            s.started_at.to_string(),
+                            s.completed_at.to_string(),
+                            s.wpm.to_string(),
+                            s.accuracy.to_string(),
+                            s.duration.to_string(),
+                            s.total_keystrokes.to_string(),
+                            s.backspaces.to_string(),
+                            s.error_count.to_string(),
+                            s.passed.to_string(),
+                            s.result_json.clone(),
+                        ]
+                    })
+                    .collect()
+            }),
+        },
+    ])
+}
+
+fn per_user(users: &[UserExport], f: impl Fn(&UserExport) -> Vec<Vec<String>>) -> Vec<Vec<String>> {
+    users.iter().flat_map(f).collect()
+}
+
+fn opt<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+/// One RFC 4180 record, CRLF-terminated
+fn csv_line(fields: impl IntoIterator<Item = String>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|f| {
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f
+            }
+        })
+        .collect();
+    fields.join(",") + "\r\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_db() -> Database {
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Ada", "cat", "2024-01-01").unwrap();
+        db.create_user(2, "Bob", "dog", "2024-01-02").unwrap();
+        db.save_settings(1, r#"{"fontSize":24}"#).unwrap();
+        db.save_snippets(
+            1,
+            &[CustomSnippetRow {
+                id: "s1".to_string(),
+                user_id: 1,
+                name: "Quote, \"with\" commas".to_string(),
+                content: "line one\nline two".to_string(),
+                language: None,
+                mode: "text".to_string(),
+                created_at: "2024-01-03".to_string(),
+                practice_count: 2,
+                best_wpm: Some(41.5),
+                best_accuracy: None,
+                version: 0,
+            }],
+        )
+        .unwrap();
+        db.save_daily_results(&[
+            DailyTestResultRow {
+                user_id: 1,
+                date: "2024-01-04".to_string(),
+                wpm: 40.0,
+                accuracy: 97.0,
+                true_accuracy: 95.0,
+                duration: 60_000,
+                completed_at: 1_704_326_400_000,
+                version: 0,
+            },
+            DailyTestResultRow {
+                user_id: 2,
+                date: "2024-01-04".to_string(),
+                wpm: 30.0,
+                accuracy: 90.0,
+                true_accuracy: 88.0,
+                duration: 60_000,
+                completed_at: 1_704_326_400_000,
+                version: 0,
+            },
+        ])
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_bundle_carries_schema_version_and_one_user() {
+        let db = sample_db();
+        let all = bundle(&db, None, "2024-02-01T00:00:00Z").unwrap();
+        assert_eq!(all.format, BUNDLE_FORMAT);
+        assert_eq!(all.schema_version, db.get_schema_version());
+        assert_eq!(all.users.len(), 2);
+
+        let one = bundle(&db, Some(1), "2024-02-01T00:00:00Z").unwrap();
+        assert_eq!(one.users.len(), 1);
+        let ada = &one.users[0];
+        assert_eq!(ada.settings.as_deref(), Some(r#"{"fontSize":24}"#));
+        assert_eq!(ada.snippets.len(), 1);
+        assert_eq!(ada.daily_results.len(), 1);
+        assert_eq!(ada.daily_results[0].wpm, 40.0);
+
+        let json = serde_json::to_string(&one).unwrap();
+        let back: ExportBundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.users[0].snippets[0].content, "line one\nline two");
+
+        assert!(matches!(
+            bundle(&db, Some(9), ""),
+            Err(StorageError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_csv_tables_quote_fields() {
+        let db = sample_db();
+        let dir = std::env::temp_dir().join(format!("export-csv-test-{}", std::process::id()));
+        let files = write_csv(&bundle(&db, None, "").unwrap(), &dir).unwrap();
+        assert_eq!(files.len(), 8);
+
+        let snippets = fs::read_to_string(dir.join("custom_snippets.csv")).unwrap();
+        assert_eq!(
+            snippets,
+            "user_id,id,name,content,language,mode,created_at,practice_count,best_wpm,best_accuracy\r\n\
+             1,s1,\"Quote, \"\"with\"\" commas\",\"line one\nline two\",,text,2024-01-03,2,41.5,\r\n"
+        );
+        let results = fs::read_to_string(dir.join("daily_test_results.csv")).unwrap();
+        assert_eq!(results.lines().count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup_is_a_complete_database() {
+        let db = sample_db();
+        let path =
+            std::env::temp_dir().join(format!("export-backup-test-{}.db", std::process::id()));
+        db.backup_to(&path).unwrap();
+
+        let copy = rusqlite::Connection::open(&path).unwrap();
+        let users: i64 = copy
+            .query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(users, 2);
+        drop(copy);
+        fs::remove_file(&path).unwrap();
+    }
+}
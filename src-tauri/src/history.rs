@@ -0,0 +1,489 @@
+use crate::lessons::{self, Task};
+use crate::metrics::{FailedCriterion, PassPolicy, TaskResult};
+use crate::models::{LessonProgressRow, SessionRow, UserStatsRow};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use thiserror::Error;
+
+#[derive(Error, Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "code", content = "message")]
+pub enum ProgressError {
+    #[error("Unknown task: {0}")]
+    UnknownTask(String),
+    #[error("Invalid result: {0}")]
+    InvalidResult(String),
+}
+
+/// What recording one task result did to its lesson
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LessonCompletion {
+    pub lesson_id: String,
+    pub task_passed: bool,
+    pub failed_criteria: Vec<FailedCriterion>,
+    pub lesson_complete: bool,
+    pub newly_completed: bool, // this result is the one that completed the lesson
+    pub progress: LessonProgressRow,
+}
+
+/// Progress for every lesson that has at least one session, ordered by lesson id.
+/// Mirrors the frontend's bookkeeping: every attempt is kept, a task counts as
+/// completed once any attempt at it passed. `known_totals` has the task counts of
+/// lessons that aren't bundled, such as generated ones; for a lesson missing there too,
+/// only the tasks attempted so far are known.
+pub fn lesson_progress(
+    sessions: &[SessionRow],
+    known_totals: &BTreeMap<String, i64>,
+) -> Vec<LessonProgressRow> {
+    let mut by_lesson: BTreeMap<&str, Vec<&SessionRow>> = BTreeMap::new();
+    for session in sessions {
+        if let Some(lesson_id) = &session.lesson_id {
+            by_lesson.entry(lesson_id).or_default().push(session);
+        }
+    }
+
+    by_lesson
+        .into_iter()
+        .map(|(lesson_id, mut attempts)| {
+            attempts.sort_by_key(|s| s.completed_at);
+            let lesson = lessons::get_lesson_by_id(lesson_id);
+            let passed: HashSet<&str> = attempts
+                .iter()
+                .filter(|s| s.passed)
+                .map(|s| s.task_id.as_str())
+                .collect();
+            let total_tasks = match &lesson {
+                Some(lesson) => lesson.tasks.len(),
+                None => {
+                    let attempted = attempts
+                        .iter()
+                        .map(|s| s.task_id.as_str())
+                        .collect::<HashSet<_>>()
+                        .len();
+                    known_totals
+                        .get(lesson_id)
+                        .map_or(attempted, |&total| attempted.max(total as usize))
+                }
+            };
+            let latest = attempts.last().expect("grouped sessions are never empty");
+            let last_task_index = lesson
+                .and_then(|l| l.tasks.iter().position(|t| t.id == latest.task_id))
+                .map(|i| i as i64);
+
+            // A row that no longer parses is left out
+            let results: Vec<serde_json::Value> = attempts
+                .iter()
+                .filter_map(|s| frontend_result(&s.result_json))
+                .collect();
+
+            LessonProgressRow {
+                lesson_id: lesson_id.to_string(),
+                completed_tasks: passed.len() as i64,
+                total_tasks: total_tasks as i64,
+                best_wpm: attempts.iter().map(|s| s.wpm).fold(0.0, f64::max),
+                average_accuracy: attempts.iter().map(|s| s.accuracy).sum::<f64>()
+                    / attempts.len() as f64,
+                last_task_index,
+                task_results_json: serde_json::Value::Array(results).to_string(),
+                version: 0,
+            }
+        })
+        .collect()
+}
+
+/// A stored result in the shape the frontend reads from lesson progress: its top-level
+/// fields in camelCase, which `TaskResult` also accepts back. Everything else is kept as
+/// it was stored, so results the frontend wrote itself pass through unchanged.
+fn frontend_result(result_json: &str) -> Option<serde_json::Value> {
+    match serde_json::from_str(result_json).ok()? {
+        serde_json::Value::Object(fields) => Some(serde_json::Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (camel_case(&name), value))
+                .collect(),
+        )),
+        other => Some(other),
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let mut parts = name.split('_');
+    let mut out = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.push_str(chars.as_str());
+        }
+    }
+    out
+}
+
+/// Add `sessions` to the running totals in `base`, which already count `base_sessions`
+/// attempts. Legacy and frontend-written totals are kept this way instead of being replaced
+/// by session sums. Streaks, true accuracy, problem keys and completed lessons come from
+/// elsewhere and are kept as they are.
+pub fn user_stats(base: UserStatsRow, base_sessions: i64, sessions: &[SessionRow]) -> UserStatsRow {
+    if sessions.is_empty() {
+        return base;
+    }
+    // Totals saved without any session rows still stand for at least one attempt
+    let base_weight = match base_sessions {
+        0 if base.total_practice_time > 0 => 1.0,
+        n => n as f64,
+    };
+    let count = base_weight + sessions.len() as f64;
+    let latest = sessions
+        .iter()
+        .map(|s| s.completed_at)
+        .max()
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|d| d.format("%Y-%m-%d").to_string());
+
+    UserStatsRow {
+        total_practice_time: base.total_practice_time
+            + sessions.iter().map(|s| s.duration).sum::<i64>(),
+        total_words_typed: base.total_words_typed
+            + sessions
+                .iter()
+                .map(|s| (s.duration as f64 / 60000.0 * s.wpm).floor() as i64)
+                .sum::<i64>(),
+        average_wpm: (base.average_wpm * base_weight + sessions.iter().map(|s| s.wpm).sum::<f64>())
+            / count,
+        average_accuracy: (base.average_accuracy * base_weight
+            + sessions.iter().map(|s| s.accuracy).sum::<f64>())
+            / count,
+        total_keystrokes: base.total_keystrokes
+            + sessions.iter().map(|s| s.total_keystrokes).sum::<i64>(),
+        total_backspaces: base.total_backspaces
+            + sessions.iter().map(|s| s.backspaces).sum::<i64>(),
+        total_correct_keystrokes: base.total_correct_keystrokes
+            + sessions
+                .iter()
+                .map(|s| (s.total_keystrokes - s.backspaces - s.error_count).max(0))
+                .sum::<i64>(),
+        last_practice_date: latest.max(base.last_practice_date.clone()),
+        ..base
+    }
+}
+
+// Slack for rounding in the frontend and typed text running past the target
+const WPM_SLACK: f32 = 1.1;
+// Faster than anyone types; a result claiming more keystrokes than this was not typed
+const MAX_KEYS_PER_SECOND: f32 = 25.0;
+
+/// Check a result the frontend measured against its task and turn it into a session row.
+/// Whether it passed is decided here from the task's requirements, and the measured numbers
+/// must fit the task: nobody types its text faster than the duration allows. The scores are
+/// otherwise the frontend's; typing sessions are the path that measures them here.
+pub fn session_for_result(
+    user_id: i64,
+    lesson_id: String,
+    task: &Task,
+    mut result: TaskResult,
+) -> Result<(SessionRow, TaskResult), ProgressError> {
+    if !(0.0..=1.0).contains(&result.accuracy) {
+        return Err(ProgressError::InvalidResult(format!(
+            "accuracy {} is outside 0-1",
+            result.accuracy
+        )));
+    }
+    if !result.wpm.is_finite() || result.wpm < 0.0 || result.duration <= 0 {
+        return Err(ProgressError::InvalidResult(format!(
+            "{} WPM over {} ms is not a possible attempt",
+            result.wpm, result.duration
+        )));
+    }
+    let minutes = result.duration as f32 / 60000.0;
+    let fastest = task.target_text.chars().count() as f32 / 5.0 / minutes;
+    if result.wpm.max(result.raw_wpm) > fastest * WPM_SLACK + 1.0 {
+        return Err(ProgressError::InvalidResult(format!(
+            "{} WPM is faster than typing '{}' in {} ms allows",
+            result.wpm.max(result.raw_wpm),
+            task.id,
+            result.duration
+        )));
+    }
+    if result.total_keystrokes as f32 > minutes * 60.0 * MAX_KEYS_PER_SECOND {
+        return Err(ProgressError::InvalidResult(format!(
+            "{} keystrokes in {} ms",
+            result.total_keystrokes, result.duration
+        )));
+    }
+    result.apply_policy(&PassPolicy::from_task(task));
+
+    let session = SessionRow {
+        // Derived from the result, so sending the same result twice records it once
+        id: format!(
+            "result-{}-{}-{}",
+            user_id, result.task_id, result.completed_at
+        ),
+        user_id,
+        lesson_id: Some(lesson_id),
+        task_id: result.task_id.clone(),
+        layout: None,
+        started_at: result.completed_at - result.duration,
+        completed_at: result.completed_at,
+        wpm: result.wpm as f64,
+        accuracy: result.accuracy as f64,
+        duration: result.duration,
+        total_keystrokes: result.total_keystrokes as i64,
+        backspaces: result.backspace_count as i64,
+        error_count: result.errors.len() as i64,
+        passed: result.passed,
+        result_json: serde_json::to_string(&result).unwrap_or_default(),
+    };
+    Ok((session, result))
+}
+
+/// Compare a lesson's progress before and after a result was recorded
+pub fn lesson_completion(
+    result: &TaskResult,
+    before: Option<&LessonProgressRow>,
+    after: LessonProgressRow,
+) -> LessonCompletion {
+    let complete = |p: &LessonProgressRow| p.total_tasks > 0 && p.completed_tasks >= p.total_tasks;
+    let lesson_complete = complete(&after);
+    LessonCompletion {
+        lesson_id: after.lesson_id.clone(),
+        task_passed: result.passed,
+        failed_criteria: result.failed_criteria.clone(),
+        lesson_complete,
+        newly_completed: lesson_complete && !before.is_some_and(complete),
+        progress: after,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lessons::GeneratedLessons;
+    use crate::metrics::MetricsCalculator;
+
+    fn session(id: &str, lesson: Option<&str>, task: &str, wpm: f64, passed: bool) -> SessionRow {
+        let completed_at = 1_717_200_000_000 + id.len() as i64 * 1000;
+        SessionRow {
+            id: id.to_string(),
+            user_id: 1,
+            lesson_id: lesson.map(String::from),
+            task_id: task.to_string(),
+            layout: None,
+            started_at: completed_at - 60_000,
+            completed_at,
+            wpm,
+            accuracy: if passed { 0.95 } else { 0.75 },
+            duration: 60_000,
+            total_keystrokes: 100,
+            backspaces: 4,
+            error_count: 2,
+            passed,
+            result_json: format!(r#"{{"task_id":"{}","wpm":{}}}"#, task, wpm),
+        }
+    }
+
+    #[test]
+    fn test_lesson_progress_from_sessions() {
+        let sessions = vec![
+            session("a", Some("home-row-basics"), "hr-1", 30.0, false),
+            session("bb", Some("home-row-basics"), "hr-1", 42.0, true),
+            session("ccc", Some("home-row-basics"), "hr-2", 35.0, true),
+            session("dddd", None, "snippet-1", 80.0, true),
+        ];
+        let progress = lesson_progress(&sessions, &BTreeMap::new());
+
+        assert_eq!(progress.len(), 1);
+        let p = &progress[0];
+        assert_eq!(p.completed_tasks, 2);
+        assert_eq!(p.total_tasks, 5);
+        assert_eq!(p.best_wpm, 42.0);
+        assert!((p.average_accuracy - (0.75 + 0.95 + 0.95) / 3.0).abs() < 1e-9);
+        assert_eq!(p.last_task_index, Some(1));
+        let results: Vec<serde_json::Value> = serde_json::from_str(&p.task_results_json).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[2]["taskId"], "hr-2");
+    }
+
+    #[test]
+    fn test_task_results_round_trip_in_the_frontends_shape() {
+        let mut measured = result("hr-1", 0.5);
+        measured.apply_policy(&PassPolicy::default());
+        let mut row = session("a", Some("home-row-basics"), "hr-1", 30.0, false);
+        row.result_json = serde_json::to_string(&measured).unwrap();
+        // What the frontend saved itself is passed on as it is
+        let mut legacy = session("bb", Some("home-row-basics"), "hr-2", 40.0, true);
+        legacy.result_json =
+            r#"{"taskId":"hr-2","wpm":40,"backspaceCount":3,"completedAt":5}"#.to_string();
+
+        let progress = lesson_progress(&[row, legacy], &BTreeMap::new());
+        let results: Vec<serde_json::Value> =
+            serde_json::from_str(&progress[0].task_results_json).unwrap();
+        assert_eq!(results[0]["taskId"], "hr-1");
+        assert!(results[0]["rawWpm"].is_number());
+        assert!(results[0]["completedAt"].is_number());
+        assert!(results[0].get("task_id").is_none());
+        assert_eq!(results[1]["backspaceCount"], 3);
+
+        let back: TaskResult = serde_json::from_value(results[0].clone()).unwrap();
+        assert_eq!(back.task_id, measured.task_id);
+        assert_eq!(back.raw_wpm, measured.raw_wpm);
+        assert_eq!(back.total_keystrokes, measured.total_keystrokes);
+        assert_eq!(back.completed_at, measured.completed_at);
+        assert_eq!(back.error_breakdown, measured.error_breakdown);
+        assert_eq!(back.failed_criteria, measured.failed_criteria);
+        assert!(!back.failed_criteria.is_empty());
+    }
+
+    #[test]
+    fn test_user_stats_keeps_fields_sessions_cannot_derive() {
+        let sessions = vec![
+            session("a", Some("home-row-basics"), "hr-1", 30.0, true),
+            session("bb", None, "snippet-1", 50.0, true),
+        ];
+        let base = UserStatsRow {
+            current_streak: 4,
+            problem_keys: vec![("q".to_string(), 3)],
+            ..Default::default()
+        };
+        let stats = user_stats(base, 0, &sessions);
+
+        assert_eq!(stats.total_practice_time, 120_000);
+        assert_eq!(stats.total_words_typed, 80);
+        assert_eq!(stats.average_wpm, 40.0);
+        assert_eq!(stats.total_backspaces, 8);
+        assert_eq!(stats.total_correct_keystrokes, 188);
+        assert_eq!(stats.last_practice_date.as_deref(), Some("2024-06-01"));
+        assert_eq!(stats.current_streak, 4);
+        assert_eq!(stats.problem_keys.len(), 1);
+
+        let legacy = UserStatsRow {
+            total_practice_time: 999,
+            ..Default::default()
+        };
+        assert_eq!(user_stats(legacy, 0, &[]).total_practice_time, 999);
+    }
+
+    #[test]
+    fn test_user_stats_add_sessions_to_legacy_totals() {
+        let legacy = UserStatsRow {
+            total_practice_time: 600_000,
+            total_words_typed: 200,
+            average_wpm: 20.0,
+            total_keystrokes: 1_000,
+            last_practice_date: Some("2025-01-01".to_string()),
+            ..Default::default()
+        };
+        let sessions = vec![session("a", None, "hr-1", 50.0, true)];
+
+        let stats = user_stats(legacy.clone(), 2, &sessions);
+        assert_eq!(stats.total_practice_time, 660_000);
+        assert_eq!(stats.total_words_typed, 250);
+        assert_eq!(stats.total_keystrokes, 1_100);
+        assert_eq!(stats.average_wpm, 30.0);
+        // A newer date from the legacy totals is not moved back
+        assert_eq!(stats.last_practice_date.as_deref(), Some("2025-01-01"));
+
+        // Totals saved without session rows still count as one attempt
+        assert_eq!(user_stats(legacy, 0, &sessions).average_wpm, 35.0);
+    }
+
+    fn result(task_id: &str, accuracy: f32) -> TaskResult {
+        let mut result = MetricsCalculator::calculate_result(
+            task_id.to_string(),
+            "asdf",
+            "asdf",
+            1_717_200_000_000,
+            1_717_200_010_000,
+            vec![],
+            None,
+        );
+        result.accuracy = accuracy;
+        result.passed = true;
+        result
+    }
+
+    fn record(
+        generated: &GeneratedLessons,
+        result: TaskResult,
+    ) -> Result<SessionRow, ProgressError> {
+        let (lesson_id, task) = lessons::resolve_task(generated, &result.task_id).unwrap();
+        session_for_result(1, lesson_id, &task, result).map(|(session, _)| session)
+    }
+
+    #[test]
+    fn test_results_are_checked_against_their_task() {
+        let (lesson_id, task) =
+            lessons::resolve_task(&GeneratedLessons::default(), "hr-1").unwrap();
+        let (session, checked) =
+            session_for_result(1, lesson_id, &task, result("hr-1", 0.8)).unwrap();
+        assert_eq!(session.lesson_id.as_deref(), Some("home-row-basics"));
+        assert_eq!(session.started_at, 1_717_200_000_000);
+        // hr-1 asks for 90% accuracy, whatever the frontend decided
+        assert!(!session.passed);
+        assert_eq!(checked.failed_criteria.len(), 1);
+
+        let none = GeneratedLessons::default();
+        assert!(matches!(
+            record(&none, result("hr-1", 1.5)),
+            Err(ProgressError::InvalidResult(_))
+        ));
+        // Faster than typing the whole of hr-1 in ten seconds allows
+        let mut fast = result("hr-1", 1.0);
+        fast.wpm = 500.0;
+        assert!(matches!(
+            record(&none, fast),
+            Err(ProgressError::InvalidResult(_))
+        ));
+        let mut mashed = result("hr-1", 1.0);
+        mashed.total_keystrokes = 10_000;
+        assert!(matches!(
+            record(&none, mashed),
+            Err(ProgressError::InvalidResult(_))
+        ));
+    }
+
+    #[test]
+    fn test_generated_tasks_and_frontend_payloads_are_recorded() {
+        let (_, mut task) = lessons::resolve_task(&GeneratedLessons::default(), "hr-1").unwrap();
+        task.id = "adaptive-7-1".to_string();
+        let mut generated = GeneratedLessons::default();
+        generated.register_task("adaptive-7", &task);
+
+        // What the frontend sends: camelCase, without the newer metrics
+        let payload = r#"{"taskId": "adaptive-7-1", "wpm": 4.0, "rawWpm": 4.8,
+            "accuracy": 0.95, "trueAccuracy": 0.9, "totalKeystrokes": 6, "backspaceCount": 1,
+            "errors": [{"index": 2, "expected": "d", "typed": "s", "timestamp": 1717200005000}],
+            "duration": 10000, "completedAt": 1717200010000, "passed": false}"#;
+        let session = record(&generated, serde_json::from_str(payload).unwrap()).unwrap();
+        assert_eq!(session.lesson_id.as_deref(), Some("adaptive-7"));
+        assert_eq!(session.task_id, "adaptive-7-1");
+        assert_eq!((session.total_keystrokes, session.error_count), (6, 1));
+        assert_eq!(session.backspaces, 1);
+        assert!(session.passed);
+    }
+
+    #[test]
+    fn test_lesson_completion_reports_the_completing_result() {
+        let progress = |completed| LessonProgressRow {
+            lesson_id: "home-row-basics".to_string(),
+            completed_tasks: completed,
+            total_tasks: 5,
+            best_wpm: 40.0,
+            average_accuracy: 0.95,
+            last_task_index: Some(4),
+            task_results_json: "[]".to_string(),
+            version: 1,
+        };
+        let (lesson_id, task) =
+            lessons::resolve_task(&GeneratedLessons::default(), "hr-5").unwrap();
+        let (_, passed) = session_for_result(1, lesson_id, &task, result("hr-5", 0.95)).unwrap();
+
+        let done = lesson_completion(&passed, Some(&progress(4)), progress(5));
+        assert!(done.task_passed && done.lesson_complete && done.newly_completed);
+
+        let again = lesson_completion(&passed, Some(&progress(5)), progress(5));
+        assert!(again.lesson_complete && !again.newly_completed);
+
+        let partway = lesson_completion(&passed, None, progress(1));
+        assert!(!partway.lesson_complete);
+    }
+}
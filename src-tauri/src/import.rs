@@ -0,0 +1,799 @@
+use crate::export::{self, ExportBundle, UserExport, BUNDLE_FORMAT, BUNDLE_VERSION};
+use crate::models::*;
+use crate::storage::{Database, StorageError, SCHEMA_VERSION};
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+// Bookkeeping that differs between databases holding the same data
+const IGNORED_FIELDS: [&str; 3] = ["version", "id", "problemKeys"];
+/// Tables an export bundle doesn't carry; an import leaves them as they are here
+pub const NOT_IMPORTED: [&str; 6] = [
+    "keystroke_events",
+    "key_error_stats",
+    "key_latency",
+    "finger_stats",
+    "review_items",
+    "custom_layouts",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportTable {
+    Users,
+    Settings,
+    UserStats,
+    LessonProgress,
+    CourseProgress,
+    Snippets,
+    DailyResults,
+    Activity,
+    Sessions,
+}
+
+impl ImportTable {
+    pub const ALL: [ImportTable; 9] = [
+        ImportTable::Users,
+        ImportTable::Settings,
+        ImportTable::UserStats,
+        ImportTable::LessonProgress,
+        ImportTable::CourseProgress,
+        ImportTable::Snippets,
+        ImportTable::DailyResults,
+        ImportTable::Activity,
+        ImportTable::Sessions,
+    ];
+}
+
+/// What to do with a row both sides have but with different contents
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStrategy {
+    #[default]
+    KeepMine,
+    TakeTheirs,
+    /// Take whichever row changed last. Rows without a time of change keep mine.
+    MergeNewest,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserMatch {
+    /// Added under its own id
+    New,
+    /// A local profile with the same creation time, and the same id or name
+    Existing,
+    /// Added under a fresh id, because its own belongs to a different local profile
+    Remapped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UserMapping {
+    pub from: i64,
+    pub to: i64,
+    pub name: String,
+    pub status: UserMatch,
+}
+
+/// Row keys as `user_id/key`, in the ids the rows end up with
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TableDiff {
+    pub table: ImportTable,
+    pub new: Vec<String>,
+    pub conflicting: Vec<String>,
+    pub identical: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub source_schema_version: i64,
+    pub users: Vec<UserMapping>,
+    pub tables: Vec<TableDiff>,
+    /// Tables left out of the import whatever the strategy
+    pub not_imported: Vec<String>,
+    /// False for a dry run
+    pub applied: bool,
+}
+
+/// Read an export bundle, or a backup database file (detected by its header)
+pub fn load(path: &Path) -> Result<ExportBundle, StorageError> {
+    let bytes = fs::read(path)?;
+    if bytes.starts_with(SQLITE_HEADER) {
+        let (copy, version) = Database::open_copy(path)?;
+        let mut bundle = export::bundle(&copy, None, "")?;
+        bundle.schema_version = version;
+        return Ok(bundle);
+    }
+
+    let bundle: ExportBundle = serde_json::from_slice(&bytes)?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(StorageError::Unsupported(format!(
+            "'{}' is not an export of this app",
+            bundle.format
+        )));
+    }
+    if bundle.format_version > BUNDLE_VERSION || bundle.schema_version > SCHEMA_VERSION {
+        return Err(StorageError::Unsupported(format!(
+            "the export was made by a newer version of the app (format {}, schema {})",
+            bundle.format_version, bundle.schema_version
+        )));
+    }
+    Ok(bundle)
+}
+
+/// Compare the bundle with the database without writing anything
+pub fn preview(db: &Database, bundle: &ExportBundle) -> Result<ImportReport, StorageError> {
+    run(db, bundle, &BTreeMap::new(), false)
+}
+
+/// Merge the bundle into the database, resolving conflicts per table. Tables without a
+/// strategy keep mine. Sessions ignore theirs: they are only ever added.
+pub fn apply(
+    db: &Database,
+    bundle: &ExportBundle,
+    strategies: &BTreeMap<ImportTable, ImportStrategy>,
+) -> Result<ImportReport, StorageError> {
+    run(db, bundle, strategies, true)
+}
+
+fn run(
+    db: &Database,
+    bundle: &ExportBundle,
+    strategies: &BTreeMap<ImportTable, ImportStrategy>,
+    write: bool,
+) -> Result<ImportReport, StorageError> {
+    let local = export::bundle(db, None, "")?;
+    let (incoming, users) = map_users(&local, bundle);
+    let mut merge = Merge {
+        strategies,
+        diffs: ImportTable::ALL
+            .into_iter()
+            .map(|table| {
+                let diff = TableDiff {
+                    table,
+                    new: Vec::new(),
+                    conflicting: Vec::new(),
+                    identical: Vec::new(),
+                };
+                (table, diff)
+            })
+            .collect(),
+        snippet_owners: local
+            .users
+            .iter()
+            .flat_map(|u| &u.snippets)
+            .map(|s| (s.id.clone(), (s.user_id, s.key())))
+            .collect(),
+        session_owners: local
+            .users
+            .iter()
+            .flat_map(|u| &u.sessions)
+            .map(|s| (s.id.clone(), s.user_id))
+            .collect(),
+    };
+
+    let merged: Vec<UserExport> = incoming
+        .into_iter()
+        .map(|theirs| {
+            let mine = local
+                .users
+                .iter()
+                .find(|u| u.profile.id == theirs.profile.id);
+            merge.user(mine, theirs)
+        })
+        .collect();
+    if write {
+        db.replace_user_data(&merged)?;
+    }
+
+    Ok(ImportReport {
+        source_schema_version: bundle.schema_version,
+        users,
+        tables: merge.diffs.into_values().collect(),
+        not_imported: NOT_IMPORTED.iter().map(|t| t.to_string()).collect(),
+        applied: write,
+    })
+}
+
+/// Give each incoming user the id it will have here
+fn map_users(local: &ExportBundle, bundle: &ExportBundle) -> (Vec<UserExport>, Vec<UserMapping>) {
+    let mut taken: BTreeSet<i64> = local
+        .users
+        .iter()
+        .chain(&bundle.users)
+        .map(|u| u.profile.id)
+        .collect();
+
+    let mut users = Vec::new();
+    let mut mappings = Vec::new();
+    for user in &bundle.users {
+        let from = user.profile.id;
+        let same = |u: &&UserExport| u.profile.created_at == user.profile.created_at;
+        // A profile remapped by an earlier import is found again by its name
+        let existing = local
+            .users
+            .iter()
+            .filter(same)
+            .find(|u| u.profile.id == from)
+            .or_else(|| {
+                local
+                    .users
+                    .iter()
+                    .filter(same)
+                    .find(|u| u.profile.name == user.profile.name)
+            });
+        let (to, status) = match existing {
+            Some(u) => (u.profile.id, UserMatch::Existing),
+            None if local.users.iter().all(|u| u.profile.id != from) => (from, UserMatch::New),
+            None => {
+                let id = taken.last().map_or(1, |id| id + 1);
+                taken.insert(id);
+                (id, UserMatch::Remapped)
+            }
+        };
+
+        let mut user = user.clone();
+        user.profile.id = to;
+        user.snippets.iter_mut().for_each(|s| s.user_id = to);
+        user.daily_results.iter_mut().for_each(|r| r.user_id = to);
+        user.sessions.iter_mut().for_each(|s| s.user_id = to);
+        mappings.push(UserMapping {
+            from,
+            to,
+            name: user.profile.name.clone(),
+            status,
+        });
+        users.push(user);
+    }
+    (users, mappings)
+}
+
+struct Merge<'a> {
+    strategies: &'a BTreeMap<ImportTable, ImportStrategy>,
+    diffs: BTreeMap<ImportTable, TableDiff>,
+    // Snippet ids are unique across users: id -> (user, key)
+    snippet_owners: HashMap<String, (i64, String)>,
+    // Session ids too: id -> user
+    session_owners: HashMap<String, i64>,
+}
+
+impl Merge<'_> {
+    fn user(&mut self, mine: Option<&UserExport>, mut theirs: UserExport) -> UserExport {
+        let id = theirs.profile.id;
+        for snippet in &mut theirs.snippets {
+            let key = snippet.key();
+            if self
+                .snippet_owners
+                .get(&snippet.id)
+                .is_some_and(|owner| *owner != (id, key.clone()))
+            {
+                snippet.id = format!("{}-{}", snippet.id, id);
+            }
+        }
+        for session in &mut theirs.sessions {
+            if self
+                .session_owners
+                .get(&session.id)
+                .is_some_and(|owner| *owner != id)
+            {
+                session.id = format!("{}-{}", session.id, id);
+            }
+            // Sessions are never rewritten, so a different one under a taken id is added
+            // under the first free id instead of going through a strategy
+            let row = content(&*session);
+            let taken = |session_id: &str| {
+                self.session_owners
+                    .get(session_id)
+                    .is_some_and(|owner| *owner != id)
+                    || mine
+                        .into_iter()
+                        .flat_map(|m| &m.sessions)
+                        .any(|m| m.id == session_id && content(m) != row)
+            };
+            let base = session.id.clone();
+            let mut n = 1;
+            while taken(&session.id) {
+                session.id = format!("{}-{}", base, n);
+                n += 1;
+            }
+        }
+
+        let profile = self
+            .rows(
+                ImportTable::Users,
+                id,
+                mine.map(|m| vec![m.profile.clone()]),
+                vec![theirs.profile],
+            )
+            .remove(0);
+        let settings = self.rows(
+            ImportTable::Settings,
+            id,
+            mine.map(|m| m.settings.iter().cloned().collect()),
+            theirs.settings.into_iter().collect(),
+        );
+        let stats = self.rows(
+            ImportTable::UserStats,
+            id,
+            mine.map(|m| m.stats.iter().cloned().collect()),
+            theirs.stats.into_iter().collect(),
+        );
+        UserExport {
+            profile,
+            settings: settings.into_iter().next(),
+            stats: stats.into_iter().next(),
+            lesson_progress: self.rows(
+                ImportTable::LessonProgress,
+                id,
+                mine.map(|m| m.lesson_progress.clone()),
+                theirs.lesson_progress,
+            ),
+            course_progress: self.rows(
+                ImportTable::CourseProgress,
+                id,
+                mine.map(|m| m.course_progress.clone()),
+                theirs.course_progress,
+            ),
+            snippets: self.rows(
+                ImportTable::Snippets,
+                id,
+                mine.map(|m| m.snippets.clone()),
+                theirs.snippets,
+            ),
+            daily_results: self.rows(
+                ImportTable::DailyResults,
+                id,
+                mine.map(|m| m.daily_results.clone()),
+                theirs.daily_results,
+            ),
+            activity: self.rows(
+                ImportTable::Activity,
+                id,
+                mine.map(|m| m.activity.clone()),
+                theirs.activity,
+            ),
+            sessions: self.rows(
+                ImportTable::Sessions,
+                id,
+                mine.map(|m| m.sessions.clone()),
+                theirs.sessions,
+            ),
+        }
+    }
+
+    /// Mine with their new rows added and conflicts resolved by the table's strategy
+    fn rows<T: ImportRow>(
+        &mut self,
+        table: ImportTable,
+        user_id: i64,
+        mine: Option<Vec<T>>,
+        theirs: Vec<T>,
+    ) -> Vec<T> {
+        let strategy = self.strategies.get(&table).copied().unwrap_or_default();
+        let diff = self.diffs.get_mut(&table).expect("every table has a diff");
+        let mut merged = mine.unwrap_or_default();
+        for mut row in theirs {
+            let key = row.key();
+            let label = format!("{}/{}", user_id, key);
+            match merged.iter_mut().find(|m| m.key() == key) {
+                None => {
+                    diff.new.push(label);
+                    merged.push(row);
+                }
+                Some(m) if content(m) == content(&row) => diff.identical.push(label),
+                Some(m) => {
+                    diff.conflicting.push(label);
+                    let take = match strategy {
+                        ImportStrategy::KeepMine => false,
+                        ImportStrategy::TakeTheirs => true,
+                        ImportStrategy::MergeNewest => row.stamp() > m.stamp(),
+                    };
+                    if take {
+                        row.adopt(m);
+                        *m = row;
+                    }
+                }
+            }
+        }
+        merged
+    }
+}
+
+fn content<T: Serialize>(row: &T) -> serde_json::Value {
+    let mut value = serde_json::to_value(row).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        for field in IGNORED_FIELDS {
+            fields.remove(field);
+        }
+    }
+    value
+}
+
+trait ImportRow: Clone + Serialize {
+    /// Identifies the row within one user's table
+    fn key(&self) -> String;
+
+    /// When the row last changed, in milliseconds
+    fn stamp(&self) -> Option<i64> {
+        None
+    }
+
+    /// Keep what identifies `mine` in this database when this row replaces it
+    fn adopt(&mut self, _mine: &Self) {}
+}
+
+impl ImportRow for UserProfile {
+    fn key(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn stamp(&self) -> Option<i64> {
+        self.last_active_at.as_deref().and_then(date_ms)
+    }
+}
+
+// Settings JSON, one per user
+impl ImportRow for String {
+    fn key(&self) -> String {
+        "settings".to_string()
+    }
+}
+
+impl ImportRow for UserStatsRow {
+    fn key(&self) -> String {
+        "stats".to_string()
+    }
+
+    fn stamp(&self) -> Option<i64> {
+        self.last_practice_date.as_deref().and_then(date_ms)
+    }
+}
+
+impl ImportRow for LessonProgressRow {
+    fn key(&self) -> String {
+        self.lesson_id.clone()
+    }
+
+    /// The latest task result; older rows were written with camelCase fields
+    fn stamp(&self) -> Option<i64> {
+        let results: Vec<serde_json::Value> =
+            serde_json::from_str(&self.task_results_json).unwrap_or_default();
+        results
+            .iter()
+            .filter_map(|r| r["completed_at"].as_i64().or(r["completedAt"].as_i64()))
+            .max()
+    }
+
+    fn adopt(&mut self, mine: &Self) {
+        self.version = mine.version;
+    }
+}
+
+impl ImportRow for CourseProgressRow {
+    fn key(&self) -> String {
+        self.course_id.clone()
+    }
+
+    fn stamp(&self) -> Option<i64> {
+        date_ms(self.completed_at.as_deref().unwrap_or(&self.enrolled_at))
+    }
+
+    fn adopt(&mut self, mine: &Self) {
+        self.version = mine.version;
+    }
+}
+
+// Names are unique per user, ids may differ between databases
+impl ImportRow for CustomSnippetRow {
+    fn key(&self) -> String {
+        self.name.to_lowercase()
+    }
+
+    fn adopt(&mut self, mine: &Self) {
+        self.id = mine.id.clone();
+        self.version = mine.version;
+    }
+}
+
+impl ImportRow for DailyTestResultRow {
+    fn key(&self) -> String {
+        self.date.clone()
+    }
+
+    fn stamp(&self) -> Option<i64> {
+        Some(self.completed_at)
+    }
+
+    fn adopt(&mut self, mine: &Self) {
+        self.version = mine.version;
+    }
+}
+
+impl ImportRow for DailyActivityRow {
+    fn key(&self) -> String {
+        self.date.clone()
+    }
+
+    fn adopt(&mut self, mine: &Self) {
+        self.version = mine.version;
+    }
+}
+
+// Ids are unique across users; colliding ones are renamed before merging
+impl ImportRow for SessionRow {
+    fn key(&self) -> String {
+        self.id.clone()
+    }
+
+    fn stamp(&self) -> Option<i64> {
+        Some(self.completed_at)
+    }
+}
+
+/// Milliseconds since epoch of an RFC 3339 time or a plain date
+fn date_ms(s: &str) -> Option<i64> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Some(time.timestamp_millis());
+    }
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(id: &str, user_id: i64, name: &str, content: &str) -> CustomSnippetRow {
+        CustomSnippetRow {
+            id: id.to_string(),
+            user_id,
+            name: name.to_string(),
+            content: content.to_string(),
+            language: None,
+            mode: "text".to_string(),
+            created_at: "2024-01-01".to_string(),
+            practice_count: 0,
+            best_wpm: None,
+            best_accuracy: None,
+            version: 0,
+        }
+    }
+
+    fn result(user_id: i64, date: &str, wpm: f64, completed_at: i64) -> DailyTestResultRow {
+        DailyTestResultRow {
+            user_id,
+            date: date.to_string(),
+            wpm,
+            accuracy: 95.0,
+            true_accuracy: 93.0,
+            duration: 60_000,
+            completed_at,
+            version: 0,
+        }
+    }
+
+    fn session(id: &str, user_id: i64, lesson_id: &str, wpm: f64) -> SessionRow {
+        SessionRow {
+            id: id.to_string(),
+            user_id,
+            lesson_id: Some(lesson_id.to_string()),
+            task_id: "hr-1".to_string(),
+            layout: None,
+            started_at: 1_000,
+            completed_at: 61_000,
+            wpm,
+            accuracy: 0.95,
+            duration: 60_000,
+            total_keystrokes: 100,
+            backspaces: 0,
+            error_count: 0,
+            passed: true,
+            result_json: "{}".to_string(),
+        }
+    }
+
+    /// Theirs: Ada (same profile as ours) and Cy, whose id 2 is Bob's here
+    fn sources() -> (Database, ExportBundle) {
+        let theirs = Database::in_memory().unwrap();
+        theirs.create_user(1, "Ada", "cat", "2024-01-01").unwrap();
+        theirs.create_user(2, "Cy", "owl", "2024-03-03").unwrap();
+        theirs
+            .save_snippets(
+                1,
+                &[
+                    snippet("a1", 1, "Poem", "theirs"),
+                    snippet("a2", 1, "Same", "same"),
+                    snippet("b1", 1, "New one", "new"),
+                ],
+            )
+            .unwrap();
+        theirs
+            .save_daily_results(&[
+                result(1, "2024-05-01", 60.0, 2_000),
+                result(1, "2024-05-02", 70.0, 1_000),
+                result(2, "2024-05-01", 20.0, 1_000),
+            ])
+            .unwrap();
+        theirs
+            .record_session(&session("t1", 1, "home-row-basics", 30.0), None)
+            .unwrap();
+        theirs
+            .record_session(&session("m1", 2, "home-row-basics", 20.0), None)
+            .unwrap();
+        // Ada's stats count her session
+        let stats = UserStatsRow {
+            total_practice_time: 60_000,
+            ..Default::default()
+        };
+        theirs.save_user_stats(1, &stats).unwrap();
+        let bundle = export::bundle(&theirs, None, "").unwrap();
+
+        let mine = Database::in_memory().unwrap();
+        mine.create_user(1, "Ada", "cat", "2024-01-01").unwrap();
+        mine.create_user(2, "Bob", "dog", "2024-01-02").unwrap();
+        mine.save_snippets(
+            1,
+            &[
+                snippet("a1", 1, "Poem", "mine"),
+                snippet("x2", 1, "Same", "same"),
+            ],
+        )
+        .unwrap();
+        mine.save_snippets(2, &[snippet("b1", 2, "Bob's", "bob")])
+            .unwrap();
+        mine.save_daily_results(&[
+            result(1, "2024-05-01", 50.0, 1_000),
+            result(1, "2024-05-02", 40.0, 2_000),
+        ])
+        .unwrap();
+        mine.record_session(&session("m1", 2, "home-row-basics", 50.0), None)
+            .unwrap();
+        (mine, bundle)
+    }
+
+    fn diff(report: &ImportReport, table: ImportTable) -> &TableDiff {
+        report.tables.iter().find(|t| t.table == table).unwrap()
+    }
+
+    #[test]
+    fn test_preview_diffs_without_writing() {
+        let (mine, bundle) = sources();
+        let report = preview(&mine, &bundle).unwrap();
+        assert!(!report.applied);
+        assert_eq!(
+            report.users,
+            [
+                UserMapping {
+                    from: 1,
+                    to: 1,
+                    name: "Ada".to_string(),
+                    status: UserMatch::Existing
+                },
+                UserMapping {
+                    from: 2,
+                    to: 3,
+                    name: "Cy".to_string(),
+                    status: UserMatch::Remapped
+                },
+            ]
+        );
+        let snippets = diff(&report, ImportTable::Snippets);
+        assert_eq!(snippets.new, ["1/new one"]);
+        assert_eq!(snippets.conflicting, ["1/poem"]);
+        assert_eq!(snippets.identical, ["1/same"]);
+        let results = diff(&report, ImportTable::DailyResults);
+        assert_eq!(results.new, ["3/2024-05-01"]);
+        assert_eq!(results.conflicting, ["1/2024-05-01", "1/2024-05-02"]);
+        assert_eq!(diff(&report, ImportTable::Users).new, ["3/3"]);
+        // m1 is Bob's here, so Cy's session gets another id
+        assert_eq!(diff(&report, ImportTable::Sessions).new, ["1/t1", "3/m1-3"]);
+        assert!(report.not_imported.iter().any(|t| t == "keystroke_events"));
+
+        assert_eq!(mine.get_all_users().unwrap().len(), 2);
+        assert_eq!(mine.get_snippets(1).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_apply_strategies_and_remapped_user() {
+        let (mine, bundle) = sources();
+        let strategies = BTreeMap::from([
+            (ImportTable::Snippets, ImportStrategy::TakeTheirs),
+            (ImportTable::DailyResults, ImportStrategy::MergeNewest),
+        ]);
+        assert!(apply(&mine, &bundle, &strategies).unwrap().applied);
+
+        let users = mine.get_all_users().unwrap();
+        let names: Vec<&str> = users.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, ["Ada", "Bob", "Cy"]);
+
+        let snippets = mine.get_snippets(1).unwrap();
+        let poem = snippets.iter().find(|s| s.name == "Poem").unwrap();
+        assert_eq!((poem.id.as_str(), poem.content.as_str()), ("a1", "theirs"));
+        // b1 is Bob's here, so Ada's new snippet gets another id
+        let new = snippets.iter().find(|s| s.name == "New one").unwrap();
+        assert_eq!(new.id, "b1-1");
+        assert_eq!(mine.get_snippets(2).unwrap()[0].content, "bob");
+
+        let results = mine.get_daily_results().unwrap();
+        let wpm = |user_id: i64, date: &str| {
+            results
+                .iter()
+                .find(|r| r.user_id == user_id && r.date == date)
+                .map(|r| r.wpm)
+        };
+        assert_eq!(wpm(1, "2024-05-01"), Some(60.0));
+        assert_eq!(wpm(1, "2024-05-02"), Some(40.0));
+        assert_eq!(wpm(3, "2024-05-01"), Some(20.0));
+        assert_eq!(wpm(2, "2024-05-01"), None);
+
+        // Imported sessions are history the next rebuild keeps, not counted twice in stats
+        assert_eq!(mine.get_sessions(3, None).unwrap()[0].id, "m1-3");
+        assert_eq!(mine.get_sessions(2, None).unwrap()[0].wpm, 50.0);
+        mine.rebuild_aggregates(1).unwrap();
+        let stats = mine.get_user_stats(1).unwrap().unwrap();
+        assert_eq!(stats.total_practice_time, 60_000);
+        assert_eq!(mine.get_all_lesson_progress(1).unwrap()[0].best_wpm, 30.0);
+
+        // Importing the same bundle again finds nothing new
+        let again = preview(&mine, &bundle).unwrap();
+        assert!(again.tables.iter().all(|t| t.new.is_empty()));
+    }
+
+    #[test]
+    fn test_sessions_are_only_ever_added() {
+        let (mine, bundle) = sources();
+        mine.record_session(&session("t1", 1, "home-row-basics", 45.0), None)
+            .unwrap();
+        // A strategy can't replace a stored session, so theirs is added next to it
+        let strategies = BTreeMap::from([(ImportTable::Sessions, ImportStrategy::TakeTheirs)]);
+        let report = apply(&mine, &bundle, &strategies).unwrap();
+        let sessions = diff(&report, ImportTable::Sessions);
+        assert_eq!(sessions.new, ["1/t1-1", "3/m1-3"]);
+        assert!(sessions.conflicting.is_empty());
+
+        // Applying it again adds nothing and changes nothing
+        let again = apply(&mine, &bundle, &strategies).unwrap();
+        assert!(again.tables.iter().all(|t| t.new.is_empty()));
+        let wpm: Vec<(String, f64)> = mine
+            .get_sessions(1, None)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.id, s.wpm))
+            .collect();
+        assert_eq!(wpm.len(), 2);
+        assert!(wpm.contains(&("t1".to_string(), 45.0)));
+        assert!(wpm.contains(&("t1-1".to_string(), 30.0)));
+        assert_eq!(mine.get_sessions(3, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_load_bundle_and_backup() {
+        let (mine, bundle) = sources();
+        let dir = std::env::temp_dir().join(format!("import-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let json = dir.join("export.json");
+        export::write_json(&bundle, &json).unwrap();
+        assert_eq!(load(&json).unwrap().users.len(), 2);
+
+        let backup = dir.join("backup.db");
+        mine.backup_to(&backup).unwrap();
+        let loaded = load(&backup).unwrap();
+        assert_eq!(loaded.schema_version, SCHEMA_VERSION);
+        assert_eq!(loaded.users[1].profile.name, "Bob");
+
+        fs::write(
+            &json,
+            r#"{"format":"other","formatVersion":1,"schemaVersion":1,"exportedAt":"","users":[]}"#,
+        )
+        .unwrap();
+        assert!(matches!(load(&json), Err(StorageError::Unsupported(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,3 +1,7 @@
+use crate::layouts::{self, LayoutId};
+use serde::Serialize;
+use std::sync::Mutex;
+
 /// Returns the current keyboard input source identifier from macOS.
 /// Example return values:
 ///   "com.apple.keylayout.US"
@@ -6,7 +10,6 @@
 ///   "com.apple.keylayout.French"
 ///   "com.apple.keylayout.Colemak"
 ///   "com.apple.keylayout.British"
-
 #[cfg(target_os = "macos")]
 pub fn get_current_input_source() -> Option<String> {
     use core_foundation::base::{CFRelease, TCFType};
@@ -89,11 +92,291 @@ pub fn get_current_input_source() -> Option<String> {
     }
 }
 
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+#[cfg(target_os = "linux")]
+mod linux;
+
+/// Returns the active XKB layout on Linux, e.g. "xkb:us" or "xkb:de:nodeadkeys".
+/// Reads the X server first, then localectl, /etc/default/keyboard and XKB_DEFAULT_LAYOUT.
+#[cfg(target_os = "linux")]
+pub fn get_current_input_source() -> Option<String> {
+    linux::detect(&linux::SystemSources).map(|layout| layout.to_source_id())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
 pub fn get_current_input_source() -> Option<String> {
     None
 }
 
+// ── Layout watcher ───────────────────────────────────────────────────
+
+/// Event emitted while the layout watcher runs and the input source changes
+pub const LAYOUT_CHANGED_EVENT: &str = "keyboard-layout-changed";
+
+/// Payload of `LAYOUT_CHANGED_EVENT`. Sources are the identifiers returned by
+/// `get_current_input_source`, layouts the app layouts they normalize to.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutChange {
+    pub old_source: Option<String>,
+    pub new_source: Option<String>,
+    pub old_layout: Option<LayoutId>,
+    pub new_layout: Option<LayoutId>,
+}
+
+/// Turns successive readings of the input source into changes
+struct ChangeDetector {
+    last: Option<String>,
+}
+
+impl ChangeDetector {
+    fn observe(&mut self, source: Option<String>) -> Option<LayoutChange> {
+        if source == self.last {
+            return None;
+        }
+        let old_source = std::mem::replace(&mut self.last, source.clone());
+        Some(LayoutChange {
+            old_layout: layout_for(&old_source),
+            new_layout: layout_for(&source),
+            old_source,
+            new_source: source,
+        })
+    }
+}
+
+fn layout_for(source: &Option<String>) -> Option<LayoutId> {
+    source
+        .as_deref()
+        .and_then(layouts::normalize_input_source)
+        .map(|m| m.layout)
+}
+
+type ChangeCheck = Box<dyn FnMut(Option<String>) + Send>;
+
+// Platform callbacks carry no user data we control, so the running check lives here.
+// Only one watcher runs at a time.
+static CHECK: Mutex<Option<ChangeCheck>> = Mutex::new(None);
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn run_check() {
+    report(get_current_input_source());
+}
+
+/// Hand a reading to the running check. Sources are read before taking the lock, so a
+/// slow one never holds up starting or dropping a watcher.
+fn report(source: Option<String>) {
+    if let Ok(mut check) = CHECK.lock() {
+        if let Some(check) = check.as_mut() {
+            check(source);
+        }
+    }
+}
+
+/// Reads the input source for the polling watcher, reusing what it can between polls
+#[cfg(target_os = "linux")]
+fn poller() -> impl FnMut() -> Option<String> {
+    let sources = linux::PolledSources::default();
+    move || linux::detect(&sources).map(|layout| layout.to_source_id())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn poller() -> impl FnMut() -> Option<String> {
+    get_current_input_source
+}
+
+/// A running watcher; dropping it stops the notifications
+pub struct LayoutWatcher {
+    platform: watcher::PlatformWatch,
+}
+
+impl Drop for LayoutWatcher {
+    fn drop(&mut self) {
+        self.platform.stop();
+        if let Ok(mut check) = CHECK.lock() {
+            *check = None;
+        }
+    }
+}
+
+/// Call `on_change` every time the input source changes, until the watcher is dropped.
+/// macOS and Windows notify the thread that starts the watcher, so start and drop it on the
+/// main thread; Tauri runs synchronous commands there. Other platforms are polled.
+pub fn watch(on_change: impl Fn(LayoutChange) + Send + 'static) -> LayoutWatcher {
+    let mut detector = ChangeDetector {
+        last: get_current_input_source(),
+    };
+    if let Ok(mut check) = CHECK.lock() {
+        *check = Some(Box::new(move |source| {
+            if let Some(change) = detector.observe(source) {
+                on_change(change);
+            }
+        }));
+    }
+    LayoutWatcher {
+        platform: watcher::PlatformWatch::start(),
+    }
+}
+
+/// Listens for the distributed notification the Text Input Sources manager posts
+#[cfg(target_os = "macos")]
+mod watcher {
+    use std::ffi::c_void;
+
+    extern "C" {
+        fn CFNotificationCenterGetDistributedCenter() -> *mut c_void;
+        fn CFNotificationCenterAddObserver(
+            center: *mut c_void,
+            observer: *const c_void,
+            callback: extern "C" fn(
+                *mut c_void,
+                *mut c_void,
+                *const c_void,
+                *const c_void,
+                *const c_void,
+            ),
+            name: *const c_void,
+            object: *const c_void,
+            suspension_behavior: isize,
+        );
+        fn CFNotificationCenterRemoveObserver(
+            center: *mut c_void,
+            observer: *const c_void,
+            name: *const c_void,
+            object: *const c_void,
+        );
+        static kTISNotifySelectedKeyboardInputSourceChanged: *const c_void;
+    }
+
+    const DELIVER_IMMEDIATELY: isize = 4; // CFNotificationSuspensionBehaviorDeliverImmediately
+
+    // Any stable address identifies our observer registration
+    static OBSERVER: u8 = 0;
+
+    extern "C" fn on_notification(
+        _center: *mut c_void,
+        _observer: *mut c_void,
+        _name: *const c_void,
+        _object: *const c_void,
+        _user_info: *const c_void,
+    ) {
+        super::run_check();
+    }
+
+    pub struct PlatformWatch;
+
+    impl PlatformWatch {
+        pub fn start() -> Self {
+            unsafe {
+                CFNotificationCenterAddObserver(
+                    CFNotificationCenterGetDistributedCenter(),
+                    &OBSERVER as *const u8 as *const c_void,
+                    on_notification,
+                    kTISNotifySelectedKeyboardInputSourceChanged,
+                    std::ptr::null(),
+                    DELIVER_IMMEDIATELY,
+                );
+            }
+            PlatformWatch
+        }
+
+        pub fn stop(&mut self) {
+            unsafe {
+                CFNotificationCenterRemoveObserver(
+                    CFNotificationCenterGetDistributedCenter(),
+                    &OBSERVER as *const u8 as *const c_void,
+                    kTISNotifySelectedKeyboardInputSourceChanged,
+                    std::ptr::null(),
+                );
+            }
+        }
+    }
+}
+
+/// WM_INPUTLANGCHANGE goes to the windows of the thread whose layout changed, so a
+/// WH_CALLWNDPROC hook on the UI thread sees it without subclassing the Tauri window.
+#[cfg(target_os = "windows")]
+mod watcher {
+    use winapi::shared::minwindef::{LPARAM, LRESULT, WPARAM};
+    use winapi::shared::windef::HHOOK;
+    use winapi::um::processthreadsapi::GetCurrentThreadId;
+    use winapi::um::winuser::{
+        CallNextHookEx, SetWindowsHookExW, UnhookWindowsHookEx, CWPSTRUCT, HC_ACTION,
+        WH_CALLWNDPROC, WM_INPUTLANGCHANGE,
+    };
+
+    unsafe extern "system" fn hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code == HC_ACTION && (*(lparam as *const CWPSTRUCT)).message == WM_INPUTLANGCHANGE {
+            super::run_check();
+        }
+        CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam)
+    }
+
+    pub struct PlatformWatch {
+        hook: usize, // HHOOK, kept as an integer so the watcher can move between threads
+    }
+
+    impl PlatformWatch {
+        pub fn start() -> Self {
+            let hook = unsafe {
+                SetWindowsHookExW(
+                    WH_CALLWNDPROC,
+                    Some(hook),
+                    std::ptr::null_mut(),
+                    GetCurrentThreadId(),
+                )
+            };
+            PlatformWatch {
+                hook: hook as usize,
+            }
+        }
+
+        pub fn stop(&mut self) {
+            if self.hook != 0 {
+                unsafe { UnhookWindowsHookEx(self.hook as HHOOK) };
+                self.hook = 0;
+            }
+        }
+    }
+}
+
+/// Linux has no notification that covers X11, Wayland compositors and the console alike
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod watcher {
+    use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    pub struct PlatformWatch {
+        stop: Option<Sender<()>>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl PlatformWatch {
+        pub fn start() -> Self {
+            let (stop, stopped) = mpsc::channel::<()>();
+            let thread = std::thread::spawn(move || {
+                let mut read = super::poller();
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(POLL_INTERVAL) {
+                    super::report(read());
+                }
+            });
+            PlatformWatch {
+                stop: Some(stop),
+                thread: Some(thread),
+            }
+        }
+
+        pub fn stop(&mut self) {
+            // Dropping the sender wakes the thread right away
+            self.stop.take();
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,4 +392,27 @@ mod tests {
         #[cfg(target_os = "windows")]
         assert!(result.is_some(), "Should detect input source on Windows");
     }
+
+    #[test]
+    fn test_change_detector_reports_transitions_only() {
+        let mut detector = ChangeDetector {
+            last: Some("com.apple.keylayout.US".to_string()),
+        };
+        assert_eq!(
+            detector.observe(Some("com.apple.keylayout.US".to_string())),
+            None
+        );
+
+        let change = detector
+            .observe(Some("com.apple.keylayout.German".to_string()))
+            .unwrap();
+        assert_eq!(change.old_layout, Some(LayoutId::QwertyUs));
+        assert_eq!(change.new_layout, Some(LayoutId::QwertyDe));
+        assert_eq!(change.old_source.as_deref(), Some("com.apple.keylayout.US"));
+
+        let change = detector.observe(None).unwrap();
+        assert_eq!(change.old_layout, Some(LayoutId::QwertyDe));
+        assert_eq!(change.new_source, None);
+        assert_eq!(detector.observe(None), None);
+    }
 }
@@ -0,0 +1,396 @@
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+/// How long the watcher reuses localectl's answer and /etc/default/keyboard
+const CONFIG_MAX_AGE: Duration = Duration::from_secs(30);
+/// How long the watcher waits before trying to open a display again
+const DISPLAY_RETRY: Duration = Duration::from_secs(30);
+
+/// A layout and optional variant as XKB names them, e.g. `de` + `nodeadkeys`
+#[derive(Debug, Clone, PartialEq)]
+pub struct XkbLayout {
+    pub layout: String,
+    pub variant: Option<String>,
+}
+
+impl XkbLayout {
+    /// Identifier in the same spirit as the macOS/Windows ones: "xkb:de:nodeadkeys" or "xkb:us"
+    pub fn to_source_id(&self) -> String {
+        match &self.variant {
+            Some(variant) => format!("xkb:{}:{}", self.layout, variant),
+            None => format!("xkb:{}", self.layout),
+        }
+    }
+
+    /// Pick entry `group` from XKB's comma-separated layout and variant lists,
+    /// e.g. layouts "us,de" with variants ",nodeadkeys"
+    fn from_lists(layouts: &str, variants: &str, group: usize) -> Option<Self> {
+        let layout = layouts.split(',').nth(group)?.trim();
+        if layout.is_empty() {
+            return None;
+        }
+        let variant = variants
+            .split(',')
+            .nth(group)
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+        Some(XkbLayout {
+            layout: layout.to_string(),
+            variant: variant.map(String::from),
+        })
+    }
+}
+
+/// Where layout information comes from, so detection can be tested without a display
+pub trait LayoutSources {
+    /// Layout list, variant list and active group from the X server's XKB extension
+    fn x11_xkb(&self) -> Option<(String, String, usize)>;
+    /// Output of `localectl status`
+    fn localectl_status(&self) -> Option<String>;
+    /// Contents of /etc/default/keyboard
+    fn default_keyboard_file(&self) -> Option<String>;
+    fn env_var(&self, name: &str) -> Option<String>;
+}
+
+/// Active layout from the first source that knows it: the X server, then
+/// localectl, /etc/default/keyboard and finally XKB_DEFAULT_LAYOUT.
+/// Configuration files only hold the default layout, so they report the first entry.
+pub fn detect(sources: &impl LayoutSources) -> Option<XkbLayout> {
+    if let Some(layout) = sources
+        .x11_xkb()
+        .and_then(|(layouts, variants, group)| XkbLayout::from_lists(&layouts, &variants, group))
+    {
+        return Some(layout);
+    }
+    if let Some(layout) = sources
+        .localectl_status()
+        .and_then(|status| parse_localectl(&status))
+    {
+        return Some(layout);
+    }
+    if let Some(layout) = sources
+        .default_keyboard_file()
+        .and_then(|contents| parse_default_keyboard(&contents))
+    {
+        return Some(layout);
+    }
+    let layouts = sources.env_var("XKB_DEFAULT_LAYOUT")?;
+    let variants = sources.env_var("XKB_DEFAULT_VARIANT").unwrap_or_default();
+    XkbLayout::from_lists(&layouts, &variants, 0)
+}
+
+/// Parse the "X11 Layout:" and "X11 Variant:" lines of `localectl status`
+fn parse_localectl(status: &str) -> Option<XkbLayout> {
+    let field = |name: &str| {
+        status.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == name).then(|| value.trim().to_string())
+        })
+    };
+    let layouts = field("X11 Layout")?;
+    XkbLayout::from_lists(&layouts, &field("X11 Variant").unwrap_or_default(), 0)
+}
+
+/// Parse XKBLAYOUT and XKBVARIANT from the shell-style /etc/default/keyboard
+fn parse_default_keyboard(contents: &str) -> Option<XkbLayout> {
+    let field = |name: &str| {
+        contents.lines().find_map(|line| {
+            let (key, value) = line.trim().split_once('=')?;
+            (key.trim() == name).then(|| value.trim().trim_matches(['"', '\'']).to_string())
+        })
+    };
+    let layouts = field("XKBLAYOUT")?;
+    XkbLayout::from_lists(&layouts, &field("XKBVARIANT").unwrap_or_default(), 0)
+}
+
+/// The real sources on a running system
+pub struct SystemSources;
+
+impl LayoutSources for SystemSources {
+    fn x11_xkb(&self) -> Option<(String, String, usize)> {
+        XDisplay::open()?.xkb()
+    }
+
+    fn localectl_status(&self) -> Option<String> {
+        let output = std::process::Command::new("localectl")
+            .arg("status")
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn default_keyboard_file(&self) -> Option<String> {
+        std::fs::read_to_string("/etc/default/keyboard").ok()
+    }
+
+    fn env_var(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+}
+
+/// The real sources for a watcher that asks twice a second: one X display stays open
+/// between polls, and localectl and the config file are read again only once they are
+/// `CONFIG_MAX_AGE` old. Not `Send`; create it on the polling thread.
+#[derive(Default)]
+pub struct PolledSources {
+    display: RefCell<Option<XDisplay>>,
+    display_tried_at: RefCell<Option<Instant>>,
+    localectl: RefCell<Option<Cached>>,
+    default_keyboard: RefCell<Option<Cached>>,
+}
+
+impl LayoutSources for PolledSources {
+    fn x11_xkb(&self) -> Option<(String, String, usize)> {
+        let mut display = self.display.borrow_mut();
+        if display.is_none() {
+            let now = Instant::now();
+            let mut tried_at = self.display_tried_at.borrow_mut();
+            if tried_at.is_some_and(|at| now.duration_since(at) < DISPLAY_RETRY) {
+                return None;
+            }
+            *tried_at = Some(now);
+            *display = XDisplay::open();
+        }
+        display.as_ref()?.xkb()
+    }
+
+    fn localectl_status(&self) -> Option<String> {
+        Cached::get(&self.localectl, Instant::now(), || {
+            SystemSources.localectl_status()
+        })
+    }
+
+    fn default_keyboard_file(&self) -> Option<String> {
+        Cached::get(&self.default_keyboard, Instant::now(), || {
+            SystemSources.default_keyboard_file()
+        })
+    }
+
+    fn env_var(&self, name: &str) -> Option<String> {
+        SystemSources.env_var(name)
+    }
+}
+
+/// A source's last answer and when it was read
+struct Cached {
+    value: Option<String>,
+    read_at: Instant,
+}
+
+impl Cached {
+    /// The cached answer, or a fresh `read` once it is `CONFIG_MAX_AGE` old
+    fn get(
+        slot: &RefCell<Option<Cached>>,
+        now: Instant,
+        read: impl FnOnce() -> Option<String>,
+    ) -> Option<String> {
+        let mut slot = slot.borrow_mut();
+        match slot.as_ref() {
+            Some(cached) if now.duration_since(cached.read_at) < CONFIG_MAX_AGE => {
+                cached.value.clone()
+            }
+            _ => {
+                let value = read();
+                *slot = Some(Cached {
+                    value: value.clone(),
+                    read_at: now,
+                });
+                value
+            }
+        }
+    }
+}
+
+/// An open connection to the X server, closed on drop
+struct XDisplay {
+    xlib: x11_dl::xlib::Xlib,
+    display: *mut x11_dl::xlib::Display,
+}
+
+impl XDisplay {
+    fn open() -> Option<Self> {
+        // libX11 is loaded at runtime, so Wayland-only and headless systems just fall through
+        let xlib = x11_dl::xlib::Xlib::open().ok()?;
+        let display = unsafe { (xlib.XOpenDisplay)(std::ptr::null()) };
+        if display.is_null() {
+            return None;
+        }
+        Some(XDisplay { xlib, display })
+    }
+
+    fn xkb(&self) -> Option<(String, String, usize)> {
+        unsafe { read_xkb_rules_names(&self.xlib, self.display) }
+    }
+}
+
+impl Drop for XDisplay {
+    fn drop(&mut self) {
+        unsafe { (self.xlib.XCloseDisplay)(self.display) };
+    }
+}
+
+/// Read the `_XKB_RULES_NAMES` root window property ("rules\0model\0layouts\0variants\0options")
+/// and the active group from XkbGetState
+unsafe fn read_xkb_rules_names(
+    xlib: &x11_dl::xlib::Xlib,
+    display: *mut x11_dl::xlib::Display,
+) -> Option<(String, String, usize)> {
+    use std::os::raw::{c_char, c_int, c_uchar, c_ulong, c_void};
+    use x11_dl::xlib;
+
+    const XKB_USE_CORE_KBD: u32 = 0x0100;
+
+    let name = b"_XKB_RULES_NAMES\0";
+    let atom = (xlib.XInternAtom)(display, name.as_ptr() as *const c_char, xlib::True);
+    if atom == 0 {
+        return None;
+    }
+
+    let mut actual_type: c_ulong = 0;
+    let mut actual_format: c_int = 0;
+    let mut item_count: c_ulong = 0;
+    let mut bytes_after: c_ulong = 0;
+    let mut data: *mut c_uchar = std::ptr::null_mut();
+    let status = (xlib.XGetWindowProperty)(
+        display,
+        (xlib.XDefaultRootWindow)(display),
+        atom,
+        0,
+        1024,
+        xlib::False,
+        xlib::XA_STRING,
+        &mut actual_type,
+        &mut actual_format,
+        &mut item_count,
+        &mut bytes_after,
+        &mut data,
+    );
+    if status != c_int::from(xlib::Success) || data.is_null() {
+        return None;
+    }
+    let bytes = std::slice::from_raw_parts(data, item_count as usize).to_vec();
+    (xlib.XFree)(data as *mut c_void);
+
+    let fields: Vec<String> = bytes
+        .split(|&b| b == 0)
+        .map(|field| String::from_utf8_lossy(field).into_owned())
+        .collect();
+    let layouts = fields.get(2)?.clone();
+    let variants = fields.get(3).cloned().unwrap_or_default();
+
+    let mut state: xlib::XkbStateRec = std::mem::zeroed();
+    let group = if (xlib.XkbGetState)(display, XKB_USE_CORE_KBD, &mut state)
+        == c_int::from(xlib::Success)
+    {
+        state.group as usize
+    } else {
+        0
+    };
+    Some((layouts, variants, group))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MockSources {
+        x11: Option<(String, String, usize)>,
+        localectl: Option<String>,
+        default_keyboard: Option<String>,
+        env: HashMap<&'static str, &'static str>,
+    }
+
+    impl LayoutSources for MockSources {
+        fn x11_xkb(&self) -> Option<(String, String, usize)> {
+            self.x11.clone()
+        }
+        fn localectl_status(&self) -> Option<String> {
+            self.localectl.clone()
+        }
+        fn default_keyboard_file(&self) -> Option<String> {
+            self.default_keyboard.clone()
+        }
+        fn env_var(&self, name: &str) -> Option<String> {
+            self.env.get(name).map(|v| v.to_string())
+        }
+    }
+
+    const LOCALECTL: &str = "   System Locale: LANG=de_DE.UTF-8
+       VC Keymap: de-latin1-nodeadkeys
+      X11 Layout: de
+       X11 Model: pc105
+     X11 Variant: nodeadkeys
+";
+
+    #[test]
+    fn test_cached_sources_are_read_again_once_stale() {
+        let slot = RefCell::new(None);
+        let start = Instant::now();
+        let reads = std::cell::Cell::new(0);
+        let read = || {
+            reads.set(reads.get() + 1);
+            Some(format!("read {}", reads.get()))
+        };
+
+        assert_eq!(Cached::get(&slot, start, read).as_deref(), Some("read 1"));
+        let soon = start + Duration::from_secs(1);
+        assert_eq!(Cached::get(&slot, soon, read).as_deref(), Some("read 1"));
+        let later = start + CONFIG_MAX_AGE;
+        assert_eq!(Cached::get(&slot, later, read).as_deref(), Some("read 2"));
+        assert_eq!(reads.get(), 2);
+    }
+
+    #[test]
+    fn test_x11_uses_active_group() {
+        let sources = MockSources {
+            x11: Some(("us,de".to_string(), ",nodeadkeys".to_string(), 1)),
+            localectl: Some("X11 Layout: fr".to_string()),
+            ..Default::default()
+        };
+        let layout = detect(&sources).unwrap();
+        assert_eq!(layout.to_source_id(), "xkb:de:nodeadkeys");
+
+        let first = MockSources {
+            x11: Some(("us,de".to_string(), ",nodeadkeys".to_string(), 0)),
+            ..Default::default()
+        };
+        assert_eq!(detect(&first).unwrap().to_source_id(), "xkb:us");
+    }
+
+    #[test]
+    fn test_falls_back_through_config_sources() {
+        let sources = MockSources {
+            localectl: Some(LOCALECTL.to_string()),
+            default_keyboard: Some("XKBLAYOUT=\"fr\"\n".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            detect(&sources).unwrap().to_source_id(),
+            "xkb:de:nodeadkeys"
+        );
+
+        let sources = MockSources {
+            localectl: Some("   System Locale: LANG=C\n".to_string()),
+            default_keyboard: Some(
+                "# KEYBOARD CONFIGURATION FILE\nXKBMODEL=\"pc105\"\nXKBLAYOUT=\"ch\"\nXKBVARIANT=\"fr\"\n"
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(detect(&sources).unwrap().to_source_id(), "xkb:ch:fr");
+
+        let mut env = HashMap::new();
+        env.insert("XKB_DEFAULT_LAYOUT", "se");
+        let sources = MockSources {
+            env,
+            ..Default::default()
+        };
+        assert_eq!(detect(&sources).unwrap().to_source_id(), "xkb:se");
+        assert_eq!(detect(&MockSources::default()), None);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::metrics::ErrorInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// What a single recorded keystroke did to the typed text
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeystrokeKind {
+    /// A character typed at a position that had no earlier mistake
+    KeyDown,
+    /// Removal of the character before the cursor
+    Backspace,
+    /// A character re-typed at a position that previously held an error
+    Correction,
+}
+
+impl KeystrokeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeystrokeKind::KeyDown => "key_down",
+            KeystrokeKind::Backspace => "backspace",
+            KeystrokeKind::Correction => "correction",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "key_down" => Some(KeystrokeKind::KeyDown),
+            "backspace" => Some(KeystrokeKind::Backspace),
+            "correction" => Some(KeystrokeKind::Correction),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeystrokeEvent {
+    pub kind: KeystrokeKind,
+    pub key: Option<char>,      // None for backspace
+    pub expected: Option<char>, // None when typing past the end of the target
+    pub position: usize,        // character index the event applied to
+    pub timestamp: i64,         // milliseconds since epoch
+    #[serde(default)]
+    pub layout: Option<String>, // layout id the key was typed on, when known
+}
+
+impl KeystrokeEvent {
+    /// Whether this event put a wrong character into the text
+    pub fn is_error(&self) -> bool {
+        self.key.is_some() && self.key != self.expected
+    }
+}
+
+/// Records the keystrokes of one typing attempt, classifying corrections as it goes.
+pub struct KeystrokeRecorder {
+    target: Vec<char>,
+    typed: Vec<char>,
+    errored_positions: HashSet<usize>,
+    events: Vec<KeystrokeEvent>,
+    layout: Option<String>,
+}
+
+impl KeystrokeRecorder {
+    pub fn new(target_text: &str) -> Self {
+        KeystrokeRecorder {
+            target: target_text.chars().collect(),
+            typed: Vec::new(),
+            errored_positions: HashSet::new(),
+            events: Vec::new(),
+            layout: None,
+        }
+    }
+
+    /// Layout stamped on the keystrokes recorded from now on
+    pub fn set_layout(&mut self, layout: Option<String>) {
+        self.layout = layout;
+    }
+
+    pub fn key_down(&mut self, key: char, timestamp: i64) -> &KeystrokeEvent {
+        let position = self.typed.len();
+        let expected = self.target.get(position).copied();
+        let kind = if self.errored_positions.contains(&position) {
+            KeystrokeKind::Correction
+        } else {
+            KeystrokeKind::KeyDown
+        };
+        if Some(key) != expected {
+            self.errored_positions.insert(position);
+        }
+
+        self.typed.push(key);
+        self.push(KeystrokeEvent {
+            kind,
+            key: Some(key),
+            expected,
+            position,
+            timestamp,
+            layout: self.layout.clone(),
+        })
+    }
+
+    /// Returns None when there is nothing left to delete
+    pub fn backspace(&mut self, timestamp: i64) -> Option<&KeystrokeEvent> {
+        self.typed.pop()?;
+        let position = self.typed.len();
+        let expected = self.target.get(position).copied();
+        Some(self.push(KeystrokeEvent {
+            kind: KeystrokeKind::Backspace,
+            key: None,
+            expected,
+            position,
+            timestamp,
+            layout: self.layout.clone(),
+        }))
+    }
+
+    pub fn typed_text(&self) -> String {
+        self.typed.iter().collect()
+    }
+
+    pub fn events(&self) -> &[KeystrokeEvent] {
+        &self.events
+    }
+
+    #[cfg(test)]
+    pub fn into_events(self) -> Vec<KeystrokeEvent> {
+        self.events
+    }
+
+    fn push(&mut self, event: KeystrokeEvent) -> &KeystrokeEvent {
+        self.events.push(event);
+        self.events.last().expect("event was just pushed")
+    }
+}
+
+/// One step of a replayed session: the text on screen right after `event`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplayFrame {
+    pub offset_ms: i64, // time since the first keystroke
+    pub text: String,
+    pub cursor: usize,
+    pub event: KeystrokeEvent,
+}
+
+/// Rebuild the typed text after every keystroke so a session can be played back
+pub fn replay(events: &[KeystrokeEvent]) -> Vec<ReplayFrame> {
+    let start = events.first().map(|e| e.timestamp).unwrap_or(0);
+    let mut typed: Vec<char> = Vec::new();
+
+    events
+        .iter()
+        .map(|event| {
+            typed.truncate(event.position);
+            if let Some(key) = event.key {
+                typed.push(key);
+            }
+            ReplayFrame {
+                offset_ms: event.timestamp - start,
+                text: typed.iter().collect(),
+                cursor: typed.len(),
+                event: event.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Every wrong character typed during the session, stamped with when it happened.
+/// Characters typed past the end of the target have no expected key and are skipped.
+pub fn errors_from_events(events: &[KeystrokeEvent]) -> Vec<ErrorInfo> {
+    events
+        .iter()
+        .filter(|e| e.is_error())
+        .filter_map(|e| {
+            Some(ErrorInfo {
+                index: e.position,
+                expected: e.expected?,
+                typed: e.key?,
+                timestamp: e.timestamp,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder_classifies_corrections() {
+        let mut rec = KeystrokeRecorder::new("cat");
+        rec.key_down('c', 1000);
+        rec.key_down('x', 1100); // wrong
+        rec.backspace(1200);
+        rec.key_down('a', 1300); // fixes the error
+        rec.key_down('t', 1400);
+
+        let kinds: Vec<_> = rec.events().iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                KeystrokeKind::KeyDown,
+                KeystrokeKind::KeyDown,
+                KeystrokeKind::Backspace,
+                KeystrokeKind::Correction,
+                KeystrokeKind::KeyDown,
+            ]
+        );
+        assert_eq!(rec.typed_text(), "cat");
+        assert!(rec.backspace(1500).is_some());
+    }
+
+    #[test]
+    fn test_replay_reconstructs_text() {
+        let mut rec = KeystrokeRecorder::new("ab");
+        rec.key_down('a', 5000);
+        rec.key_down('c', 5250);
+        rec.backspace(5400);
+        rec.key_down('b', 5600);
+
+        let frames = replay(rec.events());
+        let texts: Vec<_> = frames.iter().map(|f| f.text.as_str()).collect();
+        assert_eq!(texts, vec!["a", "ac", "a", "ab"]);
+        assert_eq!(frames[3].offset_ms, 600);
+        assert_eq!(frames[3].cursor, 2);
+    }
+
+    #[test]
+    fn test_errors_carry_timestamps() {
+        let mut rec = KeystrokeRecorder::new("hi");
+        rec.key_down('h', 10);
+        rec.key_down('o', 20);
+        rec.key_down('!', 30); // past the end, no expected char
+
+        let errors = errors_from_events(rec.events());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].index, 1);
+        assert_eq!(errors[0].expected, 'i');
+        assert_eq!(errors[0].typed, 'o');
+        assert_eq!(errors[0].timestamp, 20);
+    }
+}
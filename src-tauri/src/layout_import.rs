@@ -0,0 +1,450 @@
+use crate::layouts::{Finger, KeyDef, KeyboardLayout, LayoutId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+mod keylayout;
+mod klc;
+mod xkb;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutFormat {
+    /// XKB symbols file, as found in /usr/share/X11/xkb/symbols
+    Xkb,
+    /// Windows Microsoft Keyboard Layout Creator source
+    Klc,
+    /// macOS .keylayout XML
+    Keylayout,
+}
+
+impl LayoutFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LayoutFormat::Xkb => "xkb",
+            LayoutFormat::Klc => "klc",
+            LayoutFormat::Keylayout => "keylayout",
+        }
+    }
+
+    /// Guess from the extension, then from the contents; XKB symbols files rarely have one
+    pub fn detect(path: &Path, source: &str) -> Option<Self> {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("klc") => return Some(LayoutFormat::Klc),
+            Some("keylayout") => return Some(LayoutFormat::Keylayout),
+            _ => {}
+        }
+        if source.contains("xkb_symbols") {
+            Some(LayoutFormat::Xkb)
+        } else if source.contains("<keyboard") {
+            Some(LayoutFormat::Keylayout)
+        } else if source
+            .lines()
+            .any(|l| l.trim() == "LAYOUT" || l.starts_with("KBD"))
+        {
+            Some(LayoutFormat::Klc)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The layout is not imported
+    Error,
+    /// Something was left out, the rest of the layout is usable
+    Warning,
+}
+
+/// A problem found while importing, pointing at the offending line of the source
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LayoutDiagnostic {
+    pub line: Option<usize>, // 1-based, None when the problem isn't tied to a line
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl LayoutDiagnostic {
+    fn error(line: Option<usize>, message: String) -> Self {
+        LayoutDiagnostic {
+            line,
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(line: Option<usize>, message: String) -> Self {
+        LayoutDiagnostic {
+            line,
+            severity: Severity::Warning,
+            message,
+        }
+    }
+}
+
+/// The imported layout, present only when no diagnostic is an error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutImportReport {
+    pub format: Option<LayoutFormat>,
+    pub layout: Option<KeyboardLayout>,
+    pub diagnostics: Vec<LayoutDiagnostic>,
+}
+
+/// Characters on one physical key: base, Shift and AltGr (Option on macOS)
+type Levels = [Option<char>; 3];
+
+/// What every parser produces: characters keyed by XKB key name, e.g. "AC01" for the
+/// key under the left pinky on the home row
+#[derive(Debug, Default)]
+struct ParsedLayout {
+    name: Option<String>,
+    locale: Option<String>,
+    keys: BTreeMap<&'static str, Levels>,
+    base: Option<LayoutId>, // built-in layout filling the keys the source doesn't define
+    diagnostics: Vec<LayoutDiagnostic>,
+}
+
+impl ParsedLayout {
+    fn set(&mut self, key: &'static str, level: usize, ch: Option<char>) {
+        if ch.is_some() {
+            self.keys.entry(key).or_default()[level] = ch;
+        }
+    }
+}
+
+// Physical rows by XKB key name, with the frontend's finger number for each key.
+// BKSL closes the top row on ANSI keyboards and the home row on ISO ones, which
+// also have LSGT left of the bottom row.
+const NUMBER_ROW: &[(&str, u8)] = &[
+    ("TLDE", 1),
+    ("AE01", 1),
+    ("AE02", 2),
+    ("AE03", 3),
+    ("AE04", 4),
+    ("AE05", 4),
+    ("AE06", 7),
+    ("AE07", 7),
+    ("AE08", 8),
+    ("AE09", 9),
+    ("AE10", 10),
+    ("AE11", 10),
+    ("AE12", 10),
+];
+const TOP_ROW: &[(&str, u8)] = &[
+    ("AD01", 1),
+    ("AD02", 2),
+    ("AD03", 3),
+    ("AD04", 4),
+    ("AD05", 4),
+    ("AD06", 7),
+    ("AD07", 7),
+    ("AD08", 8),
+    ("AD09", 9),
+    ("AD10", 10),
+    ("AD11", 10),
+    ("AD12", 10),
+];
+const HOME_ROW: &[(&str, u8)] = &[
+    ("AC01", 1),
+    ("AC02", 2),
+    ("AC03", 3),
+    ("AC04", 4),
+    ("AC05", 4),
+    ("AC06", 7),
+    ("AC07", 7),
+    ("AC08", 8),
+    ("AC09", 9),
+    ("AC10", 10),
+    ("AC11", 10),
+];
+const BOTTOM_ROW: &[(&str, u8)] = &[
+    ("AB01", 1),
+    ("AB02", 2),
+    ("AB03", 3),
+    ("AB04", 4),
+    ("AB05", 4),
+    ("AB06", 7),
+    ("AB07", 7),
+    ("AB08", 8),
+    ("AB09", 9),
+    ("AB10", 10),
+];
+const BACKSLASH: (&str, u8) = ("BKSL", 10);
+const ISO_KEY: (&str, u8) = ("LSGT", 1);
+const SPACE: (&str, u8) = ("SPCE", 5);
+const HOME_KEYS: [&str; 8] = [
+    "AC01", "AC02", "AC03", "AC04", "AC07", "AC08", "AC09", "AC10",
+];
+
+/// The static name for a key we place on the keyboard, None for keys we don't show
+fn key_name(name: &str) -> Option<&'static str> {
+    [NUMBER_ROW, TOP_ROW, HOME_ROW, BOTTOM_ROW]
+        .into_iter()
+        .flatten()
+        .chain([&BACKSLASH, &ISO_KEY, &SPACE])
+        .map(|(n, _)| *n)
+        .find(|n| *n == name)
+}
+
+fn rows(iso: bool) -> Vec<Vec<(&'static str, u8)>> {
+    let mut top = TOP_ROW.to_vec();
+    let mut home = HOME_ROW.to_vec();
+    let mut bottom = BOTTOM_ROW.to_vec();
+    if iso {
+        home.push(BACKSLASH);
+        bottom.insert(0, ISO_KEY);
+    } else {
+        top.push(BACKSLASH);
+    }
+    vec![NUMBER_ROW.to_vec(), top, home, bottom, vec![SPACE]]
+}
+
+/// Key names for the keys of a built-in layout, so an XKB include can fill in the rest
+fn builtin_keys(layout: &KeyboardLayout) -> BTreeMap<&'static str, Levels> {
+    let iso = layout
+        .rows
+        .get(3)
+        .is_some_and(|row| row.len() > BOTTOM_ROW.len());
+    let first_char = |s: &str| {
+        let mut chars = s.chars();
+        chars.next().filter(|_| chars.next().is_none())
+    };
+    let mut keys = BTreeMap::new();
+    for (names, row) in rows(iso).iter().zip(&layout.rows) {
+        for ((name, _), key) in names.iter().zip(row) {
+            keys.insert(
+                *name,
+                [
+                    first_char(&key.key),
+                    key.shift.as_deref().and_then(first_char),
+                    key.alt_gr.as_deref().and_then(first_char),
+                ],
+            );
+        }
+    }
+    keys
+}
+
+/// Parse a layout in any supported format and check it can be used for finger guidance.
+/// `fallback_name` is used when the source doesn't name the layout.
+pub fn import_layout(
+    source: &str,
+    format: LayoutFormat,
+    fallback_name: &str,
+) -> LayoutImportReport {
+    let parsed = match format {
+        LayoutFormat::Xkb => xkb::parse(source),
+        LayoutFormat::Klc => klc::parse(source),
+        LayoutFormat::Keylayout => keylayout::parse(source),
+    };
+    let mut report = build(parsed, fallback_name);
+    report.format = Some(format);
+    report
+}
+
+/// Read and import a layout file. Windows saves .klc files as UTF-16, so the encoding
+/// comes from the byte order mark.
+pub fn import_layout_file(path: &Path) -> LayoutImportReport {
+    let source = match std::fs::read(path) {
+        Ok(bytes) => decode(&bytes),
+        Err(e) => return failed(None, e.to_string()),
+    };
+    let Some(format) = LayoutFormat::detect(path, &source) else {
+        return failed(
+            None,
+            format!("{} is not an XKB, .klc or .keylayout file", path.display()),
+        );
+    };
+    let fallback_name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Custom layout");
+    import_layout(&source, format, fallback_name)
+}
+
+fn failed(format: Option<LayoutFormat>, message: String) -> LayoutImportReport {
+    LayoutImportReport {
+        format,
+        layout: None,
+        diagnostics: vec![LayoutDiagnostic::error(None, message)],
+    }
+}
+
+fn decode(bytes: &[u8]) -> String {
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+fn build(parsed: ParsedLayout, fallback_name: &str) -> LayoutImportReport {
+    let mut diagnostics = parsed.diagnostics;
+
+    let mut keys = parsed
+        .base
+        .map(|base| builtin_keys(&KeyboardLayout::builtin(base)))
+        .unwrap_or_default();
+    for (name, levels) in parsed.keys {
+        let key = keys.entry(name).or_default();
+        for (slot, ch) in key.iter_mut().zip(levels) {
+            if ch.is_some() {
+                *slot = ch;
+            }
+        }
+    }
+    keys.entry("SPCE").or_default()[0].get_or_insert(' ');
+
+    for name in HOME_KEYS {
+        if keys.get(name).and_then(|levels| levels[0]).is_none() {
+            diagnostics.push(LayoutDiagnostic::error(
+                None,
+                format!("home-row key <{}> has no character", name),
+            ));
+        }
+    }
+
+    let layout = if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        None
+    } else {
+        let iso = keys.get("LSGT").is_some_and(|levels| levels[0].is_some());
+        let name = parsed
+            .name
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| fallback_name.to_string());
+        Some(KeyboardLayout {
+            id: custom_layout_id(&name),
+            name,
+            locale: parsed.locale.unwrap_or_default(),
+            rows: rows(iso)
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .filter_map(|(name, finger)| {
+                            let levels = keys.get(name)?;
+                            let finger = Finger::from_number(finger)?;
+                            let is_space = name == "SPCE";
+                            Some(KeyDef {
+                                key: levels[0]?.to_string(),
+                                shift: levels[1].map(String::from),
+                                alt_gr: levels[2].map(String::from),
+                                label: is_space.then(|| "Space".to_string()),
+                                width: is_space.then_some(6.0),
+                                finger,
+                                hand: finger.hand(),
+                                home: HOME_KEYS.contains(&name),
+                            })
+                        })
+                        .collect()
+                })
+                .collect(),
+        })
+    };
+
+    LayoutImportReport {
+        format: None,
+        layout,
+        diagnostics,
+    }
+}
+
+/// Custom layouts get ids that can never collide with the built-in ones
+pub fn custom_layout_id(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    format!("custom-{}", slug.trim_end_matches('-'))
+}
+
+/// Text between the first pair of double quotes
+fn quoted(text: &str) -> Option<&str> {
+    let start = text.find('"')? + 1;
+    let end = start + text[start..].find('"')?;
+    Some(&text[start..end])
+}
+
+/// 1-based line containing the byte offset
+fn line_at(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        let path = Path::new("layouts/neo");
+        assert_eq!(
+            LayoutFormat::detect(path, "xkb_symbols \"basic\" {"),
+            Some(LayoutFormat::Xkb)
+        );
+        assert_eq!(
+            LayoutFormat::detect(Path::new("Workman.KLC"), ""),
+            Some(LayoutFormat::Klc)
+        );
+        assert_eq!(
+            LayoutFormat::detect(path, "<?xml version=\"1.1\"?>\n<keyboard group=\"0\">"),
+            Some(LayoutFormat::Keylayout)
+        );
+        assert_eq!(LayoutFormat::detect(path, "hello"), None);
+    }
+
+    #[test]
+    fn test_decode_utf16_with_bom() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("KBD\tbépo".encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(decode(&bytes), "KBD\tbépo");
+        assert_eq!(decode("plain".as_bytes()), "plain");
+    }
+
+    #[test]
+    fn test_builtin_keys_roundtrip_through_rows() {
+        for id in [LayoutId::QwertyUs, LayoutId::QwertyDe] {
+            let builtin = KeyboardLayout::builtin(id);
+            let parsed = ParsedLayout {
+                base: Some(id),
+                ..Default::default()
+            };
+            let layout = build(parsed, "Copy").layout.unwrap();
+            assert_eq!(layout.id, "custom-copy");
+            assert_eq!(layout.rows, builtin.rows, "{}", id.as_str());
+        }
+        assert_eq!(
+            custom_layout_id("  Neo 2 (Bépo-ish)!"),
+            "custom-neo-2-bépo-ish"
+        );
+    }
+
+    #[test]
+    fn test_missing_home_keys_are_errors() {
+        let mut parsed = ParsedLayout::default();
+        for &(name, _) in HOME_ROW.iter().take(4) {
+            parsed.set(name, 0, Some('x'));
+        }
+        let report = build(parsed, "Half");
+        assert!(report.layout.is_none());
+        assert_eq!(report.diagnostics.len(), 4);
+        assert!(report.diagnostics[0].message.contains("<AC07>"));
+    }
+}
@@ -0,0 +1,425 @@
+use super::{line_at, LayoutDiagnostic, ParsedLayout};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+
+/// What a key produces in one key map: text, or an action looked up once all actions are read
+enum Output {
+    Text(String),
+    Action(String),
+}
+
+struct KeyEntry {
+    code: u16,
+    output: Output,
+    line: usize,
+}
+
+#[derive(Default)]
+struct Document {
+    name: Option<String>,
+    // modifiers and mapSet ids of the first <layout>
+    layout: Option<(String, String)>,
+    // modifierMap id -> (mapIndex, modifier keys that must be down) for every <modifier>
+    modifier_maps: HashMap<String, Vec<(String, Vec<String>)>>,
+    // keyMapSet id -> keyMap index -> keys
+    key_map_sets: HashMap<String, HashMap<String, Vec<KeyEntry>>>,
+    // action id -> output when no dead key is pending
+    actions: HashMap<String, String>,
+}
+
+/// Parse a macOS .keylayout file. Only the key maps selected by no modifier, by Shift
+/// alone and by Option alone are read; dead-key states are left out.
+pub(super) fn parse(source: &str) -> ParsedLayout {
+    let mut parsed = ParsedLayout::default();
+    let document = match read_document(source) {
+        Ok(document) => document,
+        Err(diagnostic) => {
+            parsed.diagnostics.push(diagnostic);
+            return parsed;
+        }
+    };
+    parsed.name = document.name.clone();
+
+    let Some((modifiers, map_set)) = &document.layout else {
+        parsed.diagnostics.push(LayoutDiagnostic::error(
+            None,
+            "the file has no <layout> element".to_string(),
+        ));
+        return parsed;
+    };
+    let (Some(selects), Some(key_maps)) = (
+        document.modifier_maps.get(modifiers),
+        document.key_map_sets.get(map_set),
+    ) else {
+        parsed.diagnostics.push(LayoutDiagnostic::error(
+            None,
+            format!(
+                "<layout> refers to modifierMap \"{}\" and keyMapSet \"{}\", which are not both defined",
+                modifiers, map_set
+            ),
+        ));
+        return parsed;
+    };
+
+    let mut levels: [Option<&str>; 3] = [None; 3];
+    for (index, required) in selects {
+        if let Some(level) = level_for(required) {
+            levels[level].get_or_insert(index);
+        }
+    }
+    let Some(base) = levels[0] else {
+        parsed.diagnostics.push(LayoutDiagnostic::error(
+            None,
+            "no key map is used without modifiers".to_string(),
+        ));
+        return parsed;
+    };
+    // Apple's ISO keyboards have a key at code 10 next to the left Shift, and code 50
+    // moves to the key left of 1
+    let iso = key_maps
+        .get(base)
+        .is_some_and(|keys| keys.iter().any(|k| k.code == 10));
+
+    for (level, index) in levels.iter().enumerate() {
+        let Some(keys) = index.and_then(|index| key_maps.get(index)) else {
+            continue;
+        };
+        for entry in keys {
+            let Some(name) = key_for_code(entry.code, iso) else {
+                continue;
+            };
+            let text = match &entry.output {
+                Output::Text(text) => Some(text),
+                Output::Action(id) => {
+                    let output = document.actions.get(id);
+                    if output.is_none() {
+                        parsed.diagnostics.push(LayoutDiagnostic::warning(
+                            Some(entry.line),
+                            format!("action \"{}\" is not defined", id),
+                        ));
+                    }
+                    output
+                }
+            };
+            let mut chars = text.map(|t| t.chars()).into_iter().flatten();
+            let ch = chars
+                .next()
+                .filter(|c| !c.is_control() && chars.next().is_none());
+            parsed.set(name, level, ch);
+        }
+    }
+    parsed
+}
+
+/// Level selected by a modifier combination, from the keys that must be down
+fn level_for(required: &[String]) -> Option<usize> {
+    match required {
+        [] => Some(0),
+        [key] if matches!(key.as_str(), "shift" | "rightShift" | "anyShift") => Some(1),
+        [key] if matches!(key.as_str(), "option" | "rightOption" | "anyOption") => Some(2),
+        _ => None,
+    }
+}
+
+fn read_document(source: &str) -> Result<Document, LayoutDiagnostic> {
+    let mut reader = Reader::from_str(source);
+    let mut document = Document::default();
+    let mut modifier_map: Option<String> = None;
+    let mut select: Option<String> = None;
+    let mut key_map: Option<(String, String)> = None;
+    let mut action: Option<String> = None;
+    // A <key> with its action written inline
+    let mut inline_key: Option<(u16, usize)> = None;
+
+    loop {
+        let line = line_at(source, reader.buffer_position() as usize);
+        let event = reader.read_event().map_err(|e| {
+            LayoutDiagnostic::error(
+                Some(line_at(source, reader.error_position() as usize)),
+                format!("invalid XML: {}", e),
+            )
+        })?;
+        let (element, is_empty) = match &event {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                match e.name().as_ref() {
+                    b"modifierMap" => modifier_map = None,
+                    b"keyMapSelect" => select = None,
+                    b"keyMap" => {
+                        if let Some((_, index)) = &mut key_map {
+                            index.clear();
+                        }
+                    }
+                    b"keyMapSet" => key_map = None,
+                    b"action" => action = None,
+                    b"key" => inline_key = None,
+                    _ => {}
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+        let attrs = attributes(element).map_err(|message| {
+            LayoutDiagnostic::error(Some(line), format!("invalid XML: {}", message))
+        })?;
+        let attr = |name: &str| attrs.get(name).cloned();
+
+        match element.name().as_ref() {
+            b"keyboard" => document.name = attr("name"),
+            b"layout" if document.layout.is_none() => {
+                if let (Some(modifiers), Some(map_set)) = (attr("modifiers"), attr("mapSet")) {
+                    document.layout = Some((modifiers, map_set));
+                }
+            }
+            b"modifierMap" if !is_empty => modifier_map = attr("id"),
+            b"keyMapSelect" if !is_empty => select = attr("mapIndex"),
+            b"modifier" => {
+                if let (Some(map), Some(index)) = (&modifier_map, &select) {
+                    let required = attr("keys")
+                        .unwrap_or_default()
+                        .split_whitespace()
+                        .filter(|key| !key.ends_with('?'))
+                        .map(String::from)
+                        .collect();
+                    document
+                        .modifier_maps
+                        .entry(map.clone())
+                        .or_default()
+                        .push((index.clone(), required));
+                }
+            }
+            b"keyMapSet" if !is_empty => {
+                key_map = attr("id").map(|id| (id, String::new()));
+            }
+            b"keyMap" if !is_empty => {
+                if let (Some((set, _)), Some(index)) = (&key_map, attr("index")) {
+                    key_map = Some((set.clone(), index));
+                }
+            }
+            b"key" => {
+                let Some((set, index)) = key_map.clone().filter(|(_, index)| !index.is_empty())
+                else {
+                    continue;
+                };
+                let Some(code) = attr("code").and_then(|c| c.trim().parse::<u16>().ok()) else {
+                    return Err(LayoutDiagnostic::error(
+                        Some(line),
+                        "<key> needs a numeric code".to_string(),
+                    ));
+                };
+                let output = match (attr("output"), attr("action")) {
+                    (Some(text), _) => Output::Text(text),
+                    (None, Some(id)) => Output::Action(id),
+                    (None, None) => {
+                        if !is_empty {
+                            inline_key = Some((code, line));
+                        }
+                        continue;
+                    }
+                };
+                push_key(&mut document, set, index, KeyEntry { code, output, line });
+            }
+            b"action" if !is_empty => action = attr("id"),
+            b"when" if attr("state").as_deref() == Some("none") => {
+                let Some(output) = attr("output") else {
+                    continue;
+                };
+                if let Some(id) = &action {
+                    document.actions.insert(id.clone(), output);
+                } else if let (Some((code, line)), Some((set, index))) =
+                    (inline_key, key_map.clone())
+                {
+                    let entry = KeyEntry {
+                        code,
+                        output: Output::Text(output),
+                        line,
+                    };
+                    push_key(&mut document, set, index, entry);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if document.key_map_sets.is_empty() {
+        return Err(LayoutDiagnostic::error(
+            None,
+            "the file has no <keyMapSet>, is it a .keylayout file?".to_string(),
+        ));
+    }
+    Ok(document)
+}
+
+fn push_key(document: &mut Document, set: String, index: String, entry: KeyEntry) {
+    document
+        .key_map_sets
+        .entry(set)
+        .or_default()
+        .entry(index)
+        .or_default()
+        .push(entry);
+}
+
+fn attributes(element: &BytesStart) -> Result<HashMap<String, String>, String> {
+    element
+        .attributes()
+        .map(|attr| {
+            let attr = attr.map_err(|e| e.to_string())?;
+            let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+            let value = attr.unescape_value().map_err(|e| e.to_string())?;
+            Ok((key, value.into_owned()))
+        })
+        .collect()
+}
+
+/// Mac virtual key codes of the keys we draw
+fn key_for_code(code: u16, iso: bool) -> Option<&'static str> {
+    Some(match code {
+        0 => "AC01",
+        1 => "AC02",
+        2 => "AC03",
+        3 => "AC04",
+        4 => "AC06",
+        5 => "AC05",
+        6 => "AB01",
+        7 => "AB02",
+        8 => "AB03",
+        9 => "AB04",
+        10 => "TLDE",
+        11 => "AB05",
+        12 => "AD01",
+        13 => "AD02",
+        14 => "AD03",
+        15 => "AD04",
+        16 => "AD06",
+        17 => "AD05",
+        18 => "AE01",
+        19 => "AE02",
+        20 => "AE03",
+        21 => "AE04",
+        22 => "AE06",
+        23 => "AE05",
+        24 => "AE12",
+        25 => "AE09",
+        26 => "AE07",
+        27 => "AE11",
+        28 => "AE08",
+        29 => "AE10",
+        30 => "AD12",
+        31 => "AD09",
+        32 => "AD07",
+        33 => "AD11",
+        34 => "AD08",
+        35 => "AD10",
+        37 => "AC09",
+        38 => "AC07",
+        39 => "AC11",
+        40 => "AC08",
+        41 => "AC10",
+        42 => "BKSL",
+        43 => "AB08",
+        44 => "AB10",
+        45 => "AB06",
+        46 => "AB07",
+        47 => "AB09",
+        49 => "SPCE",
+        50 if iso => "LSGT",
+        50 => "TLDE",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{import_layout, LayoutFormat};
+    use super::*;
+
+    const GERMAN_LIKE: &str = r#"<?xml version="1.1" encoding="UTF-8"?>
+<!DOCTYPE keyboard SYSTEM "file://localhost/System/Library/DTDs/KeyboardLayout.dtd">
+<keyboard group="0" id="-2" name="German (test)" maxout="1">
+    <layouts>
+        <layout first="0" last="17" mapSet="ANSI" modifiers="mods"/>
+    </layouts>
+    <modifierMap id="mods" defaultIndex="0">
+        <keyMapSelect mapIndex="0">
+            <modifier keys=""/>
+        </keyMapSelect>
+        <keyMapSelect mapIndex="1">
+            <modifier keys="anyShift caps?"/>
+        </keyMapSelect>
+        <keyMapSelect mapIndex="2">
+            <modifier keys="caps"/>
+        </keyMapSelect>
+        <keyMapSelect mapIndex="3">
+            <modifier keys="anyOption"/>
+        </keyMapSelect>
+    </modifierMap>
+    <keyMapSet id="ANSI">
+        <keyMap index="0">
+            <key code="0" output="a"/>
+            <key code="1" output="s"/>
+            <key code="2" output="d"/>
+            <key code="3" output="f"/>
+            <key code="10" output="^"/>
+            <key code="38" output="j"/>
+            <key code="40" output="k"/>
+            <key code="37" output="l"/>
+            <key code="41" action="oe"/>
+            <key code="50" output="&#x003C;"/>
+            <key code="51" output="&#x0008;"/>
+            <key code="16"><when state="none" output="z"/></key>
+        </keyMap>
+        <keyMap index="1">
+            <key code="0" output="A"/>
+            <key code="41" output="Ö"/>
+        </keyMap>
+        <keyMap index="2">
+            <key code="0" output="X"/>
+        </keyMap>
+        <keyMap index="3">
+            <key code="0" output="å"/>
+            <key code="1" action="missing"/>
+        </keyMap>
+    </keyMapSet>
+    <actions>
+        <action id="oe">
+            <when state="none" output="ö"/>
+            <when state="dead1" output="ő"/>
+        </action>
+    </actions>
+</keyboard>
+"#;
+
+    #[test]
+    fn test_parse_key_maps() {
+        let parsed = parse(GERMAN_LIKE);
+        assert_eq!(parsed.name.as_deref(), Some("German (test)"));
+        assert_eq!(parsed.keys["AC01"], [Some('a'), Some('A'), Some('å')]);
+        assert_eq!(parsed.keys["AC10"], [Some('ö'), Some('Ö'), None]);
+        assert_eq!(parsed.keys["AD06"][0], Some('z'));
+        // Code 10 is defined, so this is an ISO keyboard
+        assert_eq!(parsed.keys["TLDE"][0], Some('^'));
+        assert_eq!(parsed.keys["LSGT"][0], Some('<'));
+
+        assert_eq!(parsed.diagnostics.len(), 1);
+        assert_eq!(parsed.diagnostics[0].line, Some(45));
+
+        let layout = import_layout(GERMAN_LIKE, LayoutFormat::Keylayout, "x")
+            .layout
+            .unwrap();
+        assert_eq!(layout.rows[3][0].key, "<");
+        assert_eq!(layout.rows[2][7].key, "ö");
+    }
+
+    #[test]
+    fn test_invalid_xml_is_reported_by_line() {
+        let source = "<keyboard name=\"x\">\n  <keyMapSet id=\"a\">\n    <keyMap index=\"0\">\n  </keyMapSet>\n</keyboard>\n";
+        let report = import_layout(source, LayoutFormat::Keylayout, "x");
+        assert!(report.layout.is_none());
+        assert!(report.diagnostics[0].message.starts_with("invalid XML"));
+        assert_eq!(report.diagnostics[0].line, Some(4));
+    }
+}
@@ -0,0 +1,260 @@
+use super::{quoted, LayoutDiagnostic, ParsedLayout};
+
+const KEYWORDS: &[&str] = &[
+    "KBD",
+    "COPYRIGHT",
+    "COMPANY",
+    "LOCALENAME",
+    "LOCALEID",
+    "VERSION",
+    "SHIFTSTATE",
+    "LAYOUT",
+    "ATTRIBUTES",
+    "LIGATURE",
+    "DEADKEY",
+    "KEYNAME",
+    "KEYNAME_EXT",
+    "KEYNAME_DEAD",
+    "DESCRIPTIONS",
+    "LANGUAGENAMES",
+    "ENDKBD",
+];
+
+// Shift states of the columns MSKLC writes when a file has no SHIFTSTATE section
+const DEFAULT_SHIFT_STATES: [u8; 5] = [0, 1, 2, 6, 7];
+
+#[derive(PartialEq)]
+enum Section {
+    Header,
+    ShiftState,
+    Layout,
+    Other,
+}
+
+/// Parse the LAYOUT table of a Microsoft Keyboard Layout Creator source file.
+/// Dead keys and ligatures leave their level empty.
+pub(super) fn parse(source: &str) -> ParsedLayout {
+    let mut parsed = ParsedLayout::default();
+    let mut section = Section::Header;
+    let mut shift_states = Vec::new();
+    let mut has_layout = false;
+
+    for (index, raw) in source.lines().enumerate() {
+        let line = Some(index + 1);
+        let text = raw.split("//").next().unwrap_or_default().trim();
+        let mut tokens = text.split_whitespace();
+        let Some(first) = tokens.next() else {
+            continue;
+        };
+
+        if KEYWORDS.contains(&first) {
+            section = match first {
+                "KBD" => {
+                    parsed.name = quoted(text).map(String::from);
+                    Section::Header
+                }
+                "LOCALENAME" => {
+                    parsed.locale = quoted(text).map(String::from);
+                    Section::Header
+                }
+                "SHIFTSTATE" => Section::ShiftState,
+                "LAYOUT" => {
+                    has_layout = true;
+                    if shift_states.is_empty() {
+                        shift_states = DEFAULT_SHIFT_STATES.to_vec();
+                    }
+                    Section::Layout
+                }
+                _ => Section::Other,
+            };
+            continue;
+        }
+
+        match section {
+            Section::ShiftState => match first.parse::<u8>() {
+                Ok(state) => shift_states.push(state),
+                Err(_) => parsed.diagnostics.push(LayoutDiagnostic::error(
+                    line,
+                    format!("'{}' is not a shift state", first),
+                )),
+            },
+            Section::Layout => layout_line(&mut parsed, line, first, tokens, &shift_states),
+            Section::Header | Section::Other => {}
+        }
+    }
+
+    if !has_layout {
+        parsed.diagnostics.push(LayoutDiagnostic::error(
+            None,
+            "the file has no LAYOUT section".to_string(),
+        ));
+    }
+    parsed
+}
+
+/// `SC VK Cap col...`, e.g. `1e  A  1  a  A  -1  00e6  00c6`
+fn layout_line<'a>(
+    parsed: &mut ParsedLayout,
+    line: Option<usize>,
+    scancode: &str,
+    mut tokens: impl Iterator<Item = &'a str>,
+    shift_states: &[u8],
+) {
+    // SGCap continuation lines have no scancode of their own
+    if scancode == "-1" {
+        return;
+    }
+    let Ok(code) = u8::from_str_radix(scancode, 16) else {
+        parsed.diagnostics.push(LayoutDiagnostic::error(
+            line,
+            format!("'{}' is not a scancode", scancode),
+        ));
+        return;
+    };
+    let (Some(_vk), Some(_cap)) = (tokens.next(), tokens.next()) else {
+        parsed.diagnostics.push(LayoutDiagnostic::error(
+            line,
+            format!("scancode {} has no characters", scancode),
+        ));
+        return;
+    };
+    let Some(name) = key_for_scancode(code) else {
+        return;
+    };
+
+    for (&state, value) in shift_states.iter().zip(tokens) {
+        let level = match state {
+            0 => 0,
+            1 => 1,
+            6 => 2,
+            _ => continue,
+        };
+        match character(value) {
+            Ok(ch) => parsed.set(name, level, ch),
+            Err(()) => parsed.diagnostics.push(LayoutDiagnostic::error(
+                line,
+                format!("'{}' is not a character on scancode {}", value, scancode),
+            )),
+        }
+    }
+}
+
+/// A column value: a literal character, four hex digits, `-1` for nothing,
+/// `%%` for a ligature and a trailing `@` for a dead key
+fn character(value: &str) -> Result<Option<char>, ()> {
+    if value == "-1" || value == "%%" || value.ends_with('@') {
+        return Ok(None);
+    }
+    let mut chars = value.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Ok(Some(ch));
+    }
+    if value.len() == 4 {
+        let code = u32::from_str_radix(value, 16).map_err(|_| ())?;
+        let ch = char::from_u32(code).ok_or(())?;
+        return Ok((!ch.is_control()).then_some(ch));
+    }
+    Err(())
+}
+
+/// Set 1 scancodes of the keys we draw
+fn key_for_scancode(code: u8) -> Option<&'static str> {
+    const NUMBER_ROW: [&str; 12] = [
+        "AE01", "AE02", "AE03", "AE04", "AE05", "AE06", "AE07", "AE08", "AE09", "AE10", "AE11",
+        "AE12",
+    ];
+    const TOP_ROW: [&str; 12] = [
+        "AD01", "AD02", "AD03", "AD04", "AD05", "AD06", "AD07", "AD08", "AD09", "AD10", "AD11",
+        "AD12",
+    ];
+    const HOME_ROW: [&str; 11] = [
+        "AC01", "AC02", "AC03", "AC04", "AC05", "AC06", "AC07", "AC08", "AC09", "AC10", "AC11",
+    ];
+    const BOTTOM_ROW: [&str; 10] = [
+        "AB01", "AB02", "AB03", "AB04", "AB05", "AB06", "AB07", "AB08", "AB09", "AB10",
+    ];
+    match code {
+        0x02..=0x0d => Some(NUMBER_ROW[usize::from(code - 0x02)]),
+        0x10..=0x1b => Some(TOP_ROW[usize::from(code - 0x10)]),
+        0x1e..=0x28 => Some(HOME_ROW[usize::from(code - 0x1e)]),
+        0x2c..=0x35 => Some(BOTTOM_ROW[usize::from(code - 0x2c)]),
+        0x29 => Some("TLDE"),
+        0x2b => Some("BKSL"),
+        0x39 => Some("SPCE"),
+        0x56 => Some("LSGT"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{import_layout, LayoutFormat};
+    use super::*;
+
+    const COLEMAK_LIKE: &str = "KBD\tcolemak\t\"Colemak (partial)\"\r
+\r
+LOCALENAME\t\"en-US\"\r
+\r
+SHIFTSTATE\r
+\r
+0\t//Column 4\r
+1\t//Column 5 : Shft\r
+2\t//Column 6 :       Ctrl\r
+6\t//Column 7 :       Ctrl Alt\r
+\r
+LAYOUT\t\t;an extra '@' at the end is a dead key\r
+\r
+//SC\tVK_\t\tCap\t0\t1\t2\t6\r
+29\tOEM_3\t\t0\t`\t~\t-1\t0060@\r
+1e\tA\t\t1\ta\tA\t-1\t00e1\r
+1f\tR\t\t1\tr\tR\t-1\t-1\r
+20\tS\t\t1\ts\tS\t-1\t00df\r
+21\tT\t\t1\tt\tT\t-1\t-1\r
+24\tN\t\t1\tn\tN\t-1\t00f1\r
+25\tE\t\t1\te\tE\t-1\t00e9\r
+26\tI\t\t1\ti\tI\t-1\t00ed\r
+27\tO\t\t1\to\tO\t-1\t00f3\r
+2b\tOEM_5\t\t0\t\\\t|\t001c\t-1\r
+53\tDECIMAL\t\t0\t002e\t002e\t-1\t-1\r
+\r
+DEADKEY\t0060\r
+\r
+0061\t00e0\r
+\r
+ENDKBD\r
+";
+
+    #[test]
+    fn test_parse_layout_table() {
+        let parsed = parse(COLEMAK_LIKE);
+        assert!(parsed.diagnostics.is_empty(), "{:?}", parsed.diagnostics);
+        assert_eq!(parsed.name.as_deref(), Some("Colemak (partial)"));
+        assert_eq!(parsed.locale.as_deref(), Some("en-US"));
+        assert_eq!(parsed.keys["AC01"], [Some('a'), Some('A'), Some('á')]);
+        assert_eq!(parsed.keys["AC03"][2], Some('ß'));
+        // Dead keys and Ctrl control characters are left out
+        assert_eq!(parsed.keys["TLDE"], [Some('`'), Some('~'), None]);
+        assert_eq!(parsed.keys["BKSL"], [Some('\\'), Some('|'), None]);
+        assert_eq!(parsed.keys.len(), 10);
+
+        let layout = import_layout(COLEMAK_LIKE, LayoutFormat::Klc, "colemak")
+            .layout
+            .unwrap();
+        // Keys the file doesn't define are left off the row
+        let home: Vec<_> = layout.rows[2].iter().map(|k| k.key.as_str()).collect();
+        assert_eq!(home, ["a", "r", "s", "t", "n", "e", "i", "o"]);
+        assert!(layout.rows[2][4].home);
+    }
+
+    #[test]
+    fn test_bad_values_are_reported_by_line() {
+        let source = "KBD x \"X\"\nLAYOUT\n1e A 1 a A\n1f S 1 ss S\nzz Q 1 q Q\n20 D\n";
+        let report = import_layout(source, LayoutFormat::Klc, "x");
+        let lines: Vec<_> = report.diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines[..3], [Some(4), Some(5), Some(6)]);
+        assert!(report.layout.is_none());
+
+        let report = import_layout("KBD x \"X\"\n", LayoutFormat::Klc, "x");
+        assert!(report.diagnostics[0].message.contains("LAYOUT"));
+    }
+}
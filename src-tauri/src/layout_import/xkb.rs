@@ -0,0 +1,591 @@
+use super::{key_name, line_at, quoted, LayoutDiagnostic, ParsedLayout};
+use crate::layouts::{self, MatchKind};
+
+/// Parse the default `xkb_symbols` block of a symbols file, or the first one when none is
+/// marked default. Includes of layouts we ship fill the keys the block doesn't redefine.
+pub(super) fn parse(source: &str) -> ParsedLayout {
+    let mut parsed = ParsedLayout::default();
+    let clean = strip_comments(source);
+
+    let Some((start, end)) = pick_block(&clean) else {
+        parsed.diagnostics.push(LayoutDiagnostic::error(
+            None,
+            "no xkb_symbols block found".to_string(),
+        ));
+        return parsed;
+    };
+
+    for (offset, statement) in statements(&clean, start, end) {
+        let line = Some(line_at(source, offset));
+        if let Some(rest) = statement.strip_prefix("include") {
+            include(&mut parsed, line, quoted(rest).unwrap_or_default());
+        } else if statement.starts_with("name[") {
+            parsed.name = quoted(statement).map(String::from);
+        } else if let Some(pos) = statement.find("key <").filter(|&p| {
+            // `key <..>`, optionally preceded by `replace` or `override`
+            statement[..p].trim().chars().all(char::is_alphabetic)
+        }) {
+            key(&mut parsed, line, &statement[pos + 4..]);
+        }
+    }
+    parsed
+}
+
+fn include(parsed: &mut ParsedLayout, line: Option<usize>, name: &str) {
+    let source_id = match name.split_once('(') {
+        Some((layout, variant)) => format!("xkb:{}:{}", layout, variant.trim_end_matches(')')),
+        None => format!("xkb:{}", name),
+    };
+    match layouts::normalize_input_source(&source_id) {
+        Some(found) if parsed.base.is_none() => {
+            if found.kind == MatchKind::Fallback {
+                parsed.diagnostics.push(LayoutDiagnostic::warning(
+                    line,
+                    format!(
+                        "include \"{}\" is approximated by the built-in {} layout",
+                        name,
+                        found.layout.as_str()
+                    ),
+                ));
+            }
+            parsed.base = Some(found.layout);
+        }
+        _ => parsed.diagnostics.push(LayoutDiagnostic::warning(
+            line,
+            format!("include \"{}\" was not followed", name),
+        )),
+    }
+}
+
+fn key(parsed: &mut ParsedLayout, line: Option<usize>, statement: &str) {
+    let Some((name, body)) = statement
+        .strip_prefix('<')
+        .and_then(|rest| rest.split_once('>'))
+    else {
+        parsed.diagnostics.push(LayoutDiagnostic::error(
+            line,
+            "key name is not closed with '>'".to_string(),
+        ));
+        return;
+    };
+    // Keys we don't draw, such as the keypad or F-keys, are skipped without comment
+    let Some(name) = key_name(name.trim()) else {
+        return;
+    };
+    let Some(symbols) = symbol_list(body) else {
+        parsed.diagnostics.push(LayoutDiagnostic::error(
+            line,
+            format!("key <{}> has no symbol list", name),
+        ));
+        return;
+    };
+
+    for (level, symbol) in symbols.split(',').map(str::trim).take(3).enumerate() {
+        match keysym(symbol) {
+            Ok(ch) => parsed.set(name, level, ch),
+            Err(()) => parsed.diagnostics.push(LayoutDiagnostic::warning(
+                line,
+                format!("unknown keysym '{}' on <{}> was left out", symbol, name),
+            )),
+        }
+    }
+}
+
+/// The `[ a, A, ... ]` list of the first group. `symbols[Group1] = [...]` wins when present;
+/// bracketed group indexes like `type[Group1]` are never the list.
+fn symbol_list(body: &str) -> Option<&str> {
+    let body = match body.find("symbols[") {
+        Some(pos) => &body[pos + "symbols".len()..],
+        None => body,
+    };
+    let mut rest = body;
+    while let Some(open) = rest.find('[') {
+        let close = open + rest[open..].find(']')?;
+        let content = rest[open + 1..close].trim();
+        if !content.to_ascii_lowercase().starts_with("group") {
+            return Some(content);
+        }
+        rest = &rest[close + 1..];
+    }
+    None
+}
+
+/// Replace `//` and `#` comments with spaces, keeping byte offsets and line breaks
+fn strip_comments(source: &str) -> String {
+    source
+        .lines()
+        .map(|line| match line.find("//").or_else(|| line.find('#')) {
+            Some(pos) => format!("{}{}", &line[..pos], " ".repeat(line.len() - pos)),
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Byte range of the body of the block to import
+fn pick_block(clean: &str) -> Option<(usize, usize)> {
+    let mut blocks = Vec::new();
+    let mut header_start = 0;
+    let mut search = 0;
+    while let Some(found) = clean[search..].find("xkb_symbols") {
+        let keyword = search + found;
+        let open = keyword + clean[keyword..].find('{')? + 1;
+        let mut depth = 1;
+        let mut close = None;
+        for (i, c) in clean[open..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = Some(open + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let close = close?;
+        let is_default = clean[header_start..keyword].contains("default");
+        blocks.push((open, close, is_default));
+        header_start = close + 1;
+        search = close + 1;
+    }
+    blocks
+        .iter()
+        .find(|(_, _, is_default)| *is_default)
+        .or(blocks.first())
+        .map(|&(open, close, _)| (open, close))
+}
+
+/// Statements of a block body with the offset where each starts
+fn statements(clean: &str, start: usize, end: usize) -> Vec<(usize, &str)> {
+    let mut statements = Vec::new();
+    let mut depth = 0;
+    let mut statement_start = start;
+    for (i, c) in clean[start..end].char_indices() {
+        let i = start + i;
+        match c {
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            ';' if depth == 0 => {
+                push_statement(&mut statements, clean, statement_start, i);
+                statement_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    // `include` lines don't need a semicolon
+    push_statement(&mut statements, clean, statement_start, end);
+
+    statements
+        .into_iter()
+        .flat_map(|(offset, text)| split_includes(offset, text))
+        .collect()
+}
+
+fn push_statement<'a>(
+    statements: &mut Vec<(usize, &'a str)>,
+    clean: &'a str,
+    start: usize,
+    end: usize,
+) {
+    let raw = &clean[start..end];
+    let trimmed = raw.trim_start();
+    let offset = start + raw.len() - trimmed.len();
+    let text = trimmed.trim_end();
+    if !text.is_empty() {
+        statements.push((offset, text));
+    }
+}
+
+/// Split an `include "x"` written without a semicolon off the statement that follows it
+fn split_includes(offset: usize, text: &str) -> Vec<(usize, &str)> {
+    if !text.starts_with("include") {
+        return vec![(offset, text)];
+    }
+    let end = quoted(text)
+        .map(|name| {
+            let start = text.find('"').unwrap_or(0) + 1;
+            start + name.len() + 1
+        })
+        .unwrap_or(text.len());
+    let mut parts = vec![(offset, &text[..end])];
+    let rest = &text[end..];
+    let trimmed = rest.trim_start();
+    if !trimmed.is_empty() {
+        parts.extend(split_includes(
+            offset + end + rest.len() - trimmed.len(),
+            trimmed,
+        ));
+    }
+    parts
+}
+
+// Keysyms 0x20-0x7e and 0xa0-0xff are Latin-1 and share their code points
+const ASCII_KEYSYMS: [&str; 95] = [
+    "space",
+    "exclam",
+    "quotedbl",
+    "numbersign",
+    "dollar",
+    "percent",
+    "ampersand",
+    "apostrophe",
+    "parenleft",
+    "parenright",
+    "asterisk",
+    "plus",
+    "comma",
+    "minus",
+    "period",
+    "slash",
+    "0",
+    "1",
+    "2",
+    "3",
+    "4",
+    "5",
+    "6",
+    "7",
+    "8",
+    "9",
+    "colon",
+    "semicolon",
+    "less",
+    "equal",
+    "greater",
+    "question",
+    "at",
+    "A",
+    "B",
+    "C",
+    "D",
+    "E",
+    "F",
+    "G",
+    "H",
+    "I",
+    "J",
+    "K",
+    "L",
+    "M",
+    "N",
+    "O",
+    "P",
+    "Q",
+    "R",
+    "S",
+    "T",
+    "U",
+    "V",
+    "W",
+    "X",
+    "Y",
+    "Z",
+    "bracketleft",
+    "backslash",
+    "bracketright",
+    "asciicircum",
+    "underscore",
+    "grave",
+    "a",
+    "b",
+    "c",
+    "d",
+    "e",
+    "f",
+    "g",
+    "h",
+    "i",
+    "j",
+    "k",
+    "l",
+    "m",
+    "n",
+    "o",
+    "p",
+    "q",
+    "r",
+    "s",
+    "t",
+    "u",
+    "v",
+    "w",
+    "x",
+    "y",
+    "z",
+    "braceleft",
+    "bar",
+    "braceright",
+    "asciitilde",
+];
+
+const LATIN1_KEYSYMS: [&str; 96] = [
+    "nobreakspace",
+    "exclamdown",
+    "cent",
+    "sterling",
+    "currency",
+    "yen",
+    "brokenbar",
+    "section",
+    "diaeresis",
+    "copyright",
+    "ordfeminine",
+    "guillemotleft",
+    "notsign",
+    "hyphen",
+    "registered",
+    "macron",
+    "degree",
+    "plusminus",
+    "twosuperior",
+    "threesuperior",
+    "acute",
+    "mu",
+    "paragraph",
+    "periodcentered",
+    "cedilla",
+    "onesuperior",
+    "masculine",
+    "guillemotright",
+    "onequarter",
+    "onehalf",
+    "threequarters",
+    "questiondown",
+    "Agrave",
+    "Aacute",
+    "Acircumflex",
+    "Atilde",
+    "Adiaeresis",
+    "Aring",
+    "AE",
+    "Ccedilla",
+    "Egrave",
+    "Eacute",
+    "Ecircumflex",
+    "Ediaeresis",
+    "Igrave",
+    "Iacute",
+    "Icircumflex",
+    "Idiaeresis",
+    "ETH",
+    "Ntilde",
+    "Ograve",
+    "Oacute",
+    "Ocircumflex",
+    "Otilde",
+    "Odiaeresis",
+    "multiply",
+    "Oslash",
+    "Ugrave",
+    "Uacute",
+    "Ucircumflex",
+    "Udiaeresis",
+    "Yacute",
+    "THORN",
+    "ssharp",
+    "agrave",
+    "aacute",
+    "acircumflex",
+    "atilde",
+    "adiaeresis",
+    "aring",
+    "ae",
+    "ccedilla",
+    "egrave",
+    "eacute",
+    "ecircumflex",
+    "ediaeresis",
+    "igrave",
+    "iacute",
+    "icircumflex",
+    "idiaeresis",
+    "eth",
+    "ntilde",
+    "ograve",
+    "oacute",
+    "ocircumflex",
+    "otilde",
+    "odiaeresis",
+    "division",
+    "oslash",
+    "ugrave",
+    "uacute",
+    "ucircumflex",
+    "udiaeresis",
+    "yacute",
+    "thorn",
+    "ydiaeresis",
+];
+
+// Named keysyms outside Latin-1 that the layouts we care about put on the first levels
+const OTHER_KEYSYMS: &[(&str, char)] = &[
+    ("EuroSign", '€'),
+    ("oe", 'œ'),
+    ("OE", 'Œ'),
+    ("idotless", 'ı'),
+    ("Iabovedot", 'İ'),
+    ("gbreve", 'ğ'),
+    ("Gbreve", 'Ğ'),
+    ("scedilla", 'ş'),
+    ("Scedilla", 'Ş'),
+    ("scaron", 'š'),
+    ("Scaron", 'Š'),
+    ("zcaron", 'ž'),
+    ("Zcaron", 'Ž'),
+    ("ccaron", 'č'),
+    ("Ccaron", 'Č'),
+    ("lstroke", 'ł'),
+    ("Lstroke", 'Ł'),
+    ("guillemetleft", '«'),
+    ("guillemetright", '»'),
+    ("ordmasculine", 'º'),
+    ("Ooblique", 'Ø'),
+    ("ooblique", 'ø'),
+    ("ellipsis", '…'),
+    ("endash", '–'),
+    ("emdash", '—'),
+    ("leftsinglequotemark", '‘'),
+    ("rightsinglequotemark", '’'),
+    ("singlelowquotemark", '‚'),
+    ("leftdoublequotemark", '“'),
+    ("rightdoublequotemark", '”'),
+    ("doublelowquotemark", '„'),
+];
+
+// Keysyms that are real but type no character; they leave their level empty
+const NON_CHARACTER_KEYSYMS: &[&str] = &[
+    "NoSymbol",
+    "VoidSymbol",
+    "Mode_switch",
+    "Multi_key",
+    "Tab",
+    "Return",
+    "BackSpace",
+    "Escape",
+    "Delete",
+    "Caps_Lock",
+    "Menu",
+];
+const NON_CHARACTER_PREFIXES: &[&str] = &[
+    "dead_", "ISO_", "KP_", "XF86", "Shift_", "Control_", "Alt_", "Meta_", "Super_", "Hyper_",
+];
+
+/// The character a keysym types, None for keysyms that type nothing
+fn keysym(name: &str) -> Result<Option<char>, ()> {
+    if let Some(i) = ASCII_KEYSYMS.iter().position(|&s| s == name) {
+        return Ok(char::from_u32(0x20 + i as u32));
+    }
+    if let Some(i) = LATIN1_KEYSYMS.iter().position(|&s| s == name) {
+        return Ok(char::from_u32(0xa0 + i as u32));
+    }
+    if let Some(&(_, ch)) = OTHER_KEYSYMS.iter().find(|(s, _)| *s == name) {
+        return Ok(Some(ch));
+    }
+    // U20AC, or 0x10020ac for Unicode keysyms written as numbers
+    if let Some(hex) = name.strip_prefix('U').filter(|h| h.len() >= 4) {
+        if let Ok(code) = u32::from_str_radix(hex, 16) {
+            return char::from_u32(code).map(Some).ok_or(());
+        }
+    }
+    if let Some(hex) = name.strip_prefix("0x") {
+        let code = u32::from_str_radix(hex, 16).map_err(|_| ())?;
+        return match code {
+            0x20..=0x7e | 0xa0..=0xff => Ok(char::from_u32(code)),
+            0x0100_0000.. => char::from_u32(code - 0x0100_0000).map(Some).ok_or(()),
+            _ => Err(()),
+        };
+    }
+    let is_function_key = name
+        .strip_prefix('F')
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    if is_function_key
+        || NON_CHARACTER_KEYSYMS.contains(&name)
+        || NON_CHARACTER_PREFIXES.iter().any(|p| name.starts_with(p))
+    {
+        return Ok(None);
+    }
+    Err(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{import_layout, LayoutFormat, Severity};
+    use super::*;
+    use crate::layouts::LayoutId;
+
+    const NEO_LIKE: &str = r#"
+// A cut-down Neo-style layout: letters moved, everything else from German
+default partial alphanumeric_keys
+xkb_symbols "basic" {
+    include "de(basic)"
+    name[Group1]= "German (Neo-ish)";
+
+    key <AC01> { [ u, U, backslash ] };
+    key <AC02> { type[Group1] = "FOUR_LEVEL", [ i, I, slash, Greek_iota ] };
+    key <AC03> { symbols[Group1] = [ a, A, braceleft ] };
+    key <AC04> { [ e, E, braceright ] };
+    key <TLDE> { [ dead_circumflex, dead_caron ] };
+    key <KP1>  { [ KP_End, KP_1 ] };
+    replace key <AB01> { [ udiaeresis, Udiaeresis, numbersign ] };
+    include "level3(caps_switch)"
+};
+
+xkb_symbols "other" {
+    key <AC01> { [ q, Q ] };
+};
+"#;
+
+    #[test]
+    fn test_parse_symbols_over_include() {
+        let parsed = parse(NEO_LIKE);
+        assert_eq!(parsed.name.as_deref(), Some("German (Neo-ish)"));
+        assert_eq!(parsed.base, Some(LayoutId::QwertyDe));
+        assert_eq!(parsed.keys["AC01"], [Some('u'), Some('U'), Some('\\')]);
+        assert_eq!(parsed.keys["AC02"], [Some('i'), Some('I'), Some('/')]);
+        assert_eq!(parsed.keys["AC03"][2], Some('{'));
+        assert_eq!(parsed.keys["AB01"][0], Some('ü'));
+        assert!(!parsed.keys.contains_key("TLDE"));
+
+        let warnings: Vec<_> = parsed
+            .diagnostics
+            .iter()
+            .map(|d| (d.line, d.severity))
+            .collect();
+        assert_eq!(warnings, vec![(Some(15), Severity::Warning)]);
+
+        let layout = import_layout(NEO_LIKE, LayoutFormat::Xkb, "neo")
+            .layout
+            .unwrap();
+        assert_eq!(layout.id, "custom-german-neo-ish");
+        let home = &layout.rows[2];
+        assert_eq!(home[0].key, "u");
+        // Keys the block leaves alone come from the German include
+        assert_eq!(home[4].key, "g");
+        assert_eq!(layout.rows[3][0].key, "<");
+    }
+
+    #[test]
+    fn test_keysyms() {
+        assert_eq!(keysym("a"), Ok(Some('a')));
+        assert_eq!(keysym("asciitilde"), Ok(Some('~')));
+        assert_eq!(keysym("adiaeresis"), Ok(Some('ä')));
+        assert_eq!(keysym("EuroSign"), Ok(Some('€')));
+        assert_eq!(keysym("U1E9E"), Ok(Some('ẞ')));
+        assert_eq!(keysym("0x10020ac"), Ok(Some('€')));
+        assert_eq!(keysym("dead_acute"), Ok(None));
+        assert_eq!(keysym("F12"), Ok(None));
+        assert_eq!(keysym("Greek_alpha"), Err(()));
+    }
+
+    #[test]
+    fn test_syntax_errors_are_reported_by_line() {
+        let source = "xkb_symbols \"x\" {\n    key <AC01> { [ a, A ] };\n    key <AC02 { [ s ] };\n    key <AC03> { };\n};\n";
+        let report = import_layout(source, LayoutFormat::Xkb, "x");
+        assert!(report.layout.is_none());
+        let lines: Vec<_> = report.diagnostics.iter().filter_map(|d| d.line).collect();
+        assert_eq!(lines, vec![3, 4]);
+    }
+}
@@ -0,0 +1,618 @@
+use serde::{Deserialize, Serialize};
+
+mod data;
+
+/// The concrete keyboard layouts the app ships, using the frontend's ids
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LayoutId {
+    #[serde(rename = "qwerty-us")]
+    QwertyUs,
+    #[serde(rename = "qwerty-uk")]
+    QwertyUk,
+    #[serde(rename = "qwerty-de")]
+    QwertyDe,
+    #[serde(rename = "qwerty-es")]
+    QwertyEs,
+    #[serde(rename = "qwerty-it")]
+    QwertyIt,
+    #[serde(rename = "qwerty-pt")]
+    QwertyPt,
+    #[serde(rename = "qwerty-se")]
+    QwertySe,
+    #[serde(rename = "qwerty-no")]
+    QwertyNo,
+    #[serde(rename = "qwerty-dk")]
+    QwertyDk,
+    #[serde(rename = "qwerty-ch-fr")]
+    QwertyChFr,
+    #[serde(rename = "qwerty-tr")]
+    QwertyTr,
+    #[serde(rename = "azerty-fr")]
+    AzertyFr,
+    #[serde(rename = "azerty-be")]
+    AzertyBe,
+    #[serde(rename = "dvorak")]
+    Dvorak,
+    #[serde(rename = "colemak")]
+    Colemak,
+}
+
+impl LayoutId {
+    pub const ALL: [LayoutId; 15] = [
+        LayoutId::QwertyUs,
+        LayoutId::QwertyUk,
+        LayoutId::QwertyDe,
+        LayoutId::QwertyEs,
+        LayoutId::QwertyIt,
+        LayoutId::QwertyPt,
+        LayoutId::QwertySe,
+        LayoutId::QwertyNo,
+        LayoutId::QwertyDk,
+        LayoutId::QwertyChFr,
+        LayoutId::QwertyTr,
+        LayoutId::AzertyFr,
+        LayoutId::AzertyBe,
+        LayoutId::Dvorak,
+        LayoutId::Colemak,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LayoutId::QwertyUs => "qwerty-us",
+            LayoutId::QwertyUk => "qwerty-uk",
+            LayoutId::QwertyDe => "qwerty-de",
+            LayoutId::QwertyEs => "qwerty-es",
+            LayoutId::QwertyIt => "qwerty-it",
+            LayoutId::QwertyPt => "qwerty-pt",
+            LayoutId::QwertySe => "qwerty-se",
+            LayoutId::QwertyNo => "qwerty-no",
+            LayoutId::QwertyDk => "qwerty-dk",
+            LayoutId::QwertyChFr => "qwerty-ch-fr",
+            LayoutId::QwertyTr => "qwerty-tr",
+            LayoutId::AzertyFr => "azerty-fr",
+            LayoutId::AzertyBe => "azerty-be",
+            LayoutId::Dvorak => "dvorak",
+            LayoutId::Colemak => "colemak",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        LayoutId::ALL.into_iter().find(|id| id.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+/// Fingers in the frontend's numbering order, left pinky (1) through right pinky (10)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    LeftThumb,
+    RightThumb,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+impl Finger {
+    pub const ALL: [Finger; 10] = [
+        Finger::LeftPinky,
+        Finger::LeftRing,
+        Finger::LeftMiddle,
+        Finger::LeftIndex,
+        Finger::LeftThumb,
+        Finger::RightThumb,
+        Finger::RightIndex,
+        Finger::RightMiddle,
+        Finger::RightRing,
+        Finger::RightPinky,
+    ];
+
+    /// Finger for the frontend's 1-10 numbering
+    pub fn from_number(number: u8) -> Option<Self> {
+        Finger::ALL
+            .get(usize::from(number).checked_sub(1)?)
+            .copied()
+    }
+
+    pub fn hand(&self) -> Hand {
+        if *self <= Finger::LeftThumb {
+            Hand::Left
+        } else {
+            Hand::Right
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Finger::LeftPinky => "left_pinky",
+            Finger::LeftRing => "left_ring",
+            Finger::LeftMiddle => "left_middle",
+            Finger::LeftIndex => "left_index",
+            Finger::LeftThumb => "left_thumb",
+            Finger::RightThumb => "right_thumb",
+            Finger::RightIndex => "right_index",
+            Finger::RightMiddle => "right_middle",
+            Finger::RightRing => "right_ring",
+            Finger::RightPinky => "right_pinky",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Finger::ALL.into_iter().find(|f| f.as_str() == value)
+    }
+}
+
+/// The modifier needed to produce a character
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyLevel {
+    Base,
+    Shift,
+    AltGr,
+}
+
+/// One physical key, in the same shape as the frontend's `KeyDefinition`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyDef {
+    pub key: String,
+    pub shift: Option<String>,
+    pub alt_gr: Option<String>,
+    pub label: Option<String>,
+    pub width: Option<f32>, // in key widths, None for a regular key
+    pub finger: Finger,
+    pub hand: Hand,
+    pub home: bool,
+}
+
+impl KeyDef {
+    fn output(&self, level: KeyLevel) -> Option<&str> {
+        match level {
+            KeyLevel::Base => Some(&self.key),
+            KeyLevel::Shift => self.shift.as_deref(),
+            KeyLevel::AltGr => self.alt_gr.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyboardLayout {
+    pub id: String,
+    pub name: String,
+    pub locale: String,
+    pub rows: Vec<Vec<KeyDef>>, // number row first, space bar last
+}
+
+/// Where and how a character is typed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct KeyPosition {
+    pub row: usize,
+    pub column: usize,
+    pub level: KeyLevel,
+    pub finger: Finger,
+    pub hand: Hand,
+    pub home: bool,
+}
+
+impl KeyboardLayout {
+    pub fn builtin(id: LayoutId) -> Self {
+        let raw = data::ALL
+            .iter()
+            .find(|raw| raw.id == id)
+            .expect("every layout id has a key table");
+        let rows = raw
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|key| {
+                        let finger =
+                            Finger::from_number(key.finger).expect("key tables use fingers 1-10");
+                        let is_space = key.base == " ";
+                        KeyDef {
+                            key: key.base.to_string(),
+                            shift: Some(key.shift.to_string()).filter(|s| !s.is_empty()),
+                            alt_gr: raw
+                                .alt_gr
+                                .iter()
+                                .find(|(base, _)| *base == key.base)
+                                .map(|(_, alt_gr)| alt_gr.to_string()),
+                            label: is_space.then(|| "Space".to_string()),
+                            width: is_space.then_some(6.0),
+                            finger,
+                            hand: finger.hand(),
+                            home: key.home,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        KeyboardLayout {
+            id: id.as_str().to_string(),
+            name: raw.name.to_string(),
+            locale: raw.locale.to_string(),
+            rows,
+        }
+    }
+
+    /// Find the key that types `ch`, preferring the base level, then Shift, then AltGr
+    pub fn locate(&self, ch: char) -> Option<KeyPosition> {
+        let mut buf = [0; 4];
+        let ch = &*ch.encode_utf8(&mut buf);
+        [KeyLevel::Base, KeyLevel::Shift, KeyLevel::AltGr]
+            .into_iter()
+            .find_map(|level| {
+                self.rows.iter().enumerate().find_map(|(row, keys)| {
+                    keys.iter()
+                        .position(|key| key.output(level) == Some(ch))
+                        .map(|column| {
+                            let key = &keys[column];
+                            KeyPosition {
+                                row,
+                                column,
+                                level,
+                                finger: key.finger,
+                                hand: key.hand,
+                                home: key.home,
+                            }
+                        })
+                })
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// The platform layout is the one we ship (or identical to it)
+    Exact,
+    /// A related layout we ship that is the closest we have, e.g. Swiss German for German
+    Fallback,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LayoutMatch {
+    pub layout: LayoutId,
+    pub kind: MatchKind,
+}
+
+impl LayoutMatch {
+    fn exact(layout: LayoutId) -> Self {
+        LayoutMatch {
+            layout,
+            kind: MatchKind::Exact,
+        }
+    }
+
+    fn fallback(layout: LayoutId) -> Self {
+        LayoutMatch {
+            layout,
+            kind: MatchKind::Fallback,
+        }
+    }
+}
+
+/// Map a platform input source to one of our layouts. Accepts the identifiers returned by
+/// `keyboard::get_current_input_source`: "com.apple.keylayout.German", "windows:0407",
+/// "windows:dvorak" and "xkb:de:nodeadkeys".
+pub fn normalize_input_source(source: &str) -> Option<LayoutMatch> {
+    let source = source.trim();
+    if let Some(code) = source.strip_prefix("windows:") {
+        from_windows(&code.to_ascii_lowercase())
+    } else if let Some(rest) = source.strip_prefix("xkb:") {
+        let (layout, variant) = match rest.split_once(':') {
+            Some((layout, variant)) => (layout, Some(variant)),
+            None => (rest, None),
+        };
+        from_xkb(
+            &layout.to_ascii_lowercase(),
+            variant.map(|v| v.to_ascii_lowercase()),
+        )
+    } else {
+        // macOS: the name after the last dot
+        let suffix = source.rsplit('.').next()?.to_ascii_lowercase();
+        from_macos(&suffix)
+    }
+}
+
+// macOS keylayout names; `false` marks a related layout standing in for one we don't ship
+const MACOS_LAYOUTS: &[(&str, LayoutId, bool)] = &[
+    ("us", LayoutId::QwertyUs, true),
+    ("abc", LayoutId::QwertyUs, true),
+    ("usinternational-pc", LayoutId::QwertyUs, true),
+    ("usenglish", LayoutId::QwertyUs, true),
+    ("british", LayoutId::QwertyUk, true),
+    ("british-pc", LayoutId::QwertyUk, true),
+    ("german", LayoutId::QwertyDe, true),
+    ("austrian", LayoutId::QwertyDe, true),
+    ("swiss", LayoutId::QwertyDe, false),
+    ("swissgerman", LayoutId::QwertyDe, false),
+    ("german-dingraphswiss", LayoutId::QwertyDe, false),
+    ("french", LayoutId::AzertyFr, true),
+    ("french-pc", LayoutId::AzertyFr, true),
+    ("belgian", LayoutId::AzertyBe, true),
+    ("swissfrench", LayoutId::QwertyChFr, true),
+    ("spanish", LayoutId::QwertyEs, true),
+    ("spanish-iso", LayoutId::QwertyEs, true),
+    ("italian", LayoutId::QwertyIt, false), // Apple's Italian differs from the ISO one
+    ("italian-pro", LayoutId::QwertyIt, true),
+    ("portuguese", LayoutId::QwertyPt, true),
+    ("brazilian", LayoutId::QwertyPt, false),
+    ("brazilian-pro", LayoutId::QwertyPt, false),
+    ("swedish", LayoutId::QwertySe, true),
+    ("swedish-pro", LayoutId::QwertySe, true),
+    ("norwegian", LayoutId::QwertyNo, true),
+    ("norwegianextended", LayoutId::QwertyNo, true),
+    ("danish", LayoutId::QwertyDk, true),
+    ("turkish-qwerty", LayoutId::QwertyTr, true),
+    ("turkish-qwerty-pc", LayoutId::QwertyTr, true),
+    ("turkish", LayoutId::QwertyTr, false), // Turkish F
+    ("dvorak", LayoutId::Dvorak, true),
+    ("dvorak-left", LayoutId::Dvorak, false),
+    ("dvorak-right", LayoutId::Dvorak, false),
+    ("dvzine", LayoutId::Dvorak, false),
+    ("colemak", LayoutId::Colemak, true),
+];
+
+fn from_macos(suffix: &str) -> Option<LayoutMatch> {
+    if let Some(&(_, layout, exact)) = MACOS_LAYOUTS.iter().find(|(name, _, _)| *name == suffix) {
+        return Some(if exact {
+            LayoutMatch::exact(layout)
+        } else {
+            LayoutMatch::fallback(layout)
+        });
+    }
+    // Compound names such as "German-DIN-2137": the longest known prefix wins
+    MACOS_LAYOUTS
+        .iter()
+        .filter(|(name, _, _)| suffix.starts_with(name))
+        .max_by_key(|(name, _, _)| name.len())
+        .map(|&(_, layout, _)| LayoutMatch::fallback(layout))
+}
+
+// Windows language ids (low word of the HKL)
+const WINDOWS_LAYOUTS: &[(&str, LayoutId, bool)] = &[
+    ("0409", LayoutId::QwertyUs, true),   // English (US)
+    ("0809", LayoutId::QwertyUk, true),   // English (UK)
+    ("0c09", LayoutId::QwertyUs, true),   // English (Australia)
+    ("1009", LayoutId::QwertyUs, true),   // English (Canada)
+    ("1809", LayoutId::QwertyUk, false),  // English (Ireland)
+    ("0407", LayoutId::QwertyDe, true),   // German (Germany)
+    ("0c07", LayoutId::QwertyDe, true),   // German (Austria)
+    ("0807", LayoutId::QwertyDe, false),  // German (Switzerland)
+    ("040c", LayoutId::AzertyFr, true),   // French (France)
+    ("080c", LayoutId::AzertyBe, true),   // French (Belgium)
+    ("100c", LayoutId::QwertyChFr, true), // French (Switzerland)
+    ("0c0c", LayoutId::QwertyUs, false),  // French (Canada)
+    ("0c0a", LayoutId::QwertyEs, true),   // Spanish (Spain, international sort)
+    ("040a", LayoutId::QwertyEs, true),   // Spanish (Spain, traditional sort)
+    ("080a", LayoutId::QwertyEs, false),  // Spanish (Mexico)
+    ("2c0a", LayoutId::QwertyEs, false),  // Spanish (Argentina)
+    ("0410", LayoutId::QwertyIt, true),   // Italian (Italy)
+    ("0810", LayoutId::QwertyIt, false),  // Italian (Switzerland)
+    ("0816", LayoutId::QwertyPt, true),   // Portuguese (Portugal)
+    ("0416", LayoutId::QwertyPt, false),  // Portuguese (Brazil)
+    ("041d", LayoutId::QwertySe, true),   // Swedish
+    ("0414", LayoutId::QwertyNo, true),   // Norwegian (Bokmål)
+    ("0814", LayoutId::QwertyNo, true),   // Norwegian (Nynorsk)
+    ("0406", LayoutId::QwertyDk, true),   // Danish
+    ("041f", LayoutId::QwertyTr, true),   // Turkish
+    ("dvorak", LayoutId::Dvorak, true),   // Dvorak (detected by layout handle)
+];
+
+fn from_windows(code: &str) -> Option<LayoutMatch> {
+    if let Some(&(_, layout, exact)) = WINDOWS_LAYOUTS.iter().find(|(id, _, _)| *id == code) {
+        return Some(if exact {
+            LayoutMatch::exact(layout)
+        } else {
+            LayoutMatch::fallback(layout)
+        });
+    }
+    // Other regions of a known language: the low 10 bits are the primary language
+    let primary = u16::from_str_radix(code, 16).ok()? & 0x3ff;
+    let layout = match primary {
+        0x09 => LayoutId::QwertyUs,
+        0x07 => LayoutId::QwertyDe,
+        0x0c => LayoutId::AzertyFr,
+        0x0a => LayoutId::QwertyEs,
+        0x10 => LayoutId::QwertyIt,
+        0x16 => LayoutId::QwertyPt,
+        0x1d => LayoutId::QwertySe,
+        0x14 => LayoutId::QwertyNo,
+        0x06 => LayoutId::QwertyDk,
+        0x1f => LayoutId::QwertyTr,
+        _ => return None,
+    };
+    Some(LayoutMatch::fallback(layout))
+}
+
+// XKB variants that only change dead keys or platform details, not where letters are
+const COSMETIC_XKB_VARIANTS: &[&str] = &[
+    "basic",
+    "nodeadkeys",
+    "deadkeys",
+    "deadacute",
+    "deadgraveacute",
+    "deadtilde",
+    "mac",
+    "mac_nodeadkeys",
+    "winkeys",
+    "legacy",
+    "latin9",
+    "sundeadkeys",
+    "intl",
+    "altgr-intl",
+    "extd",
+];
+
+fn from_xkb(layout: &str, variant: Option<String>) -> Option<LayoutMatch> {
+    let variant = variant.filter(|v| !v.is_empty());
+
+    // Dvorak and Colemak are variants of a national layout in XKB; only the US ones are ours
+    if let Some(v) = &variant {
+        for (name, id) in [("dvorak", LayoutId::Dvorak), ("colemak", LayoutId::Colemak)] {
+            if v.contains(name) {
+                return Some(if layout == "us" && v == name {
+                    LayoutMatch::exact(id)
+                } else {
+                    LayoutMatch::fallback(id)
+                });
+            }
+        }
+    }
+
+    let (id, exact_layout) = match (layout, variant.as_deref()) {
+        ("ch", Some(v)) if v.starts_with("fr") => (LayoutId::QwertyChFr, true),
+        ("ch", _) => (LayoutId::QwertyDe, false),
+        ("us", _) => (LayoutId::QwertyUs, true),
+        ("gb", _) => (LayoutId::QwertyUk, true),
+        ("ie", _) => (LayoutId::QwertyUk, false),
+        ("de", _) | ("at", _) => (LayoutId::QwertyDe, true),
+        ("fr", _) => (LayoutId::AzertyFr, true),
+        ("be", _) => (LayoutId::AzertyBe, true),
+        ("es", _) => (LayoutId::QwertyEs, true),
+        ("latam", _) => (LayoutId::QwertyEs, false),
+        ("it", _) => (LayoutId::QwertyIt, true),
+        ("pt", _) => (LayoutId::QwertyPt, true),
+        ("br", _) => (LayoutId::QwertyPt, false),
+        ("se", _) => (LayoutId::QwertySe, true),
+        ("no", _) => (LayoutId::QwertyNo, true),
+        ("dk", _) => (LayoutId::QwertyDk, true),
+        ("tr", _) => (LayoutId::QwertyTr, true),
+        _ => return None,
+    };
+
+    let cosmetic = match variant.as_deref() {
+        None => true,
+        Some(v) => COSMETIC_XKB_VARIANTS.contains(&v) || (layout == "ch" && v.starts_with("fr")),
+    };
+    Some(if exact_layout && cosmetic {
+        LayoutMatch::exact(id)
+    } else {
+        LayoutMatch::fallback(id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exact(layout: LayoutId) -> Option<LayoutMatch> {
+        Some(LayoutMatch::exact(layout))
+    }
+
+    fn fallback(layout: LayoutId) -> Option<LayoutMatch> {
+        Some(LayoutMatch::fallback(layout))
+    }
+
+    #[test]
+    fn test_ids_roundtrip() {
+        for id in LayoutId::ALL {
+            assert_eq!(LayoutId::parse(id.as_str()), Some(id));
+            let json = serde_json::to_string(&id).unwrap();
+            assert_eq!(json, format!("\"{}\"", id.as_str()));
+        }
+        assert_eq!(LayoutId::parse("qwerty-xx"), None);
+    }
+
+    #[test]
+    fn test_macos_sources() {
+        let n = normalize_input_source;
+        assert_eq!(n("com.apple.keylayout.US"), exact(LayoutId::QwertyUs));
+        assert_eq!(n("com.apple.keylayout.German"), exact(LayoutId::QwertyDe));
+        assert_eq!(
+            n("com.apple.keylayout.SwissFrench"),
+            exact(LayoutId::QwertyChFr)
+        );
+        assert_eq!(
+            n("com.apple.keylayout.SwissGerman"),
+            fallback(LayoutId::QwertyDe)
+        );
+        assert_eq!(
+            n("com.apple.keylayout.German-DIN-2137"),
+            fallback(LayoutId::QwertyDe)
+        );
+        assert_eq!(n("com.apple.keylayout.Colemak"), exact(LayoutId::Colemak));
+        // "Russian" contains "us" but must not be taken for US English
+        assert_eq!(n("com.apple.keylayout.Russian"), None);
+    }
+
+    #[test]
+    fn test_windows_sources() {
+        let n = normalize_input_source;
+        assert_eq!(n("windows:0407"), exact(LayoutId::QwertyDe));
+        assert_eq!(n("windows:080C"), exact(LayoutId::AzertyBe));
+        assert_eq!(n("windows:0416"), fallback(LayoutId::QwertyPt));
+        assert_eq!(n("windows:dvorak"), exact(LayoutId::Dvorak));
+        // Spanish (Chile) isn't listed, but its primary language is
+        assert_eq!(n("windows:340a"), fallback(LayoutId::QwertyEs));
+        assert_eq!(n("windows:0419"), None);
+    }
+
+    #[test]
+    fn test_xkb_sources() {
+        let n = normalize_input_source;
+        assert_eq!(n("xkb:us"), exact(LayoutId::QwertyUs));
+        assert_eq!(n("xkb:de:nodeadkeys"), exact(LayoutId::QwertyDe));
+        assert_eq!(n("xkb:de:neo"), fallback(LayoutId::QwertyDe));
+        assert_eq!(n("xkb:ch:fr"), exact(LayoutId::QwertyChFr));
+        assert_eq!(n("xkb:ch"), fallback(LayoutId::QwertyDe));
+        assert_eq!(n("xkb:us:dvorak"), exact(LayoutId::Dvorak));
+        assert_eq!(n("xkb:fr:dvorak"), fallback(LayoutId::Dvorak));
+        assert_eq!(n("xkb:us:colemak_dh"), fallback(LayoutId::Colemak));
+        assert_eq!(n("xkb:br:abnt2"), fallback(LayoutId::QwertyPt));
+        assert_eq!(n("xkb:ru"), None);
+    }
+
+    #[test]
+    fn test_builtin_geometry() {
+        for id in LayoutId::ALL {
+            let layout = KeyboardLayout::builtin(id);
+            assert_eq!(layout.id, id.as_str());
+            assert_eq!(layout.rows.len(), 5, "{}", layout.id);
+            let keys = || layout.rows.iter().flatten();
+            assert_eq!(keys().filter(|k| k.home).count(), 8, "{}", layout.id);
+            // Every AltGr entry found the base key it was listed under
+            let raw = data::ALL.iter().find(|raw| raw.id == id).unwrap();
+            assert_eq!(
+                keys().filter(|k| k.alt_gr.is_some()).count(),
+                raw.alt_gr.len()
+            );
+
+            let space = layout.locate(' ').unwrap();
+            assert_eq!((space.row, space.finger), (4, Finger::LeftThumb));
+        }
+
+        let us = KeyboardLayout::builtin(LayoutId::QwertyUs);
+        let a = us.locate('A').unwrap();
+        assert_eq!((a.row, a.column, a.level), (2, 0, KeyLevel::Shift));
+        assert_eq!(
+            (a.finger, a.hand, a.home),
+            (Finger::LeftPinky, Hand::Left, true)
+        );
+        assert_eq!(us.locate('€'), None);
+
+        let de = KeyboardLayout::builtin(LayoutId::QwertyDe);
+        let at = de.locate('@').unwrap();
+        assert_eq!((at.row, at.column, at.level), (1, 0, KeyLevel::AltGr));
+        assert_eq!(de.locate('z').unwrap().hand, Hand::Right);
+
+        let json = serde_json::to_value(&us.rows[2][0]).unwrap();
+        assert_eq!(json["key"], "a");
+        assert_eq!(json["altGr"], serde_json::Value::Null);
+        assert_eq!(json["finger"], "left_pinky");
+        assert_eq!(Finger::from_number(6), Some(Finger::RightThumb));
+        assert_eq!(Finger::from_number(11), None);
+    }
+}
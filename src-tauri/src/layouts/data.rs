@@ -0,0 +1,1179 @@
+// Key tables for the built-in layouts, ported from src/lib/data/layouts/index.ts.
+// Rows run from the number row to the space bar; fingers use the frontend's 1-10 numbering.
+// The frontend has no AltGr legends, so those are listed separately by the base key they
+// share, following the standard Windows layouts.
+
+use super::LayoutId;
+
+pub(super) struct RawKey {
+    pub base: &'static str,
+    pub shift: &'static str, // empty when the key has no shifted character
+    pub finger: u8,
+    pub home: bool,
+}
+
+pub(super) struct RawLayout {
+    pub id: LayoutId,
+    pub name: &'static str,
+    pub locale: &'static str,
+    pub rows: &'static [&'static [RawKey]],
+    pub alt_gr: &'static [(&'static str, &'static str)],
+}
+
+const fn k(base: &'static str, shift: &'static str, finger: u8) -> RawKey {
+    RawKey {
+        base,
+        shift,
+        finger,
+        home: false,
+    }
+}
+
+// A home-row key
+const fn h(base: &'static str, shift: &'static str, finger: u8) -> RawKey {
+    RawKey {
+        base,
+        shift,
+        finger,
+        home: true,
+    }
+}
+
+pub(super) const ALL: [RawLayout; 15] = [
+    QWERTY_US,
+    QWERTY_UK,
+    QWERTY_DE,
+    QWERTY_ES,
+    QWERTY_IT,
+    QWERTY_PT,
+    QWERTY_SE,
+    QWERTY_NO,
+    QWERTY_DK,
+    QWERTY_CH_FR,
+    QWERTY_TR,
+    AZERTY_FR,
+    AZERTY_BE,
+    DVORAK,
+    COLEMAK,
+];
+
+pub(super) const QWERTY_US: RawLayout = RawLayout {
+    id: LayoutId::QwertyUs,
+    name: "QWERTY (US)",
+    locale: "en-US",
+    rows: &[
+        &[
+            k("`", "~", 1),
+            k("1", "!", 1),
+            k("2", "@", 2),
+            k("3", "#", 3),
+            k("4", "$", 4),
+            k("5", "%", 4),
+            k("6", "^", 7),
+            k("7", "&", 7),
+            k("8", "*", 8),
+            k("9", "(", 9),
+            k("0", ")", 10),
+            k("-", "_", 10),
+            k("=", "+", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("[", "{", 10),
+            k("]", "}", 10),
+            k("\\", "|", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h(";", ":", 10),
+            k("'", "\"", 10),
+        ],
+        &[
+            k("z", "Z", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k(",", "<", 8),
+            k(".", ">", 9),
+            k("/", "?", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[],
+};
+
+pub(super) const QWERTY_UK: RawLayout = RawLayout {
+    id: LayoutId::QwertyUk,
+    name: "QWERTY (UK)",
+    locale: "en-GB",
+    rows: &[
+        &[
+            k("`", "¬", 1),
+            k("1", "!", 1),
+            k("2", "\"", 2),
+            k("3", "£", 3),
+            k("4", "$", 4),
+            k("5", "%", 4),
+            k("6", "^", 7),
+            k("7", "&", 7),
+            k("8", "*", 8),
+            k("9", "(", 9),
+            k("0", ")", 10),
+            k("-", "_", 10),
+            k("=", "+", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("[", "{", 10),
+            k("]", "}", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h(";", ":", 10),
+            k("'", "@", 10),
+            k("#", "~", 10),
+        ],
+        &[
+            k("\\", "|", 1),
+            k("z", "Z", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k(",", "<", 8),
+            k(".", ">", 9),
+            k("/", "?", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[("4", "€")],
+};
+
+pub(super) const QWERTY_DE: RawLayout = RawLayout {
+    id: LayoutId::QwertyDe,
+    name: "QWERTZ (German)",
+    locale: "de-DE",
+    rows: &[
+        &[
+            k("^", "°", 1),
+            k("1", "!", 1),
+            k("2", "\"", 2),
+            k("3", "§", 3),
+            k("4", "$", 4),
+            k("5", "%", 4),
+            k("6", "&", 7),
+            k("7", "/", 7),
+            k("8", "(", 8),
+            k("9", ")", 9),
+            k("0", "=", 10),
+            k("ß", "?", 10),
+            k("´", "`", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("z", "Z", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("ü", "Ü", 10),
+            k("+", "*", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("ö", "Ö", 10),
+            k("ä", "Ä", 10),
+            k("#", "'", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("y", "Y", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k(",", ";", 8),
+            k(".", ":", 9),
+            k("-", "_", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[
+        ("2", "²"),
+        ("3", "³"),
+        ("7", "{"),
+        ("8", "["),
+        ("9", "]"),
+        ("0", "}"),
+        ("ß", "\\"),
+        ("q", "@"),
+        ("e", "€"),
+        ("+", "~"),
+        ("<", "|"),
+        ("m", "µ"),
+    ],
+};
+
+pub(super) const QWERTY_ES: RawLayout = RawLayout {
+    id: LayoutId::QwertyEs,
+    name: "QWERTY (Spanish)",
+    locale: "es-ES",
+    rows: &[
+        &[
+            k("º", "ª", 1),
+            k("1", "!", 1),
+            k("2", "\"", 2),
+            k("3", "·", 3),
+            k("4", "$", 4),
+            k("5", "%", 4),
+            k("6", "&", 7),
+            k("7", "/", 7),
+            k("8", "(", 8),
+            k("9", ")", 9),
+            k("0", "=", 10),
+            k("'", "?", 10),
+            k("¡", "¿", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("`", "^", 10),
+            k("+", "*", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("ñ", "Ñ", 10),
+            k("´", "¨", 10),
+            k("ç", "Ç", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("z", "Z", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k(",", ";", 8),
+            k(".", ":", 9),
+            k("-", "_", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[
+        ("º", "\\"),
+        ("1", "|"),
+        ("2", "@"),
+        ("3", "#"),
+        ("4", "~"),
+        ("5", "€"),
+        ("6", "¬"),
+        ("e", "€"),
+        ("`", "["),
+        ("+", "]"),
+        ("´", "{"),
+        ("ç", "}"),
+    ],
+};
+
+pub(super) const QWERTY_IT: RawLayout = RawLayout {
+    id: LayoutId::QwertyIt,
+    name: "QWERTY (Italian)",
+    locale: "it-IT",
+    rows: &[
+        &[
+            k("\\", "|", 1),
+            k("1", "!", 1),
+            k("2", "\"", 2),
+            k("3", "£", 3),
+            k("4", "$", 4),
+            k("5", "%", 4),
+            k("6", "&", 7),
+            k("7", "/", 7),
+            k("8", "(", 8),
+            k("9", ")", 9),
+            k("0", "=", 10),
+            k("'", "?", 10),
+            k("ì", "^", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("è", "é", 10),
+            k("+", "*", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("ò", "ç", 10),
+            k("à", "°", 10),
+            k("ù", "§", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("z", "Z", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k(",", ";", 8),
+            k(".", ":", 9),
+            k("-", "_", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[("e", "€"), ("è", "["), ("+", "]"), ("ò", "@"), ("à", "#")],
+};
+
+pub(super) const QWERTY_PT: RawLayout = RawLayout {
+    id: LayoutId::QwertyPt,
+    name: "QWERTY (Portuguese)",
+    locale: "pt-PT",
+    rows: &[
+        &[
+            k("\\", "|", 1),
+            k("1", "!", 1),
+            k("2", "\"", 2),
+            k("3", "#", 3),
+            k("4", "$", 4),
+            k("5", "%", 4),
+            k("6", "&", 7),
+            k("7", "/", 7),
+            k("8", "(", 8),
+            k("9", ")", 9),
+            k("0", "=", 10),
+            k("'", "?", 10),
+            k("«", "»", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("+", "*", 10),
+            k("´", "`", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("ç", "Ç", 10),
+            k("º", "ª", 10),
+            k("~", "^", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("z", "Z", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k(",", ";", 8),
+            k(".", ":", 9),
+            k("-", "_", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[
+        ("2", "@"),
+        ("3", "£"),
+        ("4", "§"),
+        ("7", "{"),
+        ("8", "["),
+        ("9", "]"),
+        ("0", "}"),
+        ("e", "€"),
+    ],
+};
+
+pub(super) const QWERTY_SE: RawLayout = RawLayout {
+    id: LayoutId::QwertySe,
+    name: "QWERTY (Swedish)",
+    locale: "sv-SE",
+    rows: &[
+        &[
+            k("§", "½", 1),
+            k("1", "!", 1),
+            k("2", "\"", 2),
+            k("3", "#", 3),
+            k("4", "¤", 4),
+            k("5", "%", 4),
+            k("6", "&", 7),
+            k("7", "/", 7),
+            k("8", "(", 8),
+            k("9", ")", 9),
+            k("0", "=", 10),
+            k("+", "?", 10),
+            k("´", "`", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("å", "Å", 10),
+            k("¨", "^", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("ö", "Ö", 10),
+            k("ä", "Ä", 10),
+            k("'", "*", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("z", "Z", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k(",", ";", 8),
+            k(".", ":", 9),
+            k("-", "_", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[
+        ("2", "@"),
+        ("3", "£"),
+        ("4", "$"),
+        ("5", "€"),
+        ("7", "{"),
+        ("8", "["),
+        ("9", "]"),
+        ("0", "}"),
+        ("+", "\\"),
+        ("e", "€"),
+        ("¨", "~"),
+        ("<", "|"),
+        ("m", "µ"),
+    ],
+};
+
+pub(super) const QWERTY_NO: RawLayout = RawLayout {
+    id: LayoutId::QwertyNo,
+    name: "QWERTY (Norwegian)",
+    locale: "nb-NO",
+    rows: &[
+        &[
+            k("|", "§", 1),
+            k("1", "!", 1),
+            k("2", "\"", 2),
+            k("3", "#", 3),
+            k("4", "¤", 4),
+            k("5", "%", 4),
+            k("6", "&", 7),
+            k("7", "/", 7),
+            k("8", "(", 8),
+            k("9", ")", 9),
+            k("0", "=", 10),
+            k("+", "?", 10),
+            k("\\", "`", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("å", "Å", 10),
+            k("¨", "^", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("ø", "Ø", 10),
+            k("æ", "Æ", 10),
+            k("'", "*", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("z", "Z", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k(",", ";", 8),
+            k(".", ":", 9),
+            k("-", "_", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[
+        ("2", "@"),
+        ("3", "£"),
+        ("4", "$"),
+        ("5", "€"),
+        ("7", "{"),
+        ("8", "["),
+        ("9", "]"),
+        ("0", "}"),
+        ("e", "€"),
+        ("¨", "~"),
+        ("m", "µ"),
+    ],
+};
+
+pub(super) const QWERTY_DK: RawLayout = RawLayout {
+    id: LayoutId::QwertyDk,
+    name: "QWERTY (Danish)",
+    locale: "da-DK",
+    rows: &[
+        &[
+            k("½", "§", 1),
+            k("1", "!", 1),
+            k("2", "\"", 2),
+            k("3", "#", 3),
+            k("4", "¤", 4),
+            k("5", "%", 4),
+            k("6", "&", 7),
+            k("7", "/", 7),
+            k("8", "(", 8),
+            k("9", ")", 9),
+            k("0", "=", 10),
+            k("+", "?", 10),
+            k("´", "`", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("å", "Å", 10),
+            k("¨", "^", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("æ", "Æ", 10),
+            k("ø", "Ø", 10),
+            k("'", "*", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("z", "Z", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k(",", ";", 8),
+            k(".", ":", 9),
+            k("-", "_", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[
+        ("2", "@"),
+        ("3", "£"),
+        ("4", "$"),
+        ("5", "€"),
+        ("7", "{"),
+        ("8", "["),
+        ("9", "]"),
+        ("0", "}"),
+        ("´", "|"),
+        ("e", "€"),
+        ("¨", "~"),
+        ("<", "\\"),
+        ("m", "µ"),
+    ],
+};
+
+pub(super) const QWERTY_CH_FR: RawLayout = RawLayout {
+    id: LayoutId::QwertyChFr,
+    name: "QWERTZ (Swiss French)",
+    locale: "fr-CH",
+    rows: &[
+        &[
+            k("§", "°", 1),
+            k("1", "+", 1),
+            k("2", "\"", 2),
+            k("3", "*", 3),
+            k("4", "ç", 4),
+            k("5", "%", 4),
+            k("6", "&", 7),
+            k("7", "/", 7),
+            k("8", "(", 8),
+            k("9", ")", 9),
+            k("0", "=", 10),
+            k("'", "?", 10),
+            k("^", "`", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("z", "Z", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("è", "ü", 10),
+            k("¨", "!", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("é", "ö", 10),
+            k("à", "ä", 10),
+            k("$", "£", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("y", "Y", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k(",", ";", 8),
+            k(".", ":", 9),
+            k("-", "_", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[
+        ("1", "¦"),
+        ("2", "@"),
+        ("3", "#"),
+        ("4", "°"),
+        ("5", "§"),
+        ("6", "¬"),
+        ("7", "|"),
+        ("8", "¢"),
+        ("'", "´"),
+        ("^", "~"),
+        ("e", "€"),
+        ("è", "["),
+        ("¨", "]"),
+        ("à", "{"),
+        ("$", "}"),
+        ("<", "\\"),
+    ],
+};
+
+pub(super) const QWERTY_TR: RawLayout = RawLayout {
+    id: LayoutId::QwertyTr,
+    name: "QWERTY (Turkish)",
+    locale: "tr-TR",
+    rows: &[
+        &[
+            k("\"", "é", 1),
+            k("1", "!", 1),
+            k("2", "'", 2),
+            k("3", "^", 3),
+            k("4", "+", 4),
+            k("5", "%", 4),
+            k("6", "&", 7),
+            k("7", "/", 7),
+            k("8", "(", 8),
+            k("9", ")", 9),
+            k("0", "=", 10),
+            k("*", "?", 10),
+            k("-", "_", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("ı", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("ğ", "Ğ", 10),
+            k("ü", "Ü", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("ş", "Ş", 10),
+            k("i", "İ", 10),
+            k(",", ";", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("z", "Z", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k("m", "M", 7),
+            k("ö", "Ö", 8),
+            k("ç", "Ç", 9),
+            k(".", ":", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[
+        ("2", "£"),
+        ("3", "#"),
+        ("4", "$"),
+        ("5", "½"),
+        ("7", "{"),
+        ("8", "["),
+        ("9", "]"),
+        ("0", "}"),
+        ("*", "\\"),
+        ("-", "|"),
+        ("q", "@"),
+        ("e", "€"),
+        ("ü", "~"),
+        ("<", "|"),
+    ],
+};
+
+pub(super) const AZERTY_FR: RawLayout = RawLayout {
+    id: LayoutId::AzertyFr,
+    name: "AZERTY (French)",
+    locale: "fr-FR",
+    rows: &[
+        &[
+            k("²", "", 1),
+            k("&", "1", 1),
+            k("é", "2", 2),
+            k("\"", "3", 3),
+            k("'", "4", 4),
+            k("(", "5", 4),
+            k("-", "6", 7),
+            k("è", "7", 7),
+            k("_", "8", 8),
+            k("ç", "9", 9),
+            k("à", "0", 10),
+            k(")", "°", 10),
+            k("=", "+", 10),
+        ],
+        &[
+            k("a", "A", 1),
+            k("z", "Z", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("^", "¨", 10),
+            k("$", "£", 10),
+        ],
+        &[
+            h("q", "Q", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("m", "M", 10),
+            k("ù", "%", 10),
+            k("*", "µ", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("w", "W", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k(",", "?", 8),
+            k(";", ".", 9),
+            k(":", "/", 10),
+            k("!", "§", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[
+        ("é", "~"),
+        ("\"", "#"),
+        ("'", "{"),
+        ("(", "["),
+        ("-", "|"),
+        ("è", "`"),
+        ("_", "\\"),
+        ("ç", "^"),
+        ("à", "@"),
+        (")", "]"),
+        ("=", "}"),
+        ("e", "€"),
+    ],
+};
+
+pub(super) const AZERTY_BE: RawLayout = RawLayout {
+    id: LayoutId::AzertyBe,
+    name: "AZERTY (Belgian)",
+    locale: "fr-BE",
+    rows: &[
+        &[
+            k("²", "³", 1),
+            k("&", "1", 1),
+            k("é", "2", 2),
+            k("\"", "3", 3),
+            k("'", "4", 4),
+            k("(", "5", 4),
+            k("§", "6", 7),
+            k("è", "7", 7),
+            k("!", "8", 8),
+            k("ç", "9", 9),
+            k("à", "0", 10),
+            k(")", "°", 10),
+            k("-", "_", 10),
+        ],
+        &[
+            k("a", "A", 1),
+            k("z", "Z", 2),
+            k("e", "E", 3),
+            k("r", "R", 4),
+            k("t", "T", 4),
+            k("y", "Y", 7),
+            k("u", "U", 7),
+            k("i", "I", 8),
+            k("o", "O", 9),
+            k("p", "P", 10),
+            k("^", "¨", 10),
+            k("$", "*", 10),
+        ],
+        &[
+            h("q", "Q", 1),
+            h("s", "S", 2),
+            h("d", "D", 3),
+            h("f", "F", 4),
+            k("g", "G", 4),
+            k("h", "H", 7),
+            h("j", "J", 7),
+            h("k", "K", 8),
+            h("l", "L", 9),
+            h("m", "M", 10),
+            k("ù", "%", 10),
+            k("µ", "£", 10),
+        ],
+        &[
+            k("<", ">", 1),
+            k("w", "W", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("n", "N", 7),
+            k(",", "?", 8),
+            k(";", ".", 9),
+            k(":", "/", 10),
+            k("=", "+", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[
+        ("&", "|"),
+        ("é", "@"),
+        ("\"", "#"),
+        ("§", "^"),
+        ("ç", "{"),
+        ("à", "}"),
+        ("-", "~"),
+        ("e", "€"),
+        ("^", "["),
+        ("$", "]"),
+        ("ù", "´"),
+        ("µ", "`"),
+        ("<", "\\"),
+    ],
+};
+
+pub(super) const DVORAK: RawLayout = RawLayout {
+    id: LayoutId::Dvorak,
+    name: "Dvorak",
+    locale: "en-US",
+    rows: &[
+        &[
+            k("`", "~", 1),
+            k("1", "!", 1),
+            k("2", "@", 2),
+            k("3", "#", 3),
+            k("4", "$", 4),
+            k("5", "%", 4),
+            k("6", "^", 7),
+            k("7", "&", 7),
+            k("8", "*", 8),
+            k("9", "(", 9),
+            k("0", ")", 10),
+            k("[", "{", 10),
+            k("]", "}", 10),
+        ],
+        &[
+            k("'", "\"", 1),
+            k(",", "<", 2),
+            k(".", ">", 3),
+            k("p", "P", 4),
+            k("y", "Y", 4),
+            k("f", "F", 7),
+            k("g", "G", 7),
+            k("c", "C", 8),
+            k("r", "R", 9),
+            k("l", "L", 10),
+            k("/", "?", 10),
+            k("=", "+", 10),
+            k("\\", "|", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("o", "O", 2),
+            h("e", "E", 3),
+            h("u", "U", 4),
+            k("i", "I", 4),
+            k("d", "D", 7),
+            h("h", "H", 7),
+            h("t", "T", 8),
+            h("n", "N", 9),
+            h("s", "S", 10),
+            k("-", "_", 10),
+        ],
+        &[
+            k(";", ":", 1),
+            k("q", "Q", 2),
+            k("j", "J", 3),
+            k("k", "K", 4),
+            k("x", "X", 4),
+            k("b", "B", 7),
+            k("m", "M", 7),
+            k("w", "W", 8),
+            k("v", "V", 9),
+            k("z", "Z", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[],
+};
+
+pub(super) const COLEMAK: RawLayout = RawLayout {
+    id: LayoutId::Colemak,
+    name: "Colemak",
+    locale: "en-US",
+    rows: &[
+        &[
+            k("`", "~", 1),
+            k("1", "!", 1),
+            k("2", "@", 2),
+            k("3", "#", 3),
+            k("4", "$", 4),
+            k("5", "%", 4),
+            k("6", "^", 7),
+            k("7", "&", 7),
+            k("8", "*", 8),
+            k("9", "(", 9),
+            k("0", ")", 10),
+            k("-", "_", 10),
+            k("=", "+", 10),
+        ],
+        &[
+            k("q", "Q", 1),
+            k("w", "W", 2),
+            k("f", "F", 3),
+            k("p", "P", 4),
+            k("g", "G", 4),
+            k("j", "J", 7),
+            k("l", "L", 7),
+            k("u", "U", 8),
+            k("y", "Y", 9),
+            k(";", ":", 10),
+            k("[", "{", 10),
+            k("]", "}", 10),
+            k("\\", "|", 10),
+        ],
+        &[
+            h("a", "A", 1),
+            h("r", "R", 2),
+            h("s", "S", 3),
+            h("t", "T", 4),
+            k("d", "D", 4),
+            k("h", "H", 7),
+            h("n", "N", 7),
+            h("e", "E", 8),
+            h("i", "I", 9),
+            h("o", "O", 10),
+            k("'", "\"", 10),
+        ],
+        &[
+            k("z", "Z", 1),
+            k("x", "X", 2),
+            k("c", "C", 3),
+            k("v", "V", 4),
+            k("b", "B", 4),
+            k("k", "K", 7),
+            k("m", "M", 7),
+            k(",", "<", 8),
+            k(".", ">", 9),
+            k("/", "?", 10),
+        ],
+        &[k(" ", "", 5)],
+    ],
+    alt_gr: &[],
+};
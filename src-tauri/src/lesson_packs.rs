@@ -0,0 +1,366 @@
+use crate::lessons::Lesson;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A problem found while loading a lesson pack, pointing at the offending file and line
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PackDiagnostic {
+    pub file: String,
+    pub line: Option<usize>, // 1-based, None when the problem isn't tied to a line
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackLoadReport {
+    pub lessons: Vec<Lesson>,
+    pub diagnostics: Vec<PackDiagnostic>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackFormat {
+    Toml,
+    Json,
+}
+
+impl PackFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(PackFormat::Toml),
+            "json" => Some(PackFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+// On-disk layout of a pack: a top-level `lessons` array using the same fields as `Lesson`
+#[derive(Debug, Deserialize)]
+struct LessonPackFile {
+    lessons: Vec<Lesson>,
+}
+
+/// Directory scanned for `*.toml` and `*.json` lesson packs
+pub fn packs_dir() -> PathBuf {
+    crate::storage::app_data_dir().join("lesson-packs")
+}
+
+/// Load every pack in `dir`, keeping only lessons that validate against each other and `builtin`.
+/// A missing directory is not an error; it simply yields no lessons.
+pub fn load_packs(dir: &Path, builtin: &[Lesson]) -> PackLoadReport {
+    let mut report = PackLoadReport {
+        lessons: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    let mut lesson_ids: HashSet<String> = builtin.iter().map(|l| l.id.clone()).collect();
+    let mut task_ids: HashSet<String> = builtin
+        .iter()
+        .flat_map(|l| l.tasks.iter().map(|t| t.id.clone()))
+        .collect();
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return report,
+        Err(e) => {
+            report.diagnostics.push(PackDiagnostic {
+                file: dir.display().to_string(),
+                line: None,
+                message: e.to_string(),
+            });
+            return report;
+        }
+    };
+
+    // Sort so duplicate ids are resolved the same way on every platform
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| PackFormat::from_path(p).is_some())
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let file = path.display().to_string();
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(e) => {
+                report.diagnostics.push(PackDiagnostic {
+                    file,
+                    line: None,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let format = PackFormat::from_path(&path).expect("filtered by extension above");
+
+        match parse_pack(&file, &source, format) {
+            Ok(lessons) => {
+                let mut locator = IdLocator::new(&source);
+                for lesson in lessons {
+                    let problems = validate_lesson(
+                        &file,
+                        &lesson,
+                        &mut locator,
+                        &mut lesson_ids,
+                        &mut task_ids,
+                    );
+                    if problems.is_empty() {
+                        report.lessons.push(lesson);
+                    } else {
+                        report.diagnostics.extend(problems);
+                    }
+                }
+            }
+            Err(diagnostic) => report.diagnostics.push(diagnostic),
+        }
+    }
+
+    report
+}
+
+/// Parse a pack's source without validating it
+pub fn parse_pack(
+    file: &str,
+    source: &str,
+    format: PackFormat,
+) -> Result<Vec<Lesson>, PackDiagnostic> {
+    let parsed: Result<LessonPackFile, PackDiagnostic> = match format {
+        PackFormat::Json => serde_json::from_str(source).map_err(|e| PackDiagnostic {
+            file: file.to_string(),
+            line: Some(e.line()).filter(|&l| l > 0),
+            message: e.to_string(),
+        }),
+        PackFormat::Toml => toml::from_str(source).map_err(|e| PackDiagnostic {
+            file: file.to_string(),
+            line: e.span().map(|span| line_at(source, span.start)),
+            message: e.message().to_string(),
+        }),
+    };
+    parsed.map(|pack| pack.lessons)
+}
+
+/// Check a single lesson, registering its ids only when it is accepted
+fn validate_lesson(
+    file: &str,
+    lesson: &Lesson,
+    locator: &mut IdLocator,
+    lesson_ids: &mut HashSet<String>,
+    task_ids: &mut HashSet<String>,
+) -> Vec<PackDiagnostic> {
+    let mut problems = Vec::new();
+    let mut report = |line: Option<usize>, message: String| {
+        problems.push(PackDiagnostic {
+            file: file.to_string(),
+            line,
+            message,
+        });
+    };
+
+    let lesson_line = locator.next(&lesson.id);
+    if lesson.id.trim().is_empty() {
+        report(
+            locator.line(&lesson.name),
+            format!("lesson '{}' has an empty id", lesson.name),
+        );
+    } else if lesson_ids.contains(&lesson.id) {
+        report(lesson_line, format!("duplicate lesson id '{}'", lesson.id));
+    }
+    if lesson.tasks.is_empty() {
+        report(lesson_line, format!("lesson '{}' has no tasks", lesson.id));
+    }
+
+    let mut seen_in_lesson = HashSet::new();
+    for task in &lesson.tasks {
+        let line = locator.next(&task.id);
+        if task.id.trim().is_empty() {
+            report(
+                lesson_line,
+                format!("a task in lesson '{}' has an empty id", lesson.id),
+            );
+        } else if task_ids.contains(&task.id) || !seen_in_lesson.insert(task.id.as_str()) {
+            report(line, format!("duplicate task id '{}'", task.id));
+        }
+        if task.target_text.trim().is_empty() {
+            report(line, format!("task '{}' has an empty target_text", task.id));
+        }
+        if !(0.0..=1.0).contains(&task.min_accuracy) {
+            report(
+                line,
+                format!(
+                    "task '{}' has min_accuracy {} outside 0..=1",
+                    task.id, task.min_accuracy
+                ),
+            );
+        }
+        if task.min_wpm.is_some_and(|wpm| wpm < 0.0 || wpm.is_nan()) {
+            report(line, format!("task '{}' has a negative min_wpm", task.id));
+        }
+    }
+
+    if problems.is_empty() {
+        lesson_ids.insert(lesson.id.clone());
+        task_ids.extend(lesson.tasks.iter().map(|t| t.id.clone()));
+    }
+    problems
+}
+
+/// 1-based line containing the byte offset
+fn line_at(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+/// Line of the `nth` place (0-based) an id is written as a quoted string, in either TOML or JSON
+fn line_of_id(source: &str, id: &str, nth: usize) -> Option<usize> {
+    if id.is_empty() {
+        return None;
+    }
+    let mut offsets: Vec<usize> = [format!("\"{}\"", id), format!("'{}'", id)]
+        .iter()
+        .flat_map(|quoted| {
+            source
+                .match_indices(quoted.as_str())
+                .map(|(offset, _)| offset)
+        })
+        .collect();
+    offsets.sort_unstable();
+    offsets.get(nth).map(|&offset| line_at(source, offset))
+}
+
+/// Walks the ids of one pack file in the order its lessons are validated, so a repeated id
+/// points at the line of the repeat rather than the first occurrence
+struct IdLocator<'a> {
+    source: &'a str,
+    seen: HashMap<String, usize>,
+}
+
+impl<'a> IdLocator<'a> {
+    fn new(source: &'a str) -> Self {
+        IdLocator {
+            source,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Line of the next occurrence of `id`, which is then passed
+    fn next(&mut self, id: &str) -> Option<usize> {
+        let nth = self.seen.entry(id.to_string()).or_insert(0);
+        let line = line_of_id(self.source, id, *nth);
+        *nth += 1;
+        line
+    }
+
+    /// Line of the next occurrence of `id`, without passing it
+    fn line(&self, id: &str) -> Option<usize> {
+        line_of_id(self.source, id, self.seen.get(id).copied().unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lessons::{
+        builtin_lessons, filter_by_category, find_lesson, merge_lessons, LessonCategory,
+    };
+
+    const TOML_PACK: &str = r#"
+[[lessons]]
+id = "pack-bottom-row"
+name = "Bottom Row"
+description = "Reach down to the bottom row."
+category = "bottom_row"
+difficulty = "beginner"
+
+[[lessons.tasks]]
+id = "pbr-1"
+instruction = "Left hand bottom row."
+target_text = "zxcv zxcv"
+min_accuracy = 0.9
+"#;
+
+    #[test]
+    fn test_parse_toml_pack() {
+        let lessons = parse_pack("a.toml", TOML_PACK, PackFormat::Toml).unwrap();
+        assert_eq!(lessons.len(), 1);
+        assert_eq!(lessons[0].category, LessonCategory::BottomRow);
+        assert_eq!(lessons[0].tasks[0].time_limit, None);
+    }
+
+    #[test]
+    fn test_parse_error_reports_line() {
+        let json = "{\n  \"lessons\": [\n    { \"id\": 5 }\n  ]\n}";
+        let err = parse_pack("b.json", json, PackFormat::Json).unwrap_err();
+        assert_eq!(err.file, "b.json");
+        assert_eq!(err.line, Some(3));
+
+        let toml = "[[lessons]]\nid = \"x\"\ndifficulty = \"impossible\"\n";
+        let err = parse_pack("c.toml", toml, PackFormat::Toml).unwrap_err();
+        assert!(err.line.is_some());
+    }
+
+    #[test]
+    fn test_validation_rejects_bad_lessons() {
+        let source = TOML_PACK
+            .replace("zxcv zxcv", "  ")
+            .replace("0.9", "1.5")
+            .replace("pbr-1", "hr-1");
+        let lessons = parse_pack("d.toml", &source, PackFormat::Toml).unwrap();
+
+        let builtin = builtin_lessons();
+        let mut lesson_ids = builtin.iter().map(|l| l.id.clone()).collect();
+        let mut task_ids = builtin
+            .iter()
+            .flat_map(|l| l.tasks.iter().map(|t| t.id.clone()))
+            .collect();
+        let problems = validate_lesson(
+            "d.toml",
+            &lessons[0],
+            &mut IdLocator::new(&source),
+            &mut lesson_ids,
+            &mut task_ids,
+        );
+
+        assert_eq!(problems.len(), 3);
+        assert!(problems[0].message.contains("duplicate task id 'hr-1'"));
+        assert!(problems.iter().all(|p| p.line == Some(10)));
+        assert!(!lesson_ids.contains("pack-bottom-row"));
+    }
+
+    #[test]
+    fn test_load_packs_from_dir() {
+        let dir = std::env::temp_dir().join(format!("lesson-packs-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.toml"), TOML_PACK).unwrap();
+        std::fs::write(dir.join("b.toml"), TOML_PACK).unwrap(); // same ids again
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let report = load_packs(&dir, &builtin_lessons());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.lessons.len(), 1);
+        assert_eq!(report.diagnostics.len(), 2);
+        assert!(report.diagnostics[0].file.ends_with("b.toml"));
+        assert!(load_packs(&dir, &[]).diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_in_same_file_points_at_repeat() {
+        let source = format!("{}\n{}", TOML_PACK, TOML_PACK);
+        let dir = std::env::temp_dir().join(format!("lesson-packs-dup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.toml"), &source).unwrap();
+        let report = load_packs(&dir, &[]);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.lessons.len(), 1);
+        let lines: Vec<Option<usize>> = report.diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines, [Some(17), Some(24)]);
+    }
+
+    #[test]
+    fn test_pack_lessons_are_merged() {
+        let packs = parse_pack("a.toml", TOML_PACK, PackFormat::Toml).unwrap();
+        let lessons = merge_lessons(builtin_lessons(), &packs);
+        assert!(find_lesson(lessons.clone(), "pack-bottom-row").is_some());
+        assert!(find_lesson(lessons.clone(), "home-row-basics").is_some());
+        assert_eq!(filter_by_category(lessons, "bottom_row").len(), 1);
+    }
+}
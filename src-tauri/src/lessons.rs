@@ -1,4 +1,7 @@
+use crate::course_lessons;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::RwLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -7,6 +10,10 @@ pub struct Task {
     pub target_text: String,
     pub time_limit: Option<u32>, // seconds
     pub min_accuracy: f32,       // 0.0 - 1.0
+    #[serde(default)]
+    pub min_wpm: Option<f32>, // net WPM
+    #[serde(default)]
+    pub max_uncorrected_errors: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +37,7 @@ pub enum LessonCategory {
     Words,
     Sentences,
     Code,
+    Commands,
     Custom,
 }
 
@@ -42,8 +50,93 @@ pub enum Difficulty {
     Expert,
 }
 
-// Static lesson data
+// Lessons loaded from external lesson packs, merged with the built-in set
+static PACK_LESSONS: RwLock<Vec<Lesson>> = RwLock::new(Vec::new());
+
+/// Replace the lessons contributed by lesson packs
+pub fn set_pack_lessons(lessons: Vec<Lesson>) {
+    if let Ok(mut packs) = PACK_LESSONS.write() {
+        *packs = lessons;
+    }
+}
+
+/// Built-in and course lessons followed by any lessons loaded from packs
 pub fn get_all_lessons() -> Vec<Lesson> {
+    match PACK_LESSONS.read() {
+        Ok(packs) => merge_lessons(bundled_lessons(), &packs),
+        Err(_) => bundled_lessons(),
+    }
+}
+
+/// The lessons shipped with the app: the built-in set, then the lessons of the built-in courses
+pub fn bundled_lessons() -> Vec<Lesson> {
+    let mut lessons = builtin_lessons();
+    lessons.extend(course_lessons::course_lessons());
+    lessons
+}
+
+/// `builtin` followed by the lessons of a pack set
+pub fn merge_lessons(mut builtin: Vec<Lesson>, packs: &[Lesson]) -> Vec<Lesson> {
+    builtin.extend(packs.iter().cloned());
+    builtin
+}
+
+// Tasks of generated lessons remembered for resolving results; the oldest are forgotten first
+const GENERATED_TASK_CAPACITY: usize = 512;
+
+/// Tasks of lessons generated on request: adaptive, review, key and word drills. They are not
+/// listed with the other lessons, but resolve by id so their results keep the task's criteria.
+#[derive(Debug, Default)]
+pub struct GeneratedLessons {
+    tasks: VecDeque<(String, Task)>, // (lesson id, task), oldest first
+}
+
+impl GeneratedLessons {
+    pub fn register(&mut self, lesson: &Lesson) {
+        for task in &lesson.tasks {
+            self.register_task(&lesson.id, task);
+        }
+    }
+
+    /// Remember one task, replacing an earlier task with the same id
+    pub fn register_task(&mut self, lesson_id: &str, task: &Task) {
+        self.tasks.retain(|(_, t)| t.id != task.id);
+        if self.tasks.len() == GENERATED_TASK_CAPACITY {
+            self.tasks.pop_front();
+        }
+        self.tasks.push_back((lesson_id.to_string(), task.clone()));
+    }
+
+    /// How many tasks are registered under `lesson_id`
+    pub fn task_count(&self, lesson_id: &str) -> usize {
+        self.tasks.iter().filter(|(l, _)| l == lesson_id).count()
+    }
+
+    pub fn task(&self, task_id: &str) -> Option<(&str, &Task)> {
+        self.tasks
+            .iter()
+            .find(|(_, t)| t.id == task_id)
+            .map(|(lesson_id, task)| (lesson_id.as_str(), task))
+    }
+}
+
+/// A task and the id of its lesson, from the built-in and pack lessons or the generated ones
+pub fn resolve_task(generated: &GeneratedLessons, task_id: &str) -> Option<(String, Task)> {
+    get_all_lessons()
+        .into_iter()
+        .find_map(|lesson| {
+            let task = lesson.tasks.into_iter().find(|t| t.id == task_id)?;
+            Some((lesson.id, task))
+        })
+        .or_else(|| {
+            generated
+                .task(task_id)
+                .map(|(lesson_id, task)| (lesson_id.to_string(), task.clone()))
+        })
+}
+
+// Static lesson data
+pub fn builtin_lessons() -> Vec<Lesson> {
     vec![
         // Home Row Lessons
         Lesson {
@@ -59,6 +152,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "asdf asdf asdf asdf".to_string(),
                     time_limit: None,
                     min_accuracy: 0.9,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "hr-2".to_string(),
@@ -66,6 +161,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "jkl; jkl; jkl; jkl;".to_string(),
                     time_limit: None,
                     min_accuracy: 0.9,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "hr-3".to_string(),
@@ -73,6 +170,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "asdf jkl; asdf jkl; asdf jkl;".to_string(),
                     time_limit: None,
                     min_accuracy: 0.9,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "hr-4".to_string(),
@@ -80,6 +179,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "aj sk dl f; aj sk dl f; aj sk dl f;".to_string(),
                     time_limit: None,
                     min_accuracy: 0.9,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "hr-5".to_string(),
@@ -87,6 +188,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "sad dad fall salad flask ask lads".to_string(),
                     time_limit: None,
                     min_accuracy: 0.85,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
             ],
         },
@@ -103,6 +206,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "as a sad lad adds salads".to_string(),
                     time_limit: None,
                     min_accuracy: 0.9,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "hrw-2".to_string(),
@@ -110,6 +215,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "all fall flask lass ska".to_string(),
                     time_limit: None,
                     min_accuracy: 0.9,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "hrw-3".to_string(),
@@ -117,6 +224,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "a sad lad falls as a lass asks dad".to_string(),
                     time_limit: None,
                     min_accuracy: 0.85,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
             ],
         },
@@ -134,6 +243,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "qwer qwer qwer qwer".to_string(),
                     time_limit: None,
                     min_accuracy: 0.85,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "tr-2".to_string(),
@@ -141,6 +252,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "uiop uiop uiop uiop".to_string(),
                     time_limit: None,
                     min_accuracy: 0.85,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "tr-3".to_string(),
@@ -148,6 +261,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "we are quite ripe to type".to_string(),
                     time_limit: None,
                     min_accuracy: 0.85,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
             ],
         },
@@ -165,6 +280,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "the be to of and a in that have I".to_string(),
                     time_limit: None,
                     min_accuracy: 0.9,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "cw-2".to_string(),
@@ -172,6 +289,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "it for not on with he as you do at".to_string(),
                     time_limit: None,
                     min_accuracy: 0.9,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
             ],
         },
@@ -189,6 +308,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "The quick brown fox jumps over the lazy dog.".to_string(),
                     time_limit: None,
                     min_accuracy: 0.9,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "ss-2".to_string(),
@@ -196,6 +317,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "Hello, how are you doing today?".to_string(),
                     time_limit: None,
                     min_accuracy: 0.9,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
             ],
         },
@@ -213,6 +336,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "function greet(name) { return \"Hello, \" + name; }".to_string(),
                     time_limit: None,
                     min_accuracy: 0.85,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "js-2".to_string(),
@@ -220,6 +345,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "const add = (a, b) => a + b;".to_string(),
                     time_limit: None,
                     min_accuracy: 0.85,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
             ],
         },
@@ -237,6 +364,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "fn main() { println!(\"Hello, world!\"); }".to_string(),
                     time_limit: None,
                     min_accuracy: 0.85,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "rs-2".to_string(),
@@ -244,6 +373,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "let mut count: i32 = 0;".to_string(),
                     time_limit: None,
                     min_accuracy: 0.85,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "rs-3".to_string(),
@@ -251,6 +382,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "struct Point { x: f64, y: f64 }".to_string(),
                     time_limit: None,
                     min_accuracy: 0.85,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
             ],
         },
@@ -268,6 +401,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "the the the the the the the the the the".to_string(),
                     time_limit: Some(30),
                     min_accuracy: 0.95,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
                 Task {
                     id: "sde-2".to_string(),
@@ -275,6 +410,8 @@ pub fn get_all_lessons() -> Vec<Lesson> {
                     target_text: "and and and and and and and and and and".to_string(),
                     time_limit: Some(30),
                     min_accuracy: 0.95,
+                    min_wpm: None,
+                    max_uncorrected_errors: None,
                 },
             ],
         },
@@ -282,10 +419,18 @@ pub fn get_all_lessons() -> Vec<Lesson> {
 }
 
 pub fn get_lesson_by_id(id: &str) -> Option<Lesson> {
-    get_all_lessons().into_iter().find(|l| l.id == id)
+    find_lesson(get_all_lessons(), id)
+}
+
+pub fn find_lesson(lessons: Vec<Lesson>, id: &str) -> Option<Lesson> {
+    lessons.into_iter().find(|l| l.id == id)
 }
 
 pub fn get_lessons_by_category(category: &str) -> Vec<Lesson> {
+    filter_by_category(get_all_lessons(), category)
+}
+
+pub fn filter_by_category(lessons: Vec<Lesson>, category: &str) -> Vec<Lesson> {
     let target_category = match category {
         "home_row" => LessonCategory::HomeRow,
         "top_row" => LessonCategory::TopRow,
@@ -295,10 +440,12 @@ pub fn get_lessons_by_category(category: &str) -> Vec<Lesson> {
         "words" => LessonCategory::Words,
         "sentences" => LessonCategory::Sentences,
         "code" => LessonCategory::Code,
+        "commands" => LessonCategory::Commands,
+        "custom" => LessonCategory::Custom,
         _ => return vec![],
     };
 
-    get_all_lessons()
+    lessons
         .into_iter()
         .filter(|l| l.category == target_category)
         .collect()
@@ -319,3 +466,43 @@ pub fn get_lessons_by_difficulty(difficulty: &str) -> Vec<Lesson> {
         .filter(|l| l.difficulty == target_difficulty)
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str) -> Task {
+        Task {
+            id: id.to_string(),
+            instruction: String::new(),
+            target_text: "asdf".to_string(),
+            time_limit: None,
+            min_accuracy: 0.97,
+            min_wpm: None,
+            max_uncorrected_errors: None,
+        }
+    }
+
+    #[test]
+    fn test_generated_tasks_resolve_by_id() {
+        let mut generated = GeneratedLessons::default();
+        generated.register_task("adaptive-7", &task("adaptive-7-1"));
+        generated.register_task("adaptive-7", &task("adaptive-7-2"));
+        generated.register_task("adaptive-7", &task("adaptive-7-2"));
+        assert_eq!(generated.task_count("adaptive-7"), 2);
+        assert_eq!(generated.task_count("home-row-basics"), 0);
+        let (lesson_id, resolved) = resolve_task(&generated, "adaptive-7-1").unwrap();
+        assert_eq!(lesson_id, "adaptive-7");
+        assert_eq!(resolved.min_accuracy, 0.97);
+        assert_eq!(
+            resolve_task(&generated, "hr-1").unwrap().0,
+            "home-row-basics"
+        );
+
+        for n in 0..GENERATED_TASK_CAPACITY {
+            generated.register_task("words", &task(&format!("words-{}", n)));
+        }
+        assert!(generated.task("adaptive-7-1").is_none());
+        assert!(generated.task("words-0").is_some());
+    }
+}
@@ -1,4 +1,21 @@
+pub mod alignment;
+pub mod backups;
+pub mod corpus;
+pub mod course_lessons;
+pub mod courses;
+pub mod drills;
+pub mod export;
+pub mod history;
+pub mod import;
+pub mod keystroke;
+pub mod layout_import;
+pub mod layouts;
+pub mod lesson_packs;
 pub mod lessons;
 pub mod metrics;
 pub mod models;
+pub mod placement;
+pub mod problem_keys;
+pub mod review;
+pub mod session;
 pub mod storage;
@@ -1,22 +1,63 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod alignment;
+mod backups;
+mod corpus;
+mod course_lessons;
+mod courses;
+mod drills;
+mod export;
+mod history;
+mod import;
 mod keyboard;
+mod keystroke;
+mod layout_import;
+mod layouts;
+mod lesson_packs;
 mod lessons;
 mod metrics;
 mod models;
+mod placement;
+mod problem_keys;
+mod review;
+mod session;
 mod storage;
 
-use lessons::Lesson;
-use metrics::{MetricsCalculator, TaskResult};
+use backups::{BackupCheck, BackupInfo, BackupKind, BackupRetention};
+use corpus::{Corpus, CorpusError, CorpusInfo, Language, WordTaskOptions};
+use courses::{Course, CourseError, CourseProgress, PracticeStats, StageState};
+use drills::{DrillError, DrillOptions, UnlockedKeys};
+use history::{LessonCompletion, ProgressError};
+use import::{ImportReport, ImportStrategy, ImportTable};
+use keystroke::{KeystrokeEvent, ReplayFrame};
+use layout_import::LayoutImportReport;
+use layouts::{KeyboardLayout, LayoutId, LayoutMatch};
+use lesson_packs::PackDiagnostic;
+use lessons::{GeneratedLessons, Lesson, Task};
+use metrics::{FingerReport, LatencyReport, MetricsCalculator, PassPolicy, TaskResult};
 use models::*;
+use placement::{PlacementReport, PlacementTest};
+use problem_keys::ProblemKey;
+use review::ReviewItem;
+use session::{FinishedSession, LiveStats, SessionError, SessionInput, SessionManager};
 use storage::{Database, StorageError};
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::sync::Mutex;
-use tauri::{Manager, State};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// How often the running app checks whether the daily backup is due
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 // Application state
 struct AppState {
     db: Mutex<Database>,
+    pack_diagnostics: Mutex<Vec<PackDiagnostic>>,
+    sessions: Mutex<SessionManager>,
+    layout_watcher: Mutex<Option<keyboard::LayoutWatcher>>,
+    generated: Mutex<GeneratedLessons>,
 }
 
 /// Convert any error to a JSON string for the frontend
@@ -30,10 +71,36 @@ fn map_storage_err(e: impl Into<StorageError>) -> String {
 }
 
 fn lock_db<'a>(state: &'a State<AppState>) -> Result<std::sync::MutexGuard<'a, Database>, String> {
-    state.db.lock().map_err(|e| map_err(e))
+    state.db.lock().map_err(map_err)
+}
+
+fn map_session_err(err: SessionError) -> String {
+    serde_json::to_string(&err).unwrap_or_else(|_| err.to_string())
+}
+
+fn map_drill_err(err: DrillError) -> String {
+    serde_json::to_string(&err).unwrap_or_else(|_| err.to_string())
+}
+
+fn map_corpus_err(err: CorpusError) -> String {
+    serde_json::to_string(&err).unwrap_or_else(|_| err.to_string())
+}
+
+fn map_progress_err(err: ProgressError) -> String {
+    serde_json::to_string(&err).unwrap_or_else(|_| err.to_string())
+}
+
+fn map_course_err(err: CourseError) -> String {
+    serde_json::to_string(&err).unwrap_or_else(|_| err.to_string())
+}
+
+fn lock_sessions<'a>(
+    state: &'a State<AppState>,
+) -> Result<std::sync::MutexGuard<'a, SessionManager>, String> {
+    state.sessions.lock().map_err(map_err)
 }
 
-// ── Lesson commands (unchanged) ──────────────────────────────────────
+// ── Lesson commands ──────────────────────────────────────────────────
 
 #[tauri::command]
 fn get_all_lessons() -> Vec<Lesson> {
@@ -50,25 +117,200 @@ fn get_lessons_by_category(category: &str) -> Vec<Lesson> {
     lessons::get_lessons_by_category(category)
 }
 
+/// Load lesson packs from disk and make their lessons visible to the lesson commands
+fn load_lesson_packs() -> Vec<PackDiagnostic> {
+    let report = lesson_packs::load_packs(&lesson_packs::packs_dir(), &lessons::bundled_lessons());
+    lessons::set_pack_lessons(report.lessons);
+    report.diagnostics
+}
+
 #[tauri::command]
-fn calculate_result(
-    task_id: String,
-    target_text: String,
-    typed_text: String,
-    start_time: i64,
-    end_time: i64,
-    errors: Vec<(usize, char, char)>,
-) -> TaskResult {
-    MetricsCalculator::calculate_result(
-        task_id,
-        &target_text,
-        &typed_text,
-        start_time,
-        end_time,
-        errors,
+fn reload_lesson_packs(state: State<AppState>) -> Result<Vec<PackDiagnostic>, String> {
+    let diagnostics = load_lesson_packs();
+    let mut stored = state.pack_diagnostics.lock().map_err(map_err)?;
+    *stored = diagnostics.clone();
+    Ok(diagnostics)
+}
+
+#[tauri::command]
+fn get_lesson_pack_diagnostics(state: State<AppState>) -> Result<Vec<PackDiagnostic>, String> {
+    let stored = state.pack_diagnostics.lock().map_err(map_err)?;
+    Ok(stored.clone())
+}
+
+#[tauri::command]
+fn generate_adaptive_lesson(
+    state: State<AppState>,
+    user_id: i64,
+    seed: u64,
+    task_count: Option<usize>,
+) -> Result<Lesson, String> {
+    let db = lock_db(&state)?;
+    let problem_keys: Vec<(String, i64)> = db
+        .get_problem_keys(user_id, chrono::Utc::now().timestamp_millis())
+        .map_err(map_storage_err)?
+        .into_iter()
+        .map(|k| (k.key.to_string(), k.errors.round() as i64))
+        .collect();
+    // Until enough keystrokes are measured, drill the keys the frontend counted errors on
+    let problem_keys = if problem_keys.is_empty() {
+        db.get_user_stats(user_id)
+            .map_err(map_storage_err)?
+            .map(|stats| stats.problem_keys)
+            .unwrap_or_default()
+    } else {
+        problem_keys
+    };
+    drop(db);
+
+    let options = DrillOptions::for_user(user_id, seed, task_count);
+    register_lesson(
+        &state,
+        drills::generate_adaptive_lesson(&problem_keys, &options),
     )
 }
 
+/// Remember a generated lesson so its tasks resolve by id, then hand it out
+fn register_lesson(state: &State<AppState>, lesson: Lesson) -> Result<Lesson, String> {
+    state.generated.lock().map_err(map_err)?.register(&lesson);
+    Ok(lesson)
+}
+
+const DUE_REVIEW_LIMIT: usize = 20;
+
+/// Characters, bigrams and words whose spaced-repetition review is due, most overdue first
+#[tauri::command]
+fn get_due_review_items(
+    state: State<AppState>,
+    user_id: i64,
+    limit: Option<usize>,
+) -> Result<Vec<ReviewItem>, String> {
+    let db = lock_db(&state)?;
+    db.get_due_review_items(
+        user_id,
+        chrono::Utc::now().timestamp_millis(),
+        limit.unwrap_or(DUE_REVIEW_LIMIT),
+    )
+    .map_err(map_storage_err)
+}
+
+#[tauri::command]
+fn generate_review_lesson(
+    state: State<AppState>,
+    user_id: i64,
+    seed: u64,
+    task_count: Option<usize>,
+) -> Result<Lesson, String> {
+    let due = lock_db(&state)?
+        .get_due_review_items(
+            user_id,
+            chrono::Utc::now().timestamp_millis(),
+            DUE_REVIEW_LIMIT,
+        )
+        .map_err(map_storage_err)?;
+    let options = DrillOptions::for_user(user_id, seed, task_count);
+    register_lesson(&state, drills::generate_review_lesson(&due, &options))
+}
+
+/// A built-in layout by id, or one the user imported
+fn load_layout(db: &Database, user_id: i64, layout_id: &str) -> Result<KeyboardLayout, String> {
+    if let Some(id) = LayoutId::parse(layout_id) {
+        return Ok(KeyboardLayout::builtin(id));
+    }
+    db.get_custom_layouts(user_id)
+        .map_err(map_storage_err)?
+        .into_iter()
+        .find(|row| row.id == layout_id)
+        .map(|row| row.layout)
+        .ok_or_else(|| map_storage_err(StorageError::NotFound(format!("layout '{}'", layout_id))))
+}
+
+/// A lesson that only uses the keys the learner has unlocked so far
+#[tauri::command]
+fn generate_key_lesson(
+    state: State<AppState>,
+    user_id: i64,
+    layout: String,
+    unlocked: UnlockedKeys,
+    seed: u64,
+    task_count: Option<usize>,
+) -> Result<Lesson, String> {
+    let layout = {
+        let db = lock_db(&state)?;
+        load_layout(&db, user_id, &layout)?
+    };
+    let options = DrillOptions::for_user(user_id, seed, task_count);
+    // Real words come from the layout's language when we have a word list for it
+    let dictionary: Vec<&str> = match Language::from_locale(&layout.locale) {
+        Some(language) => Corpus::get(language)
+            .words()
+            .iter()
+            .map(String::as_str)
+            .collect(),
+        None => drills::WORDS.to_vec(),
+    };
+    let lesson = drills::generate_key_lesson(&layout, &unlocked, &dictionary, &options)
+        .map_err(map_drill_err)?;
+    register_lesson(&state, lesson)
+}
+
+#[tauri::command]
+fn get_corpus_languages() -> Vec<CorpusInfo> {
+    corpus::available()
+}
+
+#[tauri::command]
+fn generate_word_lesson(
+    state: State<AppState>,
+    language: Language,
+    options: WordTaskOptions,
+    task_count: Option<usize>,
+) -> Result<Lesson, String> {
+    let task_count = task_count.unwrap_or(4).clamp(1, drills::MAX_TASK_COUNT);
+    let options = options.clamped();
+    let lesson =
+        corpus::generate_word_lesson(language, &options, task_count).map_err(map_corpus_err)?;
+    register_lesson(&state, lesson)
+}
+
+/// One more task for an endless word practice; the same index always gives the same text
+#[tauri::command]
+fn generate_word_task(
+    state: State<AppState>,
+    language: Language,
+    options: WordTaskOptions,
+    index: u64,
+) -> Result<Task, String> {
+    let options = options.clamped();
+    let task = corpus::word_task(language, &options, index).map_err(map_corpus_err)?;
+    state
+        .generated
+        .lock()
+        .map_err(map_err)?
+        .register_task(&corpus::lesson_id(language, &options), &task);
+    Ok(task)
+}
+
+/// A built-in, pack or generated task and the id of its lesson
+fn resolve_task(state: &State<AppState>, task_id: &str) -> Result<Option<(String, Task)>, String> {
+    let generated = state.generated.lock().map_err(map_err)?;
+    Ok(lessons::resolve_task(&generated, task_id))
+}
+
+/// How many tasks a generated lesson has. Storage counts bundled lessons itself but can't
+/// see the generated ones.
+fn generated_task_count(state: &State<AppState>, lesson_id: &str) -> Result<Option<i64>, String> {
+    let count = state.generated.lock().map_err(map_err)?.task_count(lesson_id);
+    Ok((count > 0).then_some(count as i64))
+}
+
+/// Pass criteria of a known task, or the default policy for ad-hoc text
+fn pass_policy_for(state: &State<AppState>, task_id: &str) -> Result<PassPolicy, String> {
+    Ok(resolve_task(state, task_id)?
+        .map(|(_, task)| PassPolicy::from_task(&task))
+        .unwrap_or_default())
+}
+
 // ── User commands ────────────────────────────────────────────────────
 
 #[tauri::command]
@@ -144,6 +386,8 @@ fn get_user_stats(
     db.get_user_stats(user_id).map_err(map_storage_err)
 }
 
+/// Deprecated: the totals written here become the base that sessions recorded by Rust are
+/// added to. Prefer `record_task_result` or typing sessions and read the stats back.
 #[tauri::command]
 fn save_user_stats(
     state: State<AppState>,
@@ -167,6 +411,8 @@ fn get_all_lesson_progress(
         .map_err(map_storage_err)
 }
 
+/// Deprecated: task results written here are turned into session rows, and lesson progress
+/// is recomputed from sessions on the next rebuild. Prefer `record_task_result`.
 #[tauri::command]
 fn save_lesson_progress(
     state: State<AppState>,
@@ -178,6 +424,31 @@ fn save_lesson_progress(
         .map_err(map_storage_err)
 }
 
+/// Save one lesson's progress; fails with a conflict if another window saved it first
+#[tauri::command]
+fn upsert_lesson_progress(
+    state: State<AppState>,
+    user_id: i64,
+    progress: LessonProgressRow,
+) -> Result<i64, String> {
+    let db = lock_db(&state)?;
+    db.upsert_lesson_progress(user_id, &progress)
+        .map_err(map_storage_err)
+}
+
+/// Delete one lesson's progress; fails with a conflict if it changed since `version` was read
+#[tauri::command]
+fn delete_lesson_progress(
+    state: State<AppState>,
+    user_id: i64,
+    lesson_id: String,
+    version: i64,
+) -> Result<(), String> {
+    let db = lock_db(&state)?;
+    db.delete_lesson_progress(user_id, &lesson_id, version)
+        .map_err(map_storage_err)
+}
+
 // ── Course Progress commands ─────────────────────────────────────────
 
 #[tauri::command]
@@ -201,14 +472,27 @@ fn save_course_progress(
         .map_err(map_storage_err)
 }
 
+#[tauri::command]
+fn upsert_course_progress(
+    state: State<AppState>,
+    user_id: i64,
+    progress: CourseProgressRow,
+) -> Result<i64, String> {
+    let db = lock_db(&state)?;
+    db.upsert_course_progress(user_id, &progress)
+        .map_err(map_storage_err)
+}
+
+/// Delete one course's progress; with a `version`, fails with a conflict if it changed since
 #[tauri::command]
 fn delete_course_progress(
     state: State<AppState>,
     user_id: i64,
     course_id: String,
+    version: Option<i64>,
 ) -> Result<(), String> {
     let db = lock_db(&state)?;
-    db.delete_course_progress(user_id, &course_id)
+    db.delete_course_progress(user_id, &course_id, version)
         .map_err(map_storage_err)
 }
 
@@ -219,6 +503,172 @@ fn delete_all_course_progress(state: State<AppState>, user_id: i64) -> Result<()
         .map_err(map_storage_err)
 }
 
+// ── Courses commands ─────────────────────────────────────────────────
+
+/// A user's progress in a course they are enrolled in
+fn enrolled_progress(
+    db: &Database,
+    user_id: i64,
+    course_id: &str,
+) -> Result<CourseProgress, String> {
+    db.get_course_state(user_id, course_id)
+        .map_err(map_storage_err)?
+        .ok_or_else(|| map_course_err(CourseError::NotEnrolled(course_id.to_string())))
+}
+
+fn save_course_state(
+    db: &Database,
+    user_id: i64,
+    mut progress: CourseProgress,
+) -> Result<CourseProgress, String> {
+    progress.version = db
+        .save_course_state(user_id, &progress)
+        .map_err(map_storage_err)?;
+    Ok(progress)
+}
+
+#[tauri::command]
+fn get_courses() -> Vec<Course> {
+    courses::all_courses().to_vec()
+}
+
+#[tauri::command]
+fn get_course_stages(
+    state: State<AppState>,
+    user_id: i64,
+    course_id: String,
+) -> Result<Vec<StageState>, String> {
+    let course = courses::get_course(&course_id).map_err(map_course_err)?;
+    let db = lock_db(&state)?;
+    let progress = enrolled_progress(&db, user_id, &course_id)?;
+    let lessons = db
+        .get_all_lesson_progress(user_id)
+        .map_err(map_storage_err)?;
+    Ok(courses::stage_states(
+        course,
+        &progress,
+        &PracticeStats::from_lessons(&lessons),
+    ))
+}
+
+#[tauri::command]
+fn enroll_in_course(
+    state: State<AppState>,
+    user_id: i64,
+    course_id: String,
+) -> Result<CourseProgress, String> {
+    let course = courses::get_course(&course_id).map_err(map_course_err)?;
+    let db = lock_db(&state)?;
+    if db
+        .get_course_state(user_id, &course_id)
+        .map_err(map_storage_err)?
+        .is_some()
+    {
+        return Err(map_course_err(CourseError::AlreadyEnrolled(course_id)));
+    }
+    let progress = courses::enroll(course, &chrono::Utc::now().to_rfc3339());
+    save_course_state(&db, user_id, progress)
+}
+
+/// Complete a stage once all its lessons are finished
+#[tauri::command]
+fn complete_course_stage(
+    state: State<AppState>,
+    user_id: i64,
+    course_id: String,
+    stage_id: String,
+) -> Result<CourseProgress, String> {
+    let course = courses::get_course(&course_id).map_err(map_course_err)?;
+    let db = lock_db(&state)?;
+    let mut progress = enrolled_progress(&db, user_id, &course_id)?;
+    let lessons = db
+        .get_all_lesson_progress(user_id)
+        .map_err(map_storage_err)?;
+    courses::complete_stage(
+        course,
+        &mut progress,
+        &stage_id,
+        &lessons,
+        &PracticeStats::from_lessons(&lessons),
+        &chrono::Utc::now().to_rfc3339(),
+    )
+    .map_err(map_course_err)?;
+    save_course_state(&db, user_id, progress)
+}
+
+/// Jump to `stage_id`, or skip the stage being worked on when it's omitted
+#[tauri::command]
+fn skip_course_stage(
+    state: State<AppState>,
+    user_id: i64,
+    course_id: String,
+    stage_id: Option<String>,
+) -> Result<CourseProgress, String> {
+    let course = courses::get_course(&course_id).map_err(map_course_err)?;
+    let db = lock_db(&state)?;
+    let mut progress = enrolled_progress(&db, user_id, &course_id)?;
+    let lessons = db
+        .get_all_lesson_progress(user_id)
+        .map_err(map_storage_err)?;
+    courses::skip_stage(
+        course,
+        &mut progress,
+        stage_id.as_deref(),
+        &PracticeStats::from_lessons(&lessons),
+    )
+    .map_err(map_course_err)?;
+    save_course_state(&db, user_id, progress)
+}
+
+#[tauri::command]
+fn get_placement_test(course_id: String) -> Result<PlacementTest, String> {
+    placement::test_for(&course_id).map_err(map_course_err)
+}
+
+/// Finish a placement session started with the test's task id, then skip the stages it
+/// shows the user has mastered, enrolling them if needed
+#[tauri::command]
+fn finish_placement_test(
+    app: AppHandle,
+    state: State<AppState>,
+    session_id: String,
+) -> Result<PlacementReport, String> {
+    let mut sessions = lock_sessions(&state)?;
+    let task_id = sessions
+        .task_id(&session_id)
+        .map_err(map_session_err)?
+        .to_string();
+    let course_id = placement::course_for_task(&task_id)
+        .ok_or_else(|| map_course_err(CourseError::NoPlacementTest(task_id.clone())))?;
+    let course = courses::get_course(course_id).map_err(map_course_err)?;
+    let finished = score_session(&state, &sessions, &session_id)?;
+
+    // The session stays open until its result is stored, so any failure up to here can be retried
+    let mut report =
+        placement::evaluate(course, &finished.result, &finished.events).map_err(map_course_err)?;
+    let db = lock_db(&state)?;
+    db.store_finished_session(&finished)
+        .map_err(map_storage_err)?;
+    sessions
+        .close_finished(&session_id, chrono::Utc::now().timestamp_millis())
+        .map_err(map_session_err)?;
+    drop(sessions);
+
+    let mut progress = match db
+        .get_course_state(finished.user_id, course_id)
+        .map_err(map_storage_err)?
+    {
+        Some(progress) => progress,
+        None => courses::enroll(course, &chrono::Utc::now().to_rfc3339()),
+    };
+    courses::place(course, &mut progress, &report.start_stage_id).map_err(map_course_err)?;
+    report.progress = Some(save_course_state(&db, finished.user_id, progress)?);
+
+    app.emit(session::FINISHED_EVENT, finished.result.clone())
+        .map_err(map_err)?;
+    Ok(report)
+}
+
 // ── Custom Snippets commands ─────────────────────────────────────────
 
 #[tauri::command]
@@ -241,6 +691,29 @@ fn save_snippets(
         .map_err(map_storage_err)
 }
 
+#[tauri::command]
+fn upsert_snippet(
+    state: State<AppState>,
+    user_id: i64,
+    snippet: CustomSnippetRow,
+) -> Result<i64, String> {
+    let db = lock_db(&state)?;
+    db.upsert_snippet(user_id, &snippet)
+        .map_err(map_storage_err)
+}
+
+#[tauri::command]
+fn delete_snippet(
+    state: State<AppState>,
+    user_id: i64,
+    snippet_id: String,
+    version: i64,
+) -> Result<(), String> {
+    let db = lock_db(&state)?;
+    db.delete_snippet(user_id, &snippet_id, version)
+        .map_err(map_storage_err)
+}
+
 // ── Daily Test Results commands ──────────────────────────────────────
 
 #[tauri::command]
@@ -259,6 +732,29 @@ fn save_daily_results(
         .map_err(map_storage_err)
 }
 
+#[tauri::command]
+fn record_daily_result(
+    state: State<AppState>,
+    result: DailyTestResultRow,
+) -> Result<i64, String> {
+    let db = lock_db(&state)?;
+    db.record_daily_result(&result)
+        .map_err(map_storage_err)
+}
+
+/// Delete one user's result for a day; fails with a conflict if it changed since `version`
+#[tauri::command]
+fn delete_daily_result(
+    state: State<AppState>,
+    user_id: i64,
+    date: String,
+    version: i64,
+) -> Result<(), String> {
+    let db = lock_db(&state)?;
+    db.delete_daily_result(user_id, &date, version)
+        .map_err(map_storage_err)
+}
+
 // ── Daily Activity commands ──────────────────────────────────────────
 
 #[tauri::command]
@@ -281,12 +777,297 @@ fn save_activity(
         .map_err(map_storage_err)
 }
 
+#[tauri::command]
+fn record_activity_delta(
+    state: State<AppState>,
+    user_id: i64,
+    delta: DailyActivityRow,
+) -> Result<DailyActivityRow, String> {
+    let db = lock_db(&state)?;
+    db.record_activity_delta(user_id, &delta)
+        .map_err(map_storage_err)
+}
+
 #[tauri::command]
 fn delete_activity(state: State<AppState>, user_id: i64) -> Result<(), String> {
     let db = lock_db(&state)?;
     db.delete_activity(user_id).map_err(map_storage_err)
 }
 
+// ── Keystroke Log commands ───────────────────────────────────────────
+
+/// Append keystrokes to a session the frontend recorded itself. Sessions owned by the
+/// session manager, live or stored, only take keystrokes through `session_keystroke`.
+#[tauri::command]
+fn record_keystrokes(
+    state: State<AppState>,
+    user_id: i64,
+    session_id: String,
+    task_id: String,
+    events: Vec<KeystrokeEvent>,
+) -> Result<(), String> {
+    if lock_sessions(&state)?.contains(&session_id) {
+        return Err(format!(
+            "Session {} is recorded by the typing session",
+            session_id
+        ));
+    }
+    let db = lock_db(&state)?;
+    if db.session_exists(&session_id).map_err(map_storage_err)? {
+        return Err(format!("Session {} is already stored", session_id));
+    }
+    db.append_keystroke_events(&session_id, user_id, &task_id, &events)
+        .map_err(map_storage_err)
+}
+
+#[tauri::command]
+fn get_keystroke_session(
+    state: State<AppState>,
+    session_id: String,
+) -> Result<Vec<KeystrokeEvent>, String> {
+    let db = lock_db(&state)?;
+    db.get_keystroke_events(&session_id).map_err(map_storage_err)
+}
+
+#[tauri::command]
+fn replay_keystroke_session(
+    state: State<AppState>,
+    session_id: String,
+) -> Result<Vec<ReplayFrame>, String> {
+    let db = lock_db(&state)?;
+    let events = db.get_keystroke_events(&session_id).map_err(map_storage_err)?;
+    Ok(keystroke::replay(&events))
+}
+
+#[tauri::command]
+fn list_keystroke_sessions(
+    state: State<AppState>,
+    user_id: i64,
+) -> Result<Vec<KeystrokeSessionSummary>, String> {
+    let db = lock_db(&state)?;
+    db.list_keystroke_sessions(user_id).map_err(map_storage_err)
+}
+
+#[tauri::command]
+fn calculate_keystroke_result(
+    state: State<AppState>,
+    session_id: String,
+    task_id: String,
+    target_text: String,
+) -> Result<TaskResult, String> {
+    let db = lock_db(&state)?;
+    let events = db.get_keystroke_events(&session_id).map_err(map_storage_err)?;
+    let policy = pass_policy_for(&state, &task_id)?;
+    let mut result =
+        MetricsCalculator::calculate_result_from_keystrokes(task_id, &target_text, &events);
+    result.apply_policy(&policy);
+    Ok(result)
+}
+
+// ── Key Latency commands ─────────────────────────────────────────────
+
+#[tauri::command]
+fn analyze_keystroke_latency(
+    state: State<AppState>,
+    session_id: String,
+) -> Result<LatencyReport, String> {
+    let db = lock_db(&state)?;
+    let events = db.get_keystroke_events(&session_id).map_err(map_storage_err)?;
+    Ok(MetricsCalculator::analyze_latency(&events))
+}
+
+/// Fold a recorded session's latencies into the user's rolling stats. A session counts once,
+/// also when `finish_typing_session` already merged it.
+#[tauri::command]
+fn record_session_latency(
+    state: State<AppState>,
+    user_id: i64,
+    session_id: String,
+) -> Result<(), String> {
+    let db = lock_db(&state)?;
+    let events = db.get_keystroke_events(&session_id).map_err(map_storage_err)?;
+    db.merge_key_latencies(
+        user_id,
+        &session_id,
+        &MetricsCalculator::latency_samples(&events),
+    )
+    .map_err(map_storage_err)
+}
+
+#[tauri::command]
+fn get_key_latency_stats(state: State<AppState>, user_id: i64) -> Result<LatencyReport, String> {
+    let db = lock_db(&state)?;
+    db.get_key_latencies(user_id).map_err(map_storage_err)
+}
+
+#[tauri::command]
+fn get_finger_stats(state: State<AppState>, user_id: i64) -> Result<FingerReport, String> {
+    let db = lock_db(&state)?;
+    db.get_finger_stats(user_id).map_err(map_storage_err)
+}
+
+/// Keys with a reliably high error rate over recent practice, worst first
+#[tauri::command]
+fn get_problem_keys(state: State<AppState>, user_id: i64) -> Result<Vec<ProblemKey>, String> {
+    let db = lock_db(&state)?;
+    db.get_problem_keys(user_id, chrono::Utc::now().timestamp_millis())
+        .map_err(map_storage_err)
+}
+
+// ── Typing Session commands ──────────────────────────────────────────
+
+/// Start a session on a known task, or on `target_text` for ad-hoc text such as snippets.
+/// Known tasks always use their own text so the frontend can't swap it out.
+#[tauri::command]
+fn start_typing_session(
+    state: State<AppState>,
+    user_id: i64,
+    task_id: String,
+    target_text: Option<String>,
+    layout: Option<String>,
+) -> Result<LiveStats, String> {
+    let target_text = match resolve_task(&state, &task_id)? {
+        Some((_, task)) => task.target_text,
+        None if placement::course_for_task(&task_id).is_some() => {
+            placement::PLACEMENT_TEXT.to_string()
+        }
+        None => {
+            target_text.ok_or_else(|| format!("Unknown task {} and no target text", task_id))?
+        }
+    };
+    let policy = pass_policy_for(&state, &task_id)?;
+    let mut sessions = lock_sessions(&state)?;
+    Ok(sessions.start(
+        user_id,
+        &task_id,
+        &target_text,
+        layout,
+        policy,
+        chrono::Utc::now().timestamp_millis(),
+    ))
+}
+
+#[tauri::command]
+fn session_keystroke(
+    app: AppHandle,
+    state: State<AppState>,
+    session_id: String,
+    input: SessionInput,
+) -> Result<LiveStats, String> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let stats = lock_sessions(&state)?
+        .keystroke(&session_id, input, now)
+        .map_err(map_session_err)?;
+    app.emit(session::LIVE_STATS_EVENT, stats.clone())
+        .map_err(map_err)?;
+    Ok(stats)
+}
+
+/// Score the session, then store it in the history along with its keystrokes and latencies.
+/// The session is only closed once it is stored, so a failed store can be retried.
+#[tauri::command]
+fn finish_typing_session(
+    app: AppHandle,
+    state: State<AppState>,
+    session_id: String,
+) -> Result<TaskResult, String> {
+    let mut sessions = lock_sessions(&state)?;
+    let finished = score_session(&state, &sessions, &session_id)?;
+    lock_db(&state)?
+        .store_finished_session(&finished)
+        .map_err(map_storage_err)?;
+    sessions
+        .close_finished(&session_id, chrono::Utc::now().timestamp_millis())
+        .map_err(map_session_err)?;
+    drop(sessions);
+
+    app.emit(session::FINISHED_EVENT, finished.result.clone())
+        .map_err(map_err)?;
+    Ok(finished.result)
+}
+
+/// Score a session and file it under the lesson its task resolves to, the same lookup
+/// `record_task_result` uses, so generated lessons are found too
+fn score_session(
+    state: &State<AppState>,
+    sessions: &SessionManager,
+    session_id: &str,
+) -> Result<FinishedSession, String> {
+    let mut finished = sessions.score(session_id).map_err(map_session_err)?;
+    finished.lesson_id = resolve_task(state, &finished.task_id)?.map(|(lesson_id, _)| lesson_id);
+    if let Some(lesson_id) = &finished.lesson_id {
+        finished.lesson_tasks = generated_task_count(state, lesson_id)?;
+    }
+    Ok(finished)
+}
+
+#[tauri::command]
+fn get_typing_session_stats(
+    state: State<AppState>,
+    session_id: String,
+) -> Result<LiveStats, String> {
+    lock_sessions(&state)?
+        .stats(&session_id, chrono::Utc::now().timestamp_millis())
+        .map_err(map_session_err)
+}
+
+#[tauri::command]
+fn abort_typing_session(state: State<AppState>, session_id: String) -> Result<(), String> {
+    lock_sessions(&state)?
+        .abort(&session_id, chrono::Utc::now().timestamp_millis())
+        .map_err(map_session_err)
+}
+
+// ── Session History commands ─────────────────────────────────────────
+
+/// Record a result the frontend measured itself. It is checked against its built-in, pack
+/// or generated task, and lesson progress is recomputed from the stored sessions.
+#[tauri::command]
+fn record_task_result(
+    state: State<AppState>,
+    user_id: i64,
+    result: TaskResult,
+) -> Result<LessonCompletion, String> {
+    let (lesson_id, task) = resolve_task(&state, &result.task_id)?
+        .ok_or_else(|| map_progress_err(ProgressError::UnknownTask(result.task_id.clone())))?;
+    let (session, result) = history::session_for_result(user_id, lesson_id.clone(), &task, result)
+        .map_err(map_progress_err)?;
+    let lesson_tasks = generated_task_count(&state, &lesson_id)?;
+
+    let db = lock_db(&state)?;
+    let progress_of = |db: &Database| -> Result<Option<LessonProgressRow>, String> {
+        let progress = db.get_all_lesson_progress(user_id).map_err(map_storage_err)?;
+        Ok(progress.into_iter().find(|p| p.lesson_id == lesson_id))
+    };
+    let before = progress_of(&db)?;
+    db.record_session(&session, lesson_tasks)
+        .map_err(map_storage_err)?;
+    let after = progress_of(&db)?.ok_or_else(|| {
+        map_storage_err(StorageError::NotFound(format!(
+            "lesson progress '{}'",
+            lesson_id
+        )))
+    })?;
+    Ok(history::lesson_completion(&result, before.as_ref(), after))
+}
+
+#[tauri::command]
+fn get_session_history(
+    state: State<AppState>,
+    user_id: i64,
+    limit: Option<i64>,
+) -> Result<Vec<SessionRow>, String> {
+    let db = lock_db(&state)?;
+    db.get_sessions(user_id, limit).map_err(map_storage_err)
+}
+
+/// Recompute user stats and lesson progress from the stored sessions
+#[tauri::command]
+fn rebuild_user_stats(state: State<AppState>, user_id: i64) -> Result<(), String> {
+    let db = lock_db(&state)?;
+    db.rebuild_aggregates(user_id).map_err(map_storage_err)
+}
+
 // ── Migration commands ───────────────────────────────────────────────
 
 #[tauri::command]
@@ -305,28 +1086,253 @@ fn migrate_from_localstorage(
         .map_err(map_storage_err)
 }
 
-// ── Keyboard Layout command ──────────────────────────────────────────
+// ── Export commands ──────────────────────────────────────────────────
+
+/// Write one user's data, or every user's when `user_id` is None, as a JSON bundle
+#[tauri::command]
+fn export_json(state: State<AppState>, path: String, user_id: Option<i64>) -> Result<(), String> {
+    let db = lock_db(&state)?;
+    let bundle =
+        export::bundle(&db, user_id, &chrono::Utc::now().to_rfc3339()).map_err(map_storage_err)?;
+    export::write_json(&bundle, Path::new(&path)).map_err(map_storage_err)
+}
+
+/// Write one CSV file per table into `dir`; returns the files written
+#[tauri::command]
+fn export_csv(
+    state: State<AppState>,
+    dir: String,
+    user_id: Option<i64>,
+) -> Result<Vec<String>, String> {
+    let db = lock_db(&state)?;
+    let bundle =
+        export::bundle(&db, user_id, &chrono::Utc::now().to_rfc3339()).map_err(map_storage_err)?;
+    let files = export::write_csv(&bundle, Path::new(&dir)).map_err(map_storage_err)?;
+    Ok(files
+        .iter()
+        .map(|f| f.to_string_lossy().into_owned())
+        .collect())
+}
+
+#[tauri::command]
+fn backup_database(state: State<AppState>, path: String) -> Result<(), String> {
+    let db = lock_db(&state)?;
+    db.backup_to(Path::new(&path)).map_err(map_storage_err)
+}
+
+// ── Import commands ──────────────────────────────────────────────────
+
+/// Dry run: what importing an export bundle or backup .db would add and where it conflicts
+#[tauri::command]
+fn preview_import(state: State<AppState>, path: String) -> Result<ImportReport, String> {
+    let bundle = import::load(Path::new(&path)).map_err(map_storage_err)?;
+    let db = lock_db(&state)?;
+    import::preview(&db, &bundle).map_err(map_storage_err)
+}
+
+/// Import an export bundle or backup .db, resolving conflicts with a strategy per table
+#[tauri::command]
+fn apply_import(
+    state: State<AppState>,
+    path: String,
+    strategies: Option<BTreeMap<ImportTable, ImportStrategy>>,
+) -> Result<ImportReport, String> {
+    let bundle = import::load(Path::new(&path)).map_err(map_storage_err)?;
+    let db = lock_db(&state)?;
+    import::apply(&db, &bundle, &strategies.unwrap_or_default()).map_err(map_storage_err)
+}
+
+// ── Backup commands ──────────────────────────────────────────────────
+
+/// Automatic and manual backups in the backups folder, newest first
+#[tauri::command]
+fn list_backups() -> Result<Vec<BackupInfo>, String> {
+    backups::list(&backups::backups_dir()).map_err(map_storage_err)
+}
+
+#[tauri::command]
+fn create_backup(state: State<AppState>) -> Result<BackupInfo, String> {
+    let db = lock_db(&state)?;
+    backups::create(
+        &db,
+        &backups::backups_dir(),
+        BackupKind::Manual,
+        chrono::Utc::now(),
+    )
+    .map_err(map_storage_err)
+}
+
+/// Run `PRAGMA integrity_check` on a backup
+#[tauri::command]
+fn verify_backup(file_name: String) -> Result<BackupCheck, String> {
+    backups::verify(&backups::backups_dir(), &file_name).map_err(map_storage_err)
+}
+
+/// Replace the database with a verified backup; returns the backup taken of the data it replaced
+#[tauri::command]
+fn restore_backup(state: State<AppState>, file_name: String) -> Result<BackupInfo, String> {
+    let dir = backups::backups_dir();
+    let mut db = lock_db(&state)?;
+    let safety =
+        backups::restore(&mut db, &dir, &file_name, chrono::Utc::now()).map_err(map_storage_err)?;
+    backups::prune(&dir, &BackupRetention::load(&dir)).map_err(map_storage_err)?;
+    Ok(safety)
+}
+
+#[tauri::command]
+fn get_backup_retention() -> BackupRetention {
+    BackupRetention::load(&backups::backups_dir())
+}
+
+/// Save the retention settings and delete the backups they no longer keep
+#[tauri::command]
+fn set_backup_retention(retention: BackupRetention) -> Result<Vec<String>, String> {
+    let dir = backups::backups_dir();
+    retention.save(&dir).map_err(map_storage_err)?;
+    backups::prune(&dir, &retention).map_err(map_storage_err)
+}
+
+// ── Keyboard Layout commands ─────────────────────────────────────────
 
 #[tauri::command]
 fn get_keyboard_input_source() -> Option<String> {
     keyboard::get_current_input_source()
 }
 
+/// The app layout matching the OS input source, if we ship one close enough
+#[tauri::command]
+fn get_keyboard_layout() -> Option<LayoutMatch> {
+    keyboard::get_current_input_source().and_then(|source| layouts::normalize_input_source(&source))
+}
+
+#[tauri::command]
+fn normalize_input_source(source: String) -> Option<LayoutMatch> {
+    layouts::normalize_input_source(&source)
+}
+
+/// Emit `keyboard-layout-changed` whenever the OS layout changes, and move live typing
+/// sessions onto the new layout. Starting again replaces the running watcher.
+#[tauri::command]
+fn start_layout_watcher(app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let mut watcher = state.layout_watcher.lock().map_err(map_err)?;
+    // Only one watcher can run, so the old one must stop before the new one starts
+    watcher.take();
+    *watcher = Some(keyboard::watch(move |change| {
+        if let Ok(mut sessions) = app.state::<AppState>().sessions.lock() {
+            sessions.switch_layout(change.new_layout);
+        }
+        let _ = app.emit(keyboard::LAYOUT_CHANGED_EVENT, change);
+    }));
+    Ok(())
+}
+
+#[tauri::command]
+fn stop_layout_watcher(state: State<AppState>) -> Result<(), String> {
+    state.layout_watcher.lock().map_err(map_err)?.take();
+    Ok(())
+}
+
+/// Rows, keys and finger assignments of a built-in layout
+#[tauri::command]
+fn get_layout_definition(layout: LayoutId) -> KeyboardLayout {
+    KeyboardLayout::builtin(layout)
+}
+
+// ── Custom Layout commands ───────────────────────────────────────────
+
+/// Import an XKB symbols, .klc or .keylayout file. The layout is saved for the user
+/// only when the report has no errors.
+#[tauri::command]
+fn import_custom_layout(
+    state: State<AppState>,
+    user_id: i64,
+    path: String,
+) -> Result<LayoutImportReport, String> {
+    let report = layout_import::import_layout_file(std::path::Path::new(&path));
+    if let (Some(layout), Some(format)) = (&report.layout, report.format) {
+        let db = lock_db(&state)?;
+        db.save_custom_layout(&CustomLayoutRow {
+            id: layout.id.clone(),
+            user_id,
+            name: layout.name.clone(),
+            format: format.as_str().to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            layout: layout.clone(),
+        })
+        .map_err(map_storage_err)?;
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+fn get_custom_layouts(
+    state: State<AppState>,
+    user_id: i64,
+) -> Result<Vec<CustomLayoutRow>, String> {
+    let db = lock_db(&state)?;
+    db.get_custom_layouts(user_id).map_err(map_storage_err)
+}
+
+#[tauri::command]
+fn delete_custom_layout(
+    state: State<AppState>,
+    user_id: i64,
+    layout_id: String,
+) -> Result<(), String> {
+    let db = lock_db(&state)?;
+    db.delete_custom_layout(user_id, &layout_id)
+        .map_err(map_storage_err)
+}
+
 fn main() {
     // Initialize database
     let db = Database::new().expect("Failed to initialize database");
+    // A backup that fails must not keep the app from starting
+    let _: Result<_, _> =
+        backups::run_scheduled(&db, &backups::backups_dir(), true, chrono::Utc::now());
+    let pack_diagnostics = load_lesson_packs();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .manage(AppState {
             db: Mutex::new(db),
+            pack_diagnostics: Mutex::new(pack_diagnostics),
+            sessions: Mutex::new(SessionManager::default()),
+            layout_watcher: Mutex::new(None),
+            generated: Mutex::new(GeneratedLessons::default()),
+        })
+        .setup(|app| {
+            // Take the daily backup in apps left running for days
+            let handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(BACKUP_CHECK_INTERVAL);
+                if let Some(state) = handle.try_state::<AppState>() {
+                    if let Ok(db) = state.db.lock() {
+                        let _: Result<_, _> = backups::run_scheduled(
+                            &db,
+                            &backups::backups_dir(),
+                            false,
+                            chrono::Utc::now(),
+                        );
+                    }
+                }
+            });
+            Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // Lessons
             get_all_lessons,
             get_lesson,
             get_lessons_by_category,
-            calculate_result,
+            reload_lesson_packs,
+            get_lesson_pack_diagnostics,
+            generate_adaptive_lesson,
+            generate_key_lesson,
+            get_due_review_items,
+            generate_review_lesson,
+            get_corpus_languages,
+            generate_word_lesson,
+            generate_word_task,
             // Users
             get_all_users,
             create_user,
@@ -341,26 +1347,87 @@ fn main() {
             // Lesson Progress
             get_all_lesson_progress,
             save_lesson_progress,
+            upsert_lesson_progress,
+            delete_lesson_progress,
             // Course Progress
             get_all_course_progress,
             save_course_progress,
+            upsert_course_progress,
             delete_course_progress,
             delete_all_course_progress,
+            // Courses
+            get_courses,
+            get_course_stages,
+            enroll_in_course,
+            complete_course_stage,
+            skip_course_stage,
+            get_placement_test,
+            finish_placement_test,
             // Custom Snippets
             get_snippets,
             save_snippets,
+            upsert_snippet,
+            delete_snippet,
             // Daily Test Results
             get_daily_results,
             save_daily_results,
+            record_daily_result,
+            delete_daily_result,
             // Daily Activity
             get_activity,
             save_activity,
+            record_activity_delta,
             delete_activity,
+            // Keystroke Log
+            record_keystrokes,
+            get_keystroke_session,
+            replay_keystroke_session,
+            list_keystroke_sessions,
+            calculate_keystroke_result,
+            // Key Latency
+            analyze_keystroke_latency,
+            record_session_latency,
+            get_key_latency_stats,
+            get_finger_stats,
+            get_problem_keys,
+            // Typing Sessions
+            start_typing_session,
+            session_keystroke,
+            finish_typing_session,
+            get_typing_session_stats,
+            abort_typing_session,
+            // Session History
+            get_session_history,
+            rebuild_user_stats,
+            record_task_result,
             // Migration
             is_migration_needed,
             migrate_from_localstorage,
+            // Export
+            export_json,
+            export_csv,
+            backup_database,
+            // Import
+            preview_import,
+            apply_import,
+            // Backups
+            list_backups,
+            create_backup,
+            verify_backup,
+            restore_backup,
+            get_backup_retention,
+            set_backup_retention,
             // Keyboard
             get_keyboard_input_source,
+            get_keyboard_layout,
+            normalize_input_source,
+            get_layout_definition,
+            start_layout_watcher,
+            stop_layout_watcher,
+            // Custom Layouts
+            import_custom_layout,
+            get_custom_layouts,
+            delete_custom_layout,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
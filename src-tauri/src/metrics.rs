@@ -1,4 +1,10 @@
+use crate::alignment::{self, EditKind, TextDifference};
+use crate::keystroke::{self, KeystrokeEvent, KeystrokeKind};
+use crate::layouts::{Finger, Hand, KeyboardLayout};
+use crate::lessons::Task;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorInfo {
@@ -8,79 +14,532 @@ pub struct ErrorInfo {
     pub timestamp: i64,
 }
 
+/// Where the errors of an attempt ended up, counted in graphemes
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ErrorBreakdown {
+    pub corrected: usize,   // mistakes that were fixed before finishing
+    pub uncorrected: usize, // differences left in the final text, except text not reached yet
+    pub extra: usize,       // inserted characters that aren't in the target
+    pub missing: usize,     // target characters never typed, skipped or not reached
+}
+
+/// Serialized in snake_case. Deserializing also accepts the frontend's camelCase names, and
+/// results stored before the newer metrics existed fill them with defaults.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskResult {
+    #[serde(alias = "taskId")]
     pub task_id: String,
-    pub wpm: f32,
-    pub raw_wpm: f32,
+    pub wpm: f32, // net WPM: gross WPM minus uncorrected errors per minute
+    #[serde(alias = "rawWpm")]
+    pub raw_wpm: f32, // gross WPM: every typed grapheme / 5 per minute
+    #[serde(default)]
+    pub cpm: f32, // correct graphemes per minute
+    #[serde(default)]
+    pub kspc: f32, // keystrokes per grapheme of final text, 1.0 is error-free
     pub accuracy: f32,
     pub errors: Vec<ErrorInfo>,
+    #[serde(default, alias = "errorBreakdown")]
+    pub error_breakdown: ErrorBreakdown,
+    #[serde(default)]
+    pub differences: Vec<TextDifference>, // typed vs target, classified by edit kind
+    #[serde(default, alias = "totalKeystrokes")]
+    pub total_keystrokes: usize,
+    #[serde(default, alias = "backspaceCount")]
+    pub backspace_count: usize, // 0 when whoever measured the result didn't count them
     pub duration: i64, // milliseconds
+    #[serde(alias = "completedAt")]
     pub completed_at: i64,
     pub passed: bool,
+    #[serde(default, alias = "failedCriteria")]
+    pub failed_criteria: Vec<FailedCriterion>,
+}
+
+impl TaskResult {
+    /// Re-evaluate `passed` against a policy, recording every criterion that failed
+    pub fn apply_policy(&mut self, policy: &PassPolicy) {
+        self.failed_criteria = policy.evaluate(self);
+        self.passed = self.failed_criteria.is_empty();
+    }
+}
+
+/// A pass requirement the result did not meet, with the numbers needed to explain why
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "criterion", rename_all = "snake_case")]
+pub enum FailedCriterion {
+    MinAccuracy { required: f32, actual: f32 },
+    MinWpm { required: f32, actual: f32 },
+    MaxDuration { limit_ms: i64, actual_ms: i64 },
+    MaxUncorrectedErrors { limit: usize, actual: usize },
+}
+
+/// What a result must achieve to pass a task
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PassPolicy {
+    pub min_accuracy: f32,
+    pub min_wpm: Option<f32>,
+    pub max_duration_ms: Option<i64>,
+    pub max_uncorrected_errors: Option<usize>,
+}
+
+impl Default for PassPolicy {
+    fn default() -> Self {
+        PassPolicy {
+            min_accuracy: 0.85, // Default passing threshold
+            min_wpm: None,
+            max_duration_ms: None,
+            max_uncorrected_errors: None,
+        }
+    }
+}
+
+impl PassPolicy {
+    pub fn from_task(task: &Task) -> Self {
+        PassPolicy {
+            min_accuracy: task.min_accuracy,
+            min_wpm: task.min_wpm,
+            max_duration_ms: task.time_limit.map(|secs| secs as i64 * 1000),
+            max_uncorrected_errors: task.max_uncorrected_errors,
+        }
+    }
+
+    pub fn evaluate(&self, result: &TaskResult) -> Vec<FailedCriterion> {
+        let mut failed = Vec::new();
+
+        if result.accuracy < self.min_accuracy {
+            failed.push(FailedCriterion::MinAccuracy {
+                required: self.min_accuracy,
+                actual: result.accuracy,
+            });
+        }
+        if let Some(required) = self.min_wpm {
+            if result.wpm < required {
+                failed.push(FailedCriterion::MinWpm {
+                    required,
+                    actual: result.wpm,
+                });
+            }
+        }
+        if let Some(limit_ms) = self.max_duration_ms {
+            if result.duration > limit_ms {
+                failed.push(FailedCriterion::MaxDuration {
+                    limit_ms,
+                    actual_ms: result.duration,
+                });
+            }
+        }
+        if let Some(limit) = self.max_uncorrected_errors {
+            let actual = result.error_breakdown.uncorrected;
+            if actual > limit {
+                failed.push(FailedCriterion::MaxUncorrectedErrors { limit, actual });
+            }
+        }
+
+        failed
+    }
+}
+
+/// Summary of inter-key intervals in milliseconds
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub p90: f64,
+    pub variance: f64, // population variance
+}
+
+impl LatencyStats {
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let n = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let variance = sorted.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n as f64;
+        // Middle element, or the mean of the two middle elements for even counts
+        let median = (sorted[(n - 1) / 2] + sorted[n / 2]) / 2.0;
+        // Nearest-rank percentile
+        let p90 = sorted[((n as f64 * 0.9).ceil() as usize).clamp(1, n) - 1];
+
+        Some(LatencyStats {
+            count: n,
+            mean,
+            median,
+            p90,
+            variance,
+        })
+    }
+}
+
+/// Latency for one character or bigram
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyLatency {
+    pub key: String,
+    pub stats: LatencyStats,
+}
+
+/// Per-character and per-bigram latency, slowest first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LatencyReport {
+    pub characters: Vec<KeyLatency>,
+    pub bigrams: Vec<KeyLatency>,
+}
+
+impl LatencyReport {
+    pub fn from_stats(entries: impl IntoIterator<Item = (String, LatencyStats)>) -> Self {
+        let mut characters = Vec::new();
+        let mut bigrams = Vec::new();
+        for (key, stats) in entries {
+            let target = if key.chars().count() == 1 {
+                &mut characters
+            } else {
+                &mut bigrams
+            };
+            target.push(KeyLatency { key, stats });
+        }
+        for list in [&mut characters, &mut bigrams] {
+            list.sort_by(|a, b| b.stats.mean.total_cmp(&a.stats.mean).then(a.key.cmp(&b.key)));
+        }
+        LatencyReport { characters, bigrams }
+    }
+}
+
+/// Raw inter-key intervals keyed by the character (or bigram) that ended the interval
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencySamples {
+    pub characters: BTreeMap<String, Vec<f64>>,
+    pub bigrams: BTreeMap<String, Vec<f64>>,
+}
+
+impl LatencySamples {
+    /// Characters and bigrams together, since both are stored in the same aggregate table
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<f64>)> {
+        self.characters.iter().chain(self.bigrams.iter())
+    }
+}
+
+// Gaps longer than this are pauses, not typing speed
+pub const LATENCY_PAUSE_MS: f64 = 2000.0;
+const HISTOGRAM_BUCKET_MS: f64 = 20.0;
+const HISTOGRAM_BUCKETS: usize = (LATENCY_PAUSE_MS / HISTOGRAM_BUCKET_MS) as usize;
+
+/// Rolling latency aggregate that can absorb new sessions without keeping raw samples.
+/// Mean and variance are exact; median and p90 come from a 20ms histogram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyAggregate {
+    pub count: u64,
+    pub mean: f64,
+    pub m2: f64, // sum of squared deviations from the mean
+    pub histogram: Vec<u32>,
+}
+
+impl Default for LatencyAggregate {
+    fn default() -> Self {
+        LatencyAggregate {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            histogram: vec![0; HISTOGRAM_BUCKETS],
+        }
+    }
+}
+
+impl LatencyAggregate {
+    pub fn add_samples(&mut self, samples: &[f64]) {
+        for &sample in samples {
+            // Welford's online update
+            self.count += 1;
+            let delta = sample - self.mean;
+            self.mean += delta / self.count as f64;
+            self.m2 += delta * (sample - self.mean);
+
+            if self.histogram.len() != HISTOGRAM_BUCKETS {
+                self.histogram.resize(HISTOGRAM_BUCKETS, 0);
+            }
+            let bucket = ((sample / HISTOGRAM_BUCKET_MS) as usize).min(HISTOGRAM_BUCKETS - 1);
+            self.histogram[bucket] += 1;
+        }
+    }
+
+    /// Fold in another aggregate, e.g. to combine a hand's fingers
+    pub fn merge(&mut self, other: &LatencyAggregate) {
+        if other.count == 0 {
+            return;
+        }
+        // Chan et al.'s parallel variant of Welford's update
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count as f64;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.count = count;
+
+        self.histogram.resize(HISTOGRAM_BUCKETS, 0);
+        for (bucket, &c) in self.histogram.iter_mut().zip(&other.histogram) {
+            *bucket += c;
+        }
+    }
+
+    pub fn stats(&self) -> Option<LatencyStats> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(LatencyStats {
+            count: self.count as usize,
+            mean: self.mean,
+            median: self.histogram_percentile(0.5),
+            p90: self.histogram_percentile(0.9),
+            variance: self.m2 / self.count as f64,
+        })
+    }
+
+    fn histogram_percentile(&self, p: f64) -> f64 {
+        let total: u64 = self.histogram.iter().map(|&c| c as u64).sum();
+        let rank = ((total as f64 * p).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, &c) in self.histogram.iter().enumerate() {
+            seen += c as u64;
+            if seen >= rank {
+                return (i as f64 + 0.5) * HISTOGRAM_BUCKET_MS;
+            }
+        }
+        LATENCY_PAUSE_MS
+    }
+}
+
+/// Rolling totals for the keys one finger is responsible for
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FingerAggregate {
+    pub keystrokes: u64,
+    pub errors: u64,
+    pub latency: LatencyAggregate,
+}
+
+impl FingerAggregate {
+    pub fn merge(&mut self, other: &FingerAggregate) {
+        self.keystrokes += other.keystrokes;
+        self.errors += other.errors;
+        self.latency.merge(&other.latency);
+    }
+}
+
+/// Error and speed summary for the keys typed by one finger or hand
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyGroupStats {
+    pub keystrokes: u64,
+    pub errors: u64,
+    pub error_rate: f32,
+    pub latency: Option<LatencyStats>,
+}
+
+impl From<&FingerAggregate> for KeyGroupStats {
+    fn from(agg: &FingerAggregate) -> Self {
+        KeyGroupStats {
+            keystrokes: agg.keystrokes,
+            errors: agg.errors,
+            error_rate: if agg.keystrokes > 0 {
+                agg.errors as f32 / agg.keystrokes as f32
+            } else {
+                0.0
+            },
+            latency: agg.latency.stats(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FingerStats {
+    pub finger: Finger,
+    pub stats: KeyGroupStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HandStats {
+    pub hand: Hand,
+    pub stats: KeyGroupStats,
+}
+
+/// Per-finger and per-hand statistics, fingers ordered left pinky to right pinky
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FingerReport {
+    pub fingers: Vec<FingerStats>,
+    pub hands: Vec<HandStats>,
+}
+
+impl FingerReport {
+    pub fn from_aggregates(fingers: &BTreeMap<Finger, FingerAggregate>) -> Self {
+        let mut hands: BTreeMap<Hand, FingerAggregate> = BTreeMap::new();
+        for (finger, agg) in fingers {
+            hands.entry(finger.hand()).or_default().merge(agg);
+        }
+        FingerReport {
+            fingers: fingers
+                .iter()
+                .map(|(&finger, agg)| FingerStats {
+                    finger,
+                    stats: agg.into(),
+                })
+                .collect(),
+            hands: hands
+                .iter()
+                .map(|(&hand, agg)| HandStats {
+                    hand,
+                    stats: agg.into(),
+                })
+                .collect(),
+        }
+    }
 }
 
 pub struct MetricsCalculator;
 
 impl MetricsCalculator {
-    /// Calculate typing metrics from a completed task
+    /// Calculate typing metrics from a completed task.
+    /// `errors` lists every mistake made while typing, including ones fixed later;
+    /// without `total_keystrokes` the smallest count consistent with them is assumed.
+    #[cfg(test)]
     pub fn calculate_result(
         task_id: String,
         target_text: &str,
-        _typed_text: &str,
+        typed_text: &str,
         start_time: i64,
         end_time: i64,
         errors: Vec<(usize, char, char)>,
+        total_keystrokes: Option<usize>,
+    ) -> TaskResult {
+        let error_infos: Vec<ErrorInfo> = errors
+            .into_iter()
+            .map(|(index, expected, typed)| ErrorInfo {
+                index,
+                expected,
+                typed,
+                timestamp: 0, // Would need to be passed in for accurate timestamps
+            })
+            .collect();
+
+        Self::build_result(
+            task_id,
+            target_text,
+            typed_text,
+            start_time,
+            end_time,
+            error_infos,
+            total_keystrokes,
+        )
+    }
+
+    /// Calculate typing metrics from a recorded keystroke log.
+    /// Timing comes from the first and last keystroke, and every error keeps its timestamp.
+    pub fn calculate_result_from_keystrokes(
+        task_id: String,
+        target_text: &str,
+        events: &[KeystrokeEvent],
+    ) -> TaskResult {
+        let start_time = events.first().map(|e| e.timestamp).unwrap_or(0);
+        let end_time = events.last().map(|e| e.timestamp).unwrap_or(start_time);
+        let typed_text = keystroke::replay(events)
+            .pop()
+            .map(|frame| frame.text)
+            .unwrap_or_default();
+        let errors = keystroke::errors_from_events(events);
+
+        let mut result = Self::build_result(
+            task_id,
+            target_text,
+            &typed_text,
+            start_time,
+            end_time,
+            errors,
+            Some(events.len()),
+        );
+        result.backspace_count = events
+            .iter()
+            .filter(|e| e.kind == KeystrokeKind::Backspace)
+            .count();
+        result
+    }
+
+    fn build_result(
+        task_id: String,
+        target_text: &str,
+        typed_text: &str,
+        start_time: i64,
+        end_time: i64,
+        errors: Vec<ErrorInfo>,
+        total_keystrokes: Option<usize>,
     ) -> TaskResult {
         let duration = end_time - start_time;
         let minutes = duration as f32 / 60000.0;
 
-        // Standard: 5 characters = 1 word
-        let total_chars = target_text.len();
-        let word_count = total_chars as f32 / 5.0;
-        let error_count = errors.len();
+        let alignment = alignment::align(target_text, typed_text);
+        let typed_len = alignment.typed_len;
+        let correct = alignment.matches;
 
-        // Raw WPM: total words / time
-        let raw_wpm = if minutes > 0.0 {
-            word_count / minutes
-        } else {
-            0.0
+        // A recorded error was corrected if its target grapheme ended up matched.
+        // Error indexes count chars, so map them onto target graphemes first.
+        let char_to_grapheme: Vec<usize> = target_text
+            .graphemes(true)
+            .enumerate()
+            .flat_map(|(g, grapheme)| grapheme.chars().map(move |_| g))
+            .collect();
+        let corrected = errors
+            .iter()
+            .filter_map(|e| char_to_grapheme.get(e.index))
+            .filter(|&&g| alignment.is_matched(g))
+            .count();
+
+        let breakdown = ErrorBreakdown {
+            corrected,
+            uncorrected: alignment.differences.len() - alignment.trailing_omissions(),
+            extra: alignment.count(EditKind::Insertion),
+            missing: alignment.count(EditKind::Omission),
         };
+        // Every fixed mistake costs at least the wrong key plus a backspace
+        let keystrokes = total_keystrokes.unwrap_or(typed_len + 2 * corrected);
 
-        // Net WPM: (words - errors) / time
-        let wpm = if minutes > 0.0 {
-            ((word_count - error_count as f32).max(0.0)) / minutes
+        // Standard: 5 characters = 1 word
+        let (raw_wpm, wpm, cpm) = if minutes > 0.0 {
+            let gross = typed_len as f32 / 5.0 / minutes;
+            let net = (gross - breakdown.uncorrected as f32 / minutes).max(0.0);
+            (gross, net, correct as f32 / minutes)
         } else {
-            0.0
+            (0.0, 0.0, 0.0)
         };
 
-        // Accuracy: correct characters / total characters
-        let accuracy = if total_chars > 0 {
-            ((total_chars - error_count) as f32 / total_chars as f32).max(0.0)
-        } else {
+        // Accuracy: matched graphemes over every position in the alignment
+        let accuracy = if alignment.is_empty() {
             1.0
+        } else {
+            correct as f32 / alignment.len() as f32
         };
 
-        let error_infos: Vec<ErrorInfo> = errors
-            .into_iter()
-            .map(|(index, expected, typed)| ErrorInfo {
-                index,
-                expected,
-                typed,
-                timestamp: 0, // Would need to be passed in for accurate timestamps
-            })
-            .collect();
+        let kspc = if typed_len == 0 {
+            0.0
+        } else {
+            keystrokes as f32 / typed_len as f32
+        };
 
-        TaskResult {
+        let mut result = TaskResult {
             task_id,
             wpm: round_to_decimals(wpm, 1),
             raw_wpm: round_to_decimals(raw_wpm, 1),
+            cpm: round_to_decimals(cpm, 1),
+            kspc: round_to_decimals(kspc, 2),
             accuracy: round_to_decimals(accuracy, 3),
-            errors: error_infos,
+            errors,
+            error_breakdown: breakdown,
+            differences: alignment.differences,
+            total_keystrokes: keystrokes,
+            backspace_count: 0,
             duration,
             completed_at: end_time,
-            passed: accuracy >= 0.85, // Default passing threshold
-        }
+            passed: false,
+            failed_criteria: Vec::new(),
+        };
+        result.apply_policy(&PassPolicy::default());
+        result
     }
 
     /// Calculate WPM from character count and duration
@@ -118,9 +577,89 @@ impl MetricsCalculator {
         }
 
         let mut sorted: Vec<_> = key_errors.into_iter().collect();
-        sorted.sort_by(|a, b| b.1.cmp(&a.1));
+        sorted.sort_by_key(|entry| std::cmp::Reverse(entry.1));
         sorted
     }
+
+    /// Collect inter-key intervals from a keystroke log.
+    /// Only intervals between two consecutive correct keystrokes count, so the numbers reflect
+    /// fluent typing rather than error recovery. Pauses of `LATENCY_PAUSE_MS` or more are dropped.
+    pub fn latency_samples(events: &[KeystrokeEvent]) -> LatencySamples {
+        let mut samples = LatencySamples::default();
+
+        for pair in events.windows(2) {
+            let (prev, cur) = (&pair[0], &pair[1]);
+            if prev.kind == KeystrokeKind::Backspace
+                || cur.kind == KeystrokeKind::Backspace
+                || prev.is_error()
+                || cur.is_error()
+                || cur.position != prev.position + 1
+            {
+                continue;
+            }
+            let (Some(prev_key), Some(key)) = (prev.key, cur.key) else {
+                continue;
+            };
+            let interval = (cur.timestamp - prev.timestamp) as f64;
+            if !(0.0..LATENCY_PAUSE_MS).contains(&interval) {
+                continue;
+            }
+
+            samples
+                .characters
+                .entry(key.to_string())
+                .or_default()
+                .push(interval);
+            samples
+                .bigrams
+                .entry(format!("{}{}", prev_key, key))
+                .or_default()
+                .push(interval);
+        }
+
+        samples
+    }
+
+    /// Per-finger keystroke, error and latency totals for a keystroke log typed on `layout`.
+    /// A keystroke counts against the finger meant to type the expected character, so a
+    /// slip onto a neighbouring key is charged to the finger that missed.
+    /// Characters the layout cannot produce are left out.
+    pub fn finger_aggregates(
+        events: &[KeystrokeEvent],
+        layout: &KeyboardLayout,
+    ) -> BTreeMap<Finger, FingerAggregate> {
+        let mut fingers: BTreeMap<Finger, FingerAggregate> = BTreeMap::new();
+
+        for event in events.iter().filter(|e| e.key.is_some()) {
+            // Typing past the end of the target has no expected key; use the one pressed
+            let Some(position) = event.expected.or(event.key).and_then(|c| layout.locate(c)) else {
+                continue;
+            };
+            let agg = fingers.entry(position.finger).or_default();
+            agg.keystrokes += 1;
+            agg.errors += event.is_error() as u64;
+        }
+
+        for (key, intervals) in &Self::latency_samples(events).characters {
+            if let Some(position) = key.chars().next().and_then(|c| layout.locate(c)) {
+                fingers
+                    .entry(position.finger)
+                    .or_default()
+                    .latency
+                    .add_samples(intervals);
+            }
+        }
+
+        fingers
+    }
+
+    /// Per-character and per-bigram latency statistics for a keystroke log
+    pub fn analyze_latency(events: &[KeystrokeEvent]) -> LatencyReport {
+        let samples = Self::latency_samples(events);
+        LatencyReport::from_stats(samples.iter().filter_map(|(key, values)| {
+            LatencyStats::from_samples(values).map(|stats| (key.clone(), stats))
+        }))
+    }
 }
 
 fn round_to_decimals(value: f32, decimals: u32) -> f32 {
@@ -161,10 +700,281 @@ mod tests {
             0,
             60000, // 1 minute
             vec![], // no errors
+            None,
         );
 
         assert_eq!(result.accuracy, 1.0);
         assert!(result.passed);
         assert_eq!(result.errors.len(), 0);
     }
+
+    #[test]
+    fn test_older_and_frontend_results_deserialize() {
+        let stored = r#"{"task_id":"hr-1","wpm":40.0,"raw_wpm":42.0,"accuracy":0.9,
+            "errors":[],"duration":60000,"completed_at":1,"passed":true}"#;
+        let result: TaskResult = serde_json::from_str(stored).unwrap();
+        assert_eq!(result.error_breakdown, ErrorBreakdown::default());
+        assert!(result.failed_criteria.is_empty());
+
+        let frontend = r#"{"taskId":"hr-1","wpm":40,"rawWpm":42,"accuracy":0.9,
+            "totalKeystrokes":120,"backspaceCount":3,"duration":60000,"completedAt":1,
+            "passed":true,"errors":[{"index":2,"expected":"d","typed":"f","timestamp":5}]}"#;
+        let result: TaskResult = serde_json::from_str(frontend).unwrap();
+        assert_eq!(result.task_id, "hr-1");
+        assert_eq!(result.total_keystrokes, 120);
+        assert_eq!(result.errors[0].expected, 'd');
+    }
+
+    #[test]
+    fn test_corrected_errors_do_not_lower_net_wpm() {
+        // Two mistakes were made and fixed; the final text is perfect
+        let result = MetricsCalculator::calculate_result(
+            "t".to_string(),
+            "hello world",
+            "hello world",
+            0,
+            60000,
+            vec![(1, 'e', 'r'), (6, 'w', 'q')],
+            None,
+        );
+
+        assert_eq!(result.raw_wpm, 2.2);
+        assert_eq!(result.wpm, 2.2);
+        assert_eq!(result.cpm, 11.0);
+        assert_eq!(result.accuracy, 1.0);
+        assert_eq!(result.error_breakdown.corrected, 2);
+        assert_eq!(result.error_breakdown.uncorrected, 0);
+        assert_eq!(result.total_keystrokes, 15);
+        assert_eq!(result.kspc, 1.36);
+    }
+
+    #[test]
+    fn test_uncorrected_extra_and_missing() {
+        let result = MetricsCalculator::calculate_result(
+            "t".to_string(),
+            "hello world",
+            "hellp world!!",
+            0,
+            60000,
+            vec![(4, 'o', 'p')],
+            Some(13),
+        );
+        assert_eq!(result.error_breakdown.uncorrected, 3);
+        assert_eq!(result.error_breakdown.extra, 2);
+        assert_eq!(result.error_breakdown.corrected, 0);
+        assert_eq!(result.raw_wpm, 2.6);
+        assert_eq!(result.wpm, 0.0);
+        assert_eq!(result.accuracy, 0.769);
+        assert_eq!(result.kspc, 1.0);
+
+        let result = MetricsCalculator::calculate_result(
+            "t".to_string(),
+            "hello",
+            "hel",
+            0,
+            60000,
+            vec![],
+            None,
+        );
+        assert_eq!(result.error_breakdown.missing, 2);
+        assert_eq!(result.error_breakdown.uncorrected, 0);
+        assert_eq!(result.accuracy, 0.6);
+    }
+
+    #[test]
+    fn test_skipped_character_is_one_error() {
+        // Positionally every character after "t" would be wrong; aligned it's one omission
+        let result = MetricsCalculator::calculate_result(
+            "t".to_string(),
+            "the quick brown fox",
+            "te quick brown fox",
+            0,
+            60000,
+            vec![],
+            None,
+        );
+        assert_eq!(result.error_breakdown.uncorrected, 1);
+        assert_eq!(result.error_breakdown.missing, 1);
+        assert_eq!(result.differences.len(), 1);
+        assert_eq!(result.differences[0].kind, EditKind::Omission);
+        assert_eq!(result.accuracy, 0.947);
+        assert!(result.passed);
+    }
+
+    #[test]
+    fn test_pass_policy_from_task() {
+        let task = Task {
+            id: "sprint".to_string(),
+            instruction: String::new(),
+            target_text: "hello world".to_string(),
+            time_limit: Some(30),
+            min_accuracy: 0.95,
+            min_wpm: Some(20.0),
+            max_uncorrected_errors: Some(0),
+        };
+        let policy = PassPolicy::from_task(&task);
+        assert_eq!(policy.max_duration_ms, Some(30_000));
+
+        let mut result = MetricsCalculator::calculate_result(
+            "sprint".to_string(),
+            "hello world",
+            "hellp world",
+            0,
+            60_000,
+            vec![(4, 'o', 'p')],
+            None,
+        );
+        assert!(result.passed); // 0.909 clears the default threshold
+        result.apply_policy(&policy);
+
+        assert!(!result.passed);
+        assert_eq!(
+            result.failed_criteria,
+            vec![
+                FailedCriterion::MinAccuracy { required: 0.95, actual: 0.909 },
+                FailedCriterion::MinWpm { required: 20.0, actual: 1.2 },
+                FailedCriterion::MaxDuration { limit_ms: 30_000, actual_ms: 60_000 },
+                FailedCriterion::MaxUncorrectedErrors { limit: 0, actual: 1 },
+            ]
+        );
+
+        let relaxed = PassPolicy { min_accuracy: 0.9, ..PassPolicy::default() };
+        result.apply_policy(&relaxed);
+        assert!(result.passed);
+        assert!(result.failed_criteria.is_empty());
+    }
+
+    #[test]
+    fn test_counts_graphemes_not_bytes() {
+        // "Grüße" is 5 graphemes but 7 bytes; "e\u{301}" is one grapheme of two chars
+        let result = MetricsCalculator::calculate_result(
+            "t".to_string(),
+            "Grüße cafe\u{301}",
+            "Grüße cafe\u{301}",
+            0,
+            12000,
+            vec![],
+            None,
+        );
+        assert_eq!(result.raw_wpm, 10.0);
+        assert_eq!(result.accuracy, 1.0);
+        assert_eq!(result.kspc, 1.0);
+    }
+
+    #[test]
+    fn test_result_from_keystrokes() {
+        use crate::keystroke::KeystrokeRecorder;
+
+        let mut rec = KeystrokeRecorder::new("abcde");
+        rec.key_down('a', 1_000);
+        rec.key_down('x', 2_000);
+        rec.backspace(3_000);
+        for (i, c) in "bcde".chars().enumerate() {
+            rec.key_down(c, 4_000 + i as i64 * 1_000);
+        }
+
+        let result =
+            MetricsCalculator::calculate_result_from_keystrokes("t".to_string(), "abcde", rec.events());
+        assert_eq!(result.duration, 6_000);
+        assert_eq!(result.completed_at, 7_000);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].timestamp, 2_000);
+    }
+
+    #[test]
+    fn test_latency_stats() {
+        let stats = LatencyStats::from_samples(&[100.0, 200.0, 300.0, 400.0]).unwrap();
+        assert_eq!(stats.mean, 250.0);
+        assert_eq!(stats.median, 250.0);
+        assert_eq!(stats.p90, 400.0);
+        assert_eq!(stats.variance, 12_500.0);
+        assert!(LatencyStats::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn test_latency_skips_errors_and_pauses() {
+        use crate::keystroke::KeystrokeRecorder;
+
+        let mut rec = KeystrokeRecorder::new("then the");
+        for (c, t) in [('t', 0), ('h', 300), ('e', 400), ('m', 500)] {
+            rec.key_down(c, t);
+        }
+        rec.backspace(600);
+        for (c, t) in [('n', 700), (' ', 800), ('t', 5_000), ('h', 5_250), ('e', 5_350)] {
+            rec.key_down(c, t);
+        }
+
+        let report = MetricsCalculator::analyze_latency(rec.events());
+        let th = report.bigrams.iter().find(|b| b.key == "th").unwrap();
+        assert_eq!(th.stats.count, 2);
+        assert_eq!(th.stats.mean, 275.0);
+        // "th" is the slowest bigram even though it was never mistyped
+        assert_eq!(report.bigrams[0].key, "th");
+        // the error, the correction after the backspace, and the pause are all excluded
+        assert!(report.bigrams.iter().all(|b| b.key != "em" && b.key != "en" && b.key != " t"));
+    }
+
+    #[test]
+    fn test_latency_aggregate_matches_exact_stats() {
+        let samples = [110.0, 150.0, 190.0, 230.0, 400.0];
+        let mut agg = LatencyAggregate::default();
+        agg.add_samples(&samples[..2]);
+        agg.add_samples(&samples[2..]);
+
+        let exact = LatencyStats::from_samples(&samples).unwrap();
+        let rolled = agg.stats().unwrap();
+        assert_eq!(rolled.count, 5);
+        assert!((rolled.mean - exact.mean).abs() < 1e-9);
+        assert!((rolled.variance - exact.variance).abs() < 1e-6);
+        assert!((rolled.median - exact.median).abs() <= 20.0);
+        assert!((rolled.p90 - exact.p90).abs() <= 20.0);
+    }
+
+    #[test]
+    fn test_finger_aggregates() {
+        use crate::keystroke::KeystrokeRecorder;
+        use crate::layouts::LayoutId;
+
+        // "fj fj" with a slip: 'k' typed for the second 'j', then corrected
+        let mut recorder = KeystrokeRecorder::new("fj fj");
+        for (i, key) in "fj fk".chars().enumerate() {
+            recorder.key_down(key, i as i64 * 100);
+        }
+        recorder.backspace(500);
+        recorder.key_down('j', 600);
+
+        let layout = KeyboardLayout::builtin(LayoutId::QwertyUs);
+        let fingers = MetricsCalculator::finger_aggregates(recorder.events(), &layout);
+        let index = &fingers[&Finger::RightIndex];
+        assert_eq!((index.keystrokes, index.errors), (3, 1));
+        assert_eq!(fingers[&Finger::LeftIndex].errors, 0);
+        assert_eq!(fingers[&Finger::LeftThumb].keystrokes, 1);
+        // f→j, j→space and space→f are fluent; the corrected 'j' follows a backspace
+        assert_eq!(index.latency.count, 1);
+
+        let report = FingerReport::from_aggregates(&fingers);
+        assert_eq!(report.fingers.len(), 3);
+        assert_eq!(report.hands[0].hand, Hand::Left);
+        assert_eq!(report.hands[0].stats.keystrokes, 3);
+        assert_eq!(report.hands[1].stats.errors, 1);
+        assert!((report.hands[1].stats.error_rate - 1.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_latency_aggregate_merge() {
+        let samples = [110.0, 150.0, 190.0, 230.0, 400.0];
+        let mut left = LatencyAggregate::default();
+        left.add_samples(&samples[..2]);
+        let mut right = LatencyAggregate::default();
+        right.add_samples(&samples[2..]);
+        left.merge(&right);
+        left.merge(&LatencyAggregate::default());
+
+        let mut whole = LatencyAggregate::default();
+        whole.add_samples(&samples);
+        assert_eq!(left.count, whole.count);
+        assert_eq!(left.histogram, whole.histogram);
+        assert!((left.mean - whole.mean).abs() < 1e-9);
+        assert!((left.m2 - whole.m2).abs() < 1e-6);
+    }
 }
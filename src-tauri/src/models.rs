@@ -1,3 +1,4 @@
+use crate::layouts::KeyboardLayout;
 use serde::{Deserialize, Serialize};
 
 // All structs use camelCase serialization to match TypeScript interfaces
@@ -12,7 +13,7 @@ pub struct UserProfile {
     pub last_active_at: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserStatsRow {
     pub total_practice_time: i64,
@@ -40,6 +41,8 @@ pub struct LessonProgressRow {
     pub average_accuracy: f64,
     pub last_task_index: Option<i64>,
     pub task_results_json: String, // JSON array of TaskResult
+    #[serde(default)]
+    pub version: i64, // row version for optimistic concurrency, 0 for a new row
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +54,8 @@ pub struct CourseProgressRow {
     pub skipped_stages_json: String,
     pub enrolled_at: String,
     pub completed_at: Option<String>,
+    #[serde(default)]
+    pub version: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +71,20 @@ pub struct CustomSnippetRow {
     pub practice_count: i64,
     pub best_wpm: Option<f64>,
     pub best_accuracy: Option<f64>,
+    #[serde(default)]
+    pub version: i64,
+}
+
+/// A layout the user imported, stored whole so it survives changes to the importers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomLayoutRow {
+    pub id: String,
+    pub user_id: i64,
+    pub name: String,
+    pub format: String,
+    pub created_at: String,
+    pub layout: KeyboardLayout,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +97,8 @@ pub struct DailyTestResultRow {
     pub true_accuracy: f64,
     pub duration: i64,
     pub completed_at: i64,
+    #[serde(default)]
+    pub version: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,6 +108,39 @@ pub struct DailyActivityRow {
     pub practice_time: i64,
     pub characters: i64,
     pub sessions: i64,
+    #[serde(default)]
+    pub version: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeystrokeSessionSummary {
+    pub session_id: String,
+    pub task_id: String,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub event_count: i64,
+}
+
+/// One completed task attempt. Rows are append-only; aggregates are derived from them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRow {
+    pub id: String,
+    pub user_id: i64,
+    pub lesson_id: Option<String>,
+    pub task_id: String,
+    pub layout: Option<String>,
+    pub started_at: i64,   // ms since epoch
+    pub completed_at: i64, // ms since epoch
+    pub wpm: f64,
+    pub accuracy: f64,
+    pub duration: i64, // ms
+    pub total_keystrokes: i64,
+    pub backspaces: i64,
+    pub error_count: i64,
+    pub passed: bool,
+    pub result_json: String, // the full TaskResult
 }
 
 /// Payload for one-time localStorage → SQLite migration
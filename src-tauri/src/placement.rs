@@ -0,0 +1,270 @@
+use crate::courses::{self, Course, CourseError, CourseProgress, PracticeStats};
+use crate::keystroke::KeystrokeEvent;
+use crate::metrics::TaskResult;
+use crate::problem_keys::{key_scores, KeyScore};
+use serde::{Deserialize, Serialize};
+
+/// The diagnostic: home row, top row, bottom row and full-alphabet sentences, then
+/// capitals, numbers and symbols, in the order the ten-finger course teaches them
+pub const PLACEMENT_TEXT: &str = "a sad lad asks; dad has a glass flask; all gals fall. \
+we wrote our report quietly; your tutor tipped the typist. \
+zinc boxes vex clever cubs, and seven men mix a buzz. \
+the quick brown fox jumps over the lazy dog. \
+Alice Met Bob In Oslo, Then Quebec With Zoe. \
+call 555 0123 at 9:45, or room 867 by 2026. \
+\"Why?\" she asked. \"It's fine!\" (a + b) * c = [d / 2] - {e}; \
+if (x > 0 && y < 9) { arr[i] = y_1 | z; }";
+
+/// Slower typists start at the beginning whatever their accuracy
+pub const MIN_WPM: f64 = 20.0;
+// Keystrokes on a stage's keys needed before its accuracy counts
+const MIN_SAMPLES: usize = 5;
+const MIN_KEY_ACCURACY: f64 = 0.9;
+
+const TASK_PREFIX: &str = "placement-";
+
+/// What a stage teaches: accurate typing on its keys, at a speed
+struct StageSkill {
+    stage_id: &'static str,
+    keys: &'static str,
+    min_wpm: f64,
+}
+
+const fn skill(stage_id: &'static str, keys: &'static str) -> StageSkill {
+    StageSkill {
+        stage_id,
+        keys,
+        min_wpm: MIN_WPM,
+    }
+}
+
+// The last stage is never skipped, so it has no skill
+const TEN_FINGER: &[StageSkill] = &[
+    skill("stage-1", "asdfjkl;"),
+    skill("stage-2", "gh"),
+    skill("stage-3", "qwert"),
+    skill("stage-4", "yuiop"),
+    skill("stage-5", "qwertyuiop"),
+    skill("stage-6", "zxcvb"),
+    skill("stage-7", "nm,."),
+    skill("stage-8", "abcdefghijklmnopqrstuvwxyz"),
+    skill("stage-9", "ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+    skill("stage-10", "0123456789"),
+    skill("stage-11", "?!'\"()[]{}<>+-*/="),
+    skill("stage-12", "{}()[];=<>&|_"),
+    // Speed Building is for typists below the Final Mastery gate
+    StageSkill {
+        stage_id: "stage-13",
+        keys: "",
+        min_wpm: 40.0,
+    },
+];
+
+fn skills(course_id: &str) -> Option<&'static [StageSkill]> {
+    match course_id {
+        "ten-finger" => Some(TEN_FINGER),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlacementTest {
+    pub course_id: String,
+    /// Start a typing session with this task id to take the test
+    pub task_id: String,
+    pub text: String,
+}
+
+pub fn test_for(course_id: &str) -> Result<PlacementTest, CourseError> {
+    courses::get_course(course_id)?;
+    if skills(course_id).is_none() {
+        return Err(CourseError::NoPlacementTest(course_id.to_string()));
+    }
+    Ok(PlacementTest {
+        course_id: course_id.to_string(),
+        task_id: format!("{}{}", TASK_PREFIX, course_id),
+        text: PLACEMENT_TEXT.to_string(),
+    })
+}
+
+/// The course a placement task id tests for
+pub fn course_for_task(task_id: &str) -> Option<&str> {
+    task_id
+        .strip_prefix(TASK_PREFIX)
+        .filter(|course_id| skills(course_id).is_some())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StagePlacement {
+    pub stage_id: String,
+    pub mastered: bool,
+    /// Accuracy on the stage's keys; None when it has none or too few were typed
+    pub accuracy: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlacementReport {
+    pub course_id: String,
+    pub wpm: f64,
+    pub accuracy: f64,
+    pub keys: Vec<KeyScore>,
+    pub stages: Vec<StagePlacement>,
+    pub skipped_stages: Vec<String>,
+    pub start_stage_id: String,
+    /// The course progress with the skips written, once applied
+    pub progress: Option<CourseProgress>,
+}
+
+/// Map a diagnostic onto the course: stages are skipped from the start for as long as
+/// each one is mastered, stopping before a stage the user couldn't enter.
+pub fn evaluate(
+    course: &Course,
+    result: &TaskResult,
+    events: &[KeystrokeEvent],
+) -> Result<PlacementReport, CourseError> {
+    let skills =
+        skills(&course.id).ok_or_else(|| CourseError::NoPlacementTest(course.id.clone()))?;
+    let keys = key_scores(events);
+    let wpm = result.wpm as f64;
+
+    let stages: Vec<StagePlacement> = skills
+        .iter()
+        .map(|skill| {
+            let (attempts, errors) = keys
+                .iter()
+                .filter(|k| skill.keys.contains(k.key))
+                .fold((0, 0), |(a, e), k| (a + k.attempts, e + k.errors));
+            let accuracy = (attempts >= MIN_SAMPLES).then(|| 1.0 - errors as f64 / attempts as f64);
+            let keys_mastered = skill.keys.is_empty()
+                || accuracy.is_some_and(|accuracy| accuracy >= MIN_KEY_ACCURACY);
+            StagePlacement {
+                stage_id: skill.stage_id.to_string(),
+                mastered: keys_mastered && wpm >= skill.min_wpm,
+                accuracy,
+            }
+        })
+        .collect();
+
+    let stats = PracticeStats {
+        best_wpm: wpm,
+        accuracy: result.accuracy as f64,
+    };
+    let mut start = stages.iter().take_while(|s| s.mastered).count();
+    while start > 0 && courses::check_criteria(&course.stages[start], &stats).is_err() {
+        start -= 1;
+    }
+
+    Ok(PlacementReport {
+        course_id: course.id.clone(),
+        wpm,
+        accuracy: result.accuracy as f64,
+        keys,
+        stages,
+        skipped_stages: course.stages[..start]
+            .iter()
+            .map(|s| s.id.clone())
+            .collect(),
+        start_stage_id: course.stages[start].id.clone(),
+        progress: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keystroke::KeystrokeRecorder;
+    use crate::metrics::MetricsCalculator;
+
+    /// Type the placement text at `wpm`, mistyping every character in `fumbled`
+    fn take_test(wpm: f64, fumbled: &str) -> (TaskResult, Vec<KeystrokeEvent>) {
+        let ms_per_char = (60_000.0 / (wpm * 5.0)) as i64;
+        let mut recorder = KeystrokeRecorder::new(PLACEMENT_TEXT);
+        let mut now = 1_000;
+        for ch in PLACEMENT_TEXT.chars() {
+            if fumbled.contains(ch) {
+                recorder.key_down('~', now);
+                recorder.backspace(now + 1);
+            }
+            now += ms_per_char;
+            recorder.key_down(ch, now);
+        }
+        let events = recorder.into_events();
+        let result = MetricsCalculator::calculate_result_from_keystrokes(
+            "placement-ten-finger".to_string(),
+            PLACEMENT_TEXT,
+            &events,
+        );
+        (result, events)
+    }
+
+    #[test]
+    fn test_placement_tests_exist_for_typing_courses_only() {
+        let test = test_for("ten-finger").unwrap();
+        assert_eq!(course_for_task(&test.task_id), Some("ten-finger"));
+        assert_eq!(
+            test_for("sql-mastery").unwrap_err(),
+            CourseError::NoPlacementTest("sql-mastery".to_string())
+        );
+        assert_eq!(course_for_task("placement-sql-mastery"), None);
+        assert_eq!(course_for_task("hr-1"), None);
+
+        let course = courses::get_course("ten-finger").unwrap();
+        let ids: Vec<_> = TEN_FINGER.iter().map(|s| s.stage_id).collect();
+        let stage_ids: Vec<_> = course.stages[..ids.len()]
+            .iter()
+            .map(|s| s.id.as_str())
+            .collect();
+        assert_eq!(ids, stage_ids);
+        for skill in TEN_FINGER {
+            let samples = PLACEMENT_TEXT
+                .chars()
+                .filter(|c| skill.keys.contains(*c))
+                .count();
+            assert!(
+                skill.keys.is_empty() || samples >= MIN_SAMPLES,
+                "{}",
+                skill.stage_id
+            );
+        }
+    }
+
+    #[test]
+    fn test_fast_typists_skip_to_their_first_weak_stage() {
+        let course = courses::get_course("ten-finger").unwrap();
+        let (result, events) = take_test(50.0, "0123456789");
+        let report = evaluate(course, &result, &events).unwrap();
+        assert_eq!(report.start_stage_id, "stage-10");
+        assert_eq!(report.skipped_stages.len(), 9);
+        assert_eq!(report.stages[9].accuracy, Some(0.5));
+        let seven = report.keys.iter().find(|k| k.key == '7').unwrap();
+        assert_eq!((seven.attempts, seven.errors), (2, 1));
+
+        // Everything mastered still leaves the final stage
+        let (result, events) = take_test(60.0, "");
+        let report = evaluate(course, &result, &events).unwrap();
+        assert_eq!(report.start_stage_id, "stage-14");
+
+        let mut progress = courses::enroll(course, "now");
+        courses::place(course, &mut progress, &report.start_stage_id).unwrap();
+        assert_eq!(progress.skipped_stages.len(), 13);
+        assert_eq!(progress.current_stage_id.as_deref(), Some("stage-14"));
+    }
+
+    #[test]
+    fn test_slow_typists_start_at_the_beginning() {
+        let course = courses::get_course("ten-finger").unwrap();
+        let (result, events) = take_test(15.0, "");
+        let report = evaluate(course, &result, &events).unwrap();
+        assert_eq!(report.start_stage_id, "stage-1");
+        assert!(report.skipped_stages.is_empty());
+
+        // Mastered up to Speed Building, but too slow to enter it
+        let (result, events) = take_test(28.0, "");
+        let report = evaluate(course, &result, &events).unwrap();
+        assert!(report.stages[11].mastered && !report.stages[12].mastered);
+        assert_eq!(report.start_stage_id, "stage-12");
+    }
+}
@@ -0,0 +1,219 @@
+use crate::keystroke::KeystrokeEvent;
+use crate::review::DAY_MS;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Days after which old attempts and errors count half
+pub const HALF_LIFE_DAYS: f64 = 14.0;
+/// Decayed attempts a key needs before its error rate is reported
+pub const MIN_ATTEMPTS: f64 = 20.0;
+// Two-sided 95% normal quantile
+const Z: f64 = 1.96;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyScore {
+    pub key: char,
+    pub attempts: usize,
+    pub errors: usize,
+}
+
+/// Attempts and errors per expected character, re-typed characters included
+pub fn key_scores(events: &[KeystrokeEvent]) -> Vec<KeyScore> {
+    let mut scores: BTreeMap<char, KeyScore> = BTreeMap::new();
+    for event in events.iter().filter(|e| e.key.is_some()) {
+        let Some(expected) = event.expected else {
+            continue;
+        };
+        let score = scores.entry(expected).or_insert(KeyScore {
+            key: expected,
+            attempts: 0,
+            errors: 0,
+        });
+        score.attempts += 1;
+        if event.is_error() {
+            score.errors += 1;
+        }
+    }
+    scores.into_values().collect()
+}
+
+/// Exponentially decayed attempts and errors on one key, as of `updated_at`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyErrorStats {
+    pub key: char,
+    pub attempts: f64,
+    pub errors: f64,
+    pub updated_at: i64, // milliseconds since epoch
+}
+
+impl KeyErrorStats {
+    pub fn new(key: char, at: i64) -> Self {
+        KeyErrorStats {
+            key,
+            attempts: 0.0,
+            errors: 0.0,
+            updated_at: at,
+        }
+    }
+
+    /// The counts as they stand at `now`; never grows them for a `now` in the past
+    pub fn decayed(&self, now: i64) -> Self {
+        let elapsed_days = (now - self.updated_at).max(0) as f64 / DAY_MS as f64;
+        let factor = 0.5_f64.powf(elapsed_days / HALF_LIFE_DAYS);
+        KeyErrorStats {
+            key: self.key,
+            attempts: self.attempts * factor,
+            errors: self.errors * factor,
+            updated_at: now.max(self.updated_at),
+        }
+    }
+
+    pub fn record(&mut self, attempts: usize, errors: usize, at: i64) {
+        *self = self.decayed(at);
+        self.attempts += attempts as f64;
+        self.errors += errors as f64;
+    }
+}
+
+/// Fold one session's scores into the stored stats. Returns the rows that changed.
+pub fn merge(existing: &[KeyErrorStats], scores: &[KeyScore], at: i64) -> Vec<KeyErrorStats> {
+    scores
+        .iter()
+        .filter(|s| s.attempts > 0)
+        .map(|score| {
+            let mut stats = existing
+                .iter()
+                .find(|s| s.key == score.key)
+                .copied()
+                .unwrap_or_else(|| KeyErrorStats::new(score.key, at));
+            stats.record(score.attempts, score.errors, at);
+            stats
+        })
+        .collect()
+}
+
+/// A key with a reliably measured error rate
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemKey {
+    pub key: char,
+    pub attempts: f64,
+    pub errors: f64,
+    pub error_rate: f64,
+    /// 95% Wilson score interval of the error rate
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Wilson score interval of `errors` out of `attempts`, which stays inside 0..=1 and
+/// stays wide for few samples
+pub fn wilson_interval(errors: f64, attempts: f64) -> (f64, f64) {
+    if attempts <= 0.0 {
+        return (0.0, 1.0);
+    }
+    let p = errors / attempts;
+    let z2 = Z * Z;
+    let denominator = 1.0 + z2 / attempts;
+    let center = (p + z2 / (2.0 * attempts)) / denominator;
+    let margin =
+        Z * (p * (1.0 - p) / attempts + z2 / (4.0 * attempts * attempts)).sqrt() / denominator;
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+/// Keys with errors and enough recent attempts as of `now`, most certainly bad first
+/// (highest lower bound)
+pub fn report(stats: &[KeyErrorStats], now: i64) -> Vec<ProblemKey> {
+    let mut keys: Vec<ProblemKey> = stats
+        .iter()
+        .map(|s| s.decayed(now))
+        .filter(|s| s.attempts >= MIN_ATTEMPTS && s.errors > 0.0)
+        .map(|s| {
+            let (lower, upper) = wilson_interval(s.errors, s.attempts);
+            ProblemKey {
+                key: s.key,
+                attempts: s.attempts,
+                errors: s.errors,
+                error_rate: s.errors / s.attempts,
+                lower,
+                upper,
+            }
+        })
+        .collect();
+    keys.sort_by(|a, b| b.lower.total_cmp(&a.lower).then(a.key.cmp(&b.key)));
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_halve_every_half_life() {
+        let half_life = (HALF_LIFE_DAYS * DAY_MS as f64) as i64;
+        let mut stats = KeyErrorStats::new('q', 0);
+        stats.record(40, 10, 0);
+        let later = stats.decayed(half_life);
+        assert!((later.attempts - 20.0).abs() < 1e-9);
+        assert!((later.errors - 5.0).abs() < 1e-9);
+        assert_eq!(stats.decayed(-1), stats);
+
+        let merged = merge(
+            &[stats],
+            &[KeyScore {
+                key: 'q',
+                attempts: 20,
+                errors: 0,
+            }],
+            half_life,
+        );
+        assert!((merged[0].attempts - 40.0).abs() < 1e-9);
+        assert!((merged[0].errors - 5.0).abs() < 1e-9);
+        assert_eq!(merged[0].updated_at, half_life);
+    }
+
+    #[test]
+    fn test_report_needs_samples_and_ranks_by_lower_bound() {
+        let stats = [
+            // Few attempts with a high rate are not reported
+            KeyErrorStats {
+                key: 'z',
+                attempts: 5.0,
+                errors: 4.0,
+                updated_at: 0,
+            },
+            // Same rate, but the larger sample is more certainly a problem
+            KeyErrorStats {
+                key: 'a',
+                attempts: 30.0,
+                errors: 6.0,
+                updated_at: 0,
+            },
+            KeyErrorStats {
+                key: 'b',
+                attempts: 300.0,
+                errors: 60.0,
+                updated_at: 0,
+            },
+            KeyErrorStats {
+                key: 'c',
+                attempts: 300.0,
+                errors: 0.0,
+                updated_at: 0,
+            },
+        ];
+        let ranked = report(&stats, 0);
+        let keys: Vec<char> = ranked.iter().map(|k| k.key).collect();
+        assert_eq!(keys, ['b', 'a']);
+        for key in &ranked {
+            assert!((key.error_rate - 0.2).abs() < 1e-9);
+            assert!(key.lower < key.error_rate && key.error_rate < key.upper);
+        }
+        assert!(ranked[0].upper - ranked[0].lower < ranked[1].upper - ranked[1].lower);
+
+        // Thirty days without practice drop 'a' below the sample threshold
+        let keys: Vec<char> = report(&stats, 30 * DAY_MS).iter().map(|k| k.key).collect();
+        assert_eq!(keys, ['b']);
+    }
+}
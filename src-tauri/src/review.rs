@@ -0,0 +1,263 @@
+use crate::keystroke::KeystrokeEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+pub const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+const INITIAL_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+// SM-2 grades from 3 up are a successful recall
+const PASSING_GRADE: u8 = 3;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewKind {
+    Char,
+    Bigram,
+    Word,
+}
+
+impl ReviewKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewKind::Char => "char",
+            ReviewKind::Bigram => "bigram",
+            ReviewKind::Word => "word",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "char" => Some(ReviewKind::Char),
+            "bigram" => Some(ReviewKind::Bigram),
+            "word" => Some(ReviewKind::Word),
+            _ => None,
+        }
+    }
+}
+
+/// SM-2 state of one character, bigram or word a user has had trouble with
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewItem {
+    pub kind: ReviewKind,
+    pub item: String,
+    pub ease: f64,
+    pub interval_days: f64,
+    pub repetitions: i64, // successful reviews in a row
+    pub lapses: i64,
+    pub due_at: i64, // milliseconds since epoch
+    pub last_reviewed_at: i64,
+}
+
+/// How one item went in one session
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub kind: ReviewKind,
+    pub item: String,
+    pub attempts: usize,
+    pub errors: usize,
+}
+
+impl Observation {
+    /// SM-2 quality from the share of clean attempts: 5 is flawless, below 3 a lapse
+    pub fn grade(&self) -> u8 {
+        let accuracy = 1.0 - self.errors as f64 / self.attempts.max(1) as f64;
+        match accuracy {
+            a if a >= 1.0 => 5,
+            a if a >= 0.95 => 4,
+            a if a >= 0.9 => 3,
+            a if a >= 0.75 => 2,
+            a if a >= 0.5 => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// Characters, bigrams and words of the target that the session reached. A position
+/// counts as an error if any keystroke there was wrong, even when it was corrected.
+pub fn observations(target_text: &str, events: &[KeystrokeEvent]) -> Vec<Observation> {
+    let target: Vec<char> = target_text.chars().collect();
+    let mut reached = vec![false; target.len()];
+    let mut missed = vec![false; target.len()];
+    for event in events.iter().filter(|e| e.key.is_some()) {
+        if event.position < target.len() {
+            reached[event.position] = true;
+            missed[event.position] |= event.is_error();
+        }
+    }
+
+    let mut found: BTreeMap<(ReviewKind, String), (usize, usize)> = BTreeMap::new();
+    let mut observe = |kind, item: String, error: bool| {
+        let entry = found.entry((kind, item)).or_insert((0, 0));
+        entry.0 += 1;
+        entry.1 += usize::from(error);
+    };
+
+    for (i, &ch) in target.iter().enumerate() {
+        if !reached[i] || ch.is_whitespace() {
+            continue;
+        }
+        observe(ReviewKind::Char, ch.to_string(), missed[i]);
+        // A bigram is the move from one key to the next, so it's missed when the second key is
+        if i > 0 && reached[i - 1] && !target[i - 1].is_whitespace() {
+            observe(
+                ReviewKind::Bigram,
+                format!("{}{}", target[i - 1], ch),
+                missed[i],
+            );
+        }
+    }
+
+    let mut start = 0;
+    for (i, ch) in target.iter().chain([&' ']).enumerate() {
+        if !ch.is_whitespace() {
+            continue;
+        }
+        let span = start..i;
+        start = i + 1;
+        let word: String = target[span.clone()]
+            .iter()
+            .collect::<String>()
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        if word.chars().count() > 1 && span.clone().all(|p| reached[p]) {
+            observe(ReviewKind::Word, word, span.into_iter().any(|p| missed[p]));
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|((kind, item), (attempts, errors))| Observation {
+            kind,
+            item,
+            attempts,
+            errors,
+        })
+        .collect()
+}
+
+/// Apply one session's observation to an item with the SM-2 rules. Items start being
+/// tracked on their first mistake; clean practice before an item is due leaves it alone.
+pub fn review(
+    existing: Option<&ReviewItem>,
+    observation: &Observation,
+    now: i64,
+) -> Option<ReviewItem> {
+    let grade = observation.grade();
+    let mut item = match existing {
+        Some(item) => item.clone(),
+        None if grade == 5 => return None,
+        None => ReviewItem {
+            kind: observation.kind,
+            item: observation.item.clone(),
+            ease: INITIAL_EASE,
+            interval_days: 0.0,
+            repetitions: 0,
+            lapses: 0,
+            due_at: now,
+            last_reviewed_at: now,
+        },
+    };
+    if existing.is_some() && grade >= PASSING_GRADE && now < item.due_at {
+        return None;
+    }
+
+    let q = f64::from(grade);
+    item.ease = (item.ease + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(MIN_EASE);
+    if grade < PASSING_GRADE {
+        if existing.is_some() {
+            item.lapses += 1;
+        }
+        item.repetitions = 0;
+        item.interval_days = 0.0;
+    } else {
+        item.repetitions += 1;
+        item.interval_days = match item.repetitions {
+            1 => 1.0,
+            2 => 6.0,
+            _ => (item.interval_days * item.ease).round(),
+        };
+    }
+    item.due_at = now + (item.interval_days * DAY_MS as f64) as i64;
+    item.last_reviewed_at = now;
+    Some(item)
+}
+
+/// The items a session changes, given the user's current ones
+pub fn schedule(
+    existing: &[ReviewItem],
+    observations: &[Observation],
+    now: i64,
+) -> Vec<ReviewItem> {
+    let by_key: HashMap<(ReviewKind, &str), &ReviewItem> = existing
+        .iter()
+        .map(|item| ((item.kind, item.item.as_str()), item))
+        .collect();
+    observations
+        .iter()
+        .filter_map(|o| review(by_key.get(&(o.kind, o.item.as_str())).copied(), o, now))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keystroke::KeystrokeRecorder;
+
+    fn observation(attempts: usize, errors: usize) -> Observation {
+        Observation {
+            kind: ReviewKind::Char,
+            item: "q".to_string(),
+            attempts,
+            errors,
+        }
+    }
+
+    #[test]
+    fn test_observations_from_keystrokes() {
+        let mut recorder = KeystrokeRecorder::new("the cat, the");
+        for (i, ch) in "the cxt, th".chars().enumerate() {
+            recorder.key_down(ch, i as i64);
+        }
+        let found = observations("the cat, the", recorder.events());
+        let get = |kind, item: &str| {
+            found
+                .iter()
+                .find(|o| o.kind == kind && o.item == item)
+                .map(|o| (o.attempts, o.errors))
+        };
+        assert_eq!(get(ReviewKind::Char, "a"), Some((1, 1)));
+        assert_eq!(get(ReviewKind::Char, "t"), Some((3, 0)));
+        assert_eq!(get(ReviewKind::Bigram, "ca"), Some((1, 1)));
+        assert_eq!(get(ReviewKind::Bigram, "at"), Some((1, 0)));
+        assert_eq!(get(ReviewKind::Bigram, "th"), Some((2, 0)));
+        // Punctuation is trimmed off words, and the unfinished last word isn't counted
+        assert_eq!(get(ReviewKind::Word, "cat"), Some((1, 1)));
+        assert_eq!(get(ReviewKind::Word, "the"), Some((1, 0)));
+        assert_eq!(get(ReviewKind::Char, "e"), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_sm2_intervals_and_lapses() {
+        assert_eq!(review(None, &observation(10, 0), 0), None);
+        let item = review(None, &observation(10, 4), 0).unwrap();
+        assert_eq!((item.repetitions, item.lapses, item.due_at), (0, 0, 0));
+        assert!(item.ease < INITIAL_EASE);
+
+        let item = review(Some(&item), &observation(10, 0), 0).unwrap();
+        assert_eq!((item.interval_days, item.due_at), (1.0, DAY_MS));
+        // Clean practice before it's due doesn't move it along
+        assert_eq!(review(Some(&item), &observation(10, 0), 1000), None);
+        let item = review(Some(&item), &observation(10, 0), DAY_MS).unwrap();
+        assert_eq!(item.interval_days, 6.0);
+        let ease = item.ease;
+        let item = review(Some(&item), &observation(10, 0), 7 * DAY_MS).unwrap();
+        assert_eq!(item.interval_days, (6.0 * (ease + 0.1)).round());
+
+        // A mistake any time starts it over
+        let lapsed = review(Some(&item), &observation(4, 2), 8 * DAY_MS).unwrap();
+        assert_eq!((lapsed.repetitions, lapsed.lapses), (0, 1));
+        assert_eq!(lapsed.due_at, 8 * DAY_MS);
+        assert!(lapsed.ease >= MIN_EASE);
+    }
+}
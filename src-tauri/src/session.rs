@@ -0,0 +1,526 @@
+use crate::keystroke::{KeystrokeEvent, KeystrokeRecorder};
+use crate::layouts::{Finger, KeyboardLayout, LayoutId};
+use crate::metrics::{FingerAggregate, MetricsCalculator, PassPolicy, TaskResult};
+use crate::models::SessionRow;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+
+/// Event emitted to the window after every keystroke
+pub const LIVE_STATS_EVENT: &str = "typing-session-stats";
+/// Event emitted once a session is finished and scored
+pub const FINISHED_EVENT: &str = "typing-session-finished";
+/// Ready or running sessions without a keystroke for this long are dropped
+const IDLE_TIMEOUT_MS: i64 = 30 * 60 * 1000;
+/// Finished and aborted sessions stay readable this long before they are dropped
+const CLOSED_RETENTION_MS: i64 = 5 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    /// Started, waiting for the first keystroke; the clock isn't running yet
+    Ready,
+    Running,
+    Finished,
+    Aborted,
+}
+
+#[derive(Error, Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "code", content = "message")]
+pub enum SessionError {
+    #[error("Session not found: {0}")]
+    NotFound(String),
+    #[error("Invalid transition: {0}")]
+    InvalidTransition(String),
+}
+
+/// A single keystroke sent from the frontend; timing is stamped on the Rust side
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionInput {
+    Char { key: char },
+    Backspace,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveStats {
+    pub session_id: String,
+    pub status: SessionStatus,
+    pub elapsed_ms: i64,
+    pub wpm: f32,
+    pub accuracy: f32, // correct keystrokes / character keystrokes so far
+    pub cursor: usize,
+    pub target_length: usize,
+    pub uncorrected_errors: usize,
+    pub complete: bool, // typed text matches the target exactly
+}
+
+/// A scored session, ready to be stored
+#[derive(Debug, Clone)]
+pub struct FinishedSession {
+    pub session_id: String,
+    pub user_id: i64,
+    pub task_id: String,
+    /// The lesson the task belongs to. Scoring can't tell, so the caller files it.
+    pub lesson_id: Option<String>,
+    pub lesson_tasks: Option<i64>, // task count of a generated lesson, which storage can't look up
+    pub target_text: String,
+    pub layout: Option<String>, // layout the session started on
+    pub started_at: i64,
+    pub result: TaskResult,
+    pub events: Vec<KeystrokeEvent>,
+}
+
+impl FinishedSession {
+    /// The history row stored for this attempt
+    pub fn to_session_row(&self) -> SessionRow {
+        SessionRow {
+            id: self.session_id.clone(),
+            user_id: self.user_id,
+            lesson_id: self.lesson_id.clone(),
+            task_id: self.task_id.clone(),
+            layout: self.layout.clone(),
+            started_at: self.started_at,
+            completed_at: self.result.completed_at,
+            wpm: self.result.wpm as f64,
+            accuracy: self.result.accuracy as f64,
+            duration: self.result.duration,
+            total_keystrokes: self.result.total_keystrokes as i64,
+            backspaces: self.result.backspace_count as i64,
+            error_count: self.result.errors.len() as i64,
+            passed: self.result.passed,
+            result_json: serde_json::to_string(&self.result).unwrap_or_default(),
+        }
+    }
+
+    /// Per-finger totals, each keystroke placed on the layout it was typed on.
+    /// Keystrokes on layouts without built-in geometry are left out.
+    pub fn finger_aggregates(&self) -> BTreeMap<Finger, FingerAggregate> {
+        let mut fingers: BTreeMap<Finger, FingerAggregate> = BTreeMap::new();
+        for run in self.events.chunk_by(|a, b| a.layout == b.layout) {
+            let Some(layout) = run[0].layout.as_deref().and_then(LayoutId::parse) else {
+                continue;
+            };
+            let layout = KeyboardLayout::builtin(layout);
+            for (finger, agg) in MetricsCalculator::finger_aggregates(run, &layout) {
+                fingers.entry(finger).or_default().merge(&agg);
+            }
+        }
+        fingers
+    }
+}
+
+pub struct TypingSession {
+    pub id: String,
+    pub user_id: i64,
+    pub task_id: String,
+    pub layout: Option<String>,
+    status: SessionStatus,
+    target: Vec<char>,
+    target_text: String,
+    policy: PassPolicy,
+    recorder: KeystrokeRecorder,
+    first_key_at: Option<i64>,
+    last_active: i64,
+    closed_at: Option<i64>,
+}
+
+impl TypingSession {
+    fn live_stats(&self, now: i64) -> LiveStats {
+        // A closed session's clock stopped when it was closed
+        let now = self.closed_at.unwrap_or(now);
+        let typed: Vec<char> = self.recorder.typed_text().chars().collect();
+        let correct_positions = typed
+            .iter()
+            .zip(&self.target)
+            .filter(|(t, e)| t == e)
+            .count();
+        let uncorrected_errors = typed.len() - correct_positions;
+
+        let char_keystrokes = self.recorder.events().iter().filter(|e| e.key.is_some());
+        let (typed_keys, wrong_keys) = char_keystrokes.fold((0, 0), |(n, wrong), e| {
+            (n + 1, wrong + e.is_error() as usize)
+        });
+
+        let elapsed_ms = self.first_key_at.map(|t| now - t).unwrap_or(0);
+        let minutes = elapsed_ms as f32 / 60000.0;
+        let wpm = if minutes > 0.0 {
+            ((typed.len() as f32 / 5.0 - uncorrected_errors as f32) / minutes).max(0.0)
+        } else {
+            0.0
+        };
+        let accuracy = if typed_keys > 0 {
+            (typed_keys - wrong_keys) as f32 / typed_keys as f32
+        } else {
+            1.0
+        };
+
+        LiveStats {
+            session_id: self.id.clone(),
+            status: self.status,
+            elapsed_ms,
+            wpm: (wpm * 10.0).round() / 10.0,
+            accuracy: (accuracy * 1000.0).round() / 1000.0,
+            cursor: typed.len(),
+            target_length: self.target.len(),
+            uncorrected_errors,
+            complete: typed == self.target,
+        }
+    }
+}
+
+/// Owns every live typing session. All state changes go through here so the
+/// frontend can only send keys, never scores.
+#[derive(Default)]
+pub struct SessionManager {
+    sessions: HashMap<String, TypingSession>,
+    next_id: u64,
+}
+
+impl SessionManager {
+    pub fn start(
+        &mut self,
+        user_id: i64,
+        task_id: &str,
+        target_text: &str,
+        layout: Option<String>,
+        policy: PassPolicy,
+        now: i64,
+    ) -> LiveStats {
+        self.prune(now);
+        self.next_id += 1;
+        let id = format!("session-{}-{}", now, self.next_id);
+        let mut recorder = KeystrokeRecorder::new(target_text);
+        recorder.set_layout(layout.clone());
+        let session = TypingSession {
+            id: id.clone(),
+            user_id,
+            task_id: task_id.to_string(),
+            layout,
+            status: SessionStatus::Ready,
+            target: target_text.chars().collect(),
+            target_text: target_text.to_string(),
+            policy,
+            recorder,
+            first_key_at: None,
+            last_active: now,
+            closed_at: None,
+        };
+        let stats = session.live_stats(now);
+        self.sessions.insert(id, session);
+        stats
+    }
+
+    /// Current stats without recording anything, e.g. after the view reloads.
+    /// Finished and aborted sessions report their final stats until they are pruned.
+    pub fn stats(&self, session_id: &str, now: i64) -> Result<LiveStats, SessionError> {
+        self.sessions
+            .get(session_id)
+            .map(|session| session.live_stats(now))
+            .ok_or_else(|| SessionError::NotFound(session_id.to_string()))
+    }
+
+    pub fn keystroke(
+        &mut self,
+        session_id: &str,
+        input: SessionInput,
+        now: i64,
+    ) -> Result<LiveStats, SessionError> {
+        let session = self.active_mut(session_id, "keystroke")?;
+        if session.status == SessionStatus::Ready {
+            session.status = SessionStatus::Running;
+            session.first_key_at = Some(now);
+        }
+        session.last_active = now;
+        match input {
+            SessionInput::Char { key } => {
+                session.recorder.key_down(key, now);
+            }
+            SessionInput::Backspace => {
+                session.recorder.backspace(now);
+            }
+        }
+        Ok(session.live_stats(now))
+    }
+
+    /// The task a live session is for
+    pub fn task_id(&self, session_id: &str) -> Result<&str, SessionError> {
+        self.sessions
+            .get(session_id)
+            .map(|session| session.task_id.as_str())
+            .ok_or_else(|| SessionError::NotFound(session_id.to_string()))
+    }
+
+    /// Whether the manager owns this session, live or recently closed
+    pub fn contains(&self, session_id: &str) -> bool {
+        self.sessions.contains_key(session_id)
+    }
+
+    /// Score a running session without closing it, so it survives if storing the result fails.
+    /// Call `close_finished` once the result is stored.
+    pub fn score(&self, session_id: &str) -> Result<FinishedSession, SessionError> {
+        let session = self
+            .sessions
+            .get(session_id)
+            .ok_or_else(|| SessionError::NotFound(session_id.to_string()))?;
+        if session.status != SessionStatus::Running {
+            return Err(SessionError::InvalidTransition(match session.status {
+                SessionStatus::Ready => format!(
+                    "cannot finish session {} before the first keystroke",
+                    session_id
+                ),
+                status => format!("cannot finish a session that is {:?}", status),
+            }));
+        }
+        let mut result = MetricsCalculator::calculate_result_from_keystrokes(
+            session.task_id.clone(),
+            &session.target_text,
+            session.recorder.events(),
+        );
+        result.apply_policy(&session.policy);
+
+        Ok(FinishedSession {
+            session_id: session.id.clone(),
+            user_id: session.user_id,
+            task_id: session.task_id.clone(),
+            lesson_id: None,
+            lesson_tasks: None,
+            target_text: session.target_text.clone(),
+            layout: session.layout.clone(),
+            started_at: session.first_key_at.unwrap_or(result.completed_at),
+            result,
+            events: session.recorder.events().to_vec(),
+        })
+    }
+
+    /// Mark a scored session as finished; it no longer accepts keystrokes
+    pub fn close_finished(&mut self, session_id: &str, now: i64) -> Result<(), SessionError> {
+        self.close(session_id, "finish", SessionStatus::Finished, now)
+    }
+
+    /// The OS layout changed: keystrokes in every live session are now typed on `layout`
+    pub fn switch_layout(&mut self, layout: Option<LayoutId>) {
+        for session in self.sessions.values_mut() {
+            session
+                .recorder
+                .set_layout(layout.map(|l| l.as_str().to_string()));
+        }
+    }
+
+    pub fn abort(&mut self, session_id: &str, now: i64) -> Result<(), SessionError> {
+        self.close(session_id, "abort", SessionStatus::Aborted, now)
+    }
+
+    fn close(
+        &mut self,
+        session_id: &str,
+        action: &str,
+        status: SessionStatus,
+        now: i64,
+    ) -> Result<(), SessionError> {
+        let session = self.active_mut(session_id, action)?;
+        session.status = status;
+        session.closed_at = Some(now);
+        Ok(())
+    }
+
+    /// Drop idle sessions and closed ones past their retention
+    fn prune(&mut self, now: i64) {
+        self.sessions.retain(|_, session| match session.closed_at {
+            Some(closed_at) => now - closed_at < CLOSED_RETENTION_MS,
+            None => now - session.last_active < IDLE_TIMEOUT_MS,
+        });
+    }
+
+    fn active_mut(
+        &mut self,
+        session_id: &str,
+        action: &str,
+    ) -> Result<&mut TypingSession, SessionError> {
+        let session = self
+            .sessions
+            .get_mut(session_id)
+            .ok_or_else(|| SessionError::NotFound(session_id.to_string()))?;
+        match session.status {
+            SessionStatus::Ready | SessionStatus::Running => Ok(session),
+            status => Err(SessionError::InvalidTransition(format!(
+                "cannot {} a session that is {:?}",
+                action, status
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_text(manager: &mut SessionManager, id: &str, text: &str, start: i64) -> LiveStats {
+        let mut last = None;
+        for (i, key) in text.chars().enumerate() {
+            last = Some(
+                manager
+                    .keystroke(id, SessionInput::Char { key }, start + i as i64 * 200)
+                    .unwrap(),
+            );
+        }
+        last.unwrap()
+    }
+
+    #[test]
+    fn test_session_lifecycle() {
+        let mut manager = SessionManager::default();
+        let started = manager.start(
+            1,
+            "hr-1",
+            "asdf",
+            Some("qwerty-us".to_string()),
+            PassPolicy::default(),
+            0,
+        );
+        let id = started.session_id.clone();
+        assert_eq!(started.status, SessionStatus::Ready);
+        assert!(manager.score(&id).is_err());
+
+        // The clock starts at the first keystroke, not at start()
+        let stats = type_text(&mut manager, &id, "asdx", 10_000);
+        assert_eq!(stats.status, SessionStatus::Running);
+        assert_eq!(stats.elapsed_ms, 600);
+        assert_eq!(stats.uncorrected_errors, 1);
+        assert_eq!(stats.accuracy, 0.75);
+        assert!(!stats.complete);
+
+        manager
+            .keystroke(&id, SessionInput::Backspace, 10_800)
+            .unwrap();
+        let stats = manager
+            .keystroke(&id, SessionInput::Char { key: 'f' }, 11_000)
+            .unwrap();
+        assert!(stats.complete);
+        assert_eq!(stats.uncorrected_errors, 0);
+
+        let finished = manager.score(&id).unwrap();
+        assert_eq!(finished.events.len(), 6);
+        assert_eq!(finished.result.duration, 1_000);
+        assert_eq!(finished.result.error_breakdown.corrected, 1);
+        assert!(finished.result.passed);
+
+        assert_eq!(finished.lesson_id, None);
+        let row = finished.to_session_row();
+        assert_eq!(row.layout.as_deref(), Some("qwerty-us"));
+        assert_eq!(row.started_at, 10_000);
+        assert_eq!(row.backspaces, 1);
+
+        // Scoring alone leaves the session open, so a failed store can be retried
+        assert_eq!(manager.score(&id).unwrap().events.len(), 6);
+        manager.close_finished(&id, 11_500).unwrap();
+        let closed = manager.stats(&id, 20_000).unwrap();
+        assert_eq!(closed.status, SessionStatus::Finished);
+        assert_eq!(closed.elapsed_ms, 1_500);
+        assert!(manager.score(&id).is_err());
+        assert!(manager
+            .keystroke(&id, SessionInput::Backspace, 20_000)
+            .is_err());
+    }
+
+    #[test]
+    fn test_finish_applies_policy_and_abort_discards() {
+        let mut manager = SessionManager::default();
+        let strict = PassPolicy {
+            min_accuracy: 1.0,
+            ..PassPolicy::default()
+        };
+        let id = manager.start(1, "t", "abc", None, strict, 0).session_id;
+        type_text(&mut manager, &id, "abx", 0);
+        let finished = manager.score(&id).unwrap();
+        assert!(!finished.result.passed);
+        assert_eq!(finished.result.failed_criteria.len(), 1);
+
+        let id = manager
+            .start(1, "t", "abc", None, PassPolicy::default(), 0)
+            .session_id;
+        manager.abort(&id, 1).unwrap();
+        assert_eq!(
+            manager.stats(&id, 2).unwrap().status,
+            SessionStatus::Aborted
+        );
+        assert!(matches!(
+            manager.keystroke(&id, SessionInput::Backspace, 2),
+            Err(SessionError::InvalidTransition(_))
+        ));
+    }
+
+    #[test]
+    fn test_idle_and_closed_sessions_are_pruned() {
+        let mut manager = SessionManager::default();
+        let idle = manager
+            .start(1, "t", "abc", None, PassPolicy::default(), 0)
+            .session_id;
+        let typing = manager
+            .start(1, "t", "abc", None, PassPolicy::default(), 0)
+            .session_id;
+        let aborted = manager
+            .start(1, "t", "abc", None, PassPolicy::default(), 0)
+            .session_id;
+        manager.abort(&aborted, 0).unwrap();
+        type_text(&mut manager, &typing, "a", IDLE_TIMEOUT_MS - 1);
+
+        manager.start(1, "t", "abc", None, PassPolicy::default(), IDLE_TIMEOUT_MS);
+        assert!(!manager.contains(&idle));
+        assert!(!manager.contains(&aborted));
+        assert!(manager.contains(&typing));
+    }
+
+    #[test]
+    fn test_input_deserializes_from_frontend_shape() {
+        let key: SessionInput = serde_json::from_str(r#"{"type":"char","key":"ü"}"#).unwrap();
+        assert_eq!(key, SessionInput::Char { key: 'ü' });
+        let back: SessionInput = serde_json::from_str(r#"{"type":"backspace"}"#).unwrap();
+        assert_eq!(back, SessionInput::Backspace);
+    }
+
+    #[test]
+    fn test_keystrokes_carry_the_layout_they_were_typed_on() {
+        let mut manager = SessionManager::default();
+        let id = manager
+            .start(
+                1,
+                "t",
+                "zz",
+                Some("qwerty-us".to_string()),
+                PassPolicy::default(),
+                0,
+            )
+            .session_id;
+        manager
+            .keystroke(&id, SessionInput::Char { key: 'z' }, 100)
+            .unwrap();
+        manager.switch_layout(Some(LayoutId::QwertyDe));
+        manager
+            .keystroke(&id, SessionInput::Char { key: 'z' }, 300)
+            .unwrap();
+        manager.switch_layout(None);
+        manager
+            .keystroke(&id, SessionInput::Char { key: 'x' }, 500)
+            .unwrap();
+
+        let finished = manager.score(&id).unwrap();
+        let layouts: Vec<_> = finished
+            .events
+            .iter()
+            .map(|e| e.layout.as_deref())
+            .collect();
+        assert_eq!(layouts, vec![Some("qwerty-us"), Some("qwerty-de"), None]);
+        assert_eq!(
+            finished.to_session_row().layout.as_deref(),
+            Some("qwerty-us")
+        );
+
+        // 'z' sits under the left pinky on QWERTY and the right index on QWERTZ
+        let fingers = finished.finger_aggregates();
+        assert_eq!(fingers[&Finger::LeftPinky].keystrokes, 1);
+        assert_eq!(fingers[&Finger::RightIndex].keystrokes, 1);
+        assert_eq!(fingers.len(), 2);
+    }
+}
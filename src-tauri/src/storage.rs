@@ -1,8 +1,20 @@
+use crate::backups::{self, BackupKind};
+use crate::courses::CourseProgress;
+use crate::export::UserExport;
+use crate::history;
+use crate::keystroke::{KeystrokeEvent, KeystrokeKind};
+use crate::layouts::Finger;
+use crate::metrics::{FingerAggregate, FingerReport, LatencyAggregate, LatencyReport, LatencySamples, MetricsCalculator};
 use crate::models::*;
-use rusqlite::{params, Connection, Result as SqliteResult};
+use crate::problem_keys::{self, KeyErrorStats, KeyScore, ProblemKey};
+use crate::review::{self, ReviewItem, ReviewKind};
+use crate::session::FinishedSession;
+use rusqlite::backup::Backup;
+use rusqlite::{params, Connection, OpenFlags, Result as SqliteResult};
 use serde::{Serialize, Deserialize};
-use serde_json;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug, Serialize, Deserialize, Clone)]
@@ -18,6 +30,8 @@ pub enum StorageError {
     NotFound(String),
     #[error("Conflict: {0}")]
     Conflict(String),
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
 }
 
 impl From<rusqlite::Error> for StorageError {
@@ -50,6 +64,19 @@ impl From<serde_json::Error> for StorageError {
     }
 }
 
+/// The version `migrate` brings a database to
+pub const SCHEMA_VERSION: i64 = 14;
+
+// Pages copied per backup step; the source stays readable between steps
+const BACKUP_PAGES_PER_STEP: std::os::raw::c_int = 256;
+
+/// Directory holding the database and other per-install app data
+pub fn app_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("exceptional-typing")
+}
+
 pub struct Database {
     conn: Connection,
 }
@@ -63,6 +90,15 @@ impl Database {
         let conn = Connection::open(&db_path)?;
         conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")?;
         let db = Database { conn };
+        let version = db.get_schema_version();
+        if version > 0 && version < SCHEMA_VERSION {
+            // A migration that goes wrong must not cost any progress. A backup that fails
+            // must not keep the app from starting either, and migrating is still one transaction
+            // per step, so it is reported and the migration goes ahead.
+            if let Err(e) = backups::create(&db, &backups::backups_dir(), BackupKind::PreMigration, chrono::Utc::now()) {
+                eprintln!("Pre-migration backup failed, migrating without one: {}", e);
+            }
+        }
         db.migrate()?;
         Ok(db)
     }
@@ -82,16 +118,69 @@ impl Database {
         Ok(())
     }
 
+    /// Copy the whole database to `path` with SQLite's online backup API. The copy is
+    /// written next to `path` first, so a failed backup never leaves a partial file.
+    pub fn backup_to(&self, path: &Path) -> Result<(), StorageError> {
+        let partial = path.with_extension("partial");
+        {
+            let mut dst = Connection::open(&partial)?;
+            let backup = Backup::new(&self.conn, &mut dst)?;
+            backup.run_to_completion(BACKUP_PAGES_PER_STEP, Duration::from_millis(5), None)?;
+        }
+        std::fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    /// Problems `PRAGMA integrity_check` finds in the database file at `path`, which is only
+    /// read, and the file's schema version
+    pub fn check_file(path: &Path) -> Result<(Vec<String>, i64), StorageError> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let problems = {
+            let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+            rows.collect::<SqliteResult<Vec<String>>>()?
+        };
+        let db = Database { conn };
+        Ok((problems.into_iter().filter(|p| p != "ok").collect(), db.get_schema_version()))
+    }
+
+    /// Overwrite this database with the one at `path` and migrate it to the current schema
+    pub fn restore_from(&mut self, path: &Path) -> Result<(), StorageError> {
+        let src = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        Backup::new(&src, &mut self.conn)?.run_to_completion(BACKUP_PAGES_PER_STEP, Duration::from_millis(5), None)?;
+        self.migrate()?;
+        Ok(())
+    }
+
+    /// An in-memory copy of the database file at `path`, which is only read. Returns the copy
+    /// migrated to the current schema, and the schema version the file had.
+    pub fn open_copy(path: &Path) -> Result<(Self, i64), StorageError> {
+        let src = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut conn = Connection::open_in_memory()?;
+        Backup::new(&src, &mut conn)?.run_to_completion(BACKUP_PAGES_PER_STEP, Duration::ZERO, None)?;
+        conn.execute_batch("PRAGMA foreign_keys=ON;")?;
+
+        let db = Database { conn };
+        let version = db.get_schema_version();
+        if version == 0 {
+            return Err(StorageError::Unsupported(format!("{} is not a backup of this app", path.display())));
+        }
+        if version > SCHEMA_VERSION {
+            return Err(StorageError::Unsupported(format!(
+                "{} has schema version {}, newer than this app's {}", path.display(), version, SCHEMA_VERSION
+            )));
+        }
+        db.migrate()?;
+        Ok((db, version))
+    }
+
     fn get_db_path() -> Result<PathBuf, StorageError> {
-        let data_dir = dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("exceptional-typing");
-        Ok(data_dir.join("data.db"))
+        Ok(app_data_dir().join("data.db"))
     }
 
     // ── Schema Migration ──────────────────────────────────────────
 
-    fn get_schema_version(&self) -> i64 {
+    pub fn get_schema_version(&self) -> i64 {
         self.conn
             .query_row(
                 "SELECT version FROM schema_version ORDER BY version DESC LIMIT 1",
@@ -118,6 +207,42 @@ impl Database {
         if version < 2 {
             self.migrate_to_v2()?;
         }
+        if version < 3 {
+            self.migrate_to_v3()?;
+        }
+        if version < 4 {
+            self.migrate_to_v4()?;
+        }
+        if version < 5 {
+            self.migrate_to_v5()?;
+        }
+        if version < 6 {
+            self.migrate_to_v6()?;
+        }
+        if version < 7 {
+            self.migrate_to_v7()?;
+        }
+        if version < 8 {
+            self.migrate_to_v8()?;
+        }
+        if version < 9 {
+            self.migrate_to_v9()?;
+        }
+        if version < 10 {
+            self.migrate_to_v10()?;
+        }
+        if version < 11 {
+            self.migrate_to_v11()?;
+        }
+        if version < 12 {
+            self.migrate_to_v12()?;
+        }
+        if version < 13 {
+            self.migrate_to_v13()?;
+        }
+        if version < 14 {
+            self.migrate_to_v14()?;
+        }
 
         Ok(())
     }
@@ -237,6 +362,357 @@ impl Database {
         )
     }
 
+    fn migrate_to_v3(&self) -> SqliteResult<()> {
+        self.conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS keystroke_events (
+                session_id TEXT NOT NULL,
+                seq INTEGER NOT NULL,
+                user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                task_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                key_char TEXT,
+                expected_char TEXT,
+                position INTEGER NOT NULL,
+                timestamp INTEGER NOT NULL,
+                PRIMARY KEY (session_id, seq)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_keystroke_events_user ON keystroke_events(user_id);
+
+            INSERT INTO schema_version (version) VALUES (3);
+            "
+        )
+    }
+
+    fn migrate_to_v4(&self) -> SqliteResult<()> {
+        self.conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS key_latency (
+                user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                ngram TEXT NOT NULL,
+                sample_count INTEGER NOT NULL DEFAULT 0,
+                mean_ms REAL NOT NULL DEFAULT 0,
+                m2 REAL NOT NULL DEFAULT 0,
+                histogram_json TEXT NOT NULL DEFAULT '[]',
+                PRIMARY KEY (user_id, ngram)
+            );
+
+            INSERT INTO schema_version (version) VALUES (4);
+            "
+        )
+    }
+
+    fn migrate_to_v5(&self) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS sessions (
+                id TEXT PRIMARY KEY,
+                user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                lesson_id TEXT,
+                task_id TEXT NOT NULL,
+                layout TEXT,
+                started_at INTEGER NOT NULL,
+                completed_at INTEGER NOT NULL,
+                wpm REAL NOT NULL,
+                accuracy REAL NOT NULL,
+                duration INTEGER NOT NULL,
+                total_keystrokes INTEGER NOT NULL DEFAULT 0,
+                backspaces INTEGER NOT NULL DEFAULT 0,
+                error_count INTEGER NOT NULL DEFAULT 0,
+                passed INTEGER NOT NULL,
+                result_json TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_sessions_user ON sessions(user_id, completed_at);
+
+            -- History is append-only; rows only go away with their user
+            CREATE TRIGGER IF NOT EXISTS sessions_append_only
+            BEFORE UPDATE ON sessions
+            BEGIN
+                SELECT RAISE(ABORT, 'sessions are append-only');
+            END;
+
+            INSERT INTO schema_version (version) VALUES (5);
+            "
+        )?;
+        Self::backfill_sessions(&tx, None)?;
+        tx.commit()
+    }
+
+    fn migrate_to_v6(&self) -> SqliteResult<()> {
+        self.conn.execute_batch(
+            "
+            ALTER TABLE lesson_progress ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE course_progress ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE custom_snippets ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE daily_test_results ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE daily_activity ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+
+            INSERT INTO schema_version (version) VALUES (6);
+            "
+        )
+    }
+
+    fn migrate_to_v7(&self) -> SqliteResult<()> {
+        self.conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS finger_stats (
+                user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                finger TEXT NOT NULL,
+                keystrokes INTEGER NOT NULL DEFAULT 0,
+                errors INTEGER NOT NULL DEFAULT 0,
+                sample_count INTEGER NOT NULL DEFAULT 0,
+                mean_ms REAL NOT NULL DEFAULT 0,
+                m2 REAL NOT NULL DEFAULT 0,
+                histogram_json TEXT NOT NULL DEFAULT '[]',
+                PRIMARY KEY (user_id, finger)
+            );
+
+            INSERT INTO schema_version (version) VALUES (7);
+            "
+        )
+    }
+
+    fn migrate_to_v8(&self) -> SqliteResult<()> {
+        self.conn.execute_batch(
+            "
+            ALTER TABLE keystroke_events ADD COLUMN layout TEXT;
+
+            INSERT INTO schema_version (version) VALUES (8);
+            "
+        )
+    }
+
+    fn migrate_to_v9(&self) -> SqliteResult<()> {
+        self.conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS custom_layouts (
+                user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                format TEXT NOT NULL,
+                layout_json TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (user_id, id)
+            );
+
+            INSERT INTO schema_version (version) VALUES (9);
+            "
+        )
+    }
+
+    /// Move the stage lists of course_progress out of JSON columns into one row per stage
+    fn migrate_to_v10(&self) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS course_stage_progress (
+                user_id INTEGER NOT NULL,
+                course_id TEXT NOT NULL,
+                stage_id TEXT NOT NULL,
+                status TEXT NOT NULL CHECK (status IN ('completed', 'skipped')),
+                position INTEGER NOT NULL,
+                PRIMARY KEY (user_id, course_id, stage_id),
+                FOREIGN KEY (user_id, course_id)
+                    REFERENCES course_progress(user_id, course_id) ON DELETE CASCADE
+            );
+
+            INSERT OR IGNORE INTO course_stage_progress (user_id, course_id, stage_id, status, position)
+            SELECT p.user_id, p.course_id, s.value, 'completed', s.key
+            FROM course_progress p,
+                 json_each(CASE WHEN json_valid(p.completed_stages_json) THEN p.completed_stages_json ELSE '[]' END) s
+            WHERE s.type = 'text';
+
+            INSERT OR IGNORE INTO course_stage_progress (user_id, course_id, stage_id, status, position)
+            SELECT p.user_id, p.course_id, s.value, 'skipped', s.key
+            FROM course_progress p,
+                 json_each(CASE WHEN json_valid(p.skipped_stages_json) THEN p.skipped_stages_json ELSE '[]' END) s
+            WHERE s.type = 'text';
+
+            ALTER TABLE course_progress DROP COLUMN completed_stages_json;
+            ALTER TABLE course_progress DROP COLUMN skipped_stages_json;
+
+            INSERT INTO schema_version (version) VALUES (10);
+            "
+        )?;
+        tx.commit()
+    }
+
+    fn migrate_to_v11(&self) -> SqliteResult<()> {
+        self.conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS review_items (
+                user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                kind TEXT NOT NULL,
+                item TEXT NOT NULL,
+                ease REAL NOT NULL,
+                interval_days REAL NOT NULL,
+                repetitions INTEGER NOT NULL DEFAULT 0,
+                lapses INTEGER NOT NULL DEFAULT 0,
+                due_at INTEGER NOT NULL,
+                last_reviewed_at INTEGER NOT NULL,
+                PRIMARY KEY (user_id, kind, item)
+            );
+            CREATE INDEX IF NOT EXISTS idx_review_items_due ON review_items(user_id, due_at);
+
+            INSERT INTO schema_version (version) VALUES (11);
+            "
+        )
+    }
+
+    /// Replace the raw error counts in problem_keys with decayed attempts and errors per
+    /// key, rebuilt from the recorded keystrokes. The raw counts have no attempts, so they
+    /// are kept apart in counted_errors, where the frontend's counts are saved from now on.
+    fn migrate_to_v12(&self) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS key_error_stats (
+                user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                key_char TEXT NOT NULL,
+                attempts REAL NOT NULL,
+                errors REAL NOT NULL,
+                updated_at INTEGER NOT NULL,
+                counted_errors INTEGER NOT NULL DEFAULT 0, -- the frontend's own count, not a sample
+                PRIMARY KEY (user_id, key_char)
+            );
+            "
+        )?;
+        Self::backfill_key_error_stats(&tx)?;
+        tx.execute_batch(
+            "
+            INSERT INTO key_error_stats (user_id, key_char, attempts, errors, updated_at, counted_errors)
+            SELECT user_id, key_char, 0, 0, 0, error_count FROM problem_keys WHERE error_count > 0
+            ON CONFLICT(user_id, key_char) DO UPDATE SET counted_errors = excluded.counted_errors;
+
+            DROP TABLE problem_keys;
+
+            INSERT INTO schema_version (version) VALUES (12);
+            "
+        )?;
+        tx.commit()
+    }
+
+    /// Record which sessions have been merged into which rolling aggregate, so merging a
+    /// session again is a no-op
+    fn migrate_to_v13(&self) -> SqliteResult<()> {
+        self.conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS merged_sessions (
+                session_id TEXT NOT NULL,
+                aggregate TEXT NOT NULL,
+                user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                PRIMARY KEY (session_id, aggregate)
+            );
+
+            INSERT INTO schema_version (version) VALUES (13);
+            "
+        )
+    }
+
+    /// Stored user_stats become running totals: the sessions they already include are
+    /// marked as counted, and only later sessions are added on top
+    fn migrate_to_v14(&self) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        Self::count_sessions_in_stats(&tx, None)?;
+        tx.execute("INSERT INTO schema_version (version) VALUES (14)", [])?;
+        tx.commit()
+    }
+
+    /// Mark every session of users that have stats as already counted in them
+    fn count_sessions_in_stats(conn: &Connection, user_id: Option<i64>) -> SqliteResult<()> {
+        conn.execute(
+            "INSERT OR IGNORE INTO merged_sessions (session_id, aggregate, user_id)
+             SELECT id, 'user_stats', user_id FROM sessions
+             WHERE user_id IN (SELECT user_id FROM user_stats) AND (?1 IS NULL OR user_id = ?1)",
+            params![user_id],
+        )?;
+        Ok(())
+    }
+
+    /// Turn task results kept in lesson_progress into session rows, so history that
+    /// predates the sessions table, or that the frontend saved itself, survives the next
+    /// rebuild of the aggregates. `None` covers every user.
+    fn backfill_sessions(conn: &Connection, user_id: Option<i64>) -> SqliteResult<()> {
+        let mut select = conn.prepare(
+            "SELECT user_id, lesson_id, task_results_json FROM lesson_progress
+             WHERE ?1 IS NULL OR user_id = ?1"
+        )?;
+        let progress = select
+            .query_map(params![user_id], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut insert = conn.prepare(
+            "INSERT OR IGNORE INTO sessions (id, user_id, lesson_id, task_id, layout, started_at,
+                completed_at, wpm, accuracy, duration, total_keystrokes, backspaces, error_count,
+                passed, result_json)
+             VALUES (?1, ?2, ?3, ?4, NULL, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)"
+        )?;
+        for (user_id, lesson_id, results_json) in progress {
+            // Legacy results were written by the frontend; skip anything unreadable
+            let results: Vec<serde_json::Value> =
+                serde_json::from_str(&results_json).unwrap_or_default();
+            for (i, r) in results.iter().enumerate() {
+                let Some(task_id) = r["taskId"].as_str() else { continue };
+                let duration = r["duration"].as_i64().unwrap_or(0);
+                let completed_at = r["completedAt"].as_i64().unwrap_or(0);
+                insert.execute(params![
+                    format!("legacy-{}-{}-{}", user_id, lesson_id, i),
+                    user_id,
+                    lesson_id,
+                    task_id,
+                    completed_at - duration,
+                    completed_at,
+                    r["wpm"].as_f64().unwrap_or(0.0),
+                    r["accuracy"].as_f64().unwrap_or(0.0),
+                    duration,
+                    r["totalKeystrokes"].as_i64().unwrap_or(0),
+                    r["backspaceCount"].as_i64().unwrap_or(0),
+                    r["errors"].as_array().map_or(0, |e| e.len() as i64),
+                    r["passed"].as_bool().unwrap_or(false),
+                    r.to_string(),
+                ])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replay stored keystrokes into key_error_stats
+    fn backfill_key_error_stats(conn: &Connection) -> SqliteResult<()> {
+        let mut select = conn.prepare(
+            "SELECT user_id, key_char, expected_char, timestamp FROM keystroke_events
+             WHERE key_char IS NOT NULL AND expected_char IS NOT NULL
+             ORDER BY timestamp, session_id, seq"
+        )?;
+        let events = select
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut stats: BTreeMap<(i64, char), KeyErrorStats> = BTreeMap::new();
+        for (user_id, typed, expected, timestamp) in events {
+            let Some(key) = expected.chars().next() else { continue };
+            stats
+                .entry((user_id, key))
+                .or_insert_with(|| KeyErrorStats::new(key, timestamp))
+                .record(1, usize::from(typed != expected), timestamp);
+        }
+        for ((user_id, _), key) in stats {
+            Self::write_key_error_stats(conn, user_id, &key)?;
+        }
+        Ok(())
+    }
+
     // ── Users ─────────────────────────────────────────────────────
 
     pub fn get_all_users(&self) -> SqliteResult<Vec<UserProfile>> {
@@ -298,7 +774,11 @@ impl Database {
     }
 
     pub fn save_settings(&self, user_id: i64, settings_json: &str) -> SqliteResult<()> {
-        self.conn.execute(
+        Self::write_settings(&self.conn, user_id, settings_json)
+    }
+
+    fn write_settings(conn: &Connection, user_id: i64, settings_json: &str) -> SqliteResult<()> {
+        conn.execute(
             "INSERT INTO user_settings (user_id, settings_json) VALUES (?1, ?2)
              ON CONFLICT(user_id) DO UPDATE SET settings_json = excluded.settings_json",
             params![user_id, settings_json],
@@ -334,7 +814,7 @@ impl Database {
             },
         ) {
             Ok(mut s) => {
-                s.problem_keys = self.get_problem_keys(user_id)?;
+                s.problem_keys = self.get_counted_errors(user_id)?;
                 Some(s)
             }
             Err(rusqlite::Error::QueryReturnedNoRows) => None,
@@ -343,10 +823,20 @@ impl Database {
         Ok(stats)
     }
 
+    /// Replace the running totals. They are taken to include every session stored so far,
+    /// so only later sessions are added on top by `rebuild_aggregates`.
+    /// `stats.problem_keys` holds the frontend's error counts, which replace the ones it
+    /// saved before.
     pub fn save_user_stats(&self, user_id: i64, stats: &UserStatsRow) -> SqliteResult<()> {
         let tx = self.conn.unchecked_transaction()?;
+        Self::write_user_stats(&tx, user_id, stats)?;
+        Self::count_sessions_in_stats(&tx, Some(user_id))?;
+        Self::write_counted_errors(&tx, user_id, &stats.problem_keys)?;
+        tx.commit()
+    }
 
-        tx.execute(
+    fn write_user_stats(conn: &Connection, user_id: i64, stats: &UserStatsRow) -> SqliteResult<()> {
+        conn.execute(
             "INSERT INTO user_stats (user_id, total_practice_time, total_words_typed, average_wpm,
                 average_accuracy, average_true_accuracy, total_keystrokes, total_backspaces,
                 total_correct_keystrokes, lessons_completed, current_streak, longest_streak, last_practice_date)
@@ -380,38 +870,15 @@ impl Database {
                 stats.last_practice_date,
             ],
         )?;
-
-        // Save problem keys (within same transaction)
-        tx.execute("DELETE FROM problem_keys WHERE user_id = ?1", params![user_id])?;
-        {
-            let mut stmt = tx.prepare(
-                "INSERT INTO problem_keys (user_id, key_char, error_count) VALUES (?1, ?2, ?3)"
-            )?;
-            for (key, count) in &stats.problem_keys {
-                stmt.execute(params![user_id, key, count])?;
-            }
-        }
-
-        tx.commit()?;
         Ok(())
     }
 
-    fn get_problem_keys(&self, user_id: i64) -> SqliteResult<Vec<(String, i64)>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT key_char, error_count FROM problem_keys WHERE user_id = ?1 ORDER BY error_count DESC"
-        )?;
-        let rows = stmt.query_map(params![user_id], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
-        })?;
-        rows.collect()
-    }
-
     // ── Lesson Progress ───────────────────────────────────────────
 
     pub fn get_all_lesson_progress(&self, user_id: i64) -> SqliteResult<Vec<LessonProgressRow>> {
         let mut stmt = self.conn.prepare(
             "SELECT lesson_id, completed_tasks, total_tasks, best_wpm, average_accuracy,
-                    last_task_index, task_results_json
+                    last_task_index, task_results_json, version
              FROM lesson_progress WHERE user_id = ?1"
         )?;
         let rows = stmt.query_map(params![user_id], |row| {
@@ -423,106 +890,278 @@ impl Database {
                 average_accuracy: row.get(4)?,
                 last_task_index: row.get(5)?,
                 task_results_json: row.get(6)?,
+                version: row.get(7)?,
             })
         })?;
         rows.collect()
     }
 
+    /// Replace the user's whole set. Unlike `upsert_lesson_progress` this doesn't check versions.
+    /// New task results become session rows, so rebuilding the aggregates keeps them.
     pub fn save_lesson_progress(&self, user_id: i64, progress: &[LessonProgressRow]) -> SqliteResult<()> {
         let tx = self.conn.unchecked_transaction()?;
+        Self::replace_lesson_progress(&tx, user_id, progress)?;
+        Self::backfill_sessions(&tx, Some(user_id))?;
+        tx.commit()
+    }
 
-        tx.execute("DELETE FROM lesson_progress WHERE user_id = ?1", params![user_id])?;
-        {
-            let mut stmt = tx.prepare(
-                "INSERT INTO lesson_progress (user_id, lesson_id, completed_tasks, total_tasks,
-                    best_wpm, average_accuracy, last_task_index, task_results_json)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
-            )?;
-            for p in progress {
-                stmt.execute(params![
-                    user_id,
-                    p.lesson_id,
-                    p.completed_tasks,
-                    p.total_tasks,
-                    p.best_wpm,
-                    p.average_accuracy,
-                    p.last_task_index,
-                    p.task_results_json,
-                ])?;
-            }
+    fn replace_lesson_progress(conn: &Connection, user_id: i64, progress: &[LessonProgressRow]) -> SqliteResult<()> {
+        let keep: HashSet<&str> = progress.iter().map(|p| p.lesson_id.as_str()).collect();
+        Self::delete_missing(conn, "lesson_progress", "lesson_id", user_id, &keep)?;
+        let mut stmt = conn.prepare(
+            "INSERT INTO lesson_progress (user_id, lesson_id, completed_tasks, total_tasks,
+                best_wpm, average_accuracy, last_task_index, task_results_json, version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 1)
+             ON CONFLICT(user_id, lesson_id) DO UPDATE SET
+                completed_tasks = excluded.completed_tasks,
+                total_tasks = excluded.total_tasks,
+                best_wpm = excluded.best_wpm,
+                average_accuracy = excluded.average_accuracy,
+                last_task_index = excluded.last_task_index,
+                task_results_json = excluded.task_results_json,
+                version = lesson_progress.version + 1"
+        )?;
+        for p in progress {
+            stmt.execute(params![
+                user_id,
+                p.lesson_id,
+                p.completed_tasks,
+                p.total_tasks,
+                p.best_wpm,
+                p.average_accuracy,
+                p.last_task_index,
+                p.task_results_json,
+            ])?;
         }
+        Ok(())
+    }
 
-        tx.commit()?;
+    /// Delete one lesson's progress if `version` is still current. Its sessions are kept,
+    /// so a lesson with recorded sessions gets its progress back on the next rebuild.
+    pub fn delete_lesson_progress(&self, user_id: i64, lesson_id: &str, version: i64) -> Result<(), StorageError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM lesson_progress WHERE user_id = ?1 AND lesson_id = ?2 AND version = ?3",
+            params![user_id, lesson_id, version],
+        )?;
+        if deleted == 0 {
+            return Err(StorageError::Conflict(format!(
+                "lesson progress '{}' was changed or removed elsewhere", lesson_id
+            )));
+        }
         Ok(())
     }
 
+    /// Insert or update one lesson's progress if `progress.version` is still current.
+    /// Returns the new version. New task results become session rows, as in `save_lesson_progress`.
+    pub fn upsert_lesson_progress(&self, user_id: i64, progress: &LessonProgressRow) -> Result<i64, StorageError> {
+        let p = progress;
+        let version = self.write_versioned(
+            &format!("lesson progress '{}'", p.lesson_id),
+            p.version,
+            |conn| conn.execute(
+                "UPDATE lesson_progress SET completed_tasks = ?3, total_tasks = ?4, best_wpm = ?5,
+                    average_accuracy = ?6, last_task_index = ?7, task_results_json = ?8,
+                    version = version + 1
+                 WHERE user_id = ?1 AND lesson_id = ?2 AND version = ?9",
+                params![
+                    user_id, p.lesson_id, p.completed_tasks, p.total_tasks, p.best_wpm,
+                    p.average_accuracy, p.last_task_index, p.task_results_json, p.version,
+                ],
+            ),
+            |conn| conn.execute(
+                "INSERT INTO lesson_progress (user_id, lesson_id, completed_tasks, total_tasks,
+                    best_wpm, average_accuracy, last_task_index, task_results_json, version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 1)
+                 ON CONFLICT DO NOTHING",
+                params![
+                    user_id, p.lesson_id, p.completed_tasks, p.total_tasks, p.best_wpm,
+                    p.average_accuracy, p.last_task_index, p.task_results_json,
+                ],
+            ),
+        )?;
+        Self::backfill_sessions(&self.conn, Some(user_id))?;
+        Ok(version)
+    }
+
     // ── Course Progress ───────────────────────────────────────────
 
+    /// The frontend's row format, with the stage lists as JSON
     pub fn get_all_course_progress(&self, user_id: i64) -> SqliteResult<Vec<CourseProgressRow>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT course_id, current_stage_id, completed_stages_json, skipped_stages_json,
-                    enrolled_at, completed_at
-             FROM course_progress WHERE user_id = ?1"
-        )?;
-        let rows = stmt.query_map(params![user_id], |row| {
-            Ok(CourseProgressRow {
-                course_id: row.get(0)?,
-                current_stage_id: row.get(1)?,
-                completed_stages_json: row.get(2)?,
-                skipped_stages_json: row.get(3)?,
-                enrolled_at: row.get(4)?,
-                completed_at: row.get(5)?,
-            })
-        })?;
-        rows.collect()
+        Ok(self.get_course_states(user_id)?.iter().map(CourseProgress::to_row).collect())
     }
 
-    pub fn save_course_progress(&self, user_id: i64, progress: &[CourseProgressRow]) -> SqliteResult<()> {
+    /// Replace the user's whole set. Unlike `upsert_course_progress` this doesn't check versions.
+    pub fn save_course_progress(&self, user_id: i64, progress: &[CourseProgressRow]) -> Result<(), StorageError> {
         let tx = self.conn.unchecked_transaction()?;
+        Self::replace_course_progress(&tx, user_id, progress)?;
+        tx.commit()?;
+        Ok(())
+    }
 
-        tx.execute("DELETE FROM course_progress WHERE user_id = ?1", params![user_id])?;
-        {
-            let mut stmt = tx.prepare(
-                "INSERT INTO course_progress (user_id, course_id, current_stage_id, completed_stages_json,
-                    skipped_stages_json, enrolled_at, completed_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
+    fn replace_course_progress(conn: &Connection, user_id: i64, progress: &[CourseProgressRow]) -> Result<(), StorageError> {
+        let progress = progress
+            .iter()
+            .map(CourseProgress::from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let keep: HashSet<&str> = progress.iter().map(|p| p.course_id.as_str()).collect();
+        Self::delete_missing(conn, "course_progress", "course_id", user_id, &keep)?;
+        for p in &progress {
+            conn.execute(
+                "INSERT INTO course_progress (user_id, course_id, current_stage_id, enrolled_at,
+                    completed_at, version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, 1)
+                 ON CONFLICT(user_id, course_id) DO UPDATE SET
+                    current_stage_id = excluded.current_stage_id,
+                    enrolled_at = excluded.enrolled_at,
+                    completed_at = excluded.completed_at,
+                    version = course_progress.version + 1",
+                params![user_id, p.course_id, p.current_stage_id, p.enrolled_at, p.completed_at],
             )?;
-            for p in progress {
-                stmt.execute(params![
-                    user_id,
-                    p.course_id,
-                    p.current_stage_id,
-                    p.completed_stages_json,
-                    p.skipped_stages_json,
-                    p.enrolled_at,
-                    p.completed_at,
-                ])?;
-            }
+            Self::write_course_stages(conn, user_id, p)?;
         }
-
-        tx.commit()?;
         Ok(())
     }
 
-    pub fn delete_course_progress(&self, user_id: i64, course_id: &str) -> SqliteResult<()> {
-        self.conn.execute(
-            "DELETE FROM course_progress WHERE user_id = ?1 AND course_id = ?2",
-            params![user_id, course_id],
-        )?;
-        Ok(())
+    /// Insert or update one course's progress if `progress.version` is still current.
+    /// Returns the new version.
+    pub fn upsert_course_progress(&self, user_id: i64, progress: &CourseProgressRow) -> Result<i64, StorageError> {
+        self.save_course_state(user_id, &CourseProgress::from_row(progress)?)
     }
 
-    pub fn delete_all_course_progress(&self, user_id: i64) -> SqliteResult<()> {
-        self.conn.execute("DELETE FROM course_progress WHERE user_id = ?1", params![user_id])?;
-        Ok(())
+    pub fn get_course_states(&self, user_id: i64) -> SqliteResult<Vec<CourseProgress>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT course_id FROM course_progress WHERE user_id = ?1 ORDER BY enrolled_at, course_id"
+        )?;
+        let course_ids = stmt
+            .query_map(params![user_id], |row| row.get::<_, String>(0))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        course_ids
+            .iter()
+            .filter_map(|id| self.get_course_state(user_id, id).transpose())
+            .collect()
     }
 
-    // ── Custom Snippets ───────────────────────────────────────────
+    pub fn get_course_state(&self, user_id: i64, course_id: &str) -> SqliteResult<Option<CourseProgress>> {
+        let progress = self.conn.query_row(
+            "SELECT course_id, current_stage_id, enrolled_at, completed_at, version
+             FROM course_progress WHERE user_id = ?1 AND course_id = ?2",
+            params![user_id, course_id],
+            |row| {
+                Ok(CourseProgress {
+                    course_id: row.get(0)?,
+                    current_stage_id: row.get(1)?,
+                    completed_stages: Vec::new(),
+                    skipped_stages: Vec::new(),
+                    enrolled_at: row.get(2)?,
+                    completed_at: row.get(3)?,
+                    version: row.get(4)?,
+                })
+            },
+        );
+        let mut progress = match progress {
+            Ok(progress) => progress,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e),
+        };
 
-    pub fn get_snippets(&self, user_id: i64) -> SqliteResult<Vec<CustomSnippetRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT stage_id, status FROM course_stage_progress
+             WHERE user_id = ?1 AND course_id = ?2 ORDER BY position"
+        )?;
+        let stages = stmt.query_map(params![user_id, course_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for stage in stages {
+            let (stage_id, status) = stage?;
+            match status.as_str() {
+                "completed" => progress.completed_stages.push(stage_id),
+                _ => progress.skipped_stages.push(stage_id),
+            }
+        }
+        Ok(Some(progress))
+    }
+
+    /// Versioned like `upsert_course_progress`; the stage rows are rewritten with the course row
+    pub fn save_course_state(&self, user_id: i64, progress: &CourseProgress) -> Result<i64, StorageError> {
+        let p = progress;
+        self.write_versioned(
+            &format!("course progress '{}'", p.course_id),
+            p.version,
+            |conn| {
+                let written = conn.execute(
+                    "UPDATE course_progress SET current_stage_id = ?3, enrolled_at = ?4, completed_at = ?5,
+                        version = version + 1
+                     WHERE user_id = ?1 AND course_id = ?2 AND version = ?6",
+                    params![user_id, p.course_id, p.current_stage_id, p.enrolled_at, p.completed_at, p.version],
+                )?;
+                if written > 0 {
+                    Self::write_course_stages(conn, user_id, p)?;
+                }
+                Ok(written)
+            },
+            |conn| {
+                let written = conn.execute(
+                    "INSERT INTO course_progress (user_id, course_id, current_stage_id, enrolled_at,
+                        completed_at, version)
+                     VALUES (?1, ?2, ?3, ?4, ?5, 1)
+                     ON CONFLICT DO NOTHING",
+                    params![user_id, p.course_id, p.current_stage_id, p.enrolled_at, p.completed_at],
+                )?;
+                if written > 0 {
+                    Self::write_course_stages(conn, user_id, p)?;
+                }
+                Ok(written)
+            },
+        )
+    }
+
+    // Completed stages win over skipped ones listed under both
+    fn write_course_stages(conn: &Connection, user_id: i64, p: &CourseProgress) -> SqliteResult<()> {
+        conn.execute(
+            "DELETE FROM course_stage_progress WHERE user_id = ?1 AND course_id = ?2",
+            params![user_id, p.course_id],
+        )?;
+        let mut stmt = conn.prepare(
+            "INSERT OR IGNORE INTO course_stage_progress (user_id, course_id, stage_id, status, position)
+             VALUES (?1, ?2, ?3, ?4, ?5)"
+        )?;
+        let stages = p.completed_stages.iter().map(|s| (s, "completed"))
+            .chain(p.skipped_stages.iter().map(|s| (s, "skipped")));
+        for (position, (stage_id, status)) in stages.enumerate() {
+            stmt.execute(params![user_id, p.course_id, stage_id, status, position as i64])?;
+        }
+        Ok(())
+    }
+
+    /// Delete one course's progress. With a `version` the delete fails with a conflict if the
+    /// row changed since it was read; without one it is unconditional, for callers that
+    /// don't track versions yet.
+    pub fn delete_course_progress(&self, user_id: i64, course_id: &str, version: Option<i64>) -> Result<(), StorageError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM course_progress WHERE user_id = ?1 AND course_id = ?2
+                AND (?3 IS NULL OR version = ?3)",
+            params![user_id, course_id, version],
+        )?;
+        if deleted == 0 && version.is_some() {
+            return Err(StorageError::Conflict(format!(
+                "course progress '{}' was changed or removed elsewhere", course_id
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn delete_all_course_progress(&self, user_id: i64) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM course_progress WHERE user_id = ?1", params![user_id])?;
+        Ok(())
+    }
+
+    // ── Custom Snippets ───────────────────────────────────────────
+
+    pub fn get_snippets(&self, user_id: i64) -> SqliteResult<Vec<CustomSnippetRow>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, user_id, name, content, language, mode, created_at,
-                    practice_count, best_wpm, best_accuracy
+                    practice_count, best_wpm, best_accuracy, version
              FROM custom_snippets WHERE user_id = ?1 ORDER BY created_at"
         )?;
         let rows = stmt.query_map(params![user_id], |row| {
@@ -537,30 +1176,85 @@ impl Database {
                 practice_count: row.get(7)?,
                 best_wpm: row.get(8)?,
                 best_accuracy: row.get(9)?,
+                version: row.get(10)?,
             })
         })?;
         rows.collect()
     }
 
+    /// Replace the user's whole set. Unlike `upsert_snippet` this doesn't check versions.
     pub fn save_snippets(&self, user_id: i64, snippets: &[CustomSnippetRow]) -> SqliteResult<()> {
         let tx = self.conn.unchecked_transaction()?;
+        Self::replace_snippets(&tx, user_id, snippets)?;
+        tx.commit()
+    }
 
-        tx.execute("DELETE FROM custom_snippets WHERE user_id = ?1", params![user_id])?;
-        {
-            let mut stmt = tx.prepare(
+    fn replace_snippets(conn: &Connection, user_id: i64, snippets: &[CustomSnippetRow]) -> SqliteResult<()> {
+        let keep: HashSet<&str> = snippets.iter().map(|s| s.id.as_str()).collect();
+        Self::delete_missing(conn, "custom_snippets", "id", user_id, &keep)?;
+        let mut stmt = conn.prepare(
+            "INSERT INTO custom_snippets (id, user_id, name, content, language, mode,
+                created_at, practice_count, best_wpm, best_accuracy, version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 1)
+             ON CONFLICT(id) DO UPDATE SET
+                user_id = excluded.user_id,
+                name = excluded.name,
+                content = excluded.content,
+                language = excluded.language,
+                mode = excluded.mode,
+                created_at = excluded.created_at,
+                practice_count = excluded.practice_count,
+                best_wpm = excluded.best_wpm,
+                best_accuracy = excluded.best_accuracy,
+                version = custom_snippets.version + 1"
+        )?;
+        for s in snippets {
+            stmt.execute(params![
+                s.id, s.user_id, s.name, s.content, s.language, s.mode,
+                s.created_at, s.practice_count, s.best_wpm, s.best_accuracy,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Insert or update one snippet if `snippet.version` is still current. Returns the new version.
+    pub fn upsert_snippet(&self, user_id: i64, snippet: &CustomSnippetRow) -> Result<i64, StorageError> {
+        let s = snippet;
+        self.write_versioned(
+            &format!("snippet '{}'", s.id),
+            s.version,
+            |conn| conn.execute(
+                "UPDATE custom_snippets SET name = ?3, content = ?4, language = ?5, mode = ?6,
+                    practice_count = ?7, best_wpm = ?8, best_accuracy = ?9, version = version + 1
+                 WHERE id = ?1 AND user_id = ?2 AND version = ?10",
+                params![
+                    s.id, user_id, s.name, s.content, s.language, s.mode,
+                    s.practice_count, s.best_wpm, s.best_accuracy, s.version,
+                ],
+            ),
+            |conn| conn.execute(
                 "INSERT INTO custom_snippets (id, user_id, name, content, language, mode,
-                    created_at, practice_count, best_wpm, best_accuracy)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
-            )?;
-            for s in snippets {
-                stmt.execute(params![
-                    s.id, s.user_id, s.name, s.content, s.language, s.mode,
+                    created_at, practice_count, best_wpm, best_accuracy, version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 1)
+                 ON CONFLICT DO NOTHING",
+                params![
+                    s.id, user_id, s.name, s.content, s.language, s.mode,
                     s.created_at, s.practice_count, s.best_wpm, s.best_accuracy,
-                ])?;
-            }
-        }
+                ],
+            ),
+        )
+    }
 
-        tx.commit()?;
+    pub fn delete_snippet(&self, user_id: i64, snippet_id: &str, version: i64) -> Result<(), StorageError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM custom_snippets WHERE id = ?1 AND user_id = ?2 AND version = ?3",
+            params![snippet_id, user_id, version],
+        )?;
+        if deleted == 0 {
+            return Err(StorageError::Conflict(format!(
+                "snippet '{}' was changed or removed elsewhere", snippet_id
+            )));
+        }
         Ok(())
     }
 
@@ -568,7 +1262,7 @@ impl Database {
 
     pub fn get_daily_results(&self) -> SqliteResult<Vec<DailyTestResultRow>> {
         let mut stmt = self.conn.prepare(
-            "SELECT user_id, date, wpm, accuracy, true_accuracy, duration, completed_at
+            "SELECT user_id, date, wpm, accuracy, true_accuracy, duration, completed_at, version
              FROM daily_test_results ORDER BY date, user_id"
         )?;
         let rows = stmt.query_map([], |row| {
@@ -580,36 +1274,94 @@ impl Database {
                 true_accuracy: row.get(4)?,
                 duration: row.get(5)?,
                 completed_at: row.get(6)?,
+                version: row.get(7)?,
             })
         })?;
         rows.collect()
     }
 
+    /// Replace the results of every user present in `results`; other users are left alone.
+    /// Unlike `record_daily_result` this doesn't check versions.
     pub fn save_daily_results(&self, results: &[DailyTestResultRow]) -> SqliteResult<()> {
         let tx = self.conn.unchecked_transaction()?;
 
-        tx.execute("DELETE FROM daily_test_results", [])?;
-        {
-            let mut stmt = tx.prepare(
-                "INSERT INTO daily_test_results (user_id, date, wpm, accuracy, true_accuracy, duration, completed_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
-            )?;
-            for r in results {
-                stmt.execute(params![
-                    r.user_id, r.date, r.wpm, r.accuracy, r.true_accuracy, r.duration, r.completed_at,
-                ])?;
-            }
+        let mut dates: BTreeMap<i64, HashSet<&str>> = BTreeMap::new();
+        for r in results {
+            dates.entry(r.user_id).or_default().insert(r.date.as_str());
         }
+        for (user_id, keep) in &dates {
+            Self::delete_missing(&tx, "daily_test_results", "date", *user_id, keep)?;
+        }
+        Self::upsert_daily_results(&tx, results)?;
 
         tx.commit()?;
         Ok(())
     }
 
+    fn upsert_daily_results(conn: &Connection, results: &[DailyTestResultRow]) -> SqliteResult<()> {
+        let mut stmt = conn.prepare(
+            "INSERT INTO daily_test_results (user_id, date, wpm, accuracy, true_accuracy, duration, completed_at, version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1)
+             ON CONFLICT(user_id, date) DO UPDATE SET
+                wpm = excluded.wpm,
+                accuracy = excluded.accuracy,
+                true_accuracy = excluded.true_accuracy,
+                duration = excluded.duration,
+                completed_at = excluded.completed_at,
+                version = daily_test_results.version + 1"
+        )?;
+        for r in results {
+            stmt.execute(params![
+                r.user_id, r.date, r.wpm, r.accuracy, r.true_accuracy, r.duration, r.completed_at,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Delete one user's result for a day if `version` is still current
+    pub fn delete_daily_result(&self, user_id: i64, date: &str, version: i64) -> Result<(), StorageError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM daily_test_results WHERE user_id = ?1 AND date = ?2 AND version = ?3",
+            params![user_id, date, version],
+        )?;
+        if deleted == 0 {
+            return Err(StorageError::Conflict(format!(
+                "daily result for user {} on {} was changed or removed elsewhere", user_id, date
+            )));
+        }
+        Ok(())
+    }
+
+    /// Insert or replace one user's result for a day if `result.version` is still current.
+    /// Returns the new version.
+    pub fn record_daily_result(&self, result: &DailyTestResultRow) -> Result<i64, StorageError> {
+        let r = result;
+        self.write_versioned(
+            &format!("daily result for user {} on {}", r.user_id, r.date),
+            r.version,
+            |conn| conn.execute(
+                "UPDATE daily_test_results SET wpm = ?3, accuracy = ?4, true_accuracy = ?5,
+                    duration = ?6, completed_at = ?7, version = version + 1
+                 WHERE user_id = ?1 AND date = ?2 AND version = ?8",
+                params![
+                    r.user_id, r.date, r.wpm, r.accuracy, r.true_accuracy, r.duration,
+                    r.completed_at, r.version,
+                ],
+            ),
+            |conn| conn.execute(
+                "INSERT INTO daily_test_results (user_id, date, wpm, accuracy, true_accuracy, duration, completed_at, version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1)
+                 ON CONFLICT DO NOTHING",
+                params![r.user_id, r.date, r.wpm, r.accuracy, r.true_accuracy, r.duration, r.completed_at],
+            ),
+        )
+    }
+
     // ── Daily Activity ────────────────────────────────────────────
 
     pub fn get_activity(&self, user_id: i64) -> SqliteResult<Vec<DailyActivityRow>> {
         let mut stmt = self.conn.prepare(
-            "SELECT date, practice_time, characters, sessions
+            "SELECT date, practice_time, characters, sessions, version
              FROM daily_activity WHERE user_id = ?1 ORDER BY date"
         )?;
         let rows = stmt.query_map(params![user_id], |row| {
@@ -618,31 +1370,828 @@ impl Database {
                 practice_time: row.get(1)?,
                 characters: row.get(2)?,
                 sessions: row.get(3)?,
+                version: row.get(4)?,
             })
         })?;
         rows.collect()
     }
 
+    /// Replace the user's whole set. Prefer `record_activity_delta`, which can't lose updates.
     pub fn save_activity(&self, user_id: i64, activity: &[DailyActivityRow]) -> SqliteResult<()> {
         let tx = self.conn.unchecked_transaction()?;
+        Self::replace_activity(&tx, user_id, activity)?;
+        tx.commit()
+    }
+
+    fn replace_activity(conn: &Connection, user_id: i64, activity: &[DailyActivityRow]) -> SqliteResult<()> {
+        let keep: HashSet<&str> = activity.iter().map(|a| a.date.as_str()).collect();
+        Self::delete_missing(conn, "daily_activity", "date", user_id, &keep)?;
+        let mut stmt = conn.prepare(
+            "INSERT INTO daily_activity (user_id, date, practice_time, characters, sessions, version)
+             VALUES (?1, ?2, ?3, ?4, ?5, 1)
+             ON CONFLICT(user_id, date) DO UPDATE SET
+                practice_time = excluded.practice_time,
+                characters = excluded.characters,
+                sessions = excluded.sessions,
+                version = daily_activity.version + 1"
+        )?;
+        for a in activity {
+            stmt.execute(params![user_id, a.date, a.practice_time, a.characters, a.sessions])?;
+        }
+        Ok(())
+    }
+
+    /// Add to a day's totals. Increments commute, so concurrent windows need no version check.
+    pub fn record_activity_delta(&self, user_id: i64, delta: &DailyActivityRow) -> SqliteResult<DailyActivityRow> {
+        self.conn.query_row(
+            "INSERT INTO daily_activity (user_id, date, practice_time, characters, sessions, version)
+             VALUES (?1, ?2, ?3, ?4, ?5, 1)
+             ON CONFLICT(user_id, date) DO UPDATE SET
+                practice_time = practice_time + excluded.practice_time,
+                characters = characters + excluded.characters,
+                sessions = sessions + excluded.sessions,
+                version = version + 1
+             RETURNING date, practice_time, characters, sessions, version",
+            params![user_id, delta.date, delta.practice_time, delta.characters, delta.sessions],
+            |row| {
+                Ok(DailyActivityRow {
+                    date: row.get(0)?,
+                    practice_time: row.get(1)?,
+                    characters: row.get(2)?,
+                    sessions: row.get(3)?,
+                    version: row.get(4)?,
+                })
+            },
+        )
+    }
+
+    pub fn delete_activity(&self, user_id: i64) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM daily_activity WHERE user_id = ?1", params![user_id])?;
+        Ok(())
+    }
+
+    // ── Keystroke Events ──────────────────────────────────────────
+
+    /// Append events to a session's log. Batches may arrive in several calls while typing.
+    pub fn append_keystroke_events(
+        &self,
+        session_id: &str,
+        user_id: i64,
+        task_id: &str,
+        events: &[KeystrokeEvent],
+    ) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        Self::write_keystroke_events(&tx, session_id, user_id, task_id, events)?;
+        tx.commit()
+    }
+
+    fn write_keystroke_events(
+        conn: &Connection,
+        session_id: &str,
+        user_id: i64,
+        task_id: &str,
+        events: &[KeystrokeEvent],
+    ) -> SqliteResult<()> {
+        let next_seq: i64 = conn.query_row(
+            "SELECT COALESCE(MAX(seq) + 1, 0) FROM keystroke_events WHERE session_id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )?;
+        let mut stmt = conn.prepare(
+            "INSERT INTO keystroke_events (session_id, seq, user_id, task_id, kind, key_char,
+                expected_char, position, timestamp, layout)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
+        )?;
+        for (i, e) in events.iter().enumerate() {
+            stmt.execute(params![
+                session_id,
+                next_seq + i as i64,
+                user_id,
+                task_id,
+                e.kind.as_str(),
+                e.key.map(String::from),
+                e.expected.map(String::from),
+                e.position as i64,
+                e.timestamp,
+                e.layout,
+            ])?;
+        }
+        Ok(())
+    }
+
+    pub fn get_keystroke_events(&self, session_id: &str) -> SqliteResult<Vec<KeystrokeEvent>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT kind, key_char, expected_char, position, timestamp, layout
+             FROM keystroke_events WHERE session_id = ?1 ORDER BY seq"
+        )?;
+        let rows = stmt.query_map(params![session_id], |row| {
+            let kind: String = row.get(0)?;
+            Ok(KeystrokeEvent {
+                kind: KeystrokeKind::parse(&kind).ok_or_else(|| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        0,
+                        rusqlite::types::Type::Text,
+                        format!("unknown keystroke kind '{}'", kind).into(),
+                    )
+                })?,
+                key: row.get::<_, Option<String>>(1)?.and_then(|s| s.chars().next()),
+                expected: row.get::<_, Option<String>>(2)?.and_then(|s| s.chars().next()),
+                position: row.get::<_, i64>(3)? as usize,
+                timestamp: row.get(4)?,
+                layout: row.get(5)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn list_keystroke_sessions(&self, user_id: i64) -> SqliteResult<Vec<KeystrokeSessionSummary>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, task_id, MIN(timestamp), MAX(timestamp), COUNT(*)
+             FROM keystroke_events WHERE user_id = ?1
+             GROUP BY session_id, task_id
+             ORDER BY MIN(timestamp) DESC"
+        )?;
+        let rows = stmt.query_map(params![user_id], |row| {
+            Ok(KeystrokeSessionSummary {
+                session_id: row.get(0)?,
+                task_id: row.get(1)?,
+                started_at: row.get(2)?,
+                ended_at: row.get(3)?,
+                event_count: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    // ── Key Latency ───────────────────────────────────────────────
+
+    /// Fold one session's inter-key intervals into the user's rolling per-key aggregates.
+    /// A session that was already merged is skipped.
+    pub fn merge_key_latencies(&self, user_id: i64, session_id: &str, samples: &LatencySamples) -> Result<(), StorageError> {
+        let tx = self.conn.unchecked_transaction()?;
+        Self::write_key_latencies(&tx, user_id, session_id, samples)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn write_key_latencies(conn: &Connection, user_id: i64, session_id: &str, samples: &LatencySamples) -> Result<(), StorageError> {
+        if !Self::claim_merge(conn, user_id, session_id, "key_latency")? {
+            return Ok(());
+        }
+        let mut select = conn.prepare(
+            "SELECT sample_count, mean_ms, m2, histogram_json FROM key_latency
+             WHERE user_id = ?1 AND ngram = ?2"
+        )?;
+        let mut upsert = conn.prepare(
+            "INSERT INTO key_latency (user_id, ngram, sample_count, mean_ms, m2, histogram_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(user_id, ngram) DO UPDATE SET
+                sample_count = excluded.sample_count,
+                mean_ms = excluded.mean_ms,
+                m2 = excluded.m2,
+                histogram_json = excluded.histogram_json"
+        )?;
+        for (ngram, values) in samples.iter() {
+            let mut agg = match select.query_row(params![user_id, ngram], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?, row.get::<_, String>(3)?))
+            }) {
+                Ok((count, mean, m2, histogram_json)) => LatencyAggregate {
+                    count: count as u64,
+                    mean,
+                    m2,
+                    histogram: serde_json::from_str(&histogram_json)?,
+                },
+                Err(rusqlite::Error::QueryReturnedNoRows) => LatencyAggregate::default(),
+                Err(e) => return Err(e.into()),
+            };
+            agg.add_samples(values);
+            upsert.execute(params![
+                user_id,
+                ngram,
+                agg.count as i64,
+                agg.mean,
+                agg.m2,
+                serde_json::to_string(&agg.histogram)?,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Mark `session_id` as merged into `aggregate`. False when it already was.
+    fn claim_merge(conn: &Connection, user_id: i64, session_id: &str, aggregate: &str) -> SqliteResult<bool> {
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO merged_sessions (session_id, aggregate, user_id) VALUES (?1, ?2, ?3)",
+            params![session_id, aggregate, user_id],
+        )?;
+        Ok(inserted == 1)
+    }
+
+    pub fn get_key_latencies(&self, user_id: i64) -> Result<LatencyReport, StorageError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT ngram, sample_count, mean_ms, m2, histogram_json FROM key_latency WHERE user_id = ?1"
+        )?;
+        let rows = stmt.query_map(params![user_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, f64>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (ngram, count, mean, m2, histogram_json) = row?;
+            let agg = LatencyAggregate {
+                count: count as u64,
+                mean,
+                m2,
+                histogram: serde_json::from_str(&histogram_json)?,
+            };
+            if let Some(stats) = agg.stats() {
+                entries.push((ngram, stats));
+            }
+        }
+        Ok(LatencyReport::from_stats(entries))
+    }
+
+    // ── Finger Stats ──────────────────────────────────────────────
+
+    /// Fold one session's per-finger totals into the user's running ones, at most once per session
+    fn write_finger_stats(conn: &Connection, user_id: i64, session_id: &str, fingers: &BTreeMap<Finger, FingerAggregate>) -> Result<(), StorageError> {
+        let mut totals = Self::get_finger_aggregates(conn, user_id)?;
+        if !Self::claim_merge(conn, user_id, session_id, "finger_stats")? {
+            return Ok(());
+        }
+        let mut upsert = conn.prepare(
+            "INSERT INTO finger_stats (user_id, finger, keystrokes, errors, sample_count, mean_ms, m2, histogram_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(user_id, finger) DO UPDATE SET
+                keystrokes = excluded.keystrokes,
+                errors = excluded.errors,
+                sample_count = excluded.sample_count,
+                mean_ms = excluded.mean_ms,
+                m2 = excluded.m2,
+                histogram_json = excluded.histogram_json"
+        )?;
+        for (finger, session) in fingers {
+            let agg = totals.entry(*finger).or_default();
+            agg.merge(session);
+            upsert.execute(params![
+                user_id,
+                finger.as_str(),
+                agg.keystrokes as i64,
+                agg.errors as i64,
+                agg.latency.count as i64,
+                agg.latency.mean,
+                agg.latency.m2,
+                serde_json::to_string(&agg.latency.histogram)?,
+            ])?;
+        }
+        Ok(())
+    }
+
+    pub fn get_finger_stats(&self, user_id: i64) -> Result<FingerReport, StorageError> {
+        Ok(FingerReport::from_aggregates(&Self::get_finger_aggregates(&self.conn, user_id)?))
+    }
+
+    fn get_finger_aggregates(conn: &Connection, user_id: i64) -> Result<BTreeMap<Finger, FingerAggregate>, StorageError> {
+        let mut stmt = conn.prepare(
+            "SELECT finger, keystrokes, errors, sample_count, mean_ms, m2, histogram_json
+             FROM finger_stats WHERE user_id = ?1"
+        )?;
+        let rows = stmt.query_map(params![user_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, f64>(4)?,
+                row.get::<_, f64>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })?;
+
+        let mut fingers = BTreeMap::new();
+        for row in rows {
+            let (finger, keystrokes, errors, count, mean, m2, histogram_json) = row?;
+            // Skip names a newer version might have written
+            let Some(finger) = Finger::parse(&finger) else { continue };
+            fingers.insert(finger, FingerAggregate {
+                keystrokes: keystrokes as u64,
+                errors: errors as u64,
+                latency: LatencyAggregate {
+                    count: count as u64,
+                    mean,
+                    m2,
+                    histogram: serde_json::from_str(&histogram_json)?,
+                },
+            });
+        }
+        Ok(fingers)
+    }
+
+    // ── Problem Keys ──────────────────────────────────────────────
+
+    /// Fold one session's per-key attempts and errors into the decayed totals
+    fn write_key_scores(conn: &Connection, user_id: i64, scores: &[KeyScore], at: i64) -> SqliteResult<()> {
+        for stats in problem_keys::merge(&Self::read_key_error_stats(conn, user_id)?, scores, at) {
+            Self::write_key_error_stats(conn, user_id, &stats)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_key_error_stats(&self, user_id: i64) -> SqliteResult<Vec<KeyErrorStats>> {
+        Self::read_key_error_stats(&self.conn, user_id)
+    }
+
+    fn read_key_error_stats(conn: &Connection, user_id: i64) -> SqliteResult<Vec<KeyErrorStats>> {
+        let mut stmt = conn.prepare(
+            "SELECT key_char, attempts, errors, updated_at FROM key_error_stats WHERE user_id = ?1"
+        )?;
+        let rows = stmt.query_map(params![user_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?;
+        let mut stats = Vec::new();
+        for row in rows {
+            let (key, attempts, errors, updated_at) = row?;
+            if let Some(key) = key.chars().next() {
+                stats.push(KeyErrorStats { key, attempts, errors, updated_at });
+            }
+        }
+        Ok(stats)
+    }
+
+    pub fn get_problem_keys(&self, user_id: i64, now: i64) -> SqliteResult<Vec<ProblemKey>> {
+        Ok(problem_keys::report(&self.get_key_error_stats(user_id)?, now))
+    }
+
+    /// The error counts per key the frontend last saved, as it keeps them in user stats
+    fn get_counted_errors(&self, user_id: i64) -> SqliteResult<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT key_char, counted_errors FROM key_error_stats
+             WHERE user_id = ?1 AND counted_errors > 0 ORDER BY key_char"
+        )?;
+        let rows = stmt.query_map(params![user_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    /// Save the frontend's error counts per key. They come without attempts, so they are
+    /// kept next to the decayed stats instead of in them: a key is only reported as a
+    /// problem from measured attempts, and errors measured from keystrokes aren't counted
+    /// twice when the frontend counts them too.
+    fn write_counted_errors(conn: &Connection, user_id: i64, counts: &[(String, i64)]) -> SqliteResult<()> {
+        conn.execute("UPDATE key_error_stats SET counted_errors = 0 WHERE user_id = ?1", params![user_id])?;
+        for (key, count) in counts {
+            let Some(key) = key.chars().next().filter(|_| *count > 0) else { continue };
+            conn.execute(
+                "INSERT INTO key_error_stats (user_id, key_char, attempts, errors, updated_at, counted_errors)
+                 VALUES (?1, ?2, 0, 0, 0, ?3)
+                 ON CONFLICT(user_id, key_char) DO UPDATE SET counted_errors = excluded.counted_errors",
+                params![user_id, key.to_string(), count],
+            )?;
+        }
+        // Keys that were only ever counted, and no longer are
+        conn.execute(
+            "DELETE FROM key_error_stats WHERE user_id = ?1 AND attempts = 0 AND counted_errors = 0",
+            params![user_id],
+        )?;
+        Ok(())
+    }
+
+    fn write_key_error_stats(conn: &Connection, user_id: i64, stats: &KeyErrorStats) -> SqliteResult<()> {
+        conn.execute(
+            "INSERT INTO key_error_stats (user_id, key_char, attempts, errors, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(user_id, key_char) DO UPDATE SET
+                attempts = excluded.attempts,
+                errors = excluded.errors,
+                updated_at = excluded.updated_at",
+            params![user_id, stats.key.to_string(), stats.attempts, stats.errors, stats.updated_at],
+        )?;
+        Ok(())
+    }
+
+    // ── Custom Layouts ────────────────────────────────────────────
+
+    /// Store an imported layout, replacing an earlier import with the same id
+    pub fn save_custom_layout(&self, layout: &CustomLayoutRow) -> Result<(), StorageError> {
+        self.conn.execute(
+            "INSERT INTO custom_layouts (user_id, id, name, format, layout_json, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(user_id, id) DO UPDATE SET
+                name = excluded.name,
+                format = excluded.format,
+                layout_json = excluded.layout_json,
+                created_at = excluded.created_at",
+            params![
+                layout.user_id, layout.id, layout.name, layout.format,
+                serde_json::to_string(&layout.layout)?, layout.created_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_custom_layouts(&self, user_id: i64) -> Result<Vec<CustomLayoutRow>, StorageError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_id, name, format, layout_json, created_at
+             FROM custom_layouts WHERE user_id = ?1 ORDER BY created_at, id"
+        )?;
+        let rows = stmt.query_map(params![user_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+
+        let mut layouts = Vec::new();
+        for row in rows {
+            let (id, user_id, name, format, layout_json, created_at) = row?;
+            layouts.push(CustomLayoutRow {
+                id,
+                user_id,
+                name,
+                format,
+                created_at,
+                layout: serde_json::from_str(&layout_json)?,
+            });
+        }
+        Ok(layouts)
+    }
+
+    pub fn delete_custom_layout(&self, user_id: i64, layout_id: &str) -> Result<(), StorageError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM custom_layouts WHERE user_id = ?1 AND id = ?2",
+            params![user_id, layout_id],
+        )?;
+        if deleted == 0 {
+            return Err(StorageError::NotFound(format!("custom layout '{}'", layout_id)));
+        }
+        Ok(())
+    }
+
+    // ── Review Items ──────────────────────────────────────────────
+
+    pub fn get_review_items(&self, user_id: i64) -> SqliteResult<Vec<ReviewItem>> {
+        self.query_review_items(
+            "SELECT kind, item, ease, interval_days, repetitions, lapses, due_at, last_reviewed_at
+             FROM review_items WHERE user_id = ?1 ORDER BY kind, item",
+            params![user_id],
+        )
+    }
+
+    /// Items due by `now`, the longest overdue first
+    pub fn get_due_review_items(&self, user_id: i64, now: i64, limit: usize) -> SqliteResult<Vec<ReviewItem>> {
+        self.query_review_items(
+            "SELECT kind, item, ease, interval_days, repetitions, lapses, due_at, last_reviewed_at
+             FROM review_items WHERE user_id = ?1 AND due_at <= ?2
+             ORDER BY due_at, ease, kind, item LIMIT ?3",
+            params![user_id, now, limit as i64],
+        )
+    }
+
+    fn write_review_items(conn: &Connection, user_id: i64, items: &[ReviewItem]) -> SqliteResult<()> {
+        let mut stmt = conn.prepare(
+            "INSERT INTO review_items (user_id, kind, item, ease, interval_days, repetitions,
+                lapses, due_at, last_reviewed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(user_id, kind, item) DO UPDATE SET
+                ease = excluded.ease,
+                interval_days = excluded.interval_days,
+                repetitions = excluded.repetitions,
+                lapses = excluded.lapses,
+                due_at = excluded.due_at,
+                last_reviewed_at = excluded.last_reviewed_at"
+        )?;
+        for i in items {
+            stmt.execute(params![
+                user_id, i.kind.as_str(), i.item, i.ease, i.interval_days, i.repetitions,
+                i.lapses, i.due_at, i.last_reviewed_at,
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn query_review_items(&self, sql: &str, params: impl rusqlite::Params) -> SqliteResult<Vec<ReviewItem>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(params, |row| {
+            let kind: String = row.get(0)?;
+            Ok(ReviewItem {
+                kind: ReviewKind::parse(&kind).ok_or_else(|| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        0,
+                        rusqlite::types::Type::Text,
+                        format!("unknown review kind '{}'", kind).into(),
+                    )
+                })?,
+                item: row.get(1)?,
+                ease: row.get(2)?,
+                interval_days: row.get(3)?,
+                repetitions: row.get(4)?,
+                lapses: row.get(5)?,
+                due_at: row.get(6)?,
+                last_reviewed_at: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    // ── Sessions ──────────────────────────────────────────────────
+
+    /// Add a session to the history and recompute the aggregates with it, in one transaction.
+    /// `lesson_tasks` is the task count of its lesson when that lesson was generated.
+    /// A session stored before is skipped.
+    pub fn record_session(&self, session: &SessionRow, lesson_tasks: Option<i64>) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        if self.session_exists(&session.id)? {
+            return Ok(());
+        }
+        Self::insert_session(&tx, session)?;
+        self.write_aggregates(&tx, session.user_id, session.lesson_id.as_deref().zip(lesson_tasks))?;
+        tx.commit()
+    }
+
+    fn insert_session(conn: &Connection, session: &SessionRow) -> SqliteResult<()> {
+        conn.execute(
+            "INSERT INTO sessions (id, user_id, lesson_id, task_id, layout, started_at, completed_at,
+                wpm, accuracy, duration, total_keystrokes, backspaces, error_count, passed, result_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                session.id,
+                session.user_id,
+                session.lesson_id,
+                session.task_id,
+                session.layout,
+                session.started_at,
+                session.completed_at,
+                session.wpm,
+                session.accuracy,
+                session.duration,
+                session.total_keystrokes,
+                session.backspaces,
+                session.error_count,
+                session.passed,
+                session.result_json,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Sessions are append-only, so one stored under the same id before is left as it is
+    fn write_session(conn: &Connection, session: &SessionRow) -> SqliteResult<()> {
+        conn.execute(
+            "INSERT OR IGNORE INTO sessions (id, user_id, lesson_id, task_id, layout, started_at, completed_at,
+                wpm, accuracy, duration, total_keystrokes, backspaces, error_count, passed, result_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                session.id,
+                session.user_id,
+                session.lesson_id,
+                session.task_id,
+                session.layout,
+                session.started_at,
+                session.completed_at,
+                session.wpm,
+                session.accuracy,
+                session.duration,
+                session.total_keystrokes,
+                session.backspaces,
+                session.error_count,
+                session.passed,
+                session.result_json,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Store a typing session the backend scored, with everything derived from its keystrokes:
+    /// the keystroke log, latency and finger totals, the history row and the aggregates built
+    /// from it, key scores and review items. It is all one transaction, so a failed store
+    /// leaves nothing behind and can be retried; a session stored before is skipped.
+    pub fn store_finished_session(&self, finished: &FinishedSession) -> Result<(), StorageError> {
+        let tx = self.conn.unchecked_transaction()?;
+        if self.session_exists(&finished.session_id)? {
+            return Ok(());
+        }
+        let (session_id, user_id, at) = (&finished.session_id, finished.user_id, finished.result.completed_at);
+        // Keystrokes streamed while typing are already in the log
+        if self.get_keystroke_events(session_id)?.is_empty() {
+            Self::write_keystroke_events(&tx, session_id, user_id, &finished.task_id, &finished.events)?;
+        }
+        Self::write_key_latencies(&tx, user_id, session_id, &MetricsCalculator::latency_samples(&finished.events))?;
+        Self::write_finger_stats(&tx, user_id, session_id, &finished.finger_aggregates())?;
+        Self::insert_session(&tx, &finished.to_session_row())?;
+        self.write_aggregates(&tx, user_id, finished.lesson_id.as_deref().zip(finished.lesson_tasks))?;
+        Self::write_key_scores(&tx, user_id, &problem_keys::key_scores(&finished.events), at)?;
+
+        let observations = review::observations(&finished.target_text, &finished.events);
+        let reviewed = review::schedule(&self.get_review_items(user_id)?, &observations, at);
+        Self::write_review_items(&tx, user_id, &reviewed)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn session_exists(&self, session_id: &str) -> SqliteResult<bool> {
+        self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sessions WHERE id = ?1)",
+            params![session_id],
+            |row| row.get(0),
+        )
+    }
+
+    /// A user's sessions, oldest first; `limit` keeps only the most recent ones
+    pub fn get_sessions(&self, user_id: i64, limit: Option<i64>) -> SqliteResult<Vec<SessionRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM (
+                SELECT id, user_id, lesson_id, task_id, layout, started_at, completed_at, wpm,
+                       accuracy, duration, total_keystrokes, backspaces, error_count, passed, result_json
+                FROM sessions WHERE user_id = ?1
+                ORDER BY completed_at DESC, id DESC LIMIT ?2
+             ) ORDER BY completed_at, id"
+        )?;
+        let rows = stmt.query_map(params![user_id, limit.unwrap_or(-1)], |row| {
+            Ok(SessionRow {
+                id: row.get(0)?,
+                user_id: row.get(1)?,
+                lesson_id: row.get(2)?,
+                task_id: row.get(3)?,
+                layout: row.get(4)?,
+                started_at: row.get(5)?,
+                completed_at: row.get(6)?,
+                wpm: row.get(7)?,
+                accuracy: row.get(8)?,
+                duration: row.get(9)?,
+                total_keystrokes: row.get(10)?,
+                backspaces: row.get(11)?,
+                error_count: row.get(12)?,
+                passed: row.get(13)?,
+                result_json: row.get(14)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Recompute lesson_progress from the user's sessions and add sessions not yet counted
+    /// to user_stats. Lessons without sessions keep their stored progress, and the stored
+    /// totals are kept as the base the new sessions are added to.
+    pub fn rebuild_aggregates(&self, user_id: i64) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.write_aggregates(&tx, user_id, None)?;
+        tx.commit()
+    }
+
+    /// The writes of `rebuild_aggregates` inside a transaction `tx` the caller holds on this
+    /// database's connection, whose reads through `self` see what it has written so far.
+    /// Generated lessons keep the task count stored with their progress; `lesson_tasks`
+    /// gives it for one that may not have progress yet.
+    fn write_aggregates(&self, tx: &Connection, user_id: i64, lesson_tasks: Option<(&str, i64)>) -> SqliteResult<()> {
+        let sessions = self.get_sessions(user_id, None)?;
+        if sessions.is_empty() {
+            return Ok(());
+        }
+        let mut known_totals: BTreeMap<String, i64> = self.get_all_lesson_progress(user_id)?
+            .into_iter()
+            .map(|p| (p.lesson_id, p.total_tasks))
+            .collect();
+        if let Some((lesson_id, total)) = lesson_tasks {
+            known_totals.insert(lesson_id.to_string(), total);
+        }
+        let progress = history::lesson_progress(&sessions, &known_totals);
+        let base = self.get_user_stats(user_id)?.unwrap_or_default();
 
-        tx.execute("DELETE FROM daily_activity WHERE user_id = ?1", params![user_id])?;
         {
             let mut stmt = tx.prepare(
-                "INSERT INTO daily_activity (user_id, date, practice_time, characters, sessions)
-                 VALUES (?1, ?2, ?3, ?4, ?5)"
+                "INSERT INTO lesson_progress (user_id, lesson_id, completed_tasks, total_tasks,
+                    best_wpm, average_accuracy, last_task_index, task_results_json)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT(user_id, lesson_id) DO UPDATE SET
+                    completed_tasks = excluded.completed_tasks,
+                    total_tasks = excluded.total_tasks,
+                    best_wpm = excluded.best_wpm,
+                    average_accuracy = excluded.average_accuracy,
+                    last_task_index = excluded.last_task_index,
+                    task_results_json = excluded.task_results_json,
+                    version = lesson_progress.version + 1"
             )?;
-            for a in activity {
-                stmt.execute(params![user_id, a.date, a.practice_time, a.characters, a.sessions])?;
+            for p in &progress {
+                stmt.execute(params![
+                    user_id,
+                    p.lesson_id,
+                    p.completed_tasks,
+                    p.total_tasks,
+                    p.best_wpm,
+                    p.average_accuracy,
+                    p.last_task_index,
+                    p.task_results_json,
+                ])?;
             }
         }
 
+        let counted: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM merged_sessions WHERE user_id = ?1 AND aggregate = 'user_stats'",
+            params![user_id],
+            |row| row.get(0),
+        )?;
+        let mut added = Vec::new();
+        for session in sessions {
+            if Self::claim_merge(tx, user_id, &session.id, "user_stats")? {
+                added.push(session);
+            }
+        }
+        let mut stats = history::user_stats(base, counted, &added);
+        stats.lessons_completed = tx.query_row(
+            "SELECT COUNT(*) FROM lesson_progress
+             WHERE user_id = ?1 AND total_tasks > 0 AND completed_tasks >= total_tasks",
+            params![user_id],
+            |row| row.get(0),
+        )?;
+        Self::write_user_stats(tx, user_id, &stats)?;
+        Ok(())
+    }
+
+    // ── Versioned Writes ──────────────────────────────────────────
+
+    /// Optimistic concurrency for one row. `update` must match on `expected` and bump the
+    /// version; `insert` only runs for rows the caller believes are new (`expected == 0`).
+    /// A write that matches nothing means another window got there first.
+    fn write_versioned(
+        &self,
+        what: &str,
+        expected: i64,
+        update: impl FnOnce(&Connection) -> SqliteResult<usize>,
+        insert: impl FnOnce(&Connection) -> SqliteResult<usize>,
+    ) -> Result<i64, StorageError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let written = match update(&tx)? {
+            0 if expected == 0 => insert(&tx)?,
+            n => n,
+        };
+        if written == 0 {
+            return Err(StorageError::Conflict(format!(
+                "{} was changed elsewhere (expected version {})", what, expected
+            )));
+        }
         tx.commit()?;
+        Ok(expected + 1)
+    }
+
+    /// Bulk saves upsert the rows they are given, so stored versions keep counting up, and
+    /// then delete the user's rows in `table` whose `key` isn't among them
+    fn delete_missing(conn: &Connection, table: &str, key: &str, user_id: i64, keep: &HashSet<&str>) -> SqliteResult<()> {
+        let stored = conn
+            .prepare(&format!("SELECT {} FROM {} WHERE user_id = ?1", key, table))?
+            .query_map(params![user_id], |row| row.get::<_, String>(0))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        let mut delete = conn.prepare(&format!("DELETE FROM {} WHERE user_id = ?1 AND {} = ?2", table, key))?;
+        for k in stored.iter().filter(|k| !keep.contains(k.as_str())) {
+            delete.execute(params![user_id, k])?;
+        }
         Ok(())
     }
 
-    pub fn delete_activity(&self, user_id: i64) -> SqliteResult<()> {
-        self.conn.execute("DELETE FROM daily_activity WHERE user_id = ?1", params![user_id])?;
+    // ── Import ────────────────────────────────────────────────────
+
+    /// Write imported users in one transaction: profiles, settings, stats and sessions are
+    /// upserted and every other table is replaced by the user's given set
+    pub fn replace_user_data(&self, users: &[UserExport]) -> Result<(), StorageError> {
+        let tx = self.conn.unchecked_transaction()?;
+        for u in users {
+            let p = &u.profile;
+            tx.execute(
+                "INSERT INTO users (id, name, avatar, created_at, last_active_at) VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    avatar = excluded.avatar,
+                    last_active_at = excluded.last_active_at",
+                params![p.id, p.name, p.avatar, p.created_at, p.last_active_at],
+            )?;
+            if let Some(settings) = &u.settings {
+                Self::write_settings(&tx, p.id, settings)?;
+            }
+            if let Some(stats) = &u.stats {
+                Self::write_user_stats(&tx, p.id, stats)?;
+            }
+            Self::replace_lesson_progress(&tx, p.id, &u.lesson_progress)?;
+            Self::replace_course_progress(&tx, p.id, &u.course_progress)?;
+            Self::replace_snippets(&tx, p.id, &u.snippets)?;
+            let dates: HashSet<&str> = u.daily_results.iter().map(|r| r.date.as_str()).collect();
+            Self::delete_missing(&tx, "daily_test_results", "date", p.id, &dates)?;
+            Self::upsert_daily_results(&tx, &u.daily_results)?;
+            Self::replace_activity(&tx, p.id, &u.activity)?;
+            for session in &u.sessions {
+                Self::write_session(&tx, session)?;
+            }
+            // Results that came without sessions become sessions, so the next rebuild keeps
+            // them, and the stats just written already stand for all of them
+            Self::backfill_sessions(&tx, Some(p.id))?;
+            Self::count_sessions_in_stats(&tx, Some(p.id))?;
+        }
+        tx.commit()?;
         Ok(())
     }
 
@@ -678,10 +2227,10 @@ impl Database {
         // Insert stats (stored as JSON strings from localStorage)
         for (user_id_str, json_opt) in &payload.stats {
             if let (Ok(user_id), Some(json)) = (user_id_str.parse::<i64>(), json_opt) {
-                // Parse the JSON stats and insert into the structured table
+                // Parse the JSON stats and insert into the structured table. problemKeys only
+                // held error counts, which are folded into key_error_stats.
                 if let Ok(stats) = serde_json::from_str::<serde_json::Value>(json) {
-                    let problem_keys_val = stats.get("problemKeys");
-                    tx.execute(
+                    let inserted = tx.execute(
                         "INSERT OR IGNORE INTO user_stats (user_id, total_practice_time, total_words_typed,
                             average_wpm, average_accuracy, average_true_accuracy, total_keystrokes,
                             total_backspaces, total_correct_keystrokes, lessons_completed,
@@ -703,22 +2252,12 @@ impl Database {
                             stats.get("lastPracticeDate").and_then(|v| v.as_str()),
                         ],
                     )?;
-
-                    // Insert problem keys
-                    if let Some(keys) = problem_keys_val.and_then(|v| v.as_array()) {
-                        for entry in keys {
-                            if let Some(arr) = entry.as_array() {
-                                if arr.len() == 2 {
-                                    if let (Some(key), Some(count)) = (arr[0].as_str(), arr[1].as_i64()) {
-                                        tx.execute(
-                                            "INSERT OR IGNORE INTO problem_keys (user_id, key_char, error_count)
-                                             VALUES (?1, ?2, ?3)",
-                                            params![user_id, key, count],
-                                        )?;
-                                    }
-                                }
-                            }
-                        }
+                    let counts = stats
+                        .get("problemKeys")
+                        .and_then(|v| serde_json::from_value::<Vec<(String, i64)>>(v.clone()).ok())
+                        .unwrap_or_default();
+                    if inserted > 0 {
+                        Self::write_counted_errors(&tx, user_id, &counts)?;
                     }
                 }
             }
@@ -754,20 +2293,35 @@ impl Database {
             if let (Ok(user_id), Some(json)) = (user_id_str.parse::<i64>(), json_opt) {
                 if let Ok(entries) = serde_json::from_str::<Vec<(String, serde_json::Value)>>(json) {
                     for (course_id, val) in entries {
-                        tx.execute(
+                        let stages = |key: &str| -> Vec<String> {
+                            val.get(key)
+                                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                                .unwrap_or_default()
+                        };
+                        let progress = CourseProgress {
+                            course_id,
+                            current_stage_id: val.get("currentStageId").and_then(|v| v.as_str()).map(String::from),
+                            completed_stages: stages("completedStages"),
+                            skipped_stages: stages("skippedStages"),
+                            enrolled_at: val.get("enrolledAt").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                            completed_at: val.get("completedAt").and_then(|v| v.as_str()).map(String::from),
+                            version: 0,
+                        };
+                        let inserted = tx.execute(
                             "INSERT OR IGNORE INTO course_progress (user_id, course_id, current_stage_id,
-                                completed_stages_json, skipped_stages_json, enrolled_at, completed_at)
-                             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                                enrolled_at, completed_at)
+                             VALUES (?1, ?2, ?3, ?4, ?5)",
                             params![
                                 user_id,
-                                course_id,
-                                val.get("currentStageId").and_then(|v| v.as_str()),
-                                val.get("completedStages").map(|v| v.to_string()).unwrap_or_else(|| "[]".to_string()),
-                                val.get("skippedStages").map(|v| v.to_string()).unwrap_or_else(|| "[]".to_string()),
-                                val.get("enrolledAt").and_then(|v| v.as_str()).unwrap_or(""),
-                                val.get("completedAt").and_then(|v| v.as_str()),
+                                progress.course_id,
+                                progress.current_stage_id,
+                                progress.enrolled_at,
+                                progress.completed_at,
                             ],
                         )?;
+                        if inserted > 0 {
+                            Self::write_course_stages(&tx, user_id, &progress)?;
+                        }
                     }
                 }
             }
@@ -830,6 +2384,12 @@ impl Database {
             )?;
         }
 
+        // Imported lesson results become session history, already counted in the imported stats
+        Self::backfill_sessions(&tx, None)?;
+        for user in &payload.users {
+            Self::count_sessions_in_stats(&tx, Some(user.id))?;
+        }
+
         tx.commit()?;
         Ok(())
     }
@@ -838,11 +2398,12 @@ impl Database {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::layouts::{KeyboardLayout, LayoutId};
 
     #[test]
     fn test_schema_creation() {
         let db = Database::in_memory().unwrap();
-        assert_eq!(db.get_schema_version(), 2);
+        assert_eq!(db.get_schema_version(), 14);
     }
 
     #[test]
@@ -908,11 +2469,26 @@ mod tests {
         };
         db.save_user_stats(1, &stats).unwrap();
 
+        // The frontend gets its counts back. They aren't samples, so no key is reported from them.
         let loaded = db.get_user_stats(1).unwrap().unwrap();
         assert_eq!(loaded.total_practice_time, 3600000);
         assert_eq!(loaded.average_wpm, 50.0);
-        assert_eq!(loaded.problem_keys.len(), 2);
-        assert_eq!(loaded.problem_keys[0].0, "q");
+        assert_eq!(loaded.problem_keys, stats.problem_keys);
+        assert!(db.get_problem_keys(1, 0).unwrap().is_empty());
+
+        // Measured errors are kept apart, so counting them on both sides adds nothing twice
+        Database::write_key_scores(&db.conn, 1, &[KeyScore { key: 'q', attempts: 30, errors: 2 }], 0).unwrap();
+        db.save_user_stats(1, &UserStatsRow { problem_keys: vec![("q".to_string(), 12)], ..stats.clone() }).unwrap();
+        let q = db.get_key_error_stats(1).unwrap()[0];
+        assert_eq!((q.key, q.attempts, q.errors), ('q', 30.0, 2.0));
+
+        // Each save replaces the counts before it
+        let loaded = db.get_user_stats(1).unwrap().unwrap();
+        assert_eq!(loaded.problem_keys, [("q".to_string(), 12)]);
+        assert_eq!(db.get_key_error_stats(1).unwrap().len(), 1);
+        db.save_user_stats(1, &UserStatsRow { problem_keys: vec![], ..stats }).unwrap();
+        assert!(db.get_user_stats(1).unwrap().unwrap().problem_keys.is_empty());
+        assert_eq!(db.get_key_error_stats(1).unwrap().len(), 1);
     }
 
     #[test]
@@ -936,6 +2512,521 @@ mod tests {
         assert!(db.get_user_stats(1).unwrap().is_none());
     }
 
+    #[test]
+    fn test_keystroke_events_roundtrip() {
+        use crate::keystroke::KeystrokeRecorder;
+
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+
+        let mut rec = KeystrokeRecorder::new("hé");
+        rec.key_down('h', 100);
+        rec.key_down('e', 200);
+        rec.backspace(300);
+        db.append_keystroke_events("s1", 1, "hr-1", rec.events()).unwrap();
+
+        rec.set_layout(Some("qwerty-de".to_string()));
+        rec.key_down('é', 400);
+        db.append_keystroke_events("s1", 1, "hr-1", &rec.events()[3..]).unwrap();
+
+        let loaded = db.get_keystroke_events("s1").unwrap();
+        assert_eq!(loaded, rec.events());
+
+        let sessions = db.list_keystroke_sessions(1).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].event_count, 4);
+        assert_eq!(sessions[0].started_at, 100);
+        assert_eq!(sessions[0].ended_at, 400);
+
+        db.delete_user(1).unwrap();
+        assert!(db.get_keystroke_events("s1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_key_latency_rolls_up() {
+        use crate::metrics::MetricsCalculator;
+
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+
+        let mut session = LatencySamples::default();
+        session.bigrams.insert("th".to_string(), vec![300.0, 320.0]);
+        session.characters.insert("h".to_string(), vec![300.0, 320.0]);
+        db.merge_key_latencies(1, "s1", &session).unwrap();
+        db.merge_key_latencies(1, "s2", &session).unwrap();
+        // Merging a session again doesn't count its samples twice
+        db.merge_key_latencies(1, "s2", &session).unwrap();
+
+        let report = db.get_key_latencies(1).unwrap();
+        assert_eq!(report.characters.len(), 1);
+        assert_eq!(report.bigrams[0].key, "th");
+        assert_eq!(report.bigrams[0].stats.count, 4);
+        assert_eq!(report.bigrams[0].stats.mean, 310.0);
+
+        db.merge_key_latencies(1, "s3", &MetricsCalculator::latency_samples(&[])).unwrap();
+        assert_eq!(db.get_key_latencies(1).unwrap().bigrams[0].stats.count, 4);
+    }
+
+    #[test]
+    fn test_finger_stats_roll_up() {
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+
+        let mut session = BTreeMap::new();
+        let mut index = FingerAggregate { keystrokes: 10, errors: 2, ..Default::default() };
+        index.latency.add_samples(&[200.0, 240.0]);
+        session.insert(Finger::LeftIndex, index);
+        session.insert(Finger::RightPinky, FingerAggregate { keystrokes: 4, errors: 1, ..Default::default() });
+        Database::write_finger_stats(&db.conn, 1, "s1", &session).unwrap();
+        Database::write_finger_stats(&db.conn, 1, "s2", &session).unwrap();
+        Database::write_finger_stats(&db.conn, 1, "s2", &session).unwrap();
+
+        let report = db.get_finger_stats(1).unwrap();
+        assert_eq!(report.fingers[0].finger, Finger::LeftIndex);
+        assert_eq!(report.fingers[0].stats.keystrokes, 20);
+        assert_eq!(report.fingers[0].stats.latency.as_ref().unwrap().count, 4);
+        assert_eq!(report.fingers[0].stats.latency.as_ref().unwrap().mean, 220.0);
+        assert_eq!(report.hands[1].stats.errors, 2);
+        assert!(report.hands[1].stats.latency.is_none());
+        assert!(db.get_finger_stats(2).unwrap().fingers.is_empty());
+    }
+
+    #[test]
+    fn test_custom_layouts_are_per_user() {
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Alice", "cat", "2024-01-01").unwrap();
+        db.create_user(2, "Bob", "dog", "2024-01-01").unwrap();
+
+        let mut row = CustomLayoutRow {
+            id: "custom-neo".to_string(),
+            user_id: 1,
+            name: "Neo".to_string(),
+            format: "xkb".to_string(),
+            created_at: "2024-06-01T00:00:00Z".to_string(),
+            layout: KeyboardLayout::builtin(LayoutId::QwertyDe),
+        };
+        db.save_custom_layout(&row).unwrap();
+        row.name = "Neo 2".to_string();
+        db.save_custom_layout(&row).unwrap();
+
+        let layouts = db.get_custom_layouts(1).unwrap();
+        assert_eq!(layouts.len(), 1);
+        assert_eq!(layouts[0].name, "Neo 2");
+        assert_eq!(layouts[0].layout.rows, row.layout.rows);
+        assert!(db.get_custom_layouts(2).unwrap().is_empty());
+
+        assert!(matches!(db.delete_custom_layout(2, "custom-neo"), Err(StorageError::NotFound(_))));
+        db.delete_custom_layout(1, "custom-neo").unwrap();
+        assert!(db.get_custom_layouts(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_due_review_items() {
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+        let item = |kind, item: &str, due_at| ReviewItem {
+            kind,
+            item: item.to_string(),
+            ease: 2.5,
+            interval_days: 1.0,
+            repetitions: 1,
+            lapses: 0,
+            due_at,
+            last_reviewed_at: 0,
+        };
+        Database::write_review_items(&db.conn, 1, &[
+            item(ReviewKind::Char, "q", 300),
+            item(ReviewKind::Bigram, "qu", 100),
+            item(ReviewKind::Word, "quiet", 900),
+        ]).unwrap();
+        let mut moved = item(ReviewKind::Char, "q", 50);
+        moved.lapses = 2;
+        Database::write_review_items(&db.conn, 1, &[moved]).unwrap();
+
+        let due = db.get_due_review_items(1, 500, 10).unwrap();
+        let items: Vec<_> = due.iter().map(|i| i.item.as_str()).collect();
+        assert_eq!(items, ["q", "qu"]);
+        assert_eq!(due[0].lapses, 2);
+        assert_eq!(db.get_due_review_items(1, 500, 1).unwrap().len(), 1);
+        assert_eq!(db.get_review_items(1).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_sessions_are_append_only_and_drive_aggregates() {
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+
+        let legacy = r#"[{"taskId":"hr-1","wpm":20,"accuracy":0.9,"duration":30000,"completedAt":1000000,"passed":true,"errors":[]}]"#;
+        db.save_lesson_progress(1, &[LessonProgressRow {
+            lesson_id: "home-row-basics".to_string(),
+            completed_tasks: 1,
+            total_tasks: 5,
+            best_wpm: 20.0,
+            average_accuracy: 0.9,
+            last_task_index: Some(0),
+            task_results_json: legacy.to_string(),
+            version: 0,
+        }]).unwrap();
+        Database::backfill_sessions(&db.conn, None).unwrap();
+
+        db.record_session(&SessionRow {
+            id: "s1".to_string(),
+            user_id: 1,
+            lesson_id: Some("home-row-basics".to_string()),
+            task_id: "hr-2".to_string(),
+            layout: Some("qwerty-us".to_string()),
+            started_at: 2_000_000,
+            completed_at: 2_060_000,
+            wpm: 40.0,
+            accuracy: 1.0,
+            duration: 60_000,
+            total_keystrokes: 200,
+            backspaces: 0,
+            error_count: 0,
+            passed: true,
+            result_json: "{}".to_string(),
+        }, None).unwrap();
+        assert!(db.conn.execute("UPDATE sessions SET wpm = 99 WHERE id = 's1'", []).is_err());
+
+        let sessions = db.get_sessions(1, None).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, "legacy-1-home-row-basics-0");
+        assert_eq!(db.get_sessions(1, Some(1)).unwrap()[0].id, "s1");
+
+        db.rebuild_aggregates(1).unwrap();
+        let progress = db.get_all_lesson_progress(1).unwrap();
+        assert_eq!(progress[0].completed_tasks, 2);
+        assert_eq!(progress[0].best_wpm, 40.0);
+        let stats = db.get_user_stats(1).unwrap().unwrap();
+        assert_eq!(stats.total_practice_time, 90_000);
+        assert_eq!(stats.average_wpm, 30.0);
+    }
+
+    #[test]
+    fn test_rebuild_adds_sessions_to_saved_totals() {
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+
+        // The frontend saves its own attempt, then totals that already include it
+        let attempt = r#"[{"taskId":"hr-1","wpm":20,"accuracy":0.9,"duration":30000,"completedAt":1000000,"passed":true,"errors":[]}]"#;
+        db.save_lesson_progress(1, &[LessonProgressRow {
+            lesson_id: "home-row-basics".to_string(),
+            completed_tasks: 1,
+            total_tasks: 5,
+            best_wpm: 20.0,
+            average_accuracy: 0.9,
+            last_task_index: Some(0),
+            task_results_json: attempt.to_string(),
+            version: 0,
+        }]).unwrap();
+        db.save_user_stats(1, &UserStatsRow {
+            total_practice_time: 600_000,
+            average_wpm: 20.0,
+            ..Default::default()
+        }).unwrap();
+
+        db.record_session(&SessionRow {
+            id: "s1".to_string(),
+            user_id: 1,
+            lesson_id: Some("home-row-basics".to_string()),
+            task_id: "hr-2".to_string(),
+            layout: None,
+            started_at: 2_000_000,
+            completed_at: 2_060_000,
+            wpm: 40.0,
+            accuracy: 1.0,
+            duration: 60_000,
+            total_keystrokes: 200,
+            backspaces: 0,
+            error_count: 0,
+            passed: true,
+            result_json: "{}".to_string(),
+        }, None).unwrap();
+        db.rebuild_aggregates(1).unwrap();
+        db.rebuild_aggregates(1).unwrap();
+
+        let stats = db.get_user_stats(1).unwrap().unwrap();
+        assert_eq!(stats.total_practice_time, 660_000);
+        assert_eq!(stats.average_wpm, 30.0);
+        assert_eq!(db.get_all_lesson_progress(1).unwrap()[0].completed_tasks, 2);
+
+        // A later save from the frontend replaces the totals and already counts s1
+        db.save_user_stats(1, &UserStatsRow { total_practice_time: 700_000, ..stats }).unwrap();
+        db.rebuild_aggregates(1).unwrap();
+        assert_eq!(db.get_user_stats(1).unwrap().unwrap().total_practice_time, 700_000);
+    }
+
+    #[test]
+    fn test_generated_lessons_keep_their_task_count() {
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+        let attempt = |id: &str, task: &str| SessionRow {
+            id: id.to_string(),
+            user_id: 1,
+            lesson_id: Some("adaptive-1-00ff".to_string()),
+            task_id: task.to_string(),
+            layout: None,
+            started_at: 1_000,
+            completed_at: 61_000,
+            wpm: 40.0,
+            accuracy: 1.0,
+            duration: 60_000,
+            total_keystrokes: 200,
+            backspaces: 0,
+            error_count: 0,
+            passed: true,
+            result_json: "{}".to_string(),
+        };
+
+        // One of three tasks passed doesn't complete the lesson, now or after a rebuild
+        db.record_session(&attempt("s1", "adaptive-1-00ff-1"), Some(3)).unwrap();
+        db.rebuild_aggregates(1).unwrap();
+        db.record_session(&attempt("s2", "adaptive-1-00ff-1"), None).unwrap();
+        let progress = db.get_all_lesson_progress(1).unwrap();
+        assert_eq!((progress[0].completed_tasks, progress[0].total_tasks), (1, 3));
+        assert_eq!(db.get_user_stats(1).unwrap().unwrap().lessons_completed, 0);
+
+        db.record_session(&attempt("s3", "adaptive-1-00ff-2"), Some(3)).unwrap();
+        db.record_session(&attempt("s4", "adaptive-1-00ff-3"), Some(3)).unwrap();
+        assert_eq!(db.get_all_lesson_progress(1).unwrap()[0].completed_tasks, 3);
+        assert_eq!(db.get_user_stats(1).unwrap().unwrap().lessons_completed, 1);
+    }
+
+    #[test]
+    fn test_finished_sessions_are_stored_all_or_nothing() {
+        use crate::keystroke::KeystrokeRecorder;
+
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+        let mut rec = KeystrokeRecorder::new("asdf");
+        rec.set_layout(Some("qwerty-us".to_string()));
+        for (i, c) in "asdx".chars().enumerate() {
+            rec.key_down(c, 1000 + 150 * i as i64);
+        }
+        let finished = FinishedSession {
+            session_id: "s1".to_string(),
+            user_id: 1,
+            task_id: "hr-1".to_string(),
+            lesson_id: Some("home-row-basics".to_string()),
+            lesson_tasks: None,
+            target_text: "asdf".to_string(),
+            layout: Some("qwerty-us".to_string()),
+            started_at: 900,
+            result: MetricsCalculator::calculate_result_from_keystrokes("hr-1".to_string(), "asdf", rec.events()),
+            events: rec.events().to_vec(),
+        };
+
+        // The last write fails, and none of the earlier ones stay behind
+        db.conn.execute_batch(
+            "CREATE TEMP TRIGGER fail_review BEFORE INSERT ON review_items
+             BEGIN SELECT RAISE(ABORT, 'disk full'); END;"
+        ).unwrap();
+        assert!(db.store_finished_session(&finished).is_err());
+        assert!(db.get_keystroke_events("s1").unwrap().is_empty());
+        assert!(db.get_key_latencies(1).unwrap().bigrams.is_empty());
+        assert!(db.get_finger_stats(1).unwrap().fingers.is_empty());
+        assert!(db.get_sessions(1, None).unwrap().is_empty());
+        assert!(db.get_key_error_stats(1).unwrap().is_empty());
+        assert!(db.get_user_stats(1).unwrap().is_none());
+
+        // A retry stores all of it, and storing it again changes nothing
+        db.conn.execute_batch("DROP TRIGGER fail_review;").unwrap();
+        db.store_finished_session(&finished).unwrap();
+        db.store_finished_session(&finished).unwrap();
+        assert_eq!(db.get_keystroke_events("s1").unwrap().len(), 4);
+        let sessions = db.get_sessions(1, None).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].lesson_id.as_deref(), Some("home-row-basics"));
+        assert_eq!(db.get_finger_stats(1).unwrap().fingers.iter().map(|f| f.stats.keystrokes).sum::<u64>(), 4);
+        assert_eq!(db.get_user_stats(1).unwrap().unwrap().total_keystrokes, 4);
+        assert!(!db.get_review_items(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_versioned_writes_reject_stale_saves() {
+        let db = Database::in_memory().unwrap();
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+        db.create_user(2, "Other", "dog", "2024-01-01").unwrap();
+
+        let mut snippet = CustomSnippetRow {
+            id: "s1".to_string(),
+            user_id: 1,
+            name: "Loop".to_string(),
+            content: "for x in y {}".to_string(),
+            language: Some("rust".to_string()),
+            mode: "code".to_string(),
+            created_at: "2024-01-01".to_string(),
+            practice_count: 0,
+            best_wpm: None,
+            best_accuracy: None,
+            version: 0,
+        };
+        assert_eq!(db.upsert_snippet(1, &snippet).unwrap(), 1);
+        // A second window that never saw the insert can't create it again
+        assert!(matches!(db.upsert_snippet(1, &snippet), Err(StorageError::Conflict(_))));
+
+        snippet.version = 1;
+        snippet.practice_count = 3;
+        assert_eq!(db.upsert_snippet(1, &snippet).unwrap(), 2);
+        assert!(matches!(db.upsert_snippet(1, &snippet), Err(StorageError::Conflict(_))));
+        assert!(db.delete_snippet(1, "s1", 1).is_err());
+        db.delete_snippet(1, "s1", 2).unwrap();
+        assert!(db.get_snippets(1).unwrap().is_empty());
+
+        let result = |user_id| DailyTestResultRow {
+            user_id,
+            date: "2024-06-01".to_string(),
+            wpm: 50.0,
+            accuracy: 0.95,
+            true_accuracy: 0.9,
+            duration: 60_000,
+            completed_at: 1,
+            version: 0,
+        };
+        db.record_daily_result(&result(1)).unwrap();
+        db.record_daily_result(&result(2)).unwrap();
+        // Saving one user's results leaves the other user's alone
+        db.save_daily_results(&[result(1)]).unwrap();
+        let results = db.get_daily_results().unwrap();
+        assert_eq!(results.len(), 2);
+        // Bulk saves count on from the stored version, whatever the caller sent
+        assert_eq!(results[0].version, 2);
+        assert!(matches!(
+            db.record_daily_result(&DailyTestResultRow { version: 1, ..result(1) }),
+            Err(StorageError::Conflict(_))
+        ));
+        assert!(db.delete_daily_result(1, "2024-06-01", 1).is_err());
+        db.delete_daily_result(1, "2024-06-01", 2).unwrap();
+        assert_eq!(db.get_daily_results().unwrap().len(), 1);
+
+        let progress = LessonProgressRow {
+            lesson_id: "home-row-basics".to_string(),
+            completed_tasks: 0,
+            total_tasks: 5,
+            best_wpm: 0.0,
+            average_accuracy: 0.0,
+            last_task_index: None,
+            task_results_json: "[]".to_string(),
+            version: 0,
+        };
+        db.save_lesson_progress(1, std::slice::from_ref(&progress)).unwrap();
+        db.save_lesson_progress(1, std::slice::from_ref(&progress)).unwrap();
+        assert_eq!(db.get_all_lesson_progress(1).unwrap()[0].version, 2);
+        let stale = LessonProgressRow { version: 1, ..progress };
+        assert!(matches!(db.upsert_lesson_progress(1, &stale), Err(StorageError::Conflict(_))));
+        assert!(db.delete_lesson_progress(1, "home-row-basics", 1).is_err());
+        db.delete_lesson_progress(1, "home-row-basics", 2).unwrap();
+        assert!(db.get_all_lesson_progress(1).unwrap().is_empty());
+
+        let delta = DailyActivityRow {
+            date: "2024-06-01".to_string(),
+            practice_time: 1000,
+            characters: 50,
+            sessions: 1,
+            version: 0,
+        };
+        db.record_activity_delta(1, &delta).unwrap();
+        let total = db.record_activity_delta(1, &delta).unwrap();
+        assert_eq!(total.practice_time, 2000);
+        assert_eq!(total.sessions, 2);
+        assert_eq!(total.version, 2);
+    }
+
+    #[test]
+    fn test_course_stages_move_out_of_json() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        let db = Database { conn };
+        db.conn.execute_batch("CREATE TABLE schema_version (version INTEGER NOT NULL, applied_at TEXT);").unwrap();
+        db.migrate_to_v1().unwrap();
+        for migrate in [Database::migrate_to_v2, Database::migrate_to_v3, Database::migrate_to_v4,
+                        Database::migrate_to_v5, Database::migrate_to_v6, Database::migrate_to_v7,
+                        Database::migrate_to_v8, Database::migrate_to_v9] {
+            migrate(&db).unwrap();
+        }
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+        db.conn.execute(
+            "INSERT INTO course_progress (user_id, course_id, current_stage_id, completed_stages_json,
+                skipped_stages_json, enrolled_at, version)
+             VALUES (1, 'ten-finger', 'stage-4', '[\"stage-2\",\"stage-1\"]', '[\"stage-3\"]', '2024-01-02', 3)",
+            [],
+        ).unwrap();
+
+        db.migrate().unwrap();
+        assert_eq!(db.get_schema_version(), 14);
+        let mut progress = db.get_course_state(1, "ten-finger").unwrap().unwrap();
+        assert_eq!(progress.completed_stages, ["stage-2", "stage-1"]);
+        assert_eq!(progress.skipped_stages, ["stage-3"]);
+        assert_eq!(progress.version, 3);
+        let row = &db.get_all_course_progress(1).unwrap()[0];
+        assert_eq!(row.completed_stages_json, r#"["stage-2","stage-1"]"#);
+
+        progress.skipped_stages.clear();
+        progress.completed_stages.push("stage-3".to_string());
+        assert_eq!(db.save_course_state(1, &progress).unwrap(), 4);
+        assert!(matches!(db.save_course_state(1, &progress), Err(StorageError::Conflict(_))));
+        let saved = db.get_course_state(1, "ten-finger").unwrap().unwrap();
+        assert_eq!(saved.completed_stages, ["stage-2", "stage-1", "stage-3"]);
+        assert!(saved.skipped_stages.is_empty());
+
+        // Stage rows go with their course
+        assert!(matches!(
+            db.delete_course_progress(1, "ten-finger", Some(3)),
+            Err(StorageError::Conflict(_))
+        ));
+        db.delete_course_progress(1, "ten-finger", Some(4)).unwrap();
+        let stages: i64 = db.conn
+            .query_row("SELECT COUNT(*) FROM course_stage_progress", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stages, 0);
+        assert!(db.get_course_state(1, "ten-finger").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_key_error_stats_replace_problem_keys() {
+        use crate::keystroke::KeystrokeRecorder;
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        let db = Database { conn };
+        db.conn.execute_batch("CREATE TABLE schema_version (version INTEGER NOT NULL, applied_at TEXT);").unwrap();
+        db.migrate_to_v1().unwrap();
+        for migrate in [Database::migrate_to_v2, Database::migrate_to_v3, Database::migrate_to_v4,
+                        Database::migrate_to_v5, Database::migrate_to_v6, Database::migrate_to_v7,
+                        Database::migrate_to_v8, Database::migrate_to_v9, Database::migrate_to_v10,
+                        Database::migrate_to_v11] {
+            migrate(&db).unwrap();
+        }
+        db.create_user(1, "Test", "cat", "2024-01-01").unwrap();
+        db.conn.execute("INSERT INTO problem_keys (user_id, key_char, error_count) VALUES (1, 'x', 99)", []).unwrap();
+        let mut rec = KeystrokeRecorder::new(&"ab".repeat(15));
+        for i in 0..30 {
+            let typed = if i % 2 == 0 { 'a' } else if i < 10 { 'x' } else { 'b' };
+            rec.key_down(typed, 1000 + i);
+        }
+        db.append_keystroke_events("s1", 1, "t", rec.events()).unwrap();
+
+        db.migrate().unwrap();
+        assert_eq!(db.get_schema_version(), 14);
+        // The keystrokes are replayed, and the old count is kept apart as it has no attempts
+        let stats = db.get_key_error_stats(1).unwrap();
+        assert_eq!(stats.len(), 3);
+        let x = stats.iter().find(|k| k.key == 'x').unwrap();
+        assert_eq!((x.attempts, x.errors), (0.0, 0.0));
+        assert_eq!(db.get_counted_errors(1).unwrap(), [("x".to_string(), 99)]);
+        assert!(db.get_problem_keys(1, 2000).unwrap().is_empty(), "15 attempts per key are too few");
+
+        Database::write_key_scores(&db.conn, 1, &[KeyScore { key: 'b', attempts: 15, errors: 0 }], 2000).unwrap();
+        let problems = db.get_problem_keys(1, 2000).unwrap();
+        assert_eq!(problems.len(), 1);
+        let b = problems.iter().find(|k| k.key == 'b').unwrap();
+        assert!((b.attempts - 30.0).abs() < 1e-3);
+        assert!((b.errors - 5.0).abs() < 1e-3);
+
+        db.delete_user(1).unwrap();
+        assert!(db.get_key_error_stats(1).unwrap().is_empty());
+    }
+
     #[test]
     fn test_migration_needed() {
         let db = Database::in_memory().unwrap();